@@ -0,0 +1,51 @@
+#![allow(non_snake_case)]
+
+use ark_ec::VariableBaseMSM;
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use toolbox::vec::{hadamard_product, inner_product};
+
+// compare the proof size and the verify time of binary and 4-ary folding for n = 2^16
+fn bench_ipa_verify_arity(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let n = 1 << 16;
+    let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let params = InnerProductParam::<Projective> {
+        factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        u: Affine::rand(&mut rng),
+        vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        arity: FoldingArity::Two,
+    };
+    let mut exp = hadamard_product(&vec_a, &params.factors_G);
+    exp.extend(hadamard_product(&vec_b, &params.factors_H));
+    let mut base = params.vec_G.clone();
+    base.extend(params.vec_H.clone());
+    let P = Projective::msm(&base, &exp).unwrap() + params.u*inner_product(&vec_a, &vec_b);
+
+    let mut group = c.benchmark_group("ipa_verify_arity");
+    group.sample_size(10);
+    for arity in [FoldingArity::Two, FoldingArity::Four] {
+        let params = InnerProductParam { arity, ..params.clone() };
+        let proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+        println!(
+            "n = 2^16, arity {}: {} rounds, proof size {} bytes",
+            arity.as_usize(),
+            proof.challenges.len(),
+            proof.compressed_size(),
+        );
+        group.bench_with_input(BenchmarkId::new("n=2^16, arity", arity.as_usize()), &proof, |b, proof| {
+            b.iter(|| InnerProductProtocol::verify(IPA_DOMAIN, n, P, &params, proof).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_verify_arity);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+#![allow(non_snake_case)]
+
+// cargo bench -p bulletproofs --features bench --bench ipa_bench
+// criterion plots the prover and verifier time against n in target/criterion/report,
+// the prover is expected to scale in O(n) and the verifier msm in O(n / log n)
+use ark_ec::VariableBaseMSM;
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use toolbox::vec::{hadamard_product, inner_product};
+
+const SIZES: [usize; 6] = [64, 128, 256, 512, 1024, 2048];
+
+type Ipa = InnerProductProtocol<Projective>;
+
+// random vectors, generators and the target P = G^{a * factors_G} H^{b * factors_H} u^{<a,b>}
+fn ipa_instance(n: usize) -> (InnerProductParam<Projective>, Vec<Fr>, Vec<Fr>, Projective) {
+    let mut rng = ark_std::test_rng();
+    let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let params = InnerProductParam::<Projective> {
+        factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        u: Affine::rand(&mut rng),
+        vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        arity: FoldingArity::Two,
+    };
+    let mut exp = hadamard_product(&vec_a, &params.factors_G);
+    exp.extend(hadamard_product(&vec_b, &params.factors_H));
+    let mut base = params.vec_G.clone();
+    base.extend(params.vec_H.clone());
+    let P = Projective::msm(&base, &exp).unwrap() + params.u*inner_product(&vec_a, &vec_b);
+    (params, vec_a, vec_b, P)
+}
+
+fn bench_ipa_prove(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ipa_prove");
+    group.sample_size(10);
+    for n in SIZES {
+        let (params, vec_a, vec_b, _) = ipa_instance(n);
+        group.bench_with_input(BenchmarkId::new("ipa_prove_n", n), &n, |b, _| {
+            b.iter(|| Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_ipa_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ipa_verify");
+    group.sample_size(10);
+    for n in SIZES {
+        let (params, vec_a, vec_b, P) = ipa_instance(n);
+        let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+        group.bench_with_input(BenchmarkId::new("ipa_verify_n", n), &n, |b, &n| {
+            b.iter(|| Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_prove, bench_ipa_verify);
+criterion_main!(benches);
@@ -0,0 +1,103 @@
+#![allow(non_snake_case)]
+
+// cargo bench -p bulletproofs --features bench --bench ipa_curves
+// the IPA prover and verifier at n = 2^8, 2^12, 2^16 over secp256k1 and the G1 group of BLS12-381,
+// a markdown table of the mean times is printed at the end for the README
+use std::path::PathBuf;
+use ark_ec::CurveGroup;
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IpaStatement, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use criterion::{criterion_group, BenchmarkId, Criterion};
+use toolbox::vec::{hadamard_product, inner_product};
+
+const LOG_SIZES: [u32; 3] = [8, 12, 16];
+
+// the parameters, witness and target P = G^{a * factors_G} H^{b * factors_H} u^{<a,b>} of size n,
+// drawn from the fixed test rng so that every run benchmarks the same instance
+fn ipa_instance<C: CurveGroup>(n: usize) -> (InnerProductParam<C>, IpaStatement<C::ScalarField>, C) {
+    let mut rng = ark_std::test_rng();
+    let vec_a: Vec<C::ScalarField> = (0..n).map(|_| C::ScalarField::rand(&mut rng)).collect();
+    let vec_b: Vec<C::ScalarField> = (0..n).map(|_| C::ScalarField::rand(&mut rng)).collect();
+    let random_points = |rng: &mut _| C::normalize_batch(&(0..n).map(|_| C::rand(rng)).collect::<Vec<C>>());
+    let params = InnerProductParam::<C> {
+        factors_G: (0..n).map(|_| C::ScalarField::rand(&mut rng)).collect(),
+        factors_H: (0..n).map(|_| C::ScalarField::rand(&mut rng)).collect(),
+        u: C::rand(&mut rng).into_affine(),
+        vec_G: random_points(&mut rng),
+        vec_H: random_points(&mut rng),
+        arity: FoldingArity::Two,
+    };
+    let mut exp = hadamard_product(&vec_a, &params.factors_G);
+    exp.extend(hadamard_product(&vec_b, &params.factors_H));
+    let mut base = params.vec_G.clone();
+    base.extend(params.vec_H.clone());
+    let P = C::msm(&base, &exp).unwrap() + params.u*inner_product(&vec_a, &vec_b);
+    (params, (vec_a, vec_b), P)
+}
+
+fn bench_curve<C: CurveGroup>(c: &mut Criterion, curve: &str) {
+    type Ipa<C> = InnerProductProtocol<C>;
+    let mut group = c.benchmark_group(format!("ipa_{}", curve));
+    group.sample_size(10);
+    for log_n in LOG_SIZES {
+        let n = 1usize << log_n;
+        let (params, (vec_a, vec_b), P) = ipa_instance::<C>(n);
+        group.bench_with_input(BenchmarkId::new("prove", n), &n, |b, _| {
+            b.iter(|| Ipa::<C>::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap())
+        });
+        let proof = Ipa::<C>::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+        group.bench_with_input(BenchmarkId::new("verify", n), &n, |b, &n| {
+            b.iter(|| Ipa::<C>::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_secp256k1(c: &mut Criterion) {
+    bench_curve::<ark_secp256k1::Projective>(c, "secp256k1");
+}
+
+fn bench_bls12_381(c: &mut Criterion) {
+    bench_curve::<ark_bls12_381::G1Projective>(c, "bls12_381");
+}
+
+// the criterion output directory, following criterion's own lookup
+fn criterion_home() -> PathBuf {
+    if let Some(home) = std::env::var_os("CRITERION_HOME") {
+        return PathBuf::from(home);
+    }
+    let target = std::env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target"));
+    target.join("criterion")
+}
+
+// the mean time in milliseconds that criterion recorded for a benchmark, if it ran
+fn mean_ms(group: &str, function: &str, n: usize) -> Option<f64> {
+    let path = criterion_home().join(group).join(function).join(n.to_string()).join("new/estimates.json");
+    let estimates: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    Some(estimates["mean"]["point_estimate"].as_f64()? / 1e6)
+}
+
+// prints the mean prover and verifier times of this run as a markdown table
+fn bench_report() {
+    println!("| curve | n | prove (ms) | verify (ms) |");
+    println!("|---|---|---|---|");
+    for curve in ["secp256k1", "bls12_381"] {
+        let group = format!("ipa_{}", curve);
+        for log_n in LOG_SIZES {
+            let n = 1usize << log_n;
+            let cell = |function| mean_ms(&group, function, n).map_or("-".to_string(), |ms| format!("{:.2}", ms));
+            println!("| {} | 2^{} | {} | {} |", curve, log_n, cell("prove"), cell("verify"));
+        }
+    }
+}
+
+criterion_group!(benches, bench_secp256k1, bench_bls12_381);
+
+fn main() {
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+    bench_report();
+}
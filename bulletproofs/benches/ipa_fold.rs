@@ -0,0 +1,65 @@
+#![allow(non_snake_case)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+// counts the heap allocations of the process
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+// the allocation count and the prove time of the binary IPA for n = 2^11
+fn bench_ipa_fold(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let n = 1 << 11;
+    let params = InnerProductParam::<Projective> {
+        factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        u: Affine::rand(&mut rng),
+        vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        arity: FoldingArity::Two,
+    };
+    let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+
+    let (a, b) = (vec_a.clone(), vec_b.clone());
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    InnerProductProtocol::prove(IPA_DOMAIN, &params, a, b).unwrap();
+    println!("n = 2^11: {} allocations in prove", ALLOCATIONS.load(Ordering::Relaxed) - before);
+
+    let mut group = c.benchmark_group("ipa_fold");
+    group.sample_size(10);
+    group.bench_function("prove n=2^11", |b| {
+        b.iter(|| InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_fold);
+criterion_main!(benches);
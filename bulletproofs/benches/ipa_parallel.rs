@@ -0,0 +1,47 @@
+#![allow(non_snake_case)]
+
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// prove inner products of length 2^10 and 2^16 with the rayon-parallel prover rounds,
+// rounds on vectors shorter than 256 run sequentially
+fn bench_ipa_prove_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ipa_prove_parallel");
+    group.sample_size(10);
+    for log_n in [10, 16] {
+        let mut rng = ark_std::test_rng();
+        let n = 1 << log_n;
+        let params = InnerProductParam::<Projective> {
+            factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            u: Affine::rand(&mut rng),
+            vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            arity: FoldingArity::Two,
+        };
+        let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+
+        for num_threads in [1, 4, 8] {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            let id = BenchmarkId::new(format!("n=2^{}, threads", log_n), num_threads);
+            group.bench_with_input(id, &num_threads, |b, _| {
+                b.iter(|| {
+                    pool.install(|| {
+                        InnerProductProtocol::<Projective>::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap()
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_prove_parallel);
+criterion_main!(benches);
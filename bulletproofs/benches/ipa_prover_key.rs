@@ -0,0 +1,42 @@
+// cargo bench -p bulletproofs --features bench --bench ipa_prover_key
+// compare proving with and without a precomputed prover key for n = 2^14,
+// the key is built once so that only the proofs after the first one are measured
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam, IpaProverKey};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type Ipa = InnerProductProtocol<Projective>;
+
+fn bench_ipa_prover_key(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let n = 1 << 14;
+    let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let params = InnerProductParam::<Projective> {
+        factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        u: Affine::rand(&mut rng),
+        vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        arity: FoldingArity::Two,
+    };
+
+    let mut group = c.benchmark_group("ipa_prover_key");
+    group.sample_size(10);
+    group.bench_function("n=2^14, key setup", |b| {
+        b.iter(|| IpaProverKey::new(&params))
+    });
+    let key = IpaProverKey::new(&params);
+    group.bench_function("n=2^14, prove", |b| {
+        b.iter(|| Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap())
+    });
+    group.bench_function("n=2^14, prove with key", |b| {
+        b.iter(|| Ipa::prove_with_key(IPA_DOMAIN, &key, vec_a.clone(), vec_b.clone()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_prover_key);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+#![allow(non_snake_case)]
+
+use ark_ec::VariableBaseMSM;
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use toolbox::vec::{hadamard_product, inner_product};
+
+// compare the proof size and the prove/verify time of the two-sided IPA and
+// the one-sided IPA with a public vector b for n = 2^10
+fn bench_ipa_public_b(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let n = 1 << 10;
+    let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let params = InnerProductParam::<Projective> {
+        factors_G: vec![Fr::from(1u64); n],
+        factors_H: vec![Fr::from(1u64); n],
+        u: Affine::rand(&mut rng),
+        vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        arity: FoldingArity::Two,
+    };
+    let P_a = Projective::msm(&params.vec_G, &hadamard_product(&vec_a, &params.factors_G)).unwrap()
+        + params.u*inner_product(&vec_a, &vec_b);
+    let P = P_a + Projective::msm(&params.vec_H, &hadamard_product(&vec_b, &params.factors_H)).unwrap();
+
+    let proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+    let proof_public_b = InnerProductProtocol::prove_public_b(IPA_DOMAIN, &params, vec_a.clone(), &vec_b).unwrap();
+    println!(
+        "n = 2^10: two-sided proof size {} bytes, one-sided proof size {} bytes",
+        proof.compressed_size(),
+        proof_public_b.compressed_size(),
+    );
+
+    let mut group = c.benchmark_group("ipa_public_b");
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("prove", "two-sided"), |b| {
+        b.iter(|| InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("prove", "one-sided"), |b| {
+        b.iter(|| InnerProductProtocol::prove_public_b(IPA_DOMAIN, &params, vec_a.clone(), &vec_b).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("verify", "two-sided"), |b| {
+        b.iter(|| InnerProductProtocol::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("verify", "one-sided"), |b| {
+        b.iter(|| InnerProductProtocol::verify_public_b(IPA_DOMAIN, n, P_a, &params, &vec_b, &proof_public_b).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_public_b);
+criterion_main!(benches);
@@ -0,0 +1,130 @@
+#![allow(non_snake_case)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ark_ec::CurveGroup;
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_std::UniformRand;
+use bulletproofs::ipa::{FnSource, InnerProductProtocol, VectorSource, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+// tracks the live heap bytes of the process and their peak
+struct PeakAlloc;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn grow(size: usize) {
+    let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        grow(layout.size());
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        grow(new_size);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAlloc = PeakAlloc;
+
+// the peak heap bytes allocated by f on top of the live bytes before the call
+fn peak_of<T>(f: impl FnOnce() -> T) -> usize {
+    let before = LIVE.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    drop(f());
+    PEAK.load(Ordering::Relaxed) - before
+}
+
+// generators T_1[i % 1024] + T_2[i / 1024] and scalars seed * (i + 1) computed on the fly,
+// so that vectors of length up to 2^20 are never materialized
+struct LazyInstance {
+    table_1: Vec<Affine>,
+    table_2: Vec<Affine>,
+    seed: Fr,
+}
+
+impl LazyInstance {
+    fn new() -> Self {
+        let mut rng = ark_std::test_rng();
+        LazyInstance {
+            table_1: (0..1024).map(|_| Affine::rand(&mut rng)).collect(),
+            table_2: (0..1024).map(|_| Affine::rand(&mut rng)).collect(),
+            seed: Fr::rand(&mut rng),
+        }
+    }
+
+    fn generators(&self, n: usize, offset: usize) -> FnSource<impl Fn(usize, usize) -> Vec<Affine> + '_> {
+        FnSource {
+            len: n,
+            read: move |start, len| {
+                let terms: Vec<Projective> = (start..start + len)
+                    .map(|i| self.table_1[(offset + i) % 1024] + self.table_2[(offset + i) / 1024 % 1024])
+                    .collect();
+                Projective::normalize_batch(&terms)
+            },
+        }
+    }
+
+    fn scalars(&self, n: usize, shift: u64) -> FnSource<impl Fn(usize, usize) -> Vec<Fr> + '_> {
+        FnSource {
+            len: n,
+            read: move |start, len| (start..start + len).map(|i| self.seed * Fr::from(i as u64 + shift)).collect(),
+        }
+    }
+}
+
+// the peak memory of the in-memory and the streaming prover,
+// and the prove time of the streaming prover for n = 2^16
+fn bench_ipa_streaming(c: &mut Criterion) {
+    let instance = LazyInstance::new();
+    let n = 1 << 16;
+    let (vec_a, vec_b) = (instance.scalars(n, 1), instance.scalars(n, 2));
+    let (vec_G, vec_H) = (instance.generators(n, 0), instance.generators(n, n));
+    let u = instance.table_1[0];
+
+    let params = InnerProductParam::<Projective> {
+        factors_G: vec![Fr::from(1u64); n],
+        factors_H: vec![Fr::from(1u64); n],
+        u,
+        vec_G: vec_G.read_chunk(0, n),
+        vec_H: vec_H.read_chunk(0, n),
+        arity: FoldingArity::Two,
+    };
+    let peak = peak_of(|| InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a.read_chunk(0, n), vec_b.read_chunk(0, n)).unwrap());
+    println!("n = 2^16: {} MB peak in prove, not counting the parameters", peak >> 20);
+    let peak = peak_of(|| InnerProductProtocol::<Projective>::prove_streaming(IPA_DOMAIN, u, &vec_a, &vec_b, &vec_G, &vec_H).unwrap());
+    println!("n = 2^16: {} MB peak in prove_streaming", peak >> 20);
+    drop(params);
+
+    let m = 1 << 20;
+    let (large_G, large_H) = (instance.generators(m, 0), instance.generators(m, m));
+    let peak = peak_of(|| {
+        InnerProductProtocol::<Projective>::prove_streaming(IPA_DOMAIN, u, &instance.scalars(m, 1), &instance.scalars(m, 2), &large_G, &large_H).unwrap()
+    });
+    println!("n = 2^20: {} MB peak in prove_streaming", peak >> 20);
+
+    let mut group = c.benchmark_group("ipa_streaming");
+    group.sample_size(10);
+    group.bench_function("prove_streaming n=2^16", |b| {
+        b.iter(|| InnerProductProtocol::<Projective>::prove_streaming(IPA_DOMAIN, u, &vec_a, &vec_b, &vec_G, &vec_H).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_streaming);
+criterion_main!(benches);
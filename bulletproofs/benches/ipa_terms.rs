@@ -0,0 +1,45 @@
+#![allow(non_snake_case)]
+
+use ark_ec::VariableBaseMSM;
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::InnerProductParam;
+use criterion::{criterion_group, criterion_main, Criterion};
+use toolbox::vec::inner_product;
+
+// the verify time of the binary IPA for n = 2^10 with a target given by 2n + 1 terms,
+// as a caller such as the compressed ring signature derives it:
+// the caller either computes the target with its own msm before calling verify,
+// or passes the terms to verify_with_terms, so that the verifier runs one msm in total
+fn bench_ipa_terms(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let n = 1 << 10;
+    let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let params = InnerProductParam::<Projective>::uniform(
+        (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        Affine::rand(&mut rng),
+    );
+    let mut terms: Vec<(Affine, Fr)> = vec![(params.u, inner_product(&vec_a, &vec_b))];
+    terms.extend(params.vec_G.iter().copied().zip(vec_a.iter().copied()));
+    terms.extend(params.vec_H.iter().copied().zip(vec_b.iter().copied()));
+    let proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+
+    let mut group = c.benchmark_group("ipa_terms");
+    group.bench_function("precomputed target n=2^10", |b| {
+        b.iter(|| {
+            let (bases, scalars): (Vec<Affine>, Vec<Fr>) = terms.iter().copied().unzip();
+            let P = Projective::msm(&bases, &scalars).unwrap();
+            InnerProductProtocol::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap()
+        })
+    });
+    group.bench_function("target terms n=2^10", |b| {
+        b.iter(|| InnerProductProtocol::verify_with_terms(IPA_DOMAIN, n, &terms, &params, &proof).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_terms);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+#![allow(non_snake_case)]
+
+use ark_ec::VariableBaseMSM;
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use criterion::{criterion_group, criterion_main, Criterion};
+use toolbox::vec::{hadamard_product, inner_product};
+
+// the verify time of the binary IPA for n = 2^10,
+// run with --save-baseline and --baseline to compare two versions of the verifier
+fn bench_ipa_verify(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let n = 1 << 10;
+    let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let params = InnerProductParam::<Projective> {
+        factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        u: Affine::rand(&mut rng),
+        vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        arity: FoldingArity::Two,
+    };
+    let mut exp = hadamard_product(&vec_a, &params.factors_G);
+    exp.extend(hadamard_product(&vec_b, &params.factors_H));
+    let mut base = params.vec_G.clone();
+    base.extend(params.vec_H.clone());
+    let P = Projective::msm(&base, &exp).unwrap() + params.u*inner_product(&vec_a, &vec_b);
+    let proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+
+    let mut group = c.benchmark_group("ipa_verify");
+    group.bench_function("n=2^10", |b| {
+        b.iter(|| InnerProductProtocol::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipa_verify);
+criterion_main!(benches);
@@ -1,14 +1,74 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
-use std::marker::PhantomData;
+use alloc::{format, string::{String, ToString}, vec, vec::Vec};
+use core::marker::PhantomData;
 use ark_ec::CurveGroup;
-use ark_ff::Field;
-use ark_std::{end_timer, start_timer};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_std::{end_timer, rand::Rng, start_timer, One, UniformRand, Zero};
 use toolbox::sigma::transcript::ProofTranscript;
 use toolbox::errors::SigmaErrors;
-use toolbox::vec::{vec_add, vec_split, inner_product, scalar_product, hadamard_product};
+use toolbox::vec::{ct_eq_affine, generate_powers, vec_add, vec_neg, vec_split, inner_product, scalar_product, hadamard_product};
 use crate::structs::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+// the default transcript domain of a standalone inner product argument,
+// protocols built on top of the IPA should pass their own domain to prove/verify
+pub const IPA_DOMAIN: &[u8] = b"InnerProductArgument";
+
+// the witness (vec_a, vec_b) of one statement of the aggregated IPA
+pub type IpaStatement<F> = (Vec<F>, Vec<F>);
+
+// the commitments L, R of one binary IPA round
+pub type IpaRoundCommitments<G> = (G, G);
+
+// the shift (vec_S, c) of the generators vec_G[i] to vec_G[i]^{factors_G[i]} * vec_S[i]^c for i < vec_S.len()
+pub type GeneratorShift<'a, A, F> = (&'a [A], F);
+
+// the factors (factors_G, factors_H) that a round of the binary prover applies to the generators
+type RoundFactors<'a, F> = Option<(&'a [F], &'a [F])>;
+
+// the number of entries per half read at once by the streaming prover
+pub const STREAMING_CHUNK_LEN: usize = 1 << 10;
+
+/// Read access to a vector by chunks, so that the streaming prover never holds it whole
+pub trait VectorSource<T> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Outputs the len entries from start
+    fn read_chunk(&self, start: usize, len: usize) -> Vec<T>;
+}
+
+impl<T: Clone> VectorSource<T> for [T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn read_chunk(&self, start: usize, len: usize) -> Vec<T> {
+        self[start..start + len].to_vec()
+    }
+}
+
+// a vector of length len whose chunks are computed by read(start, len)
+pub struct FnSource<F> {
+    pub len: usize,
+    pub read: F,
+}
+
+impl<T, F: Fn(usize, usize) -> Vec<T>> VectorSource<T> for FnSource<F> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read_chunk(&self, start: usize, len: usize) -> Vec<T> {
+        (self.read)(start, len)
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct InnerProductProtocol<C: CurveGroup> {
@@ -20,17 +80,77 @@ pub struct InnerProductProtocol<C: CurveGroup> {
 impl<C: CurveGroup> InnerProductProtocol<C>
 {
     pub fn prove(
+        domain: &'static [u8],
         params: &InnerProductParam<C>,
-        mut vec_a: Vec<C::ScalarField>,
-        mut vec_b: Vec<C::ScalarField>,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+    ) -> Result<InnerProductProof<C>, SigmaErrors> {
+        Self::prove_with_tables(domain, params, None, vec_a, vec_b)
+    }
+
+    /// Same as prove, but folds the generators of the first binary round with the
+    /// precomputed tables of the key, the output proof is identical
+    pub fn prove_with_key(
+        domain: &'static [u8],
+        key: &IpaProverKey<C>,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+    ) -> Result<InnerProductProof<C>, SigmaErrors> {
+        Self::prove_with_tables(domain, &key.params, Some(key), vec_a, vec_b)
+    }
+
+    fn prove_with_tables(
+        domain: &'static [u8],
+        params: &InnerProductParam<C>,
+        key: Option<&IpaProverKey<C>>,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+    ) -> Result<InnerProductProof<C>, SigmaErrors> {
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        transcript.append_message(b"IPAdomain", domain)?;
+        Self::prove_rounds(&mut transcript, params, key, vec_a, vec_b)
+    }
+
+    /// Same as prove, but runs on the transcript of an outer protocol,
+    /// so that the IPA challenges are bound to everything the transcript absorbed before
+    pub fn prove_with_transcript(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        params: &InnerProductParam<C>,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+    ) -> Result<InnerProductProof<C>, SigmaErrors> {
+        Self::prove_rounds(transcript, params, None, vec_a, vec_b)
+    }
+
+    /// Same as prove_with_transcript, for the relation vec_G^{a * factors_G} vec_H^{b * factors_H} = P
+    /// and <a, b> = c with c known to the verifier: c is absorbed by the transcript and the rounds run
+    /// on u' = u^x for a challenge x, so that the target P carries no u^c and the proof no c
+    pub fn prove_with_public_product(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        params: &InnerProductParam<C>,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+    ) -> Result<InnerProductProof<C>, SigmaErrors> {
+        if vec_a.len() != vec_b.len() {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+        let params = public_product_param(transcript, params, inner_product(&vec_a, &vec_b))?;
+        Self::prove_rounds(transcript, &params, None, vec_a, vec_b)
+    }
+
+    // runs the rounds of the prover on a transcript that already absorbed the domain
+    fn prove_rounds(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        params: &InnerProductParam<C>,
+        key: Option<&IpaProverKey<C>>,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
     ) -> Result<InnerProductProof<C>, SigmaErrors> {
         // initialization
         let start = start_timer!(|| "running inner product argument prove algorithm...");
-        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingSignature");
-
-        let mut n = params.vec_G.len();
-        let mut vec_G = params.vec_G.clone();
-        let mut vec_H = params.vec_H.clone();
+        let n = params.vec_G.len();
 
         // Ensure all vectors have the same length
         if params.vec_H.len() != n || vec_a.len() != n || vec_b.len() !=n
@@ -49,6 +169,12 @@ impl<C: CurveGroup> InnerProductProtocol<C>
         }
 
         transcript.append_field_element(b"IPAsize", &C::ScalarField::from(n as u128))?;
+        transcript.append_field_element(b"IPAarity", &C::ScalarField::from(params.arity.as_usize() as u64))?;
+        if params.arity == FoldingArity::Four {
+            let proof = Self::prove_quaternary(params, transcript, vec_a, vec_b);
+            end_timer!(start);
+            return proof;
+        }
 
         // log(n) is the trailing zeros of its binary form
         // e.g., 32 = 100000 -> log(32) = 5
@@ -58,197 +184,337 @@ impl<C: CurveGroup> InnerProductProtocol<C>
         let mut challenges = Vec::with_capacity(log_n);
 
         // compression
-        // base step
-        if n != 1 {
-            n = n / 2;
-
-            // split n-length vector to two sub-vectors
-            let (a_L, a_R) = vec_split(&vec_a, n);
-            let (b_L, b_R) = vec_split(&vec_b, n);
-            let (G_L, G_R) = vec_split(&vec_G, n);
-            let (H_L, H_R) = vec_split(&vec_H, n);
-
-            // <a_L*x + a_R*x_inv, b_L*x_inv + b_R*x> = <a, b> + x^2*<a_L, b_R> + x_inv^2*<a_R, b_L>
-            // compute c_L = <a_L, b_R>, c_R = <a_R, b_L>
-            let c_L = inner_product(&a_L, &b_R);
-            let c_R = inner_product(&a_R, &b_L);
-
-            // compute L = (G_R^factors_G[n..2n])^a_L + (H_L^factors_H[0..n])^b_R
-            let mut exp = vec![];
-            let temp_a: Vec<C::ScalarField> = hadamard_product(&a_L, &params.factors_G[n..2*n].to_vec());
-            let temp_b: Vec<C::ScalarField> = hadamard_product(&b_R, &params.factors_H[0..n].to_vec());
-            exp.extend(temp_a);
-            exp.extend(temp_b);
-            exp.push(c_L);
-
-            let mut base = G_R.to_vec();
-            base.extend(H_L.to_vec());
-            base.push(params.u);
-
-            let com_L = C::msm(&base, &exp).unwrap().into_affine();
-
-            // compute R = (G_L^factors_G[0..n])^a_R + (H_R^factors_H[n..2n])^b_L
-            let mut exp = vec![];
-            let temp_a: Vec<C::ScalarField> = hadamard_product(&a_R, &params.factors_G[0..n].to_vec());
-            let temp_b: Vec<C::ScalarField> = hadamard_product(&b_L, &params.factors_H[n..2*n].to_vec());
-            exp.extend(temp_a);
-            exp.extend(temp_b);
-            exp.push(c_R);
-
-            let mut base = G_L.to_vec();
-            base.extend(H_R.to_vec());
-            base.push(params.u);
-
-            let com_R = C::msm(&base, &exp).unwrap().into_affine();
-
+        // the first round folds the factors into the generators, the later rounds fold without factors
+        let mut state = FoldState::new(params, key, vec_a, vec_b);
+        let mut factors = Some((params.factors_G.as_slice(), params.factors_H.as_slice()));
+        while state.vec_a.len() != 1 {
+            let ((com_L, com_R), x) = fold_round(&mut state, transcript, factors.take())?;
             vec_L.push(com_L);
             vec_R.push(com_R);
-
-            // get challenge
-            transcript.append_serializable_element(b"commitments L, R", &[com_L, com_R])?;
-            let x = transcript.get_and_append_challenge(b"challenge")?;
-            let x_inv = x.inverse().unwrap();
             challenges.push(x);
-
-            // // sanity check: L,R are correct
-            // // L^{x^2}*(A*B)*R^{x_inv^2}*u^{<a,b>} = fold_G^fold_a * fold_H^fold_b * u^{<fold_a,fold_b>}
-            // let LHS = com_L*(x*x)
-            //     + C::msm(&vec_G, &hadamard_product(&vec_a, &factors_G)).unwrap()
-            //     + C::msm(&vec_H, &hadamard_product(&vec_b, &factors_H)).unwrap()
-            //     + com_R*(x_inv*x_inv)
-            //     + u*(inner_product(&vec_a, &vec_b));
-
-            // fold vec_G, vec_H, vec_a, vec_b
-            vec_a = vec_add(&scalar_product(&a_L, &x), &scalar_product(&a_R, &x_inv)).clone();
-            vec_b = vec_add(&scalar_product(&b_L, &x_inv), &scalar_product(&b_R, &x)).clone();
-            vec_G = vec![];
-            vec_H = vec![];
-            for i in 0..n {
-                let term_G = C::msm(&[G_L[i], G_R[i]], &[x_inv*params.factors_G[i], x*params.factors_G[n+i]]).unwrap();
-                let term_H = C::msm(&[H_L[i], H_R[i]], &[x*params.factors_H[i], x_inv*params.factors_H[n+i]]).unwrap();
-                vec_G.push(term_G.into_affine());
-                vec_H.push(term_H.into_affine());
-            }
-
-            // // sanity check: L, R are correct
-            // // L^{x^2}*(A*B)*R^{x_inv^2}*u^{<a,b>} = fold_G^fold_a * fold_H^fold_b * u^{<fold_a,fold_b>}
-            // let RHS = C::msm(&vec_G, &vec_a).unwrap() + C::msm(&vec_H, &vec_b).unwrap() + u*(inner_product(&vec_a, &vec_b));
-            // assert_eq!(LHS, RHS);
         }
 
-        // loop step
-        while n !=1 {
-            n = n / 2;
-            let (a_L, a_R) = vec_split(&vec_a, n);
-            let (b_L, b_R) = vec_split(&vec_b, n);
-            let (G_L, G_R) = vec_split(&vec_G, n);
-            let (H_L, H_R) = vec_split(&vec_H, n);
-
-            let c_L = inner_product(&a_L, &b_R);
-            let c_R = inner_product(&a_R, &b_L);
-
-            let mut exp = a_L.clone();
-            exp.extend(b_R.clone());
-            exp.push(c_L);
+        end_timer!(start);
+        Ok(InnerProductProof {
+            vec_L,
+            vec_R,
+            a: state.vec_a[0],
+            b: state.vec_b[0],
+            challenges,
+        })
+    }
 
-            let mut base = G_R.to_vec();
-            base.extend(H_L.to_vec());
-            base.push(params.u);
+    /// Runs one round of the streaming prover: folds the vectors with the challenge of the
+    /// previous round, then returns the commitments L, R of the next round,
+    /// or None once the vectors are folded to length 1
+    pub fn prove_step(
+        state: &mut InnerProductProverState<C>,
+    ) -> Result<Option<IpaRoundCommitments<C::Affine>>, SigmaErrors> {
+        state.fold_pending()?;
+        let n = state.vec_a.len() / 2;
+        if n == 0 {
+            return Ok(None);
+        }
+        let (a_L, a_R) = state.vec_a.split_at(n);
+        let (b_L, b_R) = state.vec_b.split_at(n);
+        let (G_L, G_R) = state.vec_G.split_at(n);
+        let (H_L, H_R) = state.vec_H.split_at(n);
+        let (c_L, c_R) = cross_terms(a_L, a_R, b_L, b_R);
 
-            let com_L = C::msm(&base, &exp).unwrap().into_affine();
+        // the halves are committed separately, so that no concatenated copy of the vectors is held
+        let com_L = msm::<C>(G_R, a_L, SigmaErrors::InvalidParameters)? + msm::<C>(H_L, b_R, SigmaErrors::InvalidParameters)? + state.u*c_L;
+        let com_R = msm::<C>(G_L, a_R, SigmaErrors::InvalidParameters)? + msm::<C>(H_R, b_L, SigmaErrors::InvalidParameters)? + state.u*c_R;
+        let coms = C::normalize_batch(&[com_L, com_R]);
+        let (com_L, com_R) = (coms[0], coms[1]);
 
-            let mut exp = vec![];
-            exp.extend(a_R.clone());
-            exp.extend(b_L.clone());
-            exp.push(c_R);
+        state.transcript.append_serializable_element(b"commitments L, R", &[com_L, com_R])?;
+        state.vec_L.push(com_L);
+        state.vec_R.push(com_R);
+        state.pending = true;
+        Ok(Some((com_L, com_R)))
+    }
 
-            let mut base = G_L.to_vec();
-            base.extend(H_R.to_vec());
-            base.push(params.u);
+    /// Runs the remaining rounds of the streaming prover and outputs its proof,
+    /// which is identical to the proof of prove if no challenge was injected
+    pub fn prove_finish(
+        mut state: InnerProductProverState<C>,
+    ) -> Result<InnerProductProof<C>, SigmaErrors> {
+        while Self::prove_step(&mut state)?.is_some() {}
+        Ok(InnerProductProof {
+            vec_L: state.vec_L,
+            vec_R: state.vec_R,
+            a: state.vec_a[0],
+            b: state.vec_b[0],
+            challenges: state.challenges,
+        })
+    }
 
-            let com_R = C::msm(&base, &exp).unwrap().into_affine();
+    /// Same as prove for binary folding, but reads vec_a, vec_b and the generators by chunks
+    /// from their sources, so that only the vectors folded by the first round are held in memory.
+    /// The generator sources must output vec_G and vec_H scaled by factors_G and factors_H
+    pub fn prove_streaming<A, B, G, H>(
+        domain: &'static [u8],
+        u: C::Affine,
+        vec_a: &A,
+        vec_b: &B,
+        vec_G: &G,
+        vec_H: &H,
+    ) -> Result<InnerProductProof<C>, SigmaErrors>
+    where
+        A: VectorSource<C::ScalarField> + ?Sized,
+        B: VectorSource<C::ScalarField> + ?Sized,
+        G: VectorSource<C::Affine> + ?Sized,
+        H: VectorSource<C::Affine> + ?Sized,
+    {
+        let n = vec_a.len();
+        if vec_b.len() != n || vec_G.len() != n || vec_H.len() != n {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+        let transcript = binary_transcript::<C::ScalarField>(domain, n)?;
+        let start = start_timer!(|| "running streaming inner product argument prove algorithm...");
 
-            vec_L.push(com_L);
-            vec_R.push(com_R);
+        let half = n / 2;
+        let mut state = InnerProductProverState {
+            transcript,
+            u,
+            vec_a: Vec::with_capacity(half.max(1)),
+            vec_b: Vec::with_capacity(half.max(1)),
+            vec_G: Vec::with_capacity(half.max(1)),
+            vec_H: Vec::with_capacity(half.max(1)),
+            vec_L: vec![],
+            vec_R: vec![],
+            challenges: vec![],
+            pending: false,
+            injected: None,
+        };
+        if n == 1 {
+            state.vec_a = vec_a.read_chunk(0, 1);
+            state.vec_b = vec_b.read_chunk(0, 1);
+            end_timer!(start);
+            return Self::prove_finish(state);
+        }
 
-            transcript.append_serializable_element(b"commitments L, R", &[com_L, com_R])?;
-            let x = transcript.get_and_append_challenge(b"challenge")?;
-            let x_inv = x.inverse().unwrap();
-            challenges.push(x);
-            // // sanity check: L, R are correct
-            // // L^{x^2}*(A*B)*R^{x_inv^2}*u^{<a,b>} = fold_G^fold_a * fold_H^fold_b * u^{<fold_a,fold_b>}
-            // let LHS = com_L*(x*x)
-            //     + C::msm(&vec_G, &vec_a).unwrap()
-            //     + C::msm(&vec_H, &vec_b).unwrap()
-            //     + com_R*(x_inv*x_inv)
-            //     + u*(inner_product(&vec_a, &vec_b));
-
-            vec_a = vec_add(&scalar_product(&a_L, &x), &scalar_product(&a_R, &x_inv)).clone();
-            vec_b = vec_add(&scalar_product(&b_L, &x_inv), &scalar_product(&b_R, &x)).clone();
-            vec_G = vec![];
-            vec_H = vec![];
-            for i in 0..n {
-                let term_G = C::msm(&[G_L[i], G_R[i]], &[x_inv, x]).unwrap();
-                let term_H = C::msm(&[H_L[i], H_R[i]], &[x, x_inv]).unwrap();
-                vec_G.push(term_G.into_affine());
-                vec_H.push(term_H.into_affine());
+        // first pass: the commitments L, R accumulated over the chunks of the halves
+        let (mut c_L, mut c_R) = (C::ScalarField::zero(), C::ScalarField::zero());
+        let (mut com_L, mut com_R) = (C::zero(), C::zero());
+        for offset in (0..half).step_by(STREAMING_CHUNK_LEN) {
+            let len = STREAMING_CHUNK_LEN.min(half - offset);
+            let (a_L, a_R) = read_halves(vec_a, half, offset, len);
+            let (b_L, b_R) = read_halves(vec_b, half, offset, len);
+            let (G_L, G_R) = read_halves(vec_G, half, offset, len);
+            let (H_L, H_R) = read_halves(vec_H, half, offset, len);
+            let (c_L_i, c_R_i) = cross_terms(&a_L, &a_R, &b_L, &b_R);
+            c_L += c_L_i;
+            c_R += c_R_i;
+            com_L += msm::<C>(&G_R, &a_L, SigmaErrors::InvalidParameters)? + msm::<C>(&H_L, &b_R, SigmaErrors::InvalidParameters)?;
+            com_R += msm::<C>(&G_L, &a_R, SigmaErrors::InvalidParameters)? + msm::<C>(&H_R, &b_L, SigmaErrors::InvalidParameters)?;
+        }
+        let coms = C::normalize_batch(&[com_L + u*c_L, com_R + u*c_R]);
+        let (com_L, com_R) = (coms[0], coms[1]);
+        state.transcript.append_serializable_element(b"commitments L, R", &[com_L, com_R])?;
+        let (x, x_inv) = match nonzero_challenge(&mut state.transcript, b"challenge") {
+            Ok(x) => x,
+            Err(e) => {
+                end_timer!(start);
+                return Err(e);
             }
-            // // sanity check: L, R are correct
-            // // L^{x^2}*(A*B)*R^{x_inv^2}*u^{<a,b>} = fold_G^fold_a * fold_H^fold_b * u^{<fold_a,fold_b>}
-            // let RHS = C::msm(&vec_G, &vec_a).unwrap() + C::msm(&vec_H, &vec_b).unwrap() + u*(inner_product(&vec_a, &vec_b));
-            // assert_eq!(LHS, RHS);
+        };
+        state.vec_L.push(com_L);
+        state.vec_R.push(com_R);
+        state.challenges.push(x);
+
+        // second pass: the first fold, chunk by chunk
+        let mut terms = Vec::with_capacity(STREAMING_CHUNK_LEN);
+        for offset in (0..half).step_by(STREAMING_CHUNK_LEN) {
+            let len = STREAMING_CHUNK_LEN.min(half - offset);
+            let (a_L, a_R) = read_halves(vec_a, half, offset, len);
+            let (b_L, b_R) = read_halves(vec_b, half, offset, len);
+            state.vec_a.extend(fold_scalars(&a_L, &a_R, &x, &x_inv));
+            state.vec_b.extend(fold_scalars(&b_L, &b_R, &x_inv, &x));
+            let (G_L, G_R) = read_halves(vec_G, half, offset, len);
+            let mut G = [G_L, G_R].concat();
+            fold_generators_in_place::<C>(&mut G, len, &x_inv, &x, &mut terms);
+            state.vec_G.extend(G);
+            let (H_L, H_R) = read_halves(vec_H, half, offset, len);
+            let mut H = [H_L, H_R].concat();
+            fold_generators_in_place::<C>(&mut H, len, &x, &x_inv, &mut terms);
+            state.vec_H.extend(H);
         }
 
+        let proof = Self::prove_finish(state);
         end_timer!(start);
-        Ok(InnerProductProof {
-            vec_L,
-            vec_R,
-            a: vec_a[0],
-            b: vec_b[0],
-            challenges,
-        })
+        proof
+    }
+
+    /// Computes the scalars vec_box = (s_0, ..., s_{n-1}) with
+    /// s_i = x_0^{b(i,0)} * ... * x_{log_n-1}^{b(i,log_n-1)}
+    /// where b(i,j) = 1 if the (log_n-1-j)-th bit of i is set and -1 otherwise,
+    /// so that the folded generator G equals vec_G^{vec_box}; a zero challenge gives InvalidProof
+    pub fn compute_s_vector(challenges: &[C::ScalarField]) -> Result<Vec<C::ScalarField>, SigmaErrors> {
+        let all_inv = challenges.iter()
+            .fold(C::ScalarField::from(1u64), |acc, x| acc * x)
+            .inverse()
+            .ok_or(SigmaErrors::InvalidProof("zero challenge value".to_string()))?;
+        let challenges_sq: Vec<C::ScalarField> = challenges.iter().map(|x| x.square()).collect();
+        Ok(box_scalars(all_inv, &challenges_sq))
     }
 
     pub fn verify(
+        domain: &'static [u8],
+        n: usize,
+        target_P: C,
+        params: &InnerProductParam<C>,
+        proof: &InnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        transcript.append_message(b"IPAdomain", domain)?;
+        Self::verify_with_transcript(&mut transcript, n, target_P, params, proof)
+    }
+
+    /// Same as verify, but runs on the transcript of an outer protocol,
+    /// which must be in the same state as the transcript passed to prove_with_transcript
+    pub fn verify_with_transcript(
+        transcript: &mut ProofTranscript<C::ScalarField>,
         n: usize,
         target_P: C,
         params: &InnerProductParam<C>,
         proof: &InnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        Self::verify_with_transcript_and_terms(transcript, n, &[(target_P.into_affine(), C::ScalarField::one())], params, proof)
+    }
+
+    /// Same as verify, but the target P = prod_i point_i^{scalar_i} is given by its terms,
+    /// which are merged into the final msm of the verifier instead of being computed by the caller
+    pub fn verify_with_terms(
+        domain: &'static [u8],
+        n: usize,
+        terms: &[(C::Affine, C::ScalarField)],
+        params: &InnerProductParam<C>,
+        proof: &InnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        transcript.append_message(b"IPAdomain", domain)?;
+        Self::verify_with_transcript_and_terms(&mut transcript, n, terms, params, proof)
+    }
+
+    /// Same as verify_with_terms, but runs on the transcript of an outer protocol
+    pub fn verify_with_transcript_and_terms(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        n: usize,
+        terms: &[(C::Affine, C::ScalarField)],
+        params: &InnerProductParam<C>,
+        proof: &InnerProductProof<C>,
     ) -> Result<(), SigmaErrors> {
         let start = start_timer!(|| "running inner product argument verify algorithm...");
-        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingSignature");
+        let result = Self::verify_rounds(transcript, n, terms, None, params, proof);
+        end_timer!(start);
+        result
+    }
 
-        assert_eq!(params.vec_G.len(), n);
-        let log_n = proof.vec_L.len();
-        let mut vec_G = params.vec_G.clone();
-        let mut vec_H = params.vec_H.clone();
+    /// Same as verify_with_transcript_and_terms, but for the generators vec_G[i]^{factors_G[i]} * vec_S[i]^c
+    /// shifted by (vec_S, c) on their first vec_S.len() entries, so that a caller whose generators depend on
+    /// its own challenges passes them as two fixed generator vectors instead of computing them,
+    /// only the binary folding is supported
+    pub fn verify_with_shifted_generators(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        n: usize,
+        terms: &[(C::Affine, C::ScalarField)],
+        params: &InnerProductParam<C>,
+        shift: GeneratorShift<'_, C::Affine, C::ScalarField>,
+        proof: &InnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let start = start_timer!(|| "running inner product argument verify algorithm...");
+        let result = Self::verify_rounds(transcript, n, terms, Some(shift), params, proof);
+        end_timer!(start);
+        result
+    }
 
-        // prevents overflow
-        if log_n >= 32 {
-            return Err(
-                SigmaErrors::InvalidParameters("vector size is too large".to_string())
-            );
+    /// Verifies a proof of prove_with_public_product for the target P = prod_i point_i^{scalar_i} given by
+    /// its terms and the public inner product c, the verifier adds u'^c to the target itself
+    pub fn verify_with_public_product(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        n: usize,
+        terms: &[(C::Affine, C::ScalarField)],
+        c: C::ScalarField,
+        params: &InnerProductParam<C>,
+        proof: &InnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        Self::verify_shifted_with_public_product(transcript, n, terms, c, params, None, proof)
+    }
+
+    /// Same as verify_with_public_product, with the generators of vec_G shifted as in verify_with_shifted_generators
+    pub fn verify_shifted_with_public_product(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        n: usize,
+        terms: &[(C::Affine, C::ScalarField)],
+        c: C::ScalarField,
+        params: &InnerProductParam<C>,
+        shift: Option<GeneratorShift<'_, C::Affine, C::ScalarField>>,
+        proof: &InnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let params = public_product_param(transcript, params, c)?;
+        let mut terms = terms.to_vec();
+        terms.push((params.u, c));
+        let start = start_timer!(|| "running inner product argument verify algorithm...");
+        let result = Self::verify_rounds(transcript, n, &terms, shift, &params, proof);
+        end_timer!(start);
+        result
+    }
+
+    // the checks of verify_with_transcript_and_terms, the caller closes the timer on every return
+    fn verify_rounds(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        n: usize,
+        terms: &[(C::Affine, C::ScalarField)],
+        shift: Option<GeneratorShift<'_, C::Affine, C::ScalarField>>,
+        params: &InnerProductParam<C>,
+        proof: &InnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        if params.vec_G.len() != n || params.vec_H.len() != n
+            || params.factors_G.len() != n || params.factors_H.len() != n
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+        if let Some((vec_S, _)) = shift
+            && (params.arity == FoldingArity::Four || vec_S.len() > n)
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "the generator shift needs the binary folding and at most n generators".to_string(),
+            ));
+        }
+        if params.arity == FoldingArity::Four {
+            return Self::verify_quaternary(n, terms, params, proof, transcript);
+        }
+        if !n.is_power_of_two() {
+            return Err(SigmaErrors::InvalidParameters(
+                "vector length is not power of two".to_string(),
+            ));
         }
-        if n != (1 << log_n) {
+        let log_n = proof.vec_L.len();
+        if n != rounds_to_length(log_n, usize::BITS)? || proof.vec_R.len() != log_n || proof.challenges.len() != log_n {
             return Err(
                 SigmaErrors::InvalidProof("incorrect proof length".to_string())
             );
         }
 
         transcript.append_field_element(b"IPAsize", &C::ScalarField::from(n as u128))?;
+        transcript.append_field_element(b"IPAarity", &C::ScalarField::from(params.arity.as_usize() as u64))?;
 
         // check challenges x at each round
-        let mut challenges = Vec::with_capacity(log_n);
-        let mut challenges_sq:Vec<C::ScalarField> = Vec::with_capacity(log_n);
-        let mut challenges_inv_sq:Vec<C::ScalarField> = Vec::with_capacity(log_n);
-        let mut all_inv = C::ScalarField::from(1u64);
+        let mut challenges_sq: Vec<C::ScalarField> = Vec::with_capacity(log_n);
+        let mut challenges_inv_sq: Vec<C::ScalarField> = Vec::with_capacity(log_n);
+        let mut all_x = C::ScalarField::one();
+        let mut all_inv = C::ScalarField::one();
         for i in 0..log_n {
             transcript.append_serializable_element(b"commitments L, R", &[proof.vec_L[i], proof.vec_R[i]])?;
-            let x = transcript.get_and_append_challenge(b"challenge")?;
-            challenges.push(x);
-            let x_inv = x.inverse().unwrap();
+            let (x, x_inv) = nonzero_challenge(transcript, b"challenge")?;
             challenges_sq.push(x*x);
             challenges_inv_sq.push(x_inv*x_inv);
+            all_x *= x;
             all_inv *= x_inv;
             if x != proof.challenges[i] {
                 return Err(SigmaErrors::InvalidProof("invalid challenge value".to_string()));
@@ -260,46 +526,9 @@ impl<C: CurveGroup> InnerProductProtocol<C>
         // the verifier can record their scalars in log(n) boxes
         // and execute the msm at the final round
         // details can be referred to https://doc-internal.dalek.rs/bulletproofs/inner_product_proof/index.html
-        let mut vec_box = Vec::with_capacity(log_n);
-        vec_box.push(all_inv);
-        for i in 1..n {
-            let log_i = (32 - 1 - (i as u32).leading_zeros()) as usize; // e.g., 64u32 has 26 leading zeros
-            let k = 1 << log_i; // = 2^{lg_i}
-            let x_log_i_sq = challenges_sq[log_n-1-log_i];
-            vec_box.push(vec_box[i-k] * x_log_i_sq);
-        }
-        let mut vec_box_reverse = vec_box.clone();
-        vec_box_reverse.reverse();
-
-        // // sanity check: make sure the final vectors G, H
-        // // computed from the original G, H directly are correct
-        // let mut expected_vec_G = Vec::with_capacity(n);
-        // let mut expected_vec_H = Vec::with_capacity(n);
-        // for i in 0..vec_G.len(){
-        //     expected_vec_G.push(C::msm(&[vec_G[i]], &[factors_G[i]]).unwrap().into_affine());
-        //     expected_vec_H.push(C::msm(&[vec_H[i]], &[factors_H[i]]).unwrap().into_affine());
-        // }
-        //
-        // let mut m = n.clone();
-        // for j in 0..proof.vec_L.len() {
-        //     m = m / 2;
-        //     let (G_L, G_R) = vec_split(&expected_vec_G, m);
-        //     let (H_L, H_R) = vec_split(&expected_vec_H, m);
-        //     expected_vec_G = vec![];
-        //     expected_vec_H = vec![];
-        //     for i in 0..m {
-        //         let term_G = C::msm(&[G_L[i], G_R[i]], &[challenges[j].inverse().unwrap(), challenges[j]]).unwrap();
-        //         let term_H = C::msm(&[H_L[i], H_R[i]], &[challenges[j], challenges[j].inverse().unwrap()]).unwrap();
-        //         expected_vec_G.push(term_G.into_affine());
-        //         expected_vec_H.push(term_H.into_affine());
-        //     }
-        // }
-        // assert_eq!(expected_vec_G[0], C::msm(&vec_G, &hadamard_product(&vec_box, &factors_G)).unwrap().into_affine());
-        // assert_eq!(expected_vec_H[0], C::msm(&vec_H, &hadamard_product(&vec_box_reverse, &factors_H)).unwrap().into_affine());
-
-        // compute factors_g*vec_box*a and factors_h*vec_box_reverse*b
-        let g_a_box = scalar_product(&hadamard_product(&vec_box, &params.factors_G), &proof.a);
-        let h_b_box = scalar_product(&hadamard_product(&vec_box_reverse, &params.factors_H), &proof.b);
+        // the folded G is vec_G^{vec_box} and the folded H is vec_H^{vec_box_reverse},
+        // where vec_box_reverse[i] = vec_box[n-1-i] = 1/vec_box[i] flips the roles of x and x^{-1},
+        // so a and b are folded into the first entry of the boxes instead of scaling them afterwards
 
         // compute P =
         // u^{ab} *
@@ -307,74 +536,2072 @@ impl<C: CurveGroup> InnerProductProtocol<C>
         // vec_h^{factors_H * b * vec_box_reverse} *
         // (L_0*...*L_{log_n-1})^{x^2} *
         // (R_0*...*R_{log_n-1}})^{x^-2}
-        let neg_challenges_sq = challenges_sq.iter().map(|&xi| -xi).collect::<Vec<C::ScalarField>>();
-        let neg_challenges_inv_sq = challenges_inv_sq.iter().map(|&xi| -xi).collect::<Vec<C::ScalarField>>();
+        let mut exp = Vec::with_capacity(2*n + 2*log_n + 1);
+        exp.push(proof.a*proof.b);
+        let box_G = box_scalars(proof.a*all_inv, &challenges_sq);
+        exp.extend(box_G.iter().zip(&params.factors_G).map(|(s, f)| *s * f));
+        exp.extend(
+            box_scalars(proof.b*all_x, &challenges_inv_sq).iter()
+                .zip(&params.factors_H).map(|(s, f)| *s * f)
+        );
+        exp.extend(vec_neg(&challenges_sq));
+        exp.extend(vec_neg(&challenges_inv_sq));
 
-        let mut exp = vec![proof.a*proof.b];
-        exp.extend(g_a_box);
-        exp.extend(h_b_box);
-        exp.extend(neg_challenges_sq);
-        exp.extend(neg_challenges_inv_sq);
+        let mut base = Vec::with_capacity(2*n + 2*log_n + 1);
+        base.push(params.u);
+        base.extend_from_slice(&params.vec_G);
+        base.extend_from_slice(&params.vec_H);
+        base.extend_from_slice(&proof.vec_L);
+        base.extend_from_slice(&proof.vec_R);
 
-        let mut base = vec![params.u];
-        base.extend(vec_G);
-        base.extend(vec_H);
-        base.extend(proof.vec_L.clone());
-        base.extend(proof.vec_R.clone());
+        // the shifted generators vec_S^c are folded by the same scalars as vec_G, without its factors
+        if let Some((vec_S, c)) = shift {
+            base.extend_from_slice(vec_S);
+            exp.extend(box_G[..vec_S.len()].iter().map(|s| *s * c));
+        }
 
-        let expected_P = C::msm(&base, &exp).unwrap();
+        check_target::<C>(base, exp, terms)
+    }
 
-        end_timer!(start);
-        if expected_P == target_P {
-            Ok(())
+    /// One-sided IPA for a public vector public_b, the relation is
+    /// vec_G^{factors_G * vec_a} * u^<vec_a, public_b> = P
+    /// so that neither vec_H nor factors_H is used, and only the binary folding is supported
+    pub fn prove_public_b(
+        domain: &'static [u8],
+        params: &InnerProductParam<C>,
+        mut vec_a: Vec<C::ScalarField>,
+        public_b: &[C::ScalarField],
+    ) -> Result<OneSidedInnerProductProof<C>, SigmaErrors> {
+        // initialization
+        let start = start_timer!(|| "running one-sided inner product argument prove algorithm...");
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        transcript.append_message(b"IPAdomain", domain)?;
+
+        let mut n = params.vec_G.len();
+        if vec_a.len() != n || public_b.len() != n || params.factors_G.len() != n {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
         }
-        else {
-            Err(SigmaErrors::InvalidProof("invalid IPA proof".to_string()))
+        if !n.is_power_of_two() || params.arity != FoldingArity::Two {
+            return Err(SigmaErrors::InvalidParameters(
+                "vector length is not power of two or folding is not binary".to_string(),
+            ));
         }
-    }
-}
 
-#[cfg(test)]
-mod tests {
-    use ark_ec::VariableBaseMSM;
-    use super::*;
-    use ark_secp256k1::{Fr, Projective, Affine};
-    use ark_std::UniformRand;
-    use toolbox::vec::convert;
+        transcript.append_field_element(b"IPAsize", &C::ScalarField::from(n as u128))?;
+        transcript.append_serializable_element(b"IPApublic", &public_b.to_vec())?;
 
-    #[test]
-    fn test_ipa() {
-        let mut rng = ark_std::test_rng();
-        let n = 4;
-        let vec_a: Vec<Fr> = convert(&[1u64, 2u64, 3u64, 4u64]);
-        let vec_b: Vec<Fr> = convert(&[1u64, 1u64, 1u64, 1u64]);
-        let vec_G = vec![Affine::rand(&mut rng); vec_a.len()];
-        let vec_H = vec![Affine::rand(&mut rng); vec_a.len()];
-        let u = Affine::rand(&mut rng);
-        let fac_G: Vec<Fr> = convert(&[1u64, 2u64, 3u64, 4u64]);
-        let fac_H: Vec<Fr> = convert(&[1u64, 1u64, 1u64, 1u64]);
+        let mut vec_b = public_b.to_vec();
+        let mut vec_G = params.vec_G.clone();
+        // factors are only applied in the first round
+        let mut factors_G = params.factors_G.clone();
+        let log_n = n.trailing_zeros() as usize;
+        let mut vec_L = Vec::with_capacity(log_n);
+        let mut vec_R = Vec::with_capacity(log_n);
+        let mut challenges = Vec::with_capacity(log_n);
 
-        type IPA = InnerProductProtocol<Projective>;
-        let params = InnerProductParam {
-            factors_G: fac_G.clone(),
-            factors_H: fac_H.clone(),
-            u,
-            vec_G: vec_G.clone(),
-            vec_H: vec_H.clone()
-        };
+        while n != 1 {
+            n /= 2;
+            let (a_L, a_R) = vec_split(&vec_a, n);
+            let (b_L, b_R) = vec_split(&vec_b, n);
+            let (G_L, G_R) = vec_split(&vec_G, n);
+            let (c_L, c_R) = cross_terms(&a_L, &a_R, &b_L, &b_R);
 
-        let proof = IPA::prove(&params, vec_a.clone(), vec_b.clone()).unwrap();
-        // compute P with uncompressed vectors vec_a, vec_b
-        let t = inner_product(&vec_a, &vec_b);
-        let mut exp = vec![];
-        exp.extend(hadamard_product(&vec_a, &fac_G));
-        exp.extend(hadamard_product(&vec_b, &fac_H));
-        let mut base = vec![];
-        base.extend(vec_G.clone());
-        base.extend(vec_H.clone());
+            // compute L = (G_R^factors_G[n..2n])^a_L * u^{c_L}, R = (G_L^factors_G[0..n])^a_R * u^{c_R}
+            let mut exp_L = hadamard_product(&a_L, &factors_G[n..2*n]);
+            exp_L.push(c_L);
+            let mut base_L = G_R.clone();
+            base_L.push(params.u);
+            let mut exp_R = hadamard_product(&a_R, &factors_G[0..n]);
+            exp_R.push(c_R);
+            let mut base_R = G_L.clone();
+            base_R.push(params.u);
 
-        let P = Projective::msm(&base, &exp).unwrap() + u*t;
-        IPA::verify(n, P, &params, &proof).unwrap();
+            let (com_L, com_R) = commit_cross::<C>(&base_L, &exp_L, &base_R, &exp_R)?;
+            vec_L.push(com_L);
+            vec_R.push(com_R);
+
+            transcript.append_serializable_element(b"commitments L, R", &[com_L, com_R])?;
+            let (x, x_inv) = nonzero_challenge(&mut transcript, b"challenge")?;
+            challenges.push(x);
+
+            // fold vec_a, vec_b, vec_G
+            vec_a = fold_scalars(&a_L, &a_R, &x, &x_inv);
+            vec_b = fold_scalars(&b_L, &b_R, &x_inv, &x);
+            vec_G = fold_generators::<C>(
+                &G_L,
+                &G_R,
+                &scalar_product(&factors_G[0..n], &x_inv),
+                &scalar_product(&factors_G[n..2*n], &x),
+            );
+            factors_G = vec![C::ScalarField::one(); n];
+        }
+
+        end_timer!(start);
+        Ok(OneSidedInnerProductProof {
+            vec_L,
+            vec_R,
+            a: vec_a[0],
+            challenges,
+        })
     }
-}
 
+    /// Verifies a one-sided IPA proof, where the verifier folds public_b by itself as
+    /// b = <public_b, vec_box> and checks
+    /// P = u^{ab} * vec_G^{factors_G * a * vec_box} * (L_0*...*L_{log_n-1})^{-x^2} * (R_0*...*R_{log_n-1})^{-x^-2}
+    pub fn verify_public_b(
+        domain: &'static [u8],
+        n: usize,
+        target_P: C,
+        params: &InnerProductParam<C>,
+        public_b: &[C::ScalarField],
+        proof: &OneSidedInnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let start = start_timer!(|| "running one-sided inner product argument verify algorithm...");
+        let result = Self::verify_public_b_rounds(domain, n, target_P, params, public_b, proof);
+        end_timer!(start);
+        result
+    }
+
+    // the checks of verify_public_b, the caller closes the timer on every return
+    fn verify_public_b_rounds(
+        domain: &'static [u8],
+        n: usize,
+        target_P: C,
+        params: &InnerProductParam<C>,
+        public_b: &[C::ScalarField],
+        proof: &OneSidedInnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        transcript.append_message(b"IPAdomain", domain)?;
+
+        if params.vec_G.len() != n || public_b.len() != n || params.factors_G.len() != n
+            || params.arity != FoldingArity::Two
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different or folding is not binary".to_string(),
+            ));
+        }
+        if !n.is_power_of_two() {
+            return Err(SigmaErrors::InvalidParameters(
+                "vector length is not power of two".to_string(),
+            ));
+        }
+        let log_n = proof.vec_L.len();
+        if n != rounds_to_length(log_n, usize::BITS)? || proof.vec_R.len() != log_n || proof.challenges.len() != log_n {
+            return Err(
+                SigmaErrors::InvalidProof("incorrect proof length".to_string())
+            );
+        }
+
+        transcript.append_field_element(b"IPAsize", &C::ScalarField::from(n as u128))?;
+        transcript.append_serializable_element(b"IPApublic", &public_b.to_vec())?;
+
+        // check challenges x at each round
+        let mut challenges = Vec::with_capacity(log_n);
+        let mut exp_LR = Vec::with_capacity(2*log_n);
+        for i in 0..log_n {
+            transcript.append_serializable_element(b"commitments L, R", &[proof.vec_L[i], proof.vec_R[i]])?;
+            let (x, x_inv) = nonzero_challenge(&mut transcript, b"challenge")?;
+            if x != proof.challenges[i] {
+                return Err(SigmaErrors::InvalidProof("invalid challenge value".to_string()));
+            }
+            challenges.push(x);
+            exp_LR.push(-(x*x));
+            exp_LR.push(-(x_inv*x_inv));
+        }
+
+        // the folded public vector b and generator G share the scalars vec_box
+        let vec_box = Self::compute_s_vector(&challenges)?;
+        let b = inner_product(public_b, &vec_box);
+
+        let mut exp = vec![proof.a*b];
+        exp.extend(scalar_product(&hadamard_product(&vec_box, &params.factors_G), &proof.a));
+        exp.extend(exp_LR);
+        let mut base = vec![params.u];
+        base.extend(params.vec_G.clone());
+        for (com_L, com_R) in proof.vec_L.iter().zip(proof.vec_R.iter()) {
+            base.push(*com_L);
+            base.push(*com_R);
+        }
+
+        let expected_P = msm(&base, &exp, SigmaErrors::InvalidProof)?;
+        if ct_eq_affine(expected_P, target_P) {
+            Ok(())
+        }
+        else {
+            Err(SigmaErrors::InvalidProof("invalid IPA proof".to_string()))
+        }
+    }
+
+    /// Zero-knowledge IPA for a hiding target with the blinding generator h, the relation is
+    /// vec_G^{factors_G * vec_a} * vec_H^{factors_H * vec_b} * u^<vec_a, vec_b> * h^rho = P
+    /// the prover commits to random vectors d_a, d_b as
+    /// S1 = vec_G^{factors_G * d_a} * vec_H^{factors_H * d_b} * u^{<vec_a, d_b> + <d_a, vec_b>} * h^{rho_1}
+    /// S2 = u^{<d_a, d_b>} * h^{rho_2}
+    /// and runs the IPA on the masked vectors vec_a + e*d_a, vec_b + e*d_b for P * S1^e * S2^{e^2} * h^{-rho'}
+    /// where rho' = rho + e*rho_1 + e^2*rho_2 is opened to the verifier
+    pub fn prove_blinded<R: Rng>(
+        rng: &mut R,
+        domain: &'static [u8],
+        params: &InnerProductParam<C>,
+        h: C::Affine,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+        rho: C::ScalarField,
+    ) -> Result<BlindedInnerProductProof<C>, SigmaErrors> {
+        let start = start_timer!(|| "running blinded inner product argument prove algorithm...");
+        let n = params.vec_G.len();
+        if params.vec_H.len() != n || vec_a.len() != n || vec_b.len() != n
+            || params.factors_G.len() != n || params.factors_H.len() != n
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+
+        // the target P is absorbed by the transcript before the masking challenge
+        let target_P = blinded_commit(params, h, &vec_a, &vec_b, inner_product(&vec_a, &vec_b), rho)?;
+        let d_a: Vec<C::ScalarField> = (0..n).map(|_| C::ScalarField::rand(rng)).collect();
+        let d_b: Vec<C::ScalarField> = (0..n).map(|_| C::ScalarField::rand(rng)).collect();
+        let rho_1 = C::ScalarField::rand(rng);
+        let rho_2 = C::ScalarField::rand(rng);
+        let com_S1 = blinded_commit(
+            params, h, &d_a, &d_b,
+            inner_product(&vec_a, &d_b) + inner_product(&d_a, &vec_b),
+            rho_1,
+        )?;
+        let com_S2 = params.u * inner_product(&d_a, &d_b) + h * rho_2;
+        let coms = C::normalize_batch(&[com_S1, com_S2]);
+        let (com_S1, com_S2) = (coms[0], coms[1]);
+
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        transcript.append_message(b"IPAdomain", domain)?;
+        transcript.append_serializable_element(b"IPAblinded", &[target_P.into_affine(), com_S1, com_S2])?;
+        let (e, _) = nonzero_challenge(&mut transcript, b"challenge e")?;
+
+        // mask the vectors and open the blinding of the masked target
+        let vec_a = vec_add(&vec_a, &scalar_product(&d_a, &e));
+        let vec_b = vec_add(&vec_b, &scalar_product(&d_b, &e));
+        let rho = rho + e*rho_1 + e*e*rho_2;
+        transcript.append_field_element(b"IPAblinding", &rho)?;
+        let ipa_proof = Self::prove_rounds(&mut transcript, params, None, vec_a, vec_b)?;
+
+        end_timer!(start);
+        Ok(BlindedInnerProductProof {
+            com_S1,
+            com_S2,
+            rho,
+            challenge: e,
+            ipa_proof,
+        })
+    }
+
+    /// Verifies a blinded IPA proof for the hiding target P by running the IPA verifier on
+    /// P * S1^e * S2^{e^2} * h^{-rho'}
+    pub fn verify_blinded(
+        domain: &'static [u8],
+        n: usize,
+        target_P: C,
+        params: &InnerProductParam<C>,
+        h: C::Affine,
+        proof: &BlindedInnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let start = start_timer!(|| "running blinded inner product argument verify algorithm...");
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        transcript.append_message(b"IPAdomain", domain)?;
+        transcript.append_serializable_element(b"IPAblinded", &[target_P.into_affine(), proof.com_S1, proof.com_S2])?;
+        let (e, _) = nonzero_challenge(&mut transcript, b"challenge e")?;
+        if e != proof.challenge {
+            end_timer!(start);
+            return Err(SigmaErrors::InvalidProof("invalid challenge value".to_string()));
+        }
+        transcript.append_field_element(b"IPAblinding", &proof.rho)?;
+
+        let masked_P = target_P + proof.com_S1 * e + proof.com_S2 * (e*e) - h * proof.rho;
+        let result = Self::verify_with_transcript(&mut transcript, n, masked_P, params, &proof.ipa_proof);
+        end_timer!(start);
+        result
+    }
+
+    /// Aggregated IPA for m statements (vec_a_j, vec_b_j) of the same length n,
+    /// where statement j runs on the j-th block of n generators of params, i.e.,
+    /// P_j = vec_G_j^{factors_G_j * vec_a_j} * vec_H_j^{factors_H_j * vec_b_j} * u^<vec_a_j, vec_b_j>,
+    /// and m is padded to a power of two with zero statements, so that params has m_pad*n generators.
+    /// The statements are combined as sum_j z^j P_j with a challenge z, as a plain sum would only
+    /// prove the sum of the inner products under the shared u, and proved by one IPA of size log(m*n)
+    pub fn prove_aggregated(
+        domain: &'static [u8],
+        params: &InnerProductParam<C>,
+        statements: Vec<IpaStatement<C::ScalarField>>,
+    ) -> Result<InnerProductProof<C>, SigmaErrors> {
+        let m = statements.len();
+        let n = statements.first().map_or(0, |(vec_a, _)| vec_a.len());
+        if m == 0 || statements.iter().any(|(vec_a, vec_b)| vec_a.len() != n || vec_b.len() != n) {
+            return Err(SigmaErrors::InvalidParameters(
+                "statements are empty or of different lengths".to_string(),
+            ));
+        }
+        let num_gens = m.next_power_of_two() * n;
+        if params.vec_G.len() != num_gens || params.vec_H.len() != num_gens
+            || params.factors_G.len() != num_gens || params.factors_H.len() != num_gens
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+
+        let start = start_timer!(|| "running aggregated inner product argument prove algorithm...");
+        // the commitments P_j of the statements
+        let targets: Vec<C> = statements.iter().enumerate()
+            .map(|(j, (vec_a, vec_b))| statement_commit(params, j*n, vec_a, vec_b))
+            .collect::<Result<_, _>>()?;
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        let powers_z = Self::aggregation_challenge(&mut transcript, domain, n, &targets)?;
+
+        // vec_a = z^0 vec_a_0 || ... || z^{m-1} vec_a_{m-1} || 0, vec_b = vec_b_0 || ... || vec_b_{m-1} || 0
+        let mut vec_a = vec![C::ScalarField::zero(); num_gens];
+        let mut vec_b = vec![C::ScalarField::zero(); num_gens];
+        for (j, (a_j, b_j)) in statements.iter().enumerate() {
+            vec_a[j*n..(j+1)*n].copy_from_slice(&scalar_product(a_j, &powers_z[j]));
+            vec_b[j*n..(j+1)*n].copy_from_slice(b_j);
+        }
+        let params = aggregated_param(params, n, &powers_z);
+        let proof = Self::prove_rounds(&mut transcript, &params, None, vec_a, vec_b);
+        end_timer!(start);
+        proof
+    }
+
+    /// Verifies an aggregated IPA proof for the commitments targets = (P_0, ..., P_{m-1})
+    /// of statements of length n
+    pub fn verify_aggregated(
+        domain: &'static [u8],
+        n: usize,
+        targets: &[C],
+        params: &InnerProductParam<C>,
+        proof: &InnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let m = targets.len();
+        let num_gens = m.next_power_of_two() * n;
+        if m == 0 || n == 0 || params.vec_G.len() != num_gens || params.vec_H.len() != num_gens
+            || params.factors_G.len() != num_gens || params.factors_H.len() != num_gens
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+        let start = start_timer!(|| "running aggregated inner product argument verify algorithm...");
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        let powers_z = Self::aggregation_challenge(&mut transcript, domain, n, targets)?;
+
+        // the combined target sum_j z^j P_j
+        let target_P = msm(&C::normalize_batch(targets), &powers_z[0..m], SigmaErrors::InvalidParameters)?;
+        let params = aggregated_param(params, n, &powers_z);
+        let result = Self::verify_with_transcript(&mut transcript, num_gens, target_P, &params, proof);
+        end_timer!(start);
+        result
+    }
+
+    // binds the number of statements, their length and their commitments, then draws
+    // the aggregation challenge z and returns its powers (1, z, ..., z^{m_pad-1})
+    fn aggregation_challenge(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        domain: &'static [u8],
+        n: usize,
+        targets: &[C],
+    ) -> Result<Vec<C::ScalarField>, SigmaErrors> {
+        transcript.append_message(b"IPAdomain", domain)?;
+        transcript.append_field_element(b"IPAstatements", &C::ScalarField::from(targets.len() as u64))?;
+        transcript.append_field_element(b"IPAstatement size", &C::ScalarField::from(n as u64))?;
+        transcript.append_serializable_element(b"IPAtargets", &C::normalize_batch(targets))?;
+        let (z, _) = nonzero_challenge(transcript, b"challenge z")?;
+        let mut powers_z = vec![C::ScalarField::one()];
+        powers_z.extend(generate_powers(z, targets.len().next_power_of_two() - 1));
+        Ok(powers_z)
+    }
+
+    // 4-ary folding with challenge x: the vectors are split into 4 sub-vectors and
+    // a' = sum_i x^i a_i, b' = sum_j x^{-j} b_j, G' = sum_j x^{-j} G_j, H' = sum_i x^i H_i,
+    // so that P' = P * prod_{d != 0} L_d^{x^d}, where L_d collects the cross terms with i - j = d
+    // (a last binary round with exponents (1, -1) is run when log2(n) is odd)
+    fn prove_quaternary(
+        params: &InnerProductParam<C>,
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        mut vec_a: Vec<C::ScalarField>,
+        mut vec_b: Vec<C::ScalarField>,
+    ) -> Result<InnerProductProof<C>, SigmaErrors> {
+        let mut n = params.vec_G.len();
+        let mut vec_G = params.vec_G.clone();
+        let mut vec_H = params.vec_H.clone();
+        // factors are only applied in the first round
+        let mut factors_G = params.factors_G.clone();
+        let mut factors_H = params.factors_H.clone();
+
+        let mut vec_L = vec![];
+        let mut vec_R = vec![];
+        let mut challenges = vec![];
+
+        while n != 1 {
+            let exps = folding_exponents(n);
+            let k = exps.len();
+            n /= k;
+            let a: Vec<&[C::ScalarField]> = vec_a.chunks(n).collect();
+            let b: Vec<&[C::ScalarField]> = vec_b.chunks(n).collect();
+            let G: Vec<&[C::Affine]> = vec_G.chunks(n).collect();
+            let H: Vec<&[C::Affine]> = vec_H.chunks(n).collect();
+            let f_G: Vec<&[C::ScalarField]> = factors_G.chunks(n).collect();
+            let f_H: Vec<&[C::ScalarField]> = factors_H.chunks(n).collect();
+
+            // L_d = prod_{i-j=d} G_j^{a_i} H_i^{b_j} u^{<a_i, b_j>}, for d > 0 (vec_L) and d < 0 (vec_R)
+            let degrees = cross_term_degrees(&exps);
+            let mut coms = Vec::with_capacity(degrees.len());
+            for &d in &degrees {
+                let mut base = vec![];
+                let mut exp = vec![];
+                let mut c_d = C::ScalarField::zero();
+                for i in 0..k {
+                    for j in 0..k {
+                        if exps[i] - exps[j] != d {
+                            continue;
+                        }
+                        base.extend_from_slice(G[j]);
+                        exp.extend(hadamard_product(a[i], f_G[j]));
+                        base.extend_from_slice(H[i]);
+                        exp.extend(hadamard_product(b[j], f_H[i]));
+                        c_d += inner_product(a[i], b[j]);
+                    }
+                }
+                base.push(params.u);
+                exp.push(c_d);
+                coms.push(msm::<C>(&base, &exp, SigmaErrors::InvalidParameters)?);
+            }
+            // the commitments L_d, R_d of the round are normalized with one inversion
+            let coms = C::normalize_batch(&coms);
+            let com_L: Vec<C::Affine> = coms.iter().zip(&degrees).filter(|(_, d)| **d > 0).map(|(com, _)| *com).collect();
+            let com_R: Vec<C::Affine> = coms.iter().zip(&degrees).filter(|(_, d)| **d < 0).map(|(com, _)| *com).collect();
+            let coms = [com_L.clone(), com_R.clone()].concat();
+            transcript.append_serializable_element(b"commitments L, R", &coms)?;
+            let (x, x_inv) = nonzero_challenge(transcript, b"challenge")?;
+            challenges.push(x);
+            vec_L.extend(com_L);
+            vec_R.extend(com_R);
+
+            // fold vec_a, vec_b, vec_G, vec_H
+            let powers: Vec<C::ScalarField> = exps.iter().map(|&e| signed_pow(x, x_inv, e)).collect();
+            let powers_inv: Vec<C::ScalarField> = exps.iter().map(|&e| signed_pow(x, x_inv, -e)).collect();
+            vec_a = (0..n).map(|t| (0..k).map(|i| a[i][t] * powers[i]).sum()).collect();
+            vec_b = (0..n).map(|t| (0..k).map(|j| b[j][t] * powers_inv[j]).sum()).collect();
+            let terms_G: Vec<C> = (0..n)
+                .map(|t| {
+                    let base: Vec<C::Affine> = (0..k).map(|j| G[j][t]).collect();
+                    let exp: Vec<C::ScalarField> = (0..k).map(|j| powers_inv[j] * f_G[j][t]).collect();
+                    msm(&base, &exp, SigmaErrors::InvalidParameters)
+                })
+                .collect::<Result<_, _>>()?;
+            let terms_H: Vec<C> = (0..n)
+                .map(|t| {
+                    let base: Vec<C::Affine> = (0..k).map(|i| H[i][t]).collect();
+                    let exp: Vec<C::ScalarField> = (0..k).map(|i| powers[i] * f_H[i][t]).collect();
+                    msm(&base, &exp, SigmaErrors::InvalidParameters)
+                })
+                .collect::<Result<_, _>>()?;
+            vec_G = C::normalize_batch(&terms_G);
+            vec_H = C::normalize_batch(&terms_H);
+            factors_G = vec![C::ScalarField::one(); n];
+            factors_H = vec![C::ScalarField::one(); n];
+        }
+
+        Ok(InnerProductProof {
+            vec_L,
+            vec_R,
+            a: vec_a[0],
+            b: vec_b[0],
+            challenges,
+        })
+    }
+
+    // the verifier expands the folding weights of all rounds into the scalars of the
+    // original generators and checks
+    // P = u^{ab} * vec_G^{a * factors_G * s_G} * vec_H^{b * factors_H * s_H} * prod L_d^{-x^d}
+    fn verify_quaternary(
+        n: usize,
+        terms: &[(C::Affine, C::ScalarField)],
+        params: &InnerProductParam<C>,
+        proof: &InnerProductProof<C>,
+        transcript: &mut ProofTranscript<C::ScalarField>,
+    ) -> Result<(), SigmaErrors> {
+        if !n.is_power_of_two() || params.vec_H.len() != n
+            || params.factors_G.len() != n || params.factors_H.len() != n
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "vector length is not power of two".to_string(),
+            ));
+        }
+        transcript.append_field_element(b"IPAsize", &C::ScalarField::from(n as u128))?;
+        transcript.append_field_element(b"IPAarity", &C::ScalarField::from(4u64))?;
+
+        // the rounds are determined by n
+        let mut rounds = vec![];
+        let mut m = n;
+        while m != 1 {
+            let exps = folding_exponents(m);
+            m /= exps.len();
+            rounds.push(exps);
+        }
+        let num_coms: usize = rounds.iter().map(|exps| cross_term_degrees(exps).len() / 2).sum();
+        if proof.vec_L.len() != num_coms || proof.vec_R.len() != num_coms
+            || proof.challenges.len() != rounds.len()
+        {
+            return Err(SigmaErrors::InvalidProof("incorrect proof length".to_string()));
+        }
+
+        // check challenges x at each round, and collect the exponents of L_d
+        let mut base = vec![params.u];
+        let mut exp = vec![proof.a * proof.b];
+        let mut weights_G = vec![];
+        let mut weights_H = vec![];
+        let mut offset = 0;
+        for (r, exps) in rounds.iter().enumerate() {
+            let degrees = cross_term_degrees(exps);
+            let half = degrees.len() / 2;
+            let com_L = &proof.vec_L[offset..offset+half];
+            let com_R = &proof.vec_R[offset..offset+half];
+            offset += half;
+
+            let mut coms = com_L.to_vec();
+            coms.extend_from_slice(com_R);
+            transcript.append_serializable_element(b"commitments L, R", &coms)?;
+            let (x, x_inv) = nonzero_challenge(transcript, b"challenge")?;
+            if x != proof.challenges[r] {
+                return Err(SigmaErrors::InvalidProof("invalid challenge value".to_string()));
+            }
+
+            base.extend(coms);
+            exp.extend(degrees.iter().map(|&d| -signed_pow(x, x_inv, d)));
+            weights_G.push(exps.iter().map(|&e| signed_pow(x, x_inv, -e)).collect::<Vec<_>>());
+            weights_H.push(exps.iter().map(|&e| signed_pow(x, x_inv, e)).collect::<Vec<_>>());
+        }
+
+        // expand the weights from the last round to the first one:
+        // the scalar of the j-th sub-vector is the weight of j times the scalars of the folded vector
+        let mut s_G = vec![C::ScalarField::one()];
+        let mut s_H = vec![C::ScalarField::one()];
+        for (w_G, w_H) in weights_G.iter().rev().zip(weights_H.iter().rev()) {
+            s_G = w_G.iter().flat_map(|w| scalar_product(&s_G, w)).collect();
+            s_H = w_H.iter().flat_map(|w| scalar_product(&s_H, w)).collect();
+        }
+
+        base.extend(params.vec_G.clone());
+        exp.extend(scalar_product(&hadamard_product(&s_G, &params.factors_G), &proof.a));
+        base.extend(params.vec_H.clone());
+        exp.extend(scalar_product(&hadamard_product(&s_H, &params.factors_H), &proof.b));
+
+        check_target::<C>(base, exp, terms)
+    }
+}
+
+
+// checks that base^exp equals the target prod_i point_i^{scalar_i} given by its terms,
+// the terms are negated into the same msm, so that the check is a single msm against the identity
+fn check_target<C: CurveGroup>(
+    mut base: Vec<C::Affine>,
+    mut exp: Vec<C::ScalarField>,
+    terms: &[(C::Affine, C::ScalarField)],
+) -> Result<(), SigmaErrors> {
+    base.extend(terms.iter().map(|(point, _)| *point));
+    exp.extend(terms.iter().map(|(_, scalar)| -*scalar));
+    let residue = msm(&base, &exp, SigmaErrors::InvalidProof)?;
+    if ct_eq_affine(residue, C::zero()) {
+        Ok(())
+    }
+    else {
+        Err(SigmaErrors::InvalidProof("invalid IPA proof".to_string()))
+    }
+}
+
+// the vector length 2^{log_n} of a proof of log_n binary rounds, computed with a checked shift
+// for a usize of the given width, so that a proof claiming too many rounds is an error rather than an overflow
+pub(crate) fn rounds_to_length(log_n: usize, bits: u32) -> Result<usize, SigmaErrors> {
+    u32::try_from(log_n).ok()
+        .filter(|&shift| shift < bits)
+        .and_then(|shift| 1usize.checked_shl(shift))
+        .ok_or(SigmaErrors::InvalidParameters(format!(
+            "a proof of {} rounds exceeds the {} bits of usize", log_n, bits,
+        )))
+}
+
+// (init * s_0, ..., init * s_{n-1}) for n = 2^{log_n}, where s_0 = 1 and s_i is obtained from s_{i-k}
+// by setting the highest bit log_i of i, i.e., multiplying squares[log_n-1-log_i], one multiplication per entry
+fn box_scalars<F: Field>(init: F, squares: &[F]) -> Vec<F> {
+    let log_n = squares.len();
+    let n = 1 << log_n;
+    let mut vec_box = Vec::with_capacity(n);
+    vec_box.push(init);
+    for i in 1..n {
+        let log_i = (usize::BITS - 1 - i.leading_zeros()) as usize;
+        let k = 1 << log_i;
+        vec_box.push(vec_box[i-k] * squares[log_n-1-log_i]);
+    }
+    vec_box
+}
+
+// P_j = vec_G_j^{factors_G_j * vec_a} * vec_H_j^{factors_H_j * vec_b} * u^<vec_a, vec_b>
+// for the block of generators starting at offset
+fn statement_commit<C: CurveGroup>(
+    params: &InnerProductParam<C>,
+    offset: usize,
+    vec_a: &[C::ScalarField],
+    vec_b: &[C::ScalarField],
+) -> Result<C, SigmaErrors> {
+    let block = offset..offset+vec_a.len();
+    let mut base = params.vec_G[block.clone()].to_vec();
+    base.extend_from_slice(&params.vec_H[block.clone()]);
+    base.push(params.u);
+    let mut exp = hadamard_product(vec_a, &params.factors_G[block.clone()]);
+    exp.extend(hadamard_product(vec_b, &params.factors_H[block]));
+    exp.push(inner_product(vec_a, vec_b));
+    msm(&base, &exp, SigmaErrors::InvalidParameters)
+}
+
+// the parameters of the aggregated instance, factors_H of the j-th block of n generators is scaled by z^j
+fn aggregated_param<C: CurveGroup>(
+    params: &InnerProductParam<C>,
+    n: usize,
+    powers_z: &[C::ScalarField],
+) -> InnerProductParam<C> {
+    let factors_H = params.factors_H.chunks(n).zip(powers_z)
+        .flat_map(|(f_j, z_j)| scalar_product(f_j, z_j))
+        .collect();
+    InnerProductParam { factors_H, ..params.clone() }
+}
+
+// absorbs the public inner product c and outputs the parameters with u' = u^x for a challenge x drawn after c,
+// so that a target without u^c cannot shift its inner product through a u-component chosen by the prover
+fn public_product_param<C: CurveGroup>(
+    transcript: &mut ProofTranscript<C::ScalarField>,
+    params: &InnerProductParam<C>,
+    c: C::ScalarField,
+) -> Result<InnerProductParam<C>, SigmaErrors> {
+    transcript.append_field_element(b"IPAproduct", &c)?;
+    let (x, _) = nonzero_challenge(transcript, b"challenge product")?;
+    Ok(InnerProductParam { u: (params.u * x).into_affine(), ..params.clone() })
+}
+
+// the source of the round challenges, which is the proof transcript outside of tests
+pub(crate) trait ChallengeSource<F: PrimeField> {
+    fn challenge(&mut self, label: &'static [u8]) -> Result<F, SigmaErrors>;
+}
+
+impl<F: PrimeField> ChallengeSource<F> for ProofTranscript<F> {
+    fn challenge(&mut self, label: &'static [u8]) -> Result<F, SigmaErrors> {
+        Ok(self.get_and_append_challenge(label)?)
+    }
+}
+
+// draws a challenge x and returns (x, x^{-1}), a zero challenge is rejected instead of panicking on its inverse
+pub(crate) fn nonzero_challenge<F: PrimeField>(
+    transcript: &mut impl ChallengeSource<F>,
+    label: &'static [u8],
+) -> Result<(F, F), SigmaErrors> {
+    let x = transcript.challenge(label)?;
+    match x.inverse() {
+        Some(x_inv) => Ok((x, x_inv)),
+        None => Err(SigmaErrors::InvalidProof("zero challenge value".to_string())),
+    }
+}
+
+// vec_G^{factors_G * vec_a} * vec_H^{factors_H * vec_b} * u^c * h^rho
+fn blinded_commit<C: CurveGroup>(
+    params: &InnerProductParam<C>,
+    h: C::Affine,
+    vec_a: &[C::ScalarField],
+    vec_b: &[C::ScalarField],
+    c: C::ScalarField,
+    rho: C::ScalarField,
+) -> Result<C, SigmaErrors> {
+    let mut base = params.vec_G.clone();
+    base.extend(params.vec_H.clone());
+    base.push(params.u);
+    base.push(h);
+    let mut exp = hadamard_product(vec_a, &params.factors_G);
+    exp.extend(hadamard_product(vec_b, &params.factors_H));
+    exp.push(c);
+    exp.push(rho);
+    msm(&base, &exp, SigmaErrors::InvalidParameters)
+}
+
+impl<C: CurveGroup> IpaProverKey<C> {
+    /// Precomputes the comb tables of vec_G and vec_H, each of 2^COMB_TEETH points,
+    /// which pays off when many proofs are generated for the same parameters
+    pub fn new(params: &InnerProductParam<C>) -> Self {
+        let start = start_timer!(|| "precomputing inner product argument prover key...");
+        #[cfg(feature = "parallel")]
+        let (tables_G, tables_H) = (
+            params.vec_G.par_iter().map(|g| comb_table::<C>(*g)).collect(),
+            params.vec_H.par_iter().map(|h| comb_table::<C>(*h)).collect(),
+        );
+        #[cfg(not(feature = "parallel"))]
+        let (tables_G, tables_H) = (
+            params.vec_G.iter().map(|g| comb_table::<C>(*g)).collect(),
+            params.vec_H.iter().map(|h| comb_table::<C>(*h)).collect(),
+        );
+        end_timer!(start);
+        IpaProverKey {
+            params: params.clone(),
+            tables_G,
+            tables_H,
+        }
+    }
+}
+
+// the number of teeth of a comb table, which holds 2^COMB_TEETH points
+const COMB_TEETH: usize = 4;
+
+// the chunks of length len at offset of both halves of a source of length 2*half
+fn read_halves<T, S: VectorSource<T> + ?Sized>(source: &S, half: usize, offset: usize, len: usize) -> (Vec<T>, Vec<T>) {
+    (source.read_chunk(offset, len), source.read_chunk(half + offset, len))
+}
+
+// the transcript of prove(domain, ...) for binary folding of vectors of length n, up to the first round
+fn binary_transcript<F: PrimeField>(domain: &'static [u8], n: usize) -> Result<ProofTranscript<F>, SigmaErrors> {
+    if !n.is_power_of_two() {
+        return Err(SigmaErrors::InvalidParameters(
+            "vector length is not power of two".to_string(),
+        ));
+    }
+    let mut transcript = ProofTranscript::<F>::new(domain);
+    transcript.append_message(b"IPAdomain", domain)?;
+    transcript.append_field_element(b"IPAsize", &F::from(n as u128))?;
+    transcript.append_field_element(b"IPAarity", &F::from(FoldingArity::Two.as_usize() as u64))?;
+    Ok(transcript)
+}
+
+impl<C: CurveGroup> InnerProductProverState<C> {
+    /// Starts a streaming binary IPA prover on the transcript of prove(domain, ...),
+    /// the factors are applied to the generators here instead of in the first round
+    pub fn new(
+        domain: &'static [u8],
+        params: &InnerProductParam<C>,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+    ) -> Result<Self, SigmaErrors> {
+        let n = params.vec_G.len();
+        if params.vec_H.len() != n || vec_a.len() != n || vec_b.len() != n
+            || params.factors_G.len() != n || params.factors_H.len() != n
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+        if params.arity != FoldingArity::Two {
+            return Err(SigmaErrors::InvalidParameters(
+                "folding is not binary".to_string(),
+            ));
+        }
+        let transcript = binary_transcript::<C::ScalarField>(domain, n)?;
+
+        let scale = |vec: &[C::Affine], factors: &[C::ScalarField]| {
+            let terms: Vec<C> = vec.iter().zip(factors).map(|(g, f)| *g * f).collect();
+            C::normalize_batch(&terms)
+        };
+        let log_n = n.trailing_zeros() as usize;
+        Ok(InnerProductProverState {
+            transcript,
+            u: params.u,
+            vec_a,
+            vec_b,
+            vec_G: scale(&params.vec_G, &params.factors_G),
+            vec_H: scale(&params.vec_H, &params.factors_H),
+            vec_L: Vec::with_capacity(log_n),
+            vec_R: Vec::with_capacity(log_n),
+            challenges: Vec::with_capacity(log_n),
+            pending: false,
+            injected: None,
+        })
+    }
+
+    /// Sets the challenge of the round whose L, R were just returned by prove_step,
+    /// in place of the challenge drawn from the transcript
+    pub fn receive_challenge(&mut self, x: C::ScalarField) -> Result<(), SigmaErrors> {
+        if !self.pending {
+            return Err(SigmaErrors::InvalidParameters(
+                "no round is waiting for a challenge".to_string(),
+            ));
+        }
+        if x.is_zero() {
+            return Err(SigmaErrors::InvalidParameters(
+                "zero challenge value".to_string(),
+            ));
+        }
+        self.injected = Some(x);
+        Ok(())
+    }
+
+    // folds the vectors of the pending round with the injected or the transcript challenge
+    fn fold_pending(&mut self) -> Result<(), SigmaErrors> {
+        if !self.pending {
+            return Ok(());
+        }
+        let (x, x_inv) = match self.injected.take() {
+            Some(x) => (x, x.inverse().unwrap()),
+            None => nonzero_challenge(&mut self.transcript, b"challenge")?,
+        };
+        let n = self.vec_a.len() / 2;
+        let mut terms = Vec::with_capacity(n);
+        fold_scalars_in_place(&mut self.vec_a, n, &x, &x_inv);
+        fold_scalars_in_place(&mut self.vec_b, n, &x_inv, &x);
+        fold_generators_in_place::<C>(&mut self.vec_G, n, &x_inv, &x, &mut terms);
+        fold_generators_in_place::<C>(&mut self.vec_H, n, &x, &x_inv, &mut terms);
+        self.challenges.push(x);
+        self.pending = false;
+        Ok(())
+    }
+}
+
+// the scalar bits are split into COMB_TEETH rows of d bits
+fn comb_spacing<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize).div_ceil(COMB_TEETH)
+}
+
+// the comb table of g, whose j-th entry is sum_{k: the k-th bit of j is set} g^{2^{k*d}}
+fn comb_table<C: CurveGroup>(g: C::Affine) -> Vec<C::Affine> {
+    let d = comb_spacing::<C::ScalarField>();
+    let mut teeth = Vec::with_capacity(COMB_TEETH);
+    let mut power: C = g.into();
+    for _ in 0..COMB_TEETH {
+        teeth.push(power);
+        for _ in 0..d {
+            power.double_in_place();
+        }
+    }
+    let mut table = vec![C::zero(); 1 << COMB_TEETH];
+    for j in 1..table.len() {
+        table[j] = table[j & (j - 1)] + teeth[j.trailing_zeros() as usize];
+    }
+    C::normalize_batch(&table)
+}
+
+// the table index of the i-th column of the scalar bits
+fn comb_index<B: BigInteger>(bits: &B, i: usize, d: usize) -> usize {
+    (0..COMB_TEETH)
+        .filter(|k| bits.get_bit(k*d + i))
+        .map(|k| 1 << k)
+        .sum()
+}
+
+// g_L^{x_L} * g_R^{x_R} from the comb tables of g_L and g_R, sharing the doublings
+fn comb_mul2<C: CurveGroup>(
+    table_L: &[C::Affine],
+    x_L: &C::ScalarField,
+    table_R: &[C::Affine],
+    x_R: &C::ScalarField,
+) -> C {
+    let d = comb_spacing::<C::ScalarField>();
+    let (bits_L, bits_R) = (x_L.into_bigint(), x_R.into_bigint());
+    let mut result = C::zero();
+    for i in (0..d).rev() {
+        result.double_in_place();
+        result += table_L[comb_index(&bits_L, i, d)];
+        result += table_R[comb_index(&bits_R, i, d)];
+    }
+    result
+}
+
+// fold the generators as in fold_generators, from the comb tables of vec_L and vec_R
+fn fold_generators_with_tables<C: CurveGroup>(
+    tables_L: &[Vec<C::Affine>],
+    tables_R: &[Vec<C::Affine>],
+    x_L: &[C::ScalarField],
+    x_R: &[C::ScalarField],
+) -> Vec<C::Affine> {
+    #[cfg(feature = "parallel")]
+    if run_parallel(tables_L.len()) {
+        let terms: Vec<C> = tables_L.par_iter().zip(tables_R.par_iter()).zip(x_L.par_iter().zip(x_R.par_iter()))
+            .map(|((t_L, t_R), (x_L_i, x_R_i))| comb_mul2::<C>(t_L, x_L_i, t_R, x_R_i))
+            .collect();
+        return C::normalize_batch(&terms);
+    }
+    let terms: Vec<C> = tables_L.iter().zip(tables_R.iter()).zip(x_L.iter().zip(x_R.iter()))
+        .map(|((t_L, t_R), (x_L_i, x_R_i))| comb_mul2::<C>(t_L, x_L_i, t_R, x_R_i))
+        .collect();
+    C::normalize_batch(&terms)
+}
+
+// exponents of the folding weights x^e: (0, 1, 2, 3) for a 4-ary round,
+// and (1, -1) for the binary round when only 2 elements are left
+fn folding_exponents(n: usize) -> Vec<i64> {
+    if n >= 4 { vec![0, 1, 2, 3] } else { vec![1, -1] }
+}
+
+// the non-zero differences d = e_i - e_j, positive ones first,
+// i.e., (1, 2, 3, -1, -2, -3) for a 4-ary round and (2, -2) for a binary round
+fn cross_term_degrees(exps: &[i64]) -> Vec<i64> {
+    let mut degrees: Vec<i64> = exps.iter()
+        .flat_map(|e_i| exps.iter().map(move |e_j| e_i - e_j))
+        .filter(|&d| d > 0)
+        .collect();
+    degrees.sort();
+    degrees.dedup();
+    let negative: Vec<i64> = degrees.iter().map(|d| -d).collect();
+    degrees.extend(negative);
+    degrees
+}
+
+// x^e for a signed exponent e
+fn signed_pow<F: Field>(x: F, x_inv: F, e: i64) -> F {
+    if e >= 0 { x.pow([e as u64]) } else { x_inv.pow([e.unsigned_abs()]) }
+}
+
+// rounds on vectors shorter than this run sequentially under the `parallel` feature,
+// where the overhead of rayon outweighs the work of the round
+const PARALLEL_MIN_LEN: usize = 256;
+
+// whether a round splitting a vector into halves of length half_len runs in parallel
+fn run_parallel(half_len: usize) -> bool {
+    cfg!(feature = "parallel") && 2*half_len >= PARALLEL_MIN_LEN
+}
+
+// <a_L, b_R> and <a_R, b_L>, computed concurrently under the `parallel` feature
+fn cross_terms<F: PrimeField>(a_L: &[F], a_R: &[F], b_L: &[F], b_R: &[F]) -> (F, F) {
+    #[cfg(feature = "parallel")]
+    if run_parallel(a_L.len()) {
+        return rayon::join(|| inner_product(a_L, b_R), || inner_product(a_R, b_L));
+    }
+    (inner_product(a_L, b_R), inner_product(a_R, b_L))
+}
+
+// the vectors of the binary prover, which are folded into their first halves round by round,
+// together with the buffers of the commitments L, R that are reused across the rounds
+struct FoldState<'a, C: CurveGroup> {
+    vec_a: Vec<C::ScalarField>,
+    vec_b: Vec<C::ScalarField>,
+    vec_G: Vec<C::Affine>,
+    vec_H: Vec<C::Affine>,
+    u: C::Affine,
+    // the precomputed tables of the generators, which only serve the first round
+    key: Option<&'a IpaProverKey<C>>,
+    base_L: Vec<C::Affine>,
+    base_R: Vec<C::Affine>,
+    exp_L: Vec<C::ScalarField>,
+    exp_R: Vec<C::ScalarField>,
+    terms: Vec<C>,
+}
+
+impl<'a, C: CurveGroup> FoldState<'a, C> {
+    fn new(
+        params: &InnerProductParam<C>,
+        key: Option<&'a IpaProverKey<C>>,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+    ) -> Self {
+        let half = vec_a.len() / 2;
+        Self {
+            vec_a,
+            vec_b,
+            vec_G: params.vec_G.clone(),
+            vec_H: params.vec_H.clone(),
+            u: params.u,
+            key,
+            base_L: Vec::with_capacity(2*half + 1),
+            base_R: Vec::with_capacity(2*half + 1),
+            exp_L: Vec::with_capacity(2*half + 1),
+            exp_R: Vec::with_capacity(2*half + 1),
+            terms: Vec::with_capacity(half),
+        }
+    }
+}
+
+// runs one round of the binary prover: commits to L, R, draws the challenge x and folds the vectors to half length,
+// with apply_factors = (factors_G, factors_H) the generators are scaled by their factors, which the first round
+// folds into the generators, so that the later rounds run without factors
+fn fold_round<C: CurveGroup>(
+    state: &mut FoldState<'_, C>,
+    transcript: &mut ProofTranscript<C::ScalarField>,
+    apply_factors: RoundFactors<'_, C::ScalarField>,
+) -> Result<(IpaRoundCommitments<C::Affine>, C::ScalarField), SigmaErrors> {
+    let n = state.vec_a.len() / 2;
+    let (a_L, a_R) = state.vec_a.split_at(n);
+    let (b_L, b_R) = state.vec_b.split_at(n);
+    let (G_L, G_R) = state.vec_G.split_at(n);
+    let (H_L, H_R) = state.vec_H.split_at(n);
+
+    // <a_L*x + a_R*x_inv, b_L*x_inv + b_R*x> = <a, b> + x^2*<a_L, b_R> + x_inv^2*<a_R, b_L>
+    // compute c_L = <a_L, b_R>, c_R = <a_R, b_L>
+    let (c_L, c_R) = cross_terms(a_L, a_R, b_L, b_R);
+
+    // compute L = (G_R^factors_G[n..2n])^a_L + (H_L^factors_H[0..n])^b_R + u^c_L
+    // and R = (G_L^factors_G[0..n])^a_R + (H_R^factors_H[n..2n])^b_L + u^c_R
+    state.exp_L.clear();
+    state.exp_R.clear();
+    match apply_factors {
+        Some((factors_G, factors_H)) => {
+            state.exp_L.extend(a_L.iter().zip(&factors_G[n..2*n]).map(|(a_i, f_i)| *a_i * f_i));
+            state.exp_L.extend(b_R.iter().zip(&factors_H[0..n]).map(|(b_i, f_i)| *b_i * f_i));
+            state.exp_R.extend(a_R.iter().zip(&factors_G[0..n]).map(|(a_i, f_i)| *a_i * f_i));
+            state.exp_R.extend(b_L.iter().zip(&factors_H[n..2*n]).map(|(b_i, f_i)| *b_i * f_i));
+        }
+        None => {
+            state.exp_L.extend_from_slice(a_L);
+            state.exp_L.extend_from_slice(b_R);
+            state.exp_R.extend_from_slice(a_R);
+            state.exp_R.extend_from_slice(b_L);
+        }
+    }
+    state.exp_L.push(c_L);
+    state.exp_R.push(c_R);
+
+    state.base_L.clear();
+    state.base_L.extend_from_slice(G_R);
+    state.base_L.extend_from_slice(H_L);
+    state.base_L.push(state.u);
+    state.base_R.clear();
+    state.base_R.extend_from_slice(G_L);
+    state.base_R.extend_from_slice(H_R);
+    state.base_R.push(state.u);
+
+    let (com_L, com_R) = commit_cross::<C>(&state.base_L, &state.exp_L, &state.base_R, &state.exp_R)?;
+
+    // get challenge
+    transcript.append_serializable_element(b"commitments L, R", &[com_L, com_R])?;
+    let (x, x_inv) = nonzero_challenge(transcript, b"challenge")?;
+
+    // fold vec_G, vec_H, vec_a, vec_b
+    fold_scalars_in_place(&mut state.vec_a, n, &x, &x_inv);
+    fold_scalars_in_place(&mut state.vec_b, n, &x_inv, &x);
+    match apply_factors {
+        Some((factors_G, factors_H)) => {
+            let x_G = (scalar_product(&factors_G[0..n], &x_inv), scalar_product(&factors_G[n..2*n], &x));
+            let x_H = (scalar_product(&factors_H[0..n], &x), scalar_product(&factors_H[n..2*n], &x_inv));
+            match state.key.take() {
+                Some(key) => {
+                    state.vec_G = fold_generators_with_tables::<C>(&key.tables_G[0..n], &key.tables_G[n..2*n], &x_G.0, &x_G.1);
+                    state.vec_H = fold_generators_with_tables::<C>(&key.tables_H[0..n], &key.tables_H[n..2*n], &x_H.0, &x_H.1);
+                }
+                None => {
+                    let (G_L, G_R) = state.vec_G.split_at(n);
+                    let (H_L, H_R) = state.vec_H.split_at(n);
+                    (state.vec_G, state.vec_H) = (
+                        fold_generators::<C>(G_L, G_R, &x_G.0, &x_G.1),
+                        fold_generators::<C>(H_L, H_R, &x_H.0, &x_H.1),
+                    );
+                }
+            }
+        }
+        None => {
+            fold_generators_in_place::<C>(&mut state.vec_G, n, &x_inv, &x, &mut state.terms);
+            fold_generators_in_place::<C>(&mut state.vec_H, n, &x, &x_inv, &mut state.terms);
+        }
+    }
+    Ok(((com_L, com_R), x))
+}
+
+// L = base_L^exp_L and R = base_R^exp_R, computed concurrently under the `parallel` feature
+fn commit_cross<C: CurveGroup>(
+    base_L: &[C::Affine],
+    exp_L: &[C::ScalarField],
+    base_R: &[C::Affine],
+    exp_R: &[C::ScalarField],
+) -> Result<(C::Affine, C::Affine), SigmaErrors> {
+    #[cfg(feature = "parallel")]
+    if run_parallel(base_L.len()) {
+        toolbox::metrics::count_msm();
+        toolbox::metrics::count_msm();
+        let (com_L, com_R) = rayon::join(
+            || msm_uncounted::<C>(base_L, exp_L, SigmaErrors::InvalidParameters),
+            || msm_uncounted::<C>(base_R, exp_R, SigmaErrors::InvalidParameters),
+        );
+        let coms = C::normalize_batch(&[com_L?, com_R?]);
+        return Ok((coms[0], coms[1]));
+    }
+    let (com_L, com_R) = (msm::<C>(base_L, exp_L, SigmaErrors::InvalidParameters)?, msm::<C>(base_R, exp_R, SigmaErrors::InvalidParameters)?);
+    // one inversion for both commitments
+    let coms = C::normalize_batch(&[com_L, com_R]);
+    Ok((coms[0], coms[1]))
+}
+
+// C::msm that reports mismatched lengths of bases and scalars with the given error instead of panicking,
+// InvalidProof on the verifier side and InvalidParameters on the prover side
+pub(crate) fn msm<C: CurveGroup>(
+    bases: &[C::Affine],
+    scalars: &[C::ScalarField],
+    error: fn(String) -> SigmaErrors,
+) -> Result<C, SigmaErrors> {
+    toolbox::metrics::count_msm();
+    msm_uncounted(bases, scalars, error)
+}
+
+// msm without the metrics hook, for the rayon tasks, which do not run on the thread of the recording
+fn msm_uncounted<C: CurveGroup>(
+    bases: &[C::Affine],
+    scalars: &[C::ScalarField],
+    error: fn(String) -> SigmaErrors,
+) -> Result<C, SigmaErrors> {
+    C::msm(bases, scalars).map_err(|_| error("msm bases and scalars have different lengths".to_string()))
+}
+
+// fold the scalars as vec_L*x_L + vec_R*x_R
+pub(crate) fn fold_scalars<F: PrimeField>(vec_L: &[F], vec_R: &[F], x_L: &F, x_R: &F) -> Vec<F> {
+    #[cfg(feature = "parallel")]
+    if run_parallel(vec_L.len()) {
+        return vec_L.par_iter().zip(vec_R.par_iter()).map(|(l_i, r_i)| *l_i * x_L + *r_i * x_R).collect();
+    }
+    vec_L.iter().zip(vec_R.iter()).map(|(l_i, r_i)| *l_i * x_L + *r_i * x_R).collect()
+}
+
+// fold the generators as vec_L[i]^{x_L[i]} * vec_R[i]^{x_R[i]}
+pub(crate) fn fold_generators<C: CurveGroup>(
+    vec_L: &[C::Affine],
+    vec_R: &[C::Affine],
+    x_L: &[C::ScalarField],
+    x_R: &[C::ScalarField],
+) -> Vec<C::Affine> {
+    #[cfg(feature = "parallel")]
+    if run_parallel(vec_L.len()) {
+        let terms: Vec<C> = vec_L.par_iter().zip(vec_R.par_iter()).zip(x_L.par_iter().zip(x_R.par_iter()))
+            .map(|((g_L, g_R), (x_L_i, x_R_i))| mul2::<C>(g_L, x_L_i, g_R, x_R_i))
+            .collect();
+        return C::normalize_batch(&terms);
+    }
+    let terms: Vec<C> = vec_L.iter().zip(vec_R.iter()).zip(x_L.iter().zip(x_R.iter()))
+        .map(|((g_L, g_R), (x_L_i, x_R_i))| mul2::<C>(g_L, x_L_i, g_R, x_R_i))
+        .collect();
+    C::normalize_batch(&terms)
+}
+
+// fold vec in place as vec_L*x_L + vec_R*x_R into its first half of length n
+fn fold_scalars_in_place<F: PrimeField>(vec: &mut Vec<F>, n: usize, x_L: &F, x_R: &F) {
+    let (vec_L, vec_R) = vec.split_at_mut(n);
+    #[cfg(feature = "parallel")]
+    if run_parallel(n) {
+        vec_L.par_iter_mut().zip(vec_R.par_iter()).for_each(|(l_i, r_i)| *l_i = *l_i * x_L + *r_i * x_R);
+        vec.truncate(n);
+        return;
+    }
+    vec_L.iter_mut().zip(vec_R.iter()).for_each(|(l_i, r_i)| *l_i = *l_i * x_L + *r_i * x_R);
+    vec.truncate(n);
+}
+
+// fold the generators in place as vec_L[i]^x_L * vec_R[i]^x_R into the first half of length n,
+// the projective points are collected in the reused buffer terms, only their batch normalization allocates
+fn fold_generators_in_place<C: CurveGroup>(
+    vec: &mut Vec<C::Affine>,
+    n: usize,
+    x_L: &C::ScalarField,
+    x_R: &C::ScalarField,
+    terms: &mut Vec<C>,
+) {
+    let (vec_L, vec_R) = vec.split_at(n);
+    terms.clear();
+    #[cfg(feature = "parallel")]
+    if run_parallel(n) {
+        terms.par_extend(vec_L.par_iter().zip(vec_R.par_iter()).map(|(g_L, g_R)| mul2::<C>(g_L, x_L, g_R, x_R)));
+        *vec = C::normalize_batch(terms);
+        return;
+    }
+    terms.extend(vec_L.iter().zip(vec_R.iter()).map(|(g_L, g_R)| mul2::<C>(g_L, x_L, g_R, x_R)));
+    *vec = C::normalize_batch(terms);
+}
+
+// g_L^{x_L} * g_R^{x_R} by a joint double-and-add over the bits of x_L and x_R, without allocating
+fn mul2<C: CurveGroup>(
+    g_L: &C::Affine,
+    x_L: &C::ScalarField,
+    g_R: &C::Affine,
+    x_R: &C::ScalarField,
+) -> C {
+    let (bits_L, bits_R) = (x_L.into_bigint(), x_R.into_bigint());
+    let mut result = C::zero();
+    for i in (0..C::ScalarField::MODULUS_BIT_SIZE as usize).rev() {
+        result.double_in_place();
+        if bits_L.get_bit(i) {
+            result += g_L;
+        }
+        if bits_R.get_bit(i) {
+            result += g_R;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::VariableBaseMSM;
+    use super::*;
+    use ark_secp256k1::{Fr, Projective, Affine};
+    use ark_std::UniformRand;
+    use toolbox::vec::{convert, rand_affine_vec, reversed};
+    use ark_serialize::CanonicalSerialize;
+
+    #[test]
+    fn test_ipa() {
+        let mut rng = ark_std::test_rng();
+        let n = 4;
+        let vec_a: Vec<Fr> = convert(&[1u64, 2u64, 3u64, 4u64]);
+        let vec_b: Vec<Fr> = convert(&[1u64, 1u64, 1u64, 1u64]);
+        let vec_G = rand_affine_vec::<Projective, _>(&mut rng, vec_a.len());
+        let vec_H = rand_affine_vec::<Projective, _>(&mut rng, vec_a.len());
+        let u = Affine::rand(&mut rng);
+        let fac_G: Vec<Fr> = convert(&[1u64, 2u64, 3u64, 4u64]);
+        let fac_H: Vec<Fr> = convert(&[1u64, 1u64, 1u64, 1u64]);
+
+        type Ipa = InnerProductProtocol<Projective>;
+        let params = InnerProductParam {
+            factors_G: fac_G.clone(),
+            factors_H: fac_H.clone(),
+            u,
+            vec_G: vec_G.clone(),
+            vec_H: vec_H.clone(),
+            arity: FoldingArity::Two,
+        };
+
+        let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+        // compute P with uncompressed vectors vec_a, vec_b
+        let t = inner_product(&vec_a, &vec_b);
+        let mut exp = vec![];
+        exp.extend(hadamard_product(&vec_a, &fac_G));
+        exp.extend(hadamard_product(&vec_b, &fac_H));
+        let mut base = vec![];
+        base.extend(vec_G.clone());
+        base.extend(vec_H.clone());
+
+        let P = Projective::msm(&base, &exp).unwrap() + u*t;
+        Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+    }
+
+    fn ipa_instance(n: usize, arity: FoldingArity) -> (InnerProductParam<Projective>, Vec<Fr>, Vec<Fr>, Projective) {
+        let mut rng = ark_std::test_rng();
+        let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let params = InnerProductParam::<Projective> {
+            factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            u: Affine::rand(&mut rng),
+            vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            arity,
+        };
+        let mut exp = hadamard_product(&vec_a, &params.factors_G);
+        exp.extend(hadamard_product(&vec_b, &params.factors_H));
+        let mut base = params.vec_G.clone();
+        base.extend(params.vec_H.clone());
+        let P = Projective::msm(&base, &exp).unwrap() + params.u*inner_product(&vec_a, &vec_b);
+        (params, vec_a, vec_b, P)
+    }
+
+    #[test]
+    fn test_ipa_factors() {
+        type Ipa = InnerProductProtocol<Projective>;
+        for n in [8, 16] {
+            let (params, vec_a, vec_b, P) = ipa_instance(n, FoldingArity::Two);
+            let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+            Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+
+            // the factors apply to the whole vectors, so pre-scaling the generators proves the same statement
+            let scaled = InnerProductParam::with_scaled_generators(
+                params.u, &params.vec_G, &params.vec_H, &params.factors_G, &params.factors_H,
+            ).unwrap();
+            let mut base = scaled.vec_G.clone();
+            base.extend(scaled.vec_H.clone());
+            let mut exp = vec_a.clone();
+            exp.extend(vec_b.clone());
+            assert_eq!(Projective::msm(&base, &exp).unwrap() + scaled.u*inner_product(&vec_a, &vec_b), P);
+            let proof_scaled = Ipa::prove(IPA_DOMAIN, &scaled, vec_a.clone(), vec_b.clone()).unwrap();
+            assert_eq!(proof_scaled, proof);
+            Ipa::verify(IPA_DOMAIN, n, P, &scaled, &proof).unwrap();
+
+            // dropping the factors changes the statement
+            let unscaled = InnerProductParam {
+                factors_G: vec![Fr::one(); n],
+                factors_H: vec![Fr::one(); n],
+                ..params.clone()
+            };
+            assert!(Ipa::verify(IPA_DOMAIN, n, P, &unscaled, &proof).is_err());
+        }
+        let (params, _, _, _) = ipa_instance(8, FoldingArity::Two);
+        assert!(InnerProductParam::<Projective>::with_scaled_generators(
+            params.u, &params.vec_G, &params.vec_H, &params.factors_G[..4], &params.factors_H,
+        ).is_err());
+    }
+
+    #[test]
+    fn test_ipa_aggregated() {
+        type Ipa = InnerProductProtocol<Projective>;
+        let mut rng = ark_std::test_rng();
+        let n = 4;
+        for m in [1usize, 2, 3] {
+            for arity in [FoldingArity::Two, FoldingArity::Four] {
+                let (params, _, _, _) = ipa_instance(m.next_power_of_two()*n, arity);
+                let statements: Vec<IpaStatement<Fr>> = (0..m)
+                    .map(|_| {
+                        let vec_a = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+                        let vec_b = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+                        (vec_a, vec_b)
+                    })
+                    .collect();
+                let targets: Vec<Projective> = statements.iter().enumerate()
+                    .map(|(j, (vec_a, vec_b))| statement_commit(&params, j*n, vec_a, vec_b).unwrap())
+                    .collect();
+                let proof = Ipa::prove_aggregated(IPA_DOMAIN, &params, statements.clone()).unwrap();
+                Ipa::verify_aggregated(IPA_DOMAIN, n, &targets, &params, &proof).unwrap();
+                // one proof of size log(m_pad*n)
+                if arity == FoldingArity::Two {
+                    assert_eq!(proof.vec_L.len(), (m.next_power_of_two()*n).trailing_zeros() as usize);
+                }
+
+                // a corrupted statement is rejected
+                let mut targets_prime = targets.clone();
+                targets_prime[m-1] += params.u;
+                assert!(Ipa::verify_aggregated(IPA_DOMAIN, n, &targets_prime, &params, &proof).is_err());
+                // and so is a dropped one
+                if m > 1 {
+                    assert!(Ipa::verify_aggregated(IPA_DOMAIN, n, &targets[..m-1], &params, &proof).is_err());
+                }
+            }
+        }
+
+        // shifting the inner product between two statements keeps the plain sum of the
+        // targets, which the challenge z rules out
+        let (params, _, _, _) = ipa_instance(2*n, FoldingArity::Two);
+        let statements: Vec<IpaStatement<Fr>> = (0..2)
+            .map(|_| ((0..n).map(|_| Fr::rand(&mut rng)).collect(), (0..n).map(|_| Fr::rand(&mut rng)).collect()))
+            .collect();
+        let proof = Ipa::prove_aggregated(IPA_DOMAIN, &params, statements.clone()).unwrap();
+        let targets = [
+            statement_commit(&params, 0, &statements[0].0, &statements[0].1).unwrap() + params.u,
+            statement_commit(&params, n, &statements[1].0, &statements[1].1).unwrap() - params.u,
+        ];
+        assert!(Ipa::verify_aggregated(IPA_DOMAIN, n, &targets, &params, &proof).is_err());
+
+        // statements of different lengths are rejected
+        let statements = vec![(vec![Fr::one(); n], vec![Fr::one(); n]), (vec![Fr::one(); n-1], vec![Fr::one(); n-1])];
+        assert!(Ipa::prove_aggregated(IPA_DOMAIN, &params, statements).is_err());
+    }
+
+    #[test]
+    fn test_ipa_arity() {
+        type Ipa = InnerProductProtocol<Projective>;
+        // n = 2 and n = 8 take a last binary round in the 4-ary mode
+        for n in [1, 2, 8, 16, 64] {
+            for arity in [FoldingArity::Two, FoldingArity::Four] {
+                let (params, vec_a, vec_b, P) = ipa_instance(n, arity);
+                let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+                Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+                assert!(Ipa::verify(IPA_DOMAIN, n, P + params.u, &params, &proof).is_err());
+            }
+        }
+
+        // 4-ary rounds halve the number of challenges
+        let (params, vec_a, vec_b, _) = ipa_instance(64, FoldingArity::Four);
+        let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+        assert_eq!(proof.challenges.len(), 3);
+        assert_eq!(proof.vec_L.len(), 9);
+    }
+
+    // the sizes of the golden proofs in testdata, one hex file ipa_n{n} per size of the binary folding
+    // and ipa4_n{n} of the quaternary folding
+    const GOLDEN_SIZES: [usize; 4] = [1, 2, 8, 64];
+    const GOLDEN_ARITIES: [FoldingArity; 2] = [FoldingArity::Two, FoldingArity::Four];
+
+    fn golden_path(n: usize, arity: FoldingArity) -> std::path::PathBuf {
+        let name = match arity {
+            FoldingArity::Two => format!("ipa_n{}", n),
+            FoldingArity::Four => format!("ipa4_n{}", n),
+        };
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata").join(name)
+    }
+
+    fn golden_proof(n: usize, arity: FoldingArity) -> String {
+        let (params, vec_a, vec_b, _) = ipa_instance(n, arity);
+        let proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    // cargo test -p bulletproofs -- --ignored write_golden_vectors
+    #[test]
+    #[ignore]
+    fn write_golden_vectors() {
+        for (n, arity) in GOLDEN_SIZES.into_iter().flat_map(|n| GOLDEN_ARITIES.map(|arity| (n, arity))) {
+            std::fs::create_dir_all(golden_path(n, arity).parent().unwrap()).unwrap();
+            std::fs::write(golden_path(n, arity), golden_proof(n, arity) + "\n").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_ipa_golden_vectors() {
+        // a change of the transcript or of the proof encoding changes the proof bytes,
+        // rerun write_golden_vectors if the change is intended
+        for (n, arity) in GOLDEN_SIZES.into_iter().flat_map(|n| GOLDEN_ARITIES.map(|arity| (n, arity))) {
+            let golden = std::fs::read_to_string(golden_path(n, arity)).unwrap();
+            assert_eq!(golden.trim_end(), golden_proof(n, arity), "golden proof for n = {}, {:?} changed", n, arity);
+        }
+    }
+
+    // the proofs that differ from proof in exactly one field: each L, R, challenge, a and b
+    fn proof_mutations(proof: &InnerProductProof<Projective>) -> Vec<(String, InnerProductProof<Projective>)> {
+        let g = <Projective as ark_ec::Group>::generator();
+        let mut mutations = vec![];
+        for i in 0..proof.vec_L.len() {
+            let mut mutated = proof.clone();
+            mutated.vec_L[i] = (mutated.vec_L[i] + g).into_affine();
+            mutations.push((format!("L_{}", i), mutated));
+            let mut mutated = proof.clone();
+            mutated.vec_R[i] = (mutated.vec_R[i] + g).into_affine();
+            mutations.push((format!("R_{}", i), mutated));
+            let mut mutated = proof.clone();
+            mutated.challenges[i] += Fr::one();
+            mutations.push((format!("challenge_{}", i), mutated));
+        }
+        let mut mutated = proof.clone();
+        mutated.a += Fr::one();
+        mutations.push(("a".to_string(), mutated));
+        let mut mutated = proof.clone();
+        mutated.b += Fr::one();
+        mutations.push(("b".to_string(), mutated));
+        mutations
+    }
+
+    #[test]
+    fn test_ipa_mutations() {
+        type Ipa = InnerProductProtocol<Projective>;
+        for n in GOLDEN_SIZES {
+            let (params, vec_a, vec_b, P) = ipa_instance(n, FoldingArity::Two);
+            let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+            Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+            let mutations = proof_mutations(&proof);
+            assert_eq!(mutations.len(), 3*n.trailing_zeros() as usize + 2);
+            for (field, mutated) in mutations {
+                assert!(Ipa::verify(IPA_DOMAIN, n, P, &params, &mutated).is_err(), "mutated {} verified for n = {}", field, n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ipa_arity_replay() {
+        // for n = 2 both modes run a single binary round, and only the transcript tells them apart
+        type Ipa = InnerProductProtocol<Projective>;
+        let (params, vec_a, vec_b, P) = ipa_instance(2, FoldingArity::Four);
+        let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+        let params_binary = InnerProductParam { arity: FoldingArity::Two, ..params.clone() };
+        assert!(Ipa::verify(IPA_DOMAIN, 2, P, &params_binary, &proof).is_err());
+
+        let (params, vec_a, vec_b, P) = ipa_instance(2, FoldingArity::Two);
+        let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+        let params_quaternary = InnerProductParam { arity: FoldingArity::Four, ..params.clone() };
+        assert!(Ipa::verify(IPA_DOMAIN, 2, P, &params_quaternary, &proof).is_err());
+    }
+
+    #[test]
+    fn test_ipa_domain_separation() {
+        // the same statement proved under two domains does not cross-verify
+        type Ipa = InnerProductProtocol<Projective>;
+        let (params, vec_a, vec_b, P) = ipa_instance(8, FoldingArity::Two);
+        let proof_1 = Ipa::prove(b"Domain1", &params, vec_a.clone(), vec_b.clone()).unwrap();
+        let proof_2 = Ipa::prove(b"Domain2", &params, vec_a, vec_b).unwrap();
+        assert_ne!(proof_1, proof_2);
+        Ipa::verify(b"Domain1", 8, P, &params, &proof_1).unwrap();
+        Ipa::verify(b"Domain2", 8, P, &params, &proof_2).unwrap();
+        assert!(Ipa::verify(b"Domain2", 8, P, &params, &proof_1).is_err());
+        assert!(Ipa::verify(b"Domain1", 8, P, &params, &proof_2).is_err());
+    }
+
+    #[test]
+    fn test_ipa_external_transcript() {
+        // the proof is bound to what the outer transcript absorbed before the IPA
+        type Ipa = InnerProductProtocol<Projective>;
+        let (params, vec_a, vec_b, P) = ipa_instance(8, FoldingArity::Two);
+        let outer = |msg: &[u8]| {
+            let mut transcript = ProofTranscript::<Fr>::new(b"Outer");
+            transcript.append_message(b"outer message", msg).unwrap();
+            transcript
+        };
+        let proof = Ipa::prove_with_transcript(&mut outer(b"statement 1"), &params, vec_a, vec_b).unwrap();
+        Ipa::verify_with_transcript(&mut outer(b"statement 1"), 8, P, &params, &proof).unwrap();
+        assert!(Ipa::verify_with_transcript(&mut outer(b"statement 2"), 8, P, &params, &proof).is_err());
+        assert!(Ipa::verify(b"Outer", 8, P, &params, &proof).is_err());
+    }
+
+    #[test]
+    fn test_ipa_target_terms() {
+        // the terms of P are accepted in place of P, for both folding arities
+        type Ipa = InnerProductProtocol<Projective>;
+        for (n, arity) in [(16, FoldingArity::Two), (16, FoldingArity::Four)] {
+            let (params, vec_a, vec_b, P) = ipa_instance(n, arity);
+            let mut terms: Vec<(Affine, Fr)> = vec![(params.u, inner_product(&vec_a, &vec_b))];
+            terms.extend(params.vec_G.iter().copied().zip(hadamard_product(&vec_a, &params.factors_G)));
+            terms.extend(params.vec_H.iter().copied().zip(hadamard_product(&vec_b, &params.factors_H)));
+            let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+            Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+            Ipa::verify_with_terms(IPA_DOMAIN, n, &terms, &params, &proof).unwrap();
+
+            // a target split into other terms of the same sum
+            let half = (P * Fr::from(2u64).inverse().unwrap()).into_affine();
+            Ipa::verify_with_terms(IPA_DOMAIN, n, &[(half, Fr::from(2u64))], &params, &proof).unwrap();
+
+            // a wrong scalar, a missing term and an empty target are rejected
+            let mut terms_prime = terms.clone();
+            terms_prime[1].1 += Fr::one();
+            assert!(Ipa::verify_with_terms(IPA_DOMAIN, n, &terms_prime, &params, &proof).is_err());
+            assert!(Ipa::verify_with_terms(IPA_DOMAIN, n, &terms[1..], &params, &proof).is_err());
+            assert!(Ipa::verify_with_terms(IPA_DOMAIN, n, &[], &params, &proof).is_err());
+        }
+    }
+
+    #[test]
+    fn test_ipa_shifted_generators() {
+        // a proof for the generators vec_G^{factors_G} * vec_S^c on the first half
+        // verifies against vec_G, factors_G and the shift (vec_S, c)
+        type Ipa = InnerProductProtocol<Projective>;
+        let mut rng = ark_std::test_rng();
+        let n = 8;
+        let (params, vec_a, vec_b, _) = ipa_instance(n, FoldingArity::Two);
+        let vec_S: Vec<Affine> = (0..n/2).map(|_| Affine::rand(&mut rng)).collect();
+        let c = Fr::rand(&mut rng);
+        let mut vec_G: Vec<Projective> = params.vec_G.iter().zip(&params.factors_G).map(|(g_i, f_i)| *g_i * f_i).collect();
+        vec_G.iter_mut().zip(&vec_S).for_each(|(g_i, s_i)| *g_i += *s_i * c);
+        let vec_H: Vec<Projective> = params.vec_H.iter().zip(&params.factors_H).map(|(h_i, f_i)| *h_i * f_i).collect();
+        let shifted = InnerProductParam::uniform(Projective::normalize_batch(&vec_G), Projective::normalize_batch(&vec_H), params.u);
+        let P = Projective::msm(&shifted.vec_G, &vec_a).unwrap() + Projective::msm(&shifted.vec_H, &vec_b).unwrap()
+            + params.u * inner_product(&vec_a, &vec_b);
+        let proof = Ipa::prove(IPA_DOMAIN, &shifted, vec_a, vec_b).unwrap();
+        Ipa::verify(IPA_DOMAIN, n, P, &shifted, &proof).unwrap();
+
+        let terms = [(P.into_affine(), Fr::one())];
+        let transcript = || {
+            let mut transcript = ProofTranscript::<Fr>::new(IPA_DOMAIN);
+            transcript.append_message(b"IPAdomain", IPA_DOMAIN).unwrap();
+            transcript
+        };
+        Ipa::verify_with_shifted_generators(&mut transcript(), n, &terms, &params, (&vec_S, c), &proof).unwrap();
+        assert!(Ipa::verify_with_shifted_generators(&mut transcript(), n, &terms, &params, (&vec_S, c + Fr::one()), &proof).is_err());
+        assert!(Ipa::verify_with_shifted_generators(&mut transcript(), n, &terms, &params, (&vec_S[1..], c), &proof).is_err());
+        assert!(Ipa::verify_with_transcript_and_terms(&mut transcript(), n, &terms, &params, &proof).is_err());
+
+        // a shift longer than the generators, or with the quaternary folding, is rejected
+        let vec_S_long: Vec<Affine> = (0..n+1).map(|_| Affine::rand(&mut rng)).collect();
+        assert!(Ipa::verify_with_shifted_generators(&mut transcript(), n, &terms, &params, (&vec_S_long, c), &proof).is_err());
+        let mut params_four = params.clone();
+        params_four.arity = FoldingArity::Four;
+        assert!(Ipa::verify_with_shifted_generators(&mut transcript(), n, &terms, &params_four, (&vec_S, c), &proof).is_err());
+    }
+
+    #[test]
+    fn test_ipa_public_product() {
+        type Ipa = InnerProductProtocol<Projective>;
+        let transcript = || {
+            let mut transcript = ProofTranscript::<Fr>::new(IPA_DOMAIN);
+            transcript.append_message(b"IPAdomain", IPA_DOMAIN).unwrap();
+            transcript
+        };
+        for arity in [FoldingArity::Two, FoldingArity::Four] {
+            let n = 16;
+            let (params, vec_a, vec_b, P) = ipa_instance(n, arity);
+            let c = inner_product(&vec_a, &vec_b);
+            // the target without u^c
+            let terms = [(P.into_affine(), Fr::one()), (params.u, -c)];
+            let proof = Ipa::prove_with_public_product(&mut transcript(), &params, vec_a.clone(), vec_b.clone()).unwrap();
+            Ipa::verify_with_public_product(&mut transcript(), n, &terms, c, &params, &proof).unwrap();
+
+            // a tampered c is rejected, also when the target is shifted by u^{c - c'} to match it
+            let c_prime = c + Fr::one();
+            assert!(Ipa::verify_with_public_product(&mut transcript(), n, &terms, c_prime, &params, &proof).is_err());
+            let terms_prime = [(P.into_affine(), Fr::one()), (params.u, -c_prime)];
+            assert!(Ipa::verify_with_public_product(&mut transcript(), n, &terms_prime, c_prime, &params, &proof).is_err());
+            // the proof is not a proof of the plain IPA, whose target carries u^c
+            assert!(Ipa::verify_with_transcript(&mut transcript(), n, P, &params, &proof).is_err());
+            assert!(Ipa::verify_with_public_product(&mut transcript(), n, &[(P.into_affine(), Fr::one())], c, &params, &proof).is_err());
+        }
+
+        // the shifted variant verifies the generators of test_ipa_shifted_generators
+        let mut rng = ark_std::test_rng();
+        let n = 8;
+        let (params, vec_a, vec_b, _) = ipa_instance(n, FoldingArity::Two);
+        let vec_S: Vec<Affine> = (0..n).map(|_| Affine::rand(&mut rng)).collect();
+        let s = Fr::rand(&mut rng);
+        let mut vec_G: Vec<Projective> = params.vec_G.iter().zip(&params.factors_G).map(|(g_i, f_i)| *g_i * f_i).collect();
+        vec_G.iter_mut().zip(&vec_S).for_each(|(g_i, s_i)| *g_i += *s_i * s);
+        let vec_H: Vec<Projective> = params.vec_H.iter().zip(&params.factors_H).map(|(h_i, f_i)| *h_i * f_i).collect();
+        let shifted = InnerProductParam::uniform(Projective::normalize_batch(&vec_G), Projective::normalize_batch(&vec_H), params.u);
+        let P = Projective::msm(&shifted.vec_G, &vec_a).unwrap() + Projective::msm(&shifted.vec_H, &vec_b).unwrap();
+        let c = inner_product(&vec_a, &vec_b);
+        let proof = Ipa::prove_with_public_product(&mut transcript(), &shifted, vec_a, vec_b).unwrap();
+        let terms = [(P.into_affine(), Fr::one())];
+        Ipa::verify_shifted_with_public_product(&mut transcript(), n, &terms, c, &params, Some((&vec_S, s)), &proof).unwrap();
+        assert!(Ipa::verify_shifted_with_public_product(&mut transcript(), n, &terms, -c, &params, Some((&vec_S, s)), &proof).is_err());
+
+        // vectors of different lengths are rejected before their inner product
+        assert!(Ipa::prove_with_public_product(&mut transcript(), &params, vec![Fr::one(); n], vec![Fr::one(); n/2]).is_err());
+    }
+
+    #[test]
+    fn test_ipa_streaming() {
+        type Ipa = InnerProductProtocol<Projective>;
+        let n = 16;
+        let (params, vec_a, vec_b, P) = ipa_instance(n, FoldingArity::Two);
+
+        // with the transcript challenges, the streamed proof is the proof of prove
+        let mut state = InnerProductProverState::new(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+        let (L, R) = Ipa::prove_step(&mut state).unwrap().unwrap();
+        let proof = Ipa::prove_finish(state).unwrap();
+        assert_eq!(proof, Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap());
+        assert_eq!((proof.vec_L[0], proof.vec_R[0]), (L, R));
+        Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+
+        // interleave the rounds with the challenges of an external verifier,
+        // which checks P * L^{x^2} * R^{x^-2} = G^{a*s} * H^{b*s_reverse} * u^{ab} at the end
+        let mut rng = ark_std::test_rng();
+        let mut state = InnerProductProverState::new(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+        assert!(state.receive_challenge(Fr::rand(&mut rng)).is_err());
+        let mut lhs = P;
+        let mut challenges = vec![];
+        while let Some((L, R)) = Ipa::prove_step(&mut state).unwrap() {
+            assert!(state.receive_challenge(Fr::zero()).is_err());
+            let x = Fr::rand(&mut rng);
+            state.receive_challenge(x).unwrap();
+            lhs += L*x.square() + R*x.inverse().unwrap().square();
+            challenges.push(x);
+        }
+        let proof = Ipa::prove_finish(state).unwrap();
+        assert_eq!(proof.challenges, challenges);
+        assert_eq!(proof.vec_L.len(), 4);
+
+        let s = Ipa::compute_s_vector(&challenges).unwrap();
+        let mut exp: Vec<Fr> = s.iter().zip(&params.factors_G).map(|(s_i, f)| proof.a * s_i * f).collect();
+        exp.extend(reversed(&s).iter().zip(&params.factors_H).map(|(s_i, f)| proof.b * s_i * f));
+        let mut base = params.vec_G.clone();
+        base.extend(params.vec_H.clone());
+        let rhs = Projective::msm(&base, &exp).unwrap() + params.u*(proof.a*proof.b);
+        assert_eq!(lhs, rhs);
+        assert!(Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).is_err());
+
+        let params_4 = InnerProductParam { arity: FoldingArity::Four, ..params };
+        assert!(InnerProductProverState::new(IPA_DOMAIN, &params_4, vec_a, vec_b).is_err());
+    }
+
+    // the generators scaled by the factors on the fly
+    fn scaled_source<'a>(vec: &'a [Affine], factors: &'a [Fr]) -> FnSource<impl Fn(usize, usize) -> Vec<Affine> + 'a> {
+        FnSource {
+            len: vec.len(),
+            read: move |start, len| {
+                let terms: Vec<Projective> = (start..start + len).map(|i| vec[i] * factors[i]).collect();
+                Projective::normalize_batch(&terms)
+            },
+        }
+    }
+
+    #[test]
+    fn test_ipa_prove_streaming() {
+        type Ipa = InnerProductProtocol<Projective>;
+        for n in [1, 2, 1 << 12] {
+            let (params, vec_a, vec_b, P) = ipa_instance(n, FoldingArity::Two);
+            let vec_G = scaled_source(&params.vec_G, &params.factors_G);
+            let vec_H = scaled_source(&params.vec_H, &params.factors_H);
+            let proof = Ipa::prove_streaming(IPA_DOMAIN, params.u, &vec_a[..], &vec_b[..], &vec_G, &vec_H).unwrap();
+            assert_eq!(proof, Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap());
+            Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+            if n > 1 {
+                assert!(Ipa::prove_streaming(IPA_DOMAIN, params.u, &vec_a[1..], &vec_b[1..], &vec_G, &vec_H).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_ipa_truncated_proof() {
+        type Ipa = InnerProductProtocol<Projective>;
+        let is_invalid_proof = |result: Result<(), SigmaErrors>| matches!(result, Err(SigmaErrors::InvalidProof(_)));
+        // a proof missing its last L is rejected as invalid rather than panicking on a length mismatch
+        for (n, arity) in [(8, FoldingArity::Two), (16, FoldingArity::Four)] {
+            let (params, vec_a, vec_b, P) = ipa_instance(n, arity);
+            let mut proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+            proof.vec_L.pop();
+            assert!(is_invalid_proof(Ipa::verify(IPA_DOMAIN, n, P, &params, &proof)));
+            proof.vec_R.pop();
+            proof.challenges.pop();
+            assert!(is_invalid_proof(Ipa::verify(IPA_DOMAIN, n, P, &params, &proof)));
+        }
+        let (params, vec_a, public_b, P) = ipa_instance(8, FoldingArity::Two);
+        let mut proof = Ipa::prove_public_b(IPA_DOMAIN, &params, vec_a, &public_b).unwrap();
+        proof.vec_L.pop();
+        assert!(is_invalid_proof(Ipa::verify_public_b(IPA_DOMAIN, 8, P, &params, &public_b, &proof)));
+
+        // the msm helper reports mismatched lengths with the error of its caller's side
+        let mut rng = ark_std::test_rng();
+        let bases: Vec<Affine> = (0..4).map(|_| Affine::rand(&mut rng)).collect();
+        let scalars: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+        assert!(matches!(msm::<Projective>(&bases, &scalars, SigmaErrors::InvalidProof), Err(SigmaErrors::InvalidProof(_))));
+        assert!(matches!(msm::<Projective>(&bases, &scalars, SigmaErrors::InvalidParameters), Err(SigmaErrors::InvalidParameters(_))));
+        assert!(msm::<Projective>(&bases[..3], &scalars, SigmaErrors::InvalidProof).is_ok());
+    }
+
+    #[test]
+    fn test_ipa_rounds_overflow() {
+        type Ipa = InnerProductProtocol<Projective>;
+        let (params, vec_a, vec_b, P) = ipa_instance(8, FoldingArity::Two);
+        let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+
+        // a proof claiming an impossible number of rounds is rejected without overflowing the shift
+        for log_n in [64, 100] {
+            let mut proof_prime = proof.clone();
+            proof_prime.vec_L = vec![Affine::default(); log_n];
+            proof_prime.vec_R = vec![Affine::default(); log_n];
+            proof_prime.challenges = vec![Fr::one(); log_n];
+            assert!(matches!(
+                Ipa::verify(IPA_DOMAIN, 8, P, &params, &proof_prime),
+                Err(SigmaErrors::InvalidParameters(_))
+            ));
+        }
+        assert!(Ipa::verify(IPA_DOMAIN, 8, P, &params, &proof).is_ok());
+
+        // the 64-bit and the 32-bit limits of the round count
+        assert_eq!(rounds_to_length(63, 64).unwrap(), 1 << 63);
+        assert!(rounds_to_length(64, 64).is_err());
+        assert_eq!(rounds_to_length(31, 32).unwrap(), 1 << 31);
+        assert!(rounds_to_length(32, 32).is_err());
+        assert!(rounds_to_length(usize::MAX, 64).is_err());
+
+        // the claimed vector length must be a power of two
+        let params_6 = InnerProductParam {
+            vec_G: params.vec_G[..6].to_vec(),
+            vec_H: params.vec_H[..6].to_vec(),
+            factors_G: params.factors_G[..6].to_vec(),
+            factors_H: params.factors_H[..6].to_vec(),
+            ..params.clone()
+        };
+        assert!(matches!(
+            Ipa::verify(IPA_DOMAIN, 6, P, &params_6, &proof),
+            Err(SigmaErrors::InvalidParameters(msg)) if msg == "vector length is not power of two"
+        ));
+
+        // parameters longer than n, or with a vector of another length, are rejected instead of truncated
+        assert!(matches!(
+            Ipa::verify(IPA_DOMAIN, 4, P, &params, &proof),
+            Err(SigmaErrors::InvalidParameters(_))
+        ));
+        let truncations: [fn(&mut InnerProductParam<Projective>); 3] = [
+            |params| { params.vec_H.pop(); },
+            |params| { params.factors_G.pop(); },
+            |params| { params.factors_H.pop(); },
+        ];
+        for truncate in truncations {
+            let mut params_prime = params.clone();
+            truncate(&mut params_prime);
+            assert!(matches!(
+                Ipa::verify(IPA_DOMAIN, 8, P, &params_prime, &proof),
+                Err(SigmaErrors::InvalidParameters(msg)) if msg == "vectors length are different"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_fold_in_place() {
+        // the in-place folding of the loop rounds matches the folding into new vectors
+        let mut rng = ark_std::test_rng();
+        let n = 8;
+        let (x, x_inv) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let mut vec_a: Vec<Fr> = (0..2*n).map(|_| Fr::rand(&mut rng)).collect();
+        let mut vec_G: Vec<Affine> = (0..2*n).map(|_| Affine::rand(&mut rng)).collect();
+        let expected_a = fold_scalars(&vec_a[0..n], &vec_a[n..], &x, &x_inv);
+        let expected_G = fold_generators::<Projective>(&vec_G[0..n], &vec_G[n..], &vec![x_inv; n], &vec![x; n]);
+        let mut terms = vec![];
+        fold_scalars_in_place(&mut vec_a, n, &x, &x_inv);
+        fold_generators_in_place::<Projective>(&mut vec_G, n, &x_inv, &x, &mut terms);
+        assert_eq!(vec_a, expected_a);
+        assert_eq!(vec_G, expected_G);
+
+        // including the zero and one scalars
+        let (g_L, g_R) = (vec_G[0], vec_G[1]);
+        for (x_L, x_R) in [(Fr::zero(), Fr::one()), (Fr::one(), Fr::zero()), (-Fr::one(), Fr::from(2u64))] {
+            assert_eq!(mul2::<Projective>(&g_L, &x_L, &g_R, &x_R), g_L*x_L + g_R*x_R);
+        }
+    }
+
+    #[test]
+    fn test_compute_s_vector() {
+        let mut rng = ark_std::test_rng();
+        let log_n = 3;
+        let challenges: Vec<Fr> = (0..log_n).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_box = InnerProductProtocol::<Projective>::compute_s_vector(&challenges).unwrap();
+
+        // naive O(n log n) product of challenges
+        for (i, s_i) in vec_box.iter().enumerate() {
+            let mut expected = Fr::from(1u64);
+            for (j, x_j) in challenges.iter().enumerate() {
+                if (i >> (log_n-1-j)) & 1 == 1 {
+                    expected *= x_j;
+                } else {
+                    expected *= x_j.inverse().unwrap();
+                }
+            }
+            assert_eq!(*s_i, expected);
+        }
+        assert_eq!(vec_box.len(), 8);
+
+        // the boxes of the verifier with a and b folded in, where the H side runs in reverse
+        let (a, b) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let all_x: Fr = challenges.iter().product();
+        let challenges_sq: Vec<Fr> = challenges.iter().map(|x| x.square()).collect();
+        let challenges_inv_sq: Vec<Fr> = challenges.iter().map(|x| x.inverse().unwrap().square()).collect();
+        let box_a = box_scalars(a*all_x.inverse().unwrap(), &challenges_sq);
+        let box_b = box_scalars(b*all_x, &challenges_inv_sq);
+        for i in 0..8 {
+            assert_eq!(box_a[i], a*vec_box[i]);
+            assert_eq!(box_b[i], b*vec_box[7-i]);
+        }
+
+        // a zero challenge has no inverse
+        let mut challenges_zero = challenges.clone();
+        challenges_zero[1] = Fr::zero();
+        assert!(matches!(
+            InnerProductProtocol::<Projective>::compute_s_vector(&challenges_zero),
+            Err(SigmaErrors::InvalidProof(_))
+        ));
+    }
+
+    // a transcript that emits a zero challenge once, and then falls back to the proof transcript
+    struct ZeroOnceTranscript {
+        transcript: ProofTranscript<Fr>,
+        emitted: bool,
+    }
+
+    impl ChallengeSource<Fr> for ZeroOnceTranscript {
+        fn challenge(&mut self, label: &'static [u8]) -> Result<Fr, SigmaErrors> {
+            if !self.emitted {
+                self.emitted = true;
+                return Ok(Fr::zero());
+            }
+            self.transcript.challenge(label)
+        }
+    }
+
+    #[test]
+    fn test_nonzero_challenge() {
+        let mut transcript = ProofTranscript::<Fr>::new(IPA_DOMAIN);
+        transcript.append_message(b"IPAdomain", IPA_DOMAIN).unwrap();
+        let mut stub = ZeroOnceTranscript { transcript, emitted: false };
+        // the zero challenge is an error rather than a panic on its inverse
+        assert!(matches!(
+            nonzero_challenge(&mut stub, b"challenge"),
+            Err(SigmaErrors::InvalidProof(_))
+        ));
+        let (x, x_inv) = nonzero_challenge(&mut stub, b"challenge").unwrap();
+        assert_eq!(x * x_inv, Fr::one());
+    }
+
+    #[test]
+    fn test_ipa_prover_key() {
+        type Ipa = InnerProductProtocol<Projective>;
+        for n in [1, 2, 8, 64] {
+            for arity in [FoldingArity::Two, FoldingArity::Four] {
+                let (params, vec_a, vec_b, P) = ipa_instance(n, arity);
+                let key = IpaProverKey::new(&params);
+                let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+                // the keyed and unkeyed proofs are identical, also for a second proof with the same key
+                for _ in 0..2 {
+                    let proof_key = Ipa::prove_with_key(IPA_DOMAIN, &key, vec_a.clone(), vec_b.clone()).unwrap();
+                    assert_eq!(proof_key, proof);
+                }
+                Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+            }
+        }
+
+        // the comb multiplication agrees with the plain scalar multiplication
+        let mut rng = ark_std::test_rng();
+        let (g, h) = (Affine::rand(&mut rng), Affine::rand(&mut rng));
+        for (x, y) in [(Fr::rand(&mut rng), Fr::rand(&mut rng)), (Fr::from(0u64), -Fr::from(1u64))] {
+            let expected = g*x + h*y;
+            assert_eq!(comb_mul2::<Projective>(&comb_table::<Projective>(g), &x, &comb_table::<Projective>(h), &y), expected);
+        }
+    }
+
+    #[test]
+    fn test_ipa_blinded() {
+        type Ipa = InnerProductProtocol<Projective>;
+        let mut rng = ark_std::test_rng();
+        for n in [1, 4, 16] {
+            for arity in [FoldingArity::Two, FoldingArity::Four] {
+                let (params, vec_a, vec_b, P) = ipa_instance(n, arity);
+                let h = Affine::rand(&mut rng);
+                let rho = Fr::rand(&mut rng);
+                let P_hiding = P + h*rho;
+                let proof = Ipa::prove_blinded(&mut rng, IPA_DOMAIN, &params, h, vec_a.clone(), vec_b.clone(), rho).unwrap();
+                Ipa::verify_blinded(IPA_DOMAIN, n, P_hiding, &params, h, &proof).unwrap();
+
+                // soundness: the target, the blinding opening and the commitments are all checked
+                assert!(Ipa::verify_blinded(IPA_DOMAIN, n, P, &params, h, &proof).is_err());
+                let mut proof_prime = proof.clone();
+                proof_prime.rho += Fr::from(1u64);
+                assert!(Ipa::verify_blinded(IPA_DOMAIN, n, P_hiding, &params, h, &proof_prime).is_err());
+                let mut proof_prime = proof.clone();
+                proof_prime.com_S1 = (proof.com_S1 + h).into_affine();
+                assert!(Ipa::verify_blinded(IPA_DOMAIN, n, P_hiding, &params, h, &proof_prime).is_err());
+                let mut proof_prime = proof.clone();
+                proof_prime.com_S2 = (proof.com_S2 + h).into_affine();
+                assert!(Ipa::verify_blinded(IPA_DOMAIN, n, P_hiding, &params, h, &proof_prime).is_err());
+                let mut proof_prime = proof.clone();
+                proof_prime.ipa_proof.a += Fr::from(1u64);
+                assert!(Ipa::verify_blinded(IPA_DOMAIN, n, P_hiding, &params, h, &proof_prime).is_err());
+                // a wrong witness does not verify
+                let proof_prime = Ipa::prove_blinded(&mut rng, IPA_DOMAIN, &params, h, vec_b.clone(), vec_a.clone(), rho).unwrap();
+                assert!(Ipa::verify_blinded(IPA_DOMAIN, n, P_hiding, &params, h, &proof_prime).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_ipa_blinded_hvzk() {
+        type Ipa = InnerProductProtocol<Projective>;
+        let mut rng = ark_std::test_rng();
+        let n = 8;
+        // generators with known discrete logarithms to g, so that two witnesses open the same target
+        let g = Projective::rand(&mut rng);
+        let dlog_G: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let dlog_H: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let (dlog_u, dlog_h) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let params = InnerProductParam::<Projective> {
+            factors_G: vec![Fr::from(1u64); n],
+            factors_H: vec![Fr::from(1u64); n],
+            u: (g*dlog_u).into_affine(),
+            vec_G: dlog_G.iter().map(|x| (g*x).into_affine()).collect(),
+            vec_H: dlog_H.iter().map(|x| (g*x).into_affine()).collect(),
+            arity: FoldingArity::Two,
+        };
+        let h = (g*dlog_h).into_affine();
+        let dlog_P = |a: &[Fr], b: &[Fr], rho: Fr| {
+            inner_product(a, &dlog_G) + inner_product(b, &dlog_H) + dlog_u*inner_product(a, b) + dlog_h*rho
+        };
+
+        let vec_a1: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_b1: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let rho1 = Fr::rand(&mut rng);
+        let vec_a2: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_b2: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let rho2 = (dlog_P(&vec_a1, &vec_b1, rho1) - dlog_P(&vec_a2, &vec_b2, Fr::from(0u64))) / dlog_h;
+        let P = g*dlog_P(&vec_a1, &vec_b1, rho1);
+        assert_eq!(P, g*dlog_P(&vec_a2, &vec_b2, rho2));
+
+        // both witnesses give accepting transcripts for the same statement
+        let proof1 = Ipa::prove_blinded(&mut rng, IPA_DOMAIN, &params, h, vec_a1, vec_b1, rho1).unwrap();
+        let proof2 = Ipa::prove_blinded(&mut rng, IPA_DOMAIN, &params, h, vec_a2, vec_b2, rho2).unwrap();
+        Ipa::verify_blinded(IPA_DOMAIN, n, P, &params, h, &proof1).unwrap();
+        Ipa::verify_blinded(IPA_DOMAIN, n, P, &params, h, &proof2).unwrap();
+
+        // and both are distributed as the simulated transcript, which is computed without a witness
+        // from uniform masked vectors a', b', opening rho' and S2 for the challenge e of the honest verifier:
+        // S1 = (vec_G^a' vec_H^b' u^<a', b'> h^rho' - P - S2^{e^2})^{1/e}
+        for proof in [proof1, proof2] {
+            let e = proof.challenge;
+            let masked_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+            let masked_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+            let rho_sim = Fr::rand(&mut rng);
+            let com_S2 = Projective::rand(&mut rng);
+            let masked_P = blinded_commit(&params, h, &masked_a, &masked_b, inner_product(&masked_a, &masked_b), rho_sim).unwrap();
+            let com_S1 = (masked_P - P - com_S2*(e*e)) * e.inverse().unwrap();
+            // the simulated transcript passes the check that the IPA verifier runs on the masked target
+            assert_eq!(
+                P + com_S1*e + com_S2*(e*e) - h*rho_sim,
+                blinded_commit(&params, h, &masked_a, &masked_b, inner_product(&masked_a, &masked_b), Fr::from(0u64)).unwrap(),
+            );
+        }
+    }
+
+    #[test]
+    fn test_ipa_public_b() {
+        type Ipa = InnerProductProtocol<Projective>;
+        for n in [1, 2, 8, 64] {
+            let (params, vec_a, public_b, _) = ipa_instance(n, FoldingArity::Two);
+            // P = vec_G^{factors_G * a} * u^<a, b> does not involve vec_H
+            let P = Projective::msm(&params.vec_G, &hadamard_product(&vec_a, &params.factors_G)).unwrap()
+                + params.u*inner_product(&vec_a, &public_b);
+            let proof = Ipa::prove_public_b(IPA_DOMAIN, &params, vec_a.clone(), &public_b).unwrap();
+            assert_eq!(proof.vec_L.len(), n.trailing_zeros() as usize);
+            Ipa::verify_public_b(IPA_DOMAIN, n, P, &params, &public_b, &proof).unwrap();
+
+            // the proof is bound to the public vector and the opening a
+            let mut public_b_prime = public_b.clone();
+            public_b_prime[0] += Fr::from(1u64);
+            assert!(Ipa::verify_public_b(IPA_DOMAIN, n, P, &params, &public_b_prime, &proof).is_err());
+            let mut proof_prime = proof.clone();
+            proof_prime.a += Fr::from(1u64);
+            assert!(Ipa::verify_public_b(IPA_DOMAIN, n, P, &params, &public_b, &proof_prime).is_err());
+        }
+
+        // the one-sided proof has no b and each L, R is an msm of half the size
+        let (params, vec_a, vec_b, P) = ipa_instance(16, FoldingArity::Two);
+        let proof = Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+        let proof_public_b = Ipa::prove_public_b(IPA_DOMAIN, &params, vec_a, &vec_b).unwrap();
+        assert_eq!(proof_public_b.compressed_size() + proof.b.compressed_size(), proof.compressed_size());
+        Ipa::verify(IPA_DOMAIN, 16, P, &params, &proof).unwrap();
+
+        // the 4-ary folding is not supported
+        let (params, vec_a, public_b, _) = ipa_instance(16, FoldingArity::Four);
+        assert!(Ipa::prove_public_b(IPA_DOMAIN, &params, vec_a, &public_b).is_err());
+    }
+
+    #[test]
+    fn test_ipa_folds_match_serial() {
+        let mut rng = ark_std::test_rng();
+        let n = 64;
+        let vec_L: Vec<Affine> = (0..n).map(|_| Affine::rand(&mut rng)).collect();
+        let vec_R: Vec<Affine> = (0..n).map(|_| Affine::rand(&mut rng)).collect();
+        let a_L: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let a_R: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let x = Fr::rand(&mut rng);
+        let x_inv = x.inverse().unwrap();
+
+        // fold the generators and scalars index by index
+        let mut expected_G = vec![];
+        for i in 0..n {
+            expected_G.push(Projective::msm(&[vec_L[i], vec_R[i]], &[x_inv*a_L[i], x*a_R[i]]).unwrap().into_affine());
+        }
+        let expected_a: Vec<Fr> = (0..n).map(|i| a_L[i]*x + a_R[i]*x_inv).collect();
+        let fold_G = fold_generators::<Projective>(&vec_L, &vec_R, &scalar_product(&a_L, &x_inv), &scalar_product(&a_R, &x));
+        assert_eq!(fold_G, expected_G);
+        assert_eq!(fold_scalars(&a_L, &a_R, &x, &x_inv), expected_a);
+
+        // cross terms and the commitments L, R
+        let (c_L, c_R) = cross_terms(&a_L, &a_R, &expected_a, &a_L);
+        assert_eq!(c_L, inner_product(&a_L, &a_L));
+        assert_eq!(c_R, inner_product(&a_R, &expected_a));
+        let (com_L, com_R) = commit_cross::<Projective>(&vec_L, &a_L, &vec_R, &a_R).unwrap();
+        assert_eq!(com_L, Projective::msm(&vec_L, &a_L).unwrap().into_affine());
+        assert_eq!(com_R, Projective::msm(&vec_R, &a_R).unwrap().into_affine());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_ipa_parallel_matches_single_thread() {
+        let mut rng = ark_std::test_rng();
+        // the first rounds run in parallel, the last ones below PARALLEL_MIN_LEN sequentially
+        let n = 512;
+        let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let params = InnerProductParam::<Projective> {
+            factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            u: Affine::rand(&mut rng),
+            vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            arity: FoldingArity::Two,
+        };
+
+        type Ipa = InnerProductProtocol<Projective>;
+        let prove_with = |num_threads: usize| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap()
+                .install(|| Ipa::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap())
+        };
+        let proof = prove_with(4);
+        assert_eq!(proof, prove_with(1));
+
+        let mut exp = hadamard_product(&vec_a, &params.factors_G);
+        exp.extend(hadamard_product(&vec_b, &params.factors_H));
+        let mut base = params.vec_G.clone();
+        base.extend(params.vec_H.clone());
+        let P = Projective::msm(&base, &exp).unwrap() + params.u*inner_product(&vec_a, &vec_b);
+        Ipa::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+    }
+}
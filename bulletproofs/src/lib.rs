@@ -1,2 +1,14 @@
+#![no_std]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+// the standard library is only linked with the std feature, and by the tests
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
 pub mod ipa;
-pub mod structs;
\ No newline at end of file
+pub mod poly_commit;
+pub mod range;
+pub mod structs;
+pub mod vc_open;
+pub mod wip;
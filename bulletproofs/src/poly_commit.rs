@@ -0,0 +1,186 @@
+use alloc::{string::ToString, vec, vec::Vec};
+use core::marker::PhantomData;
+use ark_ec::CurveGroup;
+use ark_ff::PrimeField;
+use ark_std::{end_timer, rand::Rng, start_timer, One, UniformRand, Zero};
+use toolbox::errors::SigmaErrors;
+use toolbox::sigma::transcript::ProofTranscript;
+use toolbox::vec::{generate_powers, inner_product, rand_affine_vec};
+use crate::ipa::InnerProductProtocol;
+use crate::structs::*;
+
+// the transcript domain of the evaluation proofs
+const POLY_COMMIT_DOMAIN: &[u8] = b"PolynomialCommitment";
+
+// Polynomial commitment of f(X) = sum_i f_i X^i of degree below n - 1 for the IPA parameters of length n:
+// com = vec_G^{(f_0, ..., f_{n-2}, r)}, where the last generator of vec_G blinds the commitment,
+// and f(z) = v is the inner product of (f_0, ..., f_{n-2}, r) with b = (1, z, ..., z^{n-2}, 0),
+// so that the IPA proves the target com * vec_H^b * u^v without revealing r.
+// The IPA is not zero-knowledge, so that the evaluation proof leaks combinations of the coefficients
+// beyond f(z); vc_open runs the blinded IPA when the other entries should stay hidden
+
+/// IPA-based polynomial commitment
+#[derive(Clone, Debug)]
+pub struct PolyCommit<C: CurveGroup> {
+    phantom: PhantomData<C>,
+}
+
+impl<C: CurveGroup> PolyCommit<C> {
+    /// Setup algorithm generates the IPA parameters of length n, a power of two of at least 2,
+    /// for polynomials of degree below n - 1
+    pub fn setup<R: Rng>(rng: &mut R, n: usize) -> Result<InnerProductParam<C>, SigmaErrors> {
+        if n < 2 {
+            return Err(SigmaErrors::InvalidParameters(
+                "vector length should be at least 2".to_string(),
+            ));
+        }
+        InnerProductParam::new(
+            vec![C::ScalarField::one(); n],
+            vec![C::ScalarField::one(); n],
+            C::Affine::rand(rng),
+            rand_affine_vec::<C, R>(rng, n),
+            rand_affine_vec::<C, R>(rng, n),
+        )
+    }
+
+    /// Commit algorithm commits to the polynomial with coefficients coeffs, from the constant term up,
+    /// under a fresh blinding random, and outputs the commitment with its opening
+    pub fn commit<R: Rng>(
+        rng: &mut R,
+        params: &InnerProductParam<C>,
+        coeffs: &[C::ScalarField],
+    ) -> Result<(C, PolynomialOpening<C>), SigmaErrors> {
+        let opening = PolynomialOpening {
+            coeffs: coeffs.to_vec(),
+            random: C::ScalarField::rand(rng),
+        };
+        let vec_a = committed_vector(params, &opening)?;
+        let com = C::msm(&params.vec_G, &vec_a)
+            .map_err(|_| SigmaErrors::InvalidParameters("msm length mismatch".to_string()))?;
+        Ok((com, opening))
+    }
+
+    /// Open algorithm evaluates the committed polynomial at z, and outputs the value with its evaluation proof
+    pub fn open(
+        params: &InnerProductParam<C>,
+        com: &C,
+        opening: &PolynomialOpening<C>,
+        z: &C::ScalarField,
+    ) -> Result<(C::ScalarField, EvalProof<C>), SigmaErrors> {
+        let start = start_timer!(|| "running polynomial commitment open algorithm...");
+        let vec_a = committed_vector(params, opening)?;
+        let vec_b = evaluation_vector(params.vec_G.len(), z);
+        let value = inner_product(&vec_a, &vec_b);
+        let mut transcript = statement_transcript(com, z, &value)?;
+        let ipa = InnerProductProtocol::<C>::prove_with_transcript(&mut transcript, params, vec_a, vec_b)?;
+        end_timer!(start);
+        Ok((value, EvalProof { ipa }))
+    }
+
+    /// Verify algorithm checks that the polynomial committed in com evaluates to value at z
+    pub fn verify(
+        params: &InnerProductParam<C>,
+        com: &C,
+        z: &C::ScalarField,
+        value: &C::ScalarField,
+        proof: &EvalProof<C>,
+    ) -> bool {
+        let n = params.vec_G.len();
+        if n < 2 || params.vec_H.len() != n {
+            return false;
+        }
+        let vec_b = evaluation_vector(n, z);
+        let Ok(H_b) = C::msm(&params.vec_H, &vec_b) else {
+            return false;
+        };
+        let target_P = *com + H_b + params.u * value;
+        let Ok(mut transcript) = statement_transcript(com, z, value) else {
+            return false;
+        };
+        InnerProductProtocol::<C>::verify_with_transcript(&mut transcript, n, target_P, params, &proof.ipa).is_ok()
+    }
+}
+
+// the vector (f_0, ..., f_{n-2}, r) committed under vec_G, with the coefficients padded by zeros
+fn committed_vector<C: CurveGroup>(
+    params: &InnerProductParam<C>,
+    opening: &PolynomialOpening<C>,
+) -> Result<Vec<C::ScalarField>, SigmaErrors> {
+    let n = params.vec_G.len();
+    if n < 2 || opening.coeffs.len() > n - 1 {
+        return Err(SigmaErrors::InvalidParameters(
+            "the degree of the polynomial should be below the vector length minus one".to_string(),
+        ));
+    }
+    let mut vec_a = opening.coeffs.clone();
+    vec_a.resize(n - 1, C::ScalarField::zero());
+    vec_a.push(opening.random);
+    Ok(vec_a)
+}
+
+// the powers (1, z, ..., z^{n-2}) of the evaluation point, and 0 against the blinding random
+fn evaluation_vector<F: PrimeField>(n: usize, z: &F) -> Vec<F> {
+    let mut vec_b = vec![F::one()];
+    vec_b.extend(generate_powers(*z, n - 2));
+    vec_b.push(F::zero());
+    vec_b
+}
+
+// the transcript binding the IPA challenges to the commitment, the evaluation point and the value
+fn statement_transcript<C: CurveGroup>(
+    com: &C,
+    z: &C::ScalarField,
+    value: &C::ScalarField,
+) -> Result<ProofTranscript<C::ScalarField>, SigmaErrors> {
+    let mut transcript = ProofTranscript::<C::ScalarField>::new(POLY_COMMIT_DOMAIN);
+    transcript.append_serializable_element(b"commitment", &com.into_affine())?;
+    transcript.append_field_element(b"point", z)?;
+    transcript.append_field_element(b"value", value)?;
+    Ok(transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use toolbox::vec::convert;
+    use ark_secp256k1::{Fr, Projective};
+
+    type Poly = PolyCommit<Projective>;
+
+    #[test]
+    fn test_poly_commit() {
+        let mut rng = ark_std::test_rng();
+        let params = Poly::setup(&mut rng, 8).unwrap();
+        // f(X) = X^3 + 2X + 1
+        let f: Vec<Fr> = convert(&[1, 2, 0, 1]);
+        let (com, opening) = Poly::commit(&mut rng, &params, &f).unwrap();
+        let z = Fr::from(3u64);
+        let (value, proof) = Poly::open(&params, &com, &opening, &z).unwrap();
+        assert_eq!(value, Fr::from(34u64));
+        assert!(Poly::verify(&params, &com, &z, &value, &proof));
+
+        // another value, point or commitment fails
+        assert!(!Poly::verify(&params, &com, &z, &Fr::from(35u64), &proof));
+        assert!(!Poly::verify(&params, &com, &Fr::from(2u64), &value, &proof));
+        let (com_prime, _) = Poly::commit(&mut rng, &params, &f).unwrap();
+        assert_ne!(com, com_prime);
+        assert!(!Poly::verify(&params, &com_prime, &z, &value, &proof));
+
+        // a polynomial of the largest degree n - 2, evaluated at zero, is its constant term
+        let f: Vec<Fr> = (0..7).map(|_| Fr::rand(&mut rng)).collect();
+        let (com, opening) = Poly::commit(&mut rng, &params, &f).unwrap();
+        let (value, proof) = Poly::open(&params, &com, &opening, &Fr::zero()).unwrap();
+        assert_eq!(value, f[0]);
+        assert!(Poly::verify(&params, &com, &Fr::zero(), &value, &proof));
+    }
+
+    #[test]
+    fn test_poly_commit_invalid() {
+        let mut rng = ark_std::test_rng();
+        let params = Poly::setup(&mut rng, 4).unwrap();
+        // degrees of n - 1 and above, and lengths other than powers of two are rejected
+        assert!(Poly::commit(&mut rng, &params, &convert::<Fr>(&[1, 2, 3, 4])).is_err());
+        assert!(Poly::setup(&mut rng, 1).is_err());
+        assert!(Poly::setup(&mut rng, 6).is_err());
+    }
+}
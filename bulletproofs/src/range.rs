@@ -0,0 +1,493 @@
+#![allow(non_snake_case)]
+#![allow(dead_code)]
+
+use alloc::{string::ToString, vec, vec::Vec};
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField};
+use ark_std::{end_timer, rand::Rng, start_timer, One, UniformRand, Zero};
+use toolbox::sigma::transcript::ProofTranscript;
+use toolbox::errors::SigmaErrors;
+use toolbox::vec::{vec_add, vec_sum, inner_product, scalar_product, hadamard_product, generate_powers};
+use crate::ipa::{msm, InnerProductProtocol};
+use crate::structs::*;
+
+// the bit length of an amount, proved by prove and verify
+pub const RANGE_BITS: usize = 64;
+
+// Aggregated range proof relation (Bulletproofs, Section 4.3):
+// for j = 0..m, V_j = g^{v_j} h^{gamma_j} and v_j \in [0, 2^n)
+// the bits a_L of all values are committed in A, and the m*n range constraints
+// are folded by challenges y, z into a single inner product <l(x), r(x)> = t(x)
+impl<C: CurveGroup> RangeProof<C>
+{
+    /// Setup algorithm generates the generators for m aggregated values of n_bits each
+    pub fn setup<R: Rng>(
+        rng: &mut R,
+        n_bits: usize,
+        m: usize,
+    ) -> Result<RangeProofParam<C>, SigmaErrors> {
+        Self::check_sizes(n_bits, m)?;
+        let vec_G = (0..n_bits*m).map(|_| C::Affine::rand(rng)).collect();
+        let vec_H = (0..n_bits*m).map(|_| C::Affine::rand(rng)).collect();
+        Ok(RangeProofParam {
+            g: C::Affine::rand(rng),
+            h: C::Affine::rand(rng),
+            u: C::Affine::rand(rng),
+            vec_G,
+            vec_H,
+        })
+    }
+
+    /// Commit algorithm computes the value commitment V = g^v h^gamma
+    pub fn commit(
+        params: &RangeProofParam<C>,
+        value: u64,
+        blinding: &C::ScalarField,
+    ) -> C {
+        params.g * C::ScalarField::from(value) + params.h * blinding
+    }
+
+    /// Prove algorithm takes inputs as
+    /// - params: the range proof generators
+    /// - values: m amounts, each in [0, 2^n_bits)
+    /// - blindings: the blinding factors of the value commitments
+    ///
+    /// then outputs the aggregated proof and the m value commitments
+    pub fn prove_aggregated<R: Rng>(
+        rng: &mut R,
+        params: &RangeProofParam<C>,
+        values: &[u64],
+        blindings: &[C::ScalarField],
+        n_bits: usize,
+    ) -> Result<(RangeProof<C>, Vec<C>), SigmaErrors> {
+        // initialization
+        let start = start_timer!(|| "running aggregated range proof prove algorithm...");
+        let m = values.len();
+        if blindings.len() != m {
+            return Err(SigmaErrors::InvalidParameters(
+                "values and blindings length are different".to_string(),
+            ));
+        }
+        Self::check_sizes(n_bits, m)?;
+        let nm = n_bits * m;
+        if params.vec_G.len() < nm || params.vec_H.len() < nm {
+            return Err(SigmaErrors::InvalidParameters(
+                "not enough generators for the aggregated values".to_string(),
+            ));
+        }
+        if values.iter().any(|&v| n_bits < 64 && v >> n_bits != 0) {
+            return Err(SigmaErrors::InvalidProver(
+                "value is out of range".to_string(),
+            ));
+        }
+
+        let commitments: Vec<C> = values.iter()
+            .zip(blindings.iter())
+            .map(|(&v, gamma)| Self::commit(params, v, gamma))
+            .collect();
+
+        // a_L = bits of v_0 || ... || bits of v_{m-1}
+        let mut a_L = Vec::with_capacity(nm);
+        for &v in values {
+            for i in 0..n_bits {
+                a_L.push(C::ScalarField::from((v >> i) & 1));
+            }
+        }
+        let proof = Self::prove_bits(rng, params, &commitments, a_L, blindings, n_bits)?;
+
+        // proving ends
+        end_timer!(start);
+        Ok((proof, commitments))
+    }
+
+    // the rounds of prove_aggregated on the bit vector a_L of the committed values, which is not checked to be binary,
+    // so that a test can forge a proof for a value out of range
+    fn prove_bits<R: Rng>(
+        rng: &mut R,
+        params: &RangeProofParam<C>,
+        commitments: &[C],
+        a_L: Vec<C::ScalarField>,
+        blindings: &[C::ScalarField],
+        n_bits: usize,
+    ) -> Result<RangeProof<C>, SigmaErrors> {
+        let m = commitments.len();
+        let nm = n_bits * m;
+        let mut transcript = Self::new_transcript(n_bits, m, commitments)?;
+
+        let vec_G = &params.vec_G[..nm];
+        let vec_H = &params.vec_H[..nm];
+
+        // a_R = a_L - 1^{nm}
+        let a_R: Vec<C::ScalarField> = a_L.iter()
+            .map(|&a_i| a_i - C::ScalarField::one())
+            .collect();
+
+        // computes A = G^{a_L} H^{a_R} h^{alpha}, S = G^{s_L} H^{s_R} h^{rho}
+        let alpha = C::ScalarField::rand(rng);
+        let rho = C::ScalarField::rand(rng);
+        let s_L: Vec<C::ScalarField> = (0..nm).map(|_| C::ScalarField::rand(rng)).collect();
+        let s_R: Vec<C::ScalarField> = (0..nm).map(|_| C::ScalarField::rand(rng)).collect();
+
+        let mut base = vec_G.to_vec();
+        base.extend(vec_H);
+        base.push(params.h);
+        let mut exp = a_L.clone();
+        exp.extend(a_R.clone());
+        exp.push(alpha);
+        let com_A = C::msm(&base, &exp).unwrap().into_affine();
+        let mut exp = s_L.clone();
+        exp.extend(s_R.clone());
+        exp.push(rho);
+        let com_S = C::msm(&base, &exp).unwrap().into_affine();
+
+        // P->V: A, S
+        transcript.append_serializable_element(b"commitments A,S", &[com_A, com_S])?;
+
+        // V->P: challenges y, z
+        let y = transcript.get_and_append_challenge(b"challenge y")?;
+        let z = transcript.get_and_append_challenge(b"challenge z")?;
+
+        // l(X) = (a_L - z*1^{nm}) + s_L*X
+        // r(X) = y^{nm} \circ (a_R + z*1^{nm} + s_R*X) + (z^2*2^n || ... || z^{m+1}*2^n)
+        let powers_y = powers_from_one(y, nm);
+        let vec_z2n = Self::z_two_powers(z, n_bits, m);
+        let l0: Vec<C::ScalarField> = a_L.iter().map(|&a_i| a_i - z).collect();
+        let l1 = s_L;
+        let a_R_z: Vec<C::ScalarField> = a_R.iter().map(|&a_i| a_i + z).collect();
+        let r0 = vec_add(&hadamard_product(&powers_y, &a_R_z), &vec_z2n);
+        let r1 = hadamard_product(&powers_y, &s_R);
+
+        // t(X) = <l(X), r(X)> = t0 + t1*X + t2*X^2
+        let t1 = inner_product(&l0, &r1) + inner_product(&l1, &r0);
+        let t2 = inner_product(&l1, &r1);
+
+        // computes T1 = g^{t1}h^{tau1}, T2 = g^{t2}h^{tau2}
+        let tau1 = C::ScalarField::rand(rng);
+        let tau2 = C::ScalarField::rand(rng);
+        let com_T1 = C::msm(&[params.g, params.h], &[t1, tau1]).unwrap().into_affine();
+        let com_T2 = C::msm(&[params.g, params.h], &[t2, tau2]).unwrap().into_affine();
+
+        // P->V: T1, T2
+        transcript.append_serializable_element(b"commitments T1,T2", &[com_T1, com_T2])?;
+
+        // V->P: challenge x
+        let x = transcript.get_and_append_challenge(b"challenge x")?;
+
+        // computes l = l(x), r = r(x), hat_t = <l, r>
+        let l = vec_add(&l0, &scalar_product(&l1, &x));
+        let r = vec_add(&r0, &scalar_product(&r1, &x));
+        let hat_t = inner_product(&l, &r);
+
+        // tau_x = tau1*x + tau2*x^2 + \sum_j z^{2+j} gamma_j
+        let powers_z = powers_from_one(z, m + 2);
+        let taux = tau1*x + tau2*x*x + inner_product(&powers_z[2..], blindings);
+        // mu = alpha + rho*x
+        let mu = alpha + rho*x;
+
+        // P->V: tau_x, mu, hat_t
+        transcript.append_serializable_element(b"openings", &[taux, mu, hat_t])?;
+
+        // V->P: challenge w scaling the inner product generator
+        let w = transcript.get_and_append_challenge(b"challenge w")?;
+
+        // Bulletproofs Compression over (G, H^{y^{-nm}}, u^w)
+        let param = Self::ipa_param(params, y, w, nm)?;
+        let ipa_proof = InnerProductProtocol::<C>::prove(b"RangeProof", &param, l, r)?;
+
+        Ok(RangeProof {
+            com_A,
+            com_S,
+            com_T1,
+            com_T2,
+            hat_t,
+            taux,
+            mu,
+            ipa_proof,
+        })
+    }
+
+    /// Verify algorithm checks that every commitment V_j hides a value in [0, 2^n_bits)
+    pub fn verify_aggregated(
+        params: &RangeProofParam<C>,
+        commitments: &[C],
+        n_bits: usize,
+        proof: &RangeProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let start = start_timer!(|| "running aggregated range proof verify algorithm...");
+        let result = Self::verify_checks(params, commitments, n_bits, proof);
+        end_timer!(start);
+        result
+    }
+
+    // the checks of verify_aggregated, the caller closes the timer on every return
+    fn verify_checks(
+        params: &RangeProofParam<C>,
+        commitments: &[C],
+        n_bits: usize,
+        proof: &RangeProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let m = commitments.len();
+        Self::check_sizes(n_bits, m)?;
+        let nm = n_bits * m;
+        if params.vec_G.len() < nm || params.vec_H.len() < nm {
+            return Err(SigmaErrors::InvalidParameters(
+                "not enough generators for the aggregated values".to_string(),
+            ));
+        }
+
+        // recompute the challenges
+        let mut transcript = Self::new_transcript(n_bits, m, commitments)?;
+        transcript.append_serializable_element(b"commitments A,S", &[proof.com_A, proof.com_S])?;
+        let y = transcript.get_and_append_challenge(b"challenge y")?;
+        let z = transcript.get_and_append_challenge(b"challenge z")?;
+        transcript.append_serializable_element(b"commitments T1,T2", &[proof.com_T1, proof.com_T2])?;
+        let x = transcript.get_and_append_challenge(b"challenge x")?;
+        transcript.append_serializable_element(b"openings", &[proof.taux, proof.mu, proof.hat_t])?;
+        let w = transcript.get_and_append_challenge(b"challenge w")?;
+
+        // check validity of T1 T2
+        // g^{hat_t} h^{tau_x} = V^{z^2 z^m} g^delta T1^x T2^{x^2}
+        // where delta = (z - z^2) <1^{nm}, y^{nm}> - \sum_j z^{3+j} <1^n, 2^n>
+        let powers_y = powers_from_one(y, nm);
+        let powers_z = powers_from_one(z, m + 3);
+        let sum_2n = C::ScalarField::from(2u64).pow([n_bits as u64]) - C::ScalarField::one();
+        let delta = (z - z*z) * vec_sum(&powers_y) - vec_sum(&powers_z[3..]) * sum_2n;
+        let lhs: C = msm(&[params.g, params.h], &[proof.hat_t, proof.taux], SigmaErrors::InvalidProof)?;
+        let rhs = commitments.iter()
+            .zip(powers_z[2..].iter())
+            .fold(C::zero(), |acc, (com_j, z_j)| acc + *com_j * z_j)
+            + params.g * delta + proof.com_T1 * x + proof.com_T2 * (x*x);
+        if lhs != rhs {
+            return Err(SigmaErrors::InvalidProof("t(x) check fails".to_string()));
+        }
+
+        // compute the target of the inner product argument
+        // P = A S^x G^{-z*1^{nm}} H'^{z*y^{nm} + z^2 2^n || ... || z^{m+1} 2^n} h^{-mu} (u^w)^{hat_t}
+        // where H' = H^{y^{-nm}}, i.e., the exponent on H is z + (z^{2+j} 2^n)_i * y^{-i}
+        let powers_y_inverse = powers_from_one(inverse_challenge(y)?, nm);
+        let vec_z2n = Self::z_two_powers(z, n_bits, m);
+        let exp_H: Vec<C::ScalarField> = vec_z2n.iter()
+            .zip(powers_y_inverse.iter())
+            .map(|(&z2n_i, &y_inv_i)| z + z2n_i * y_inv_i)
+            .collect();
+        let param = Self::ipa_param(params, y, w, nm)?;
+
+        let mut exp = vec![-z; nm];
+        exp.extend(exp_H);
+        exp.extend([C::ScalarField::one(), x, -proof.mu, proof.hat_t]);
+        let mut base = param.vec_G.clone();
+        base.extend(param.vec_H.clone());
+        base.extend([proof.com_A, proof.com_S, params.h, param.u]);
+        let target_P = msm(&base, &exp, SigmaErrors::InvalidProof)?;
+
+        // call Bulletproofs verifier
+        InnerProductProtocol::<C>::verify(b"RangeProof", nm, target_P, &param, &proof.ipa_proof)
+    }
+
+    /// Proves that every amount of values lies in [0, 2^64) for the commitments V_j = g^{v_j} h^{gamma_j},
+    /// the m values are padded to a power of two with zero values and blindings, i.e., with the identity
+    /// as commitment, so that params needs 64 * m.next_power_of_two() generators;
+    /// outputs the proof and the m commitments of the values
+    pub fn prove<R: Rng>(
+        rng: &mut R,
+        params: &RangeProofParam<C>,
+        values: &[u64],
+        blindings: &[C::ScalarField],
+    ) -> Result<(RangeProof<C>, Vec<C>), SigmaErrors> {
+        if values.is_empty() || blindings.len() != values.len() {
+            return Err(SigmaErrors::InvalidParameters(
+                "values should be non-empty and of the same length as blindings".to_string(),
+            ));
+        }
+        let m = values.len();
+        let mut values = values.to_vec();
+        values.resize(m.next_power_of_two(), 0);
+        let mut blindings = blindings.to_vec();
+        blindings.resize(m.next_power_of_two(), C::ScalarField::zero());
+        let (proof, mut commitments) = Self::prove_aggregated(rng, params, &values, &blindings, RANGE_BITS)?;
+        commitments.truncate(m);
+        Ok((proof, commitments))
+    }
+
+    /// Verifies a proof of prove, i.e., that every commitment hides an amount in [0, 2^64)
+    pub fn verify(
+        params: &RangeProofParam<C>,
+        commitments: &[C],
+        proof: &RangeProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        if commitments.is_empty() {
+            return Err(SigmaErrors::InvalidParameters(
+                "no value commitments".to_string(),
+            ));
+        }
+        let mut commitments = commitments.to_vec();
+        commitments.resize(commitments.len().next_power_of_two(), C::zero());
+        Self::verify_aggregated(params, &commitments, RANGE_BITS, proof)
+    }
+
+    // both the bit length and the number of aggregated values should be powers of two
+    fn check_sizes(n_bits: usize, m: usize) -> Result<(), SigmaErrors> {
+        if n_bits == 0 || n_bits > 64 || !n_bits.is_power_of_two() {
+            return Err(SigmaErrors::InvalidParameters(
+                "bit length should be a power of two at most 64".to_string(),
+            ));
+        }
+        if !m.is_power_of_two() {
+            return Err(SigmaErrors::InvalidParameters(
+                "number of values is not power of two".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    // initialize the transcript with the statement
+    fn new_transcript(
+        n_bits: usize,
+        m: usize,
+        commitments: &[C],
+    ) -> Result<ProofTranscript<C::ScalarField>, SigmaErrors> {
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RangeProof");
+        transcript.append_field_element(b"bit length", &C::ScalarField::from(n_bits as u64))?;
+        transcript.append_field_element(b"aggregation size", &C::ScalarField::from(m as u64))?;
+        transcript.append_serializable_element(b"value commitments", &commitments.to_vec())?;
+        Ok(transcript)
+    }
+
+    // z^2*2^n || z^3*2^n || ... || z^{m+1}*2^n
+    fn z_two_powers(z: C::ScalarField, n_bits: usize, m: usize) -> Vec<C::ScalarField> {
+        let powers_2 = powers_from_one(C::ScalarField::from(2u64), n_bits);
+        let powers_z = powers_from_one(z, m + 2);
+        let mut vec_z2n = Vec::with_capacity(n_bits * m);
+        for z_j in powers_z[2..].iter() {
+            vec_z2n.extend(scalar_product(&powers_2, z_j));
+        }
+        vec_z2n
+    }
+
+    // the inner product parameters (G, H, u^w) with factors_H = y^{-nm}
+    fn ipa_param(
+        params: &RangeProofParam<C>,
+        y: C::ScalarField,
+        w: C::ScalarField,
+        nm: usize,
+    ) -> Result<InnerProductParam<C>, SigmaErrors> {
+        InnerProductParam::builder()
+            .with_vec_G(params.vec_G[..nm].to_vec())
+            .with_vec_H(params.vec_H[..nm].to_vec())
+            .with_u((params.u * w).into_affine())
+            .with_factors_H(powers_from_one(inverse_challenge(y)?, nm))
+            .build()
+    }
+}
+
+// the inverse of a challenge, a zero challenge is rejected instead of panicking
+fn inverse_challenge<F: PrimeField>(x: F) -> Result<F, SigmaErrors> {
+    x.inverse().ok_or(SigmaErrors::InvalidProof("zero challenge value".to_string()))
+}
+
+// (1, x, x^2, ..., x^{n-1})
+fn powers_from_one<F: PrimeField>(x: F, n: usize) -> Vec<F> {
+    if n == 0 {
+        return vec![];
+    }
+    let mut powers = vec![F::one()];
+    powers.extend(generate_powers(x, n - 1));
+    powers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Fr, Projective};
+
+    type Range = RangeProof<Projective>;
+
+    fn prove_and_verify(values: &[u64], n_bits: usize) -> Result<(), SigmaErrors> {
+        let mut rng = ark_std::test_rng();
+        let params = Range::setup(&mut rng, n_bits, values.len()).unwrap();
+        let blindings: Vec<Fr> = values.iter().map(|_| Fr::rand(&mut rng)).collect();
+        let (proof, commitments) = Range::prove_aggregated(&mut rng, &params, values, &blindings, n_bits)?;
+        Range::verify_aggregated(&params, &commitments, n_bits, &proof)
+    }
+
+    #[test]
+    fn test_range_proof_aggregated_2() {
+        prove_and_verify(&[0u64, u32::MAX as u64], 32).unwrap();
+    }
+
+    #[test]
+    fn test_range_proof_aggregated_4() {
+        prove_and_verify(&[1u64, 20u64, 300u64, u16::MAX as u64], 16).unwrap();
+        prove_and_verify(&[0u64, 1u64, u64::MAX, 1u64 << 63], 64).unwrap();
+    }
+
+    #[test]
+    fn test_range_proof_64() {
+        let mut rng = ark_std::test_rng();
+        // up to 4 amounts, 3 of them are padded with a zero value
+        let params = Range::setup(&mut rng, RANGE_BITS, 4).unwrap();
+        let values = [0u64, u64::MAX, 1u64 << 32];
+        let blindings: Vec<Fr> = values.iter().map(|_| Fr::rand(&mut rng)).collect();
+        let (proof, commitments) = Range::prove(&mut rng, &params, &values, &blindings).unwrap();
+        assert_eq!(commitments.len(), 3);
+        assert_eq!(commitments[1], Range::commit(&params, u64::MAX, &blindings[1]));
+        Range::verify(&params, &commitments, &proof).unwrap();
+        // the padding is not a free slot for another commitment
+        let mut commitments_prime = commitments.clone();
+        commitments_prime.push(Range::commit(&params, 7u64, &Fr::rand(&mut rng)));
+        assert!(Range::verify(&params, &commitments_prime, &proof).is_err());
+
+        // a commitment to 2^64 is rejected
+        let mut commitments_prime = commitments.clone();
+        commitments_prime[1] = params.g * Fr::from(1u128 << 64) + params.h * blindings[1];
+        assert!(Range::verify(&params, &commitments_prime, &proof).is_err());
+
+        // one amount, and more amounts than generators or none at all
+        let (proof, commitments) = Range::prove(&mut rng, &params, &[u64::MAX], &blindings[..1]).unwrap();
+        Range::verify(&params, &commitments, &proof).unwrap();
+        assert!(Range::prove(&mut rng, &params, &[1u64; 5], &[Fr::one(); 5]).is_err());
+        assert!(Range::prove(&mut rng, &params, &[], &[]).is_err());
+        assert!(Range::verify(&params, &[], &proof).is_err());
+    }
+
+    #[test]
+    fn test_range_proof_out_of_range() {
+        // the honest prover refuses a value out of range
+        assert!(prove_and_verify(&[5u64, 1u64 << 16], 16).is_err());
+
+        // the verifier rejects the proof against a commitment to an out-of-range value
+        let mut rng = ark_std::test_rng();
+        let n_bits = 16;
+        let params = Range::setup(&mut rng, n_bits, 4).unwrap();
+        let values = [3u64, 4u64, 5u64, 6u64];
+        let blindings: Vec<Fr> = values.iter().map(|_| Fr::rand(&mut rng)).collect();
+        let (proof, mut commitments) = Range::prove_aggregated(&mut rng, &params, &values, &blindings, n_bits).unwrap();
+        Range::verify_aggregated(&params, &commitments, n_bits, &proof).unwrap();
+        commitments[2] = Range::commit(&params, 5u64 + (1u64 << 16), &blindings[2]);
+        assert!(Range::verify_aggregated(&params, &commitments, n_bits, &proof).is_err());
+
+        // a prover bypassing the range check encodes 5 + 2^16 in a vector a_L with <a_L, 2^n> = 5 + 2^16,
+        // which cannot be binary, so that the bit constraints a_L \circ a_R = 0 break the t(x) check
+        let mut a_L: Vec<Fr> = values.iter()
+            .flat_map(|&v| (0..n_bits).map(move |i| Fr::from((v >> i) & 1)))
+            .collect();
+        a_L[2*n_bits] += Fr::from(1u64 << 16);
+        let forged = Range::prove_bits(&mut rng, &params, &commitments, a_L, &blindings, n_bits).unwrap();
+        assert!(matches!(
+            Range::verify_aggregated(&params, &commitments, n_bits, &forged),
+            Err(SigmaErrors::InvalidProof(msg)) if msg == "t(x) check fails"
+        ));
+    }
+
+    #[test]
+    fn test_range_proof_zero_challenge() {
+        // a zero challenge y has no inverse, which is an error of the IPA parameters instead of a panic
+        let mut rng = ark_std::test_rng();
+        let params = Range::setup(&mut rng, 8, 1).unwrap();
+        assert!(matches!(
+            Range::ipa_param(&params, Fr::zero(), Fr::one(), 8),
+            Err(SigmaErrors::InvalidProof(msg)) if msg == "zero challenge value"
+        ));
+        assert!(Range::ipa_param(&params, Fr::one(), Fr::one(), 8).is_ok());
+    }
+}
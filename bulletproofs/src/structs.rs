@@ -1,19 +1,529 @@
+use alloc::{string::ToString, vec, vec::Vec};
 use ark_ec::CurveGroup;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use ark_std::{io::{Read, Write}, One};
+use toolbox::errors::SigmaErrors;
+use toolbox::sigma::transcript::ProofTranscript;
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+// the number of sub-vectors folded into one at each IPA round
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FoldingArity {
+    // halve the vectors, log2(n) rounds with L, R per round
+    #[default]
+    Two,
+    // quarter the vectors, log4(n) rounds with 6 cross terms per round
+    // (a last binary round is run when log2(n) is odd)
+    Four,
+}
+
+impl FoldingArity {
+    pub fn as_usize(&self) -> usize {
+        match self {
+            FoldingArity::Two => 2,
+            FoldingArity::Four => 4,
+        }
+    }
+}
+
+impl CanonicalSerialize for FoldingArity {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        (self.as_usize() as u8).serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        (self.as_usize() as u8).serialized_size(compress)
+    }
+}
+
+impl Valid for FoldingArity {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for FoldingArity {
+    fn deserialize_with_mode<R: Read>(
+        reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        match u8::deserialize_with_mode(reader, compress, validate)? {
+            2 => Ok(FoldingArity::Two),
+            4 => Ok(FoldingArity::Four),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
 pub struct InnerProductParam<C: CurveGroup> {
+    // the IPA proves P = vec_G^{a * factors_G} vec_H^{b * factors_H} u^{<a, b>},
+    // the factors are absorbed into the generators by the first fold and the verifier's s-vectors
     pub factors_G: Vec<C::ScalarField>,
     pub factors_H: Vec<C::ScalarField>,
     pub u: C::Affine,
     pub vec_G: Vec<C::Affine>,
     pub vec_H: Vec<C::Affine>,
+    // folding arity of the prover and verifier rounds
+    pub arity: FoldingArity,
 }
 
+// the prover key for a fixed IPA parameter, holding a comb table per generator of vec_G and vec_H
+// so that the first round folds the generators with fixed-base multiplications
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IpaProverKey<C: CurveGroup> {
+    pub params: InnerProductParam<C>,
+    pub(crate) tables_G: Vec<Vec<C::Affine>>,
+    pub(crate) tables_H: Vec<Vec<C::Affine>>,
+}
+
+// the state of a streaming binary IPA prover, advanced one round at a time by
+// InnerProductProtocol::prove_step and turned into a proof by prove_finish
+#[derive(Clone)]
+pub struct InnerProductProverState<C: CurveGroup> {
+    pub(crate) transcript: ProofTranscript<C::ScalarField>,
+    pub(crate) u: C::Affine,
+    pub(crate) vec_a: Vec<C::ScalarField>,
+    pub(crate) vec_b: Vec<C::ScalarField>,
+    // the generators, scaled by factors_G and factors_H on creation
+    pub(crate) vec_G: Vec<C::Affine>,
+    pub(crate) vec_H: Vec<C::Affine>,
+    pub(crate) vec_L: Vec<C::Affine>,
+    pub(crate) vec_R: Vec<C::Affine>,
+    pub(crate) challenges: Vec<C::ScalarField>,
+    // whether the last L, R are waiting for their challenge before the vectors are folded
+    pub(crate) pending: bool,
+    // the challenge of the pending round set by the caller instead of the transcript
+    pub(crate) injected: Option<C::ScalarField>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
 pub struct InnerProductProof<C: CurveGroup> {
+    // L, R per binary round; L_1, L_2, L_3 and L_{-1}, L_{-2}, L_{-3} per 4-ary round
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
+    pub vec_L: Vec<C::Affine>,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
+    pub vec_R: Vec<C::Affine>,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
+    pub a: C::ScalarField,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
+    pub b: C::ScalarField,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
+    pub challenges: Vec<C::ScalarField>,
+}
+
+impl<C: CurveGroup> InnerProductParam<C> {
+    /// Returns binary-folding parameters with all-ones factors, proving P = vec_G^a vec_H^b u^{<a, b>}
+    pub fn uniform(vec_G: Vec<C::Affine>, vec_H: Vec<C::Affine>, u: C::Affine) -> Self {
+        let n = vec_G.len();
+        Self {
+            factors_G: vec![C::ScalarField::one(); n],
+            factors_H: vec![C::ScalarField::one(); vec_H.len()],
+            u,
+            vec_G,
+            vec_H,
+            arity: FoldingArity::Two,
+        }
+    }
+
+    /// Returns binary-folding parameters proving P = vec_G^{a * factors_G} vec_H^{b * factors_H} u^{<a, b>},
+    /// or an error if vec_G is not of a nonzero power of two length, or vec_H and the factors differ in length from vec_G
+    pub fn new(
+        factors_G: Vec<C::ScalarField>,
+        factors_H: Vec<C::ScalarField>,
+        u: C::Affine,
+        vec_G: Vec<C::Affine>,
+        vec_H: Vec<C::Affine>,
+    ) -> Result<Self, SigmaErrors> {
+        let params = Self { factors_G, factors_H, u, vec_G, vec_H, arity: FoldingArity::Two };
+        params.check_lengths()?;
+        Ok(params)
+    }
+
+    /// Returns a builder, whose factors default to all-ones and arity to binary folding
+    pub fn builder() -> InnerProductParamBuilder<C> {
+        InnerProductParamBuilder::new()
+    }
+
+    /// Multiplies the generators by the factors once, and outputs parameters with unit factors
+    /// proving the same statement P = vec_G^{a * factors_G} vec_H^{b * factors_H} u^{<a, b>}
+    pub fn with_scaled_generators(
+        u: C::Affine,
+        vec_G: &[C::Affine],
+        vec_H: &[C::Affine],
+        factors_G: &[C::ScalarField],
+        factors_H: &[C::ScalarField],
+    ) -> Result<Self, SigmaErrors> {
+        let n = vec_G.len();
+        if vec_H.len() != n || factors_G.len() != n || factors_H.len() != n {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+        let scale = |vec: &[C::Affine], factors: &[C::ScalarField]| {
+            let scaled: Vec<C> = vec.iter().zip(factors.iter()).map(|(g_i, f_i)| *g_i * f_i).collect();
+            C::normalize_batch(&scaled)
+        };
+        Ok(Self::uniform(scale(vec_G, factors_G), scale(vec_H, factors_H), u))
+    }
+
+    // the invariants of new and the builder: vec_G is of a nonzero power of two length,
+    // and vec_H, factors_G and factors_H are of the same length
+    fn check_lengths(&self) -> Result<(), SigmaErrors> {
+        let n = self.vec_G.len();
+        if !n.is_power_of_two() {
+            return Err(SigmaErrors::InvalidParameters(
+                "vector length is not power of two".to_string(),
+            ));
+        }
+        if self.vec_H.len() != n || self.factors_G.len() != n || self.factors_H.len() != n {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// builds an InnerProductParam, checking on build that the generators and factors have one length
+#[derive(Clone, Debug)]
+pub struct InnerProductParamBuilder<C: CurveGroup> {
+    vec_G: Vec<C::Affine>,
+    vec_H: Vec<C::Affine>,
+    u: Option<C::Affine>,
+    // all-ones when not set
+    factors_G: Option<Vec<C::ScalarField>>,
+    factors_H: Option<Vec<C::ScalarField>>,
+    arity: FoldingArity,
+}
+
+impl<C: CurveGroup> Default for InnerProductParamBuilder<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: CurveGroup> InnerProductParamBuilder<C> {
+    pub fn new() -> Self {
+        Self {
+            vec_G: vec![],
+            vec_H: vec![],
+            u: None,
+            factors_G: None,
+            factors_H: None,
+            arity: FoldingArity::Two,
+        }
+    }
+
+    pub fn with_vec_G(mut self, vec_G: Vec<C::Affine>) -> Self {
+        self.vec_G = vec_G;
+        self
+    }
+
+    pub fn with_vec_H(mut self, vec_H: Vec<C::Affine>) -> Self {
+        self.vec_H = vec_H;
+        self
+    }
+
+    pub fn with_u(mut self, u: C::Affine) -> Self {
+        self.u = Some(u);
+        self
+    }
+
+    pub fn with_factors_G(mut self, factors_G: Vec<C::ScalarField>) -> Self {
+        self.factors_G = Some(factors_G);
+        self
+    }
+
+    pub fn with_factors_H(mut self, factors_H: Vec<C::ScalarField>) -> Self {
+        self.factors_H = Some(factors_H);
+        self
+    }
+
+    pub fn with_factors(self, factors_G: Vec<C::ScalarField>, factors_H: Vec<C::ScalarField>) -> Self {
+        self.with_factors_G(factors_G).with_factors_H(factors_H)
+    }
+
+    pub fn with_arity(mut self, arity: FoldingArity) -> Self {
+        self.arity = arity;
+        self
+    }
+
+    /// Outputs the parameters, or an error if u is not set, vec_G is not of a nonzero power of two length,
+    /// or vec_H and the factors differ in length from vec_G
+    pub fn build(self) -> Result<InnerProductParam<C>, SigmaErrors> {
+        let n = self.vec_G.len();
+        let u = self.u.ok_or(SigmaErrors::InvalidParameters(
+            "the generator u is not set".to_string(),
+        ))?;
+        let params = InnerProductParam {
+            factors_G: self.factors_G.unwrap_or_else(|| vec![C::ScalarField::one(); n]),
+            factors_H: self.factors_H.unwrap_or_else(|| vec![C::ScalarField::one(); n]),
+            u,
+            vec_G: self.vec_G,
+            vec_H: self.vec_H,
+            arity: self.arity,
+        };
+        params.check_lengths()?;
+        Ok(params)
+    }
+}
+
+impl<C: CurveGroup> InnerProductProof<C> {
+    /// Returns the number of bytes of the compressed serialization, with the points encoded compressed:
+    /// the 2*log2(n) points L, R and the log2(n) challenges, plus a, b and the three length prefixes
+    pub fn size_in_bytes(&self) -> usize {
+        self.compressed_size()
+    }
+}
+
+// proof of the weighted inner product argument, with the same layout as the IPA proof:
+// L, R per binary round, the folded a, b and the round challenges
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct WeightedInnerProductProof<C: CurveGroup> {
     pub vec_L: Vec<C::Affine>,
     pub vec_R: Vec<C::Affine>,
     pub a: C::ScalarField,
     pub b: C::ScalarField,
     pub challenges: Vec<C::ScalarField>,
 }
+
+impl<C: CurveGroup> WeightedInnerProductProof<C> {
+    /// Returns the number of bytes of the compressed serialization
+    pub fn size_in_bytes(&self) -> usize {
+        self.compressed_size()
+    }
+}
+
+// proof of the blinded IPA: the commitments S1, S2 to the blinding vectors,
+// the blinding opening rho of the masked target and the IPA proof on the masked vectors
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BlindedInnerProductProof<C: CurveGroup> {
+    pub com_S1: C::Affine,
+    pub com_S2: C::Affine,
+    pub rho: C::ScalarField,
+    pub challenge: C::ScalarField,
+    pub ipa_proof: InnerProductProof<C>,
+}
+
+// proof of the one-sided IPA, where the right vector b is public and folded by the verifier
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct OneSidedInnerProductProof<C: CurveGroup> {
+    pub vec_L: Vec<C::Affine>,
+    pub vec_R: Vec<C::Affine>,
+    pub a: C::ScalarField,
+    pub challenges: Vec<C::ScalarField>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RangeProofParam<C: CurveGroup> {
+    // the generator committing to the value
+    pub g: C::Affine,
+    // the generator committing to the blinding factor
+    pub h: C::Affine,
+    // the generator binding the inner product
+    pub u: C::Affine,
+    // the bit-commitment generators, one per bit of every aggregated value
+    pub vec_G: Vec<C::Affine>,
+    pub vec_H: Vec<C::Affine>,
+}
+
+// the parameters of a Pedersen vector commitment com = vec_g^m h^r with its index openings,
+// where ipa.vec_G = vec_g and ipa.vec_H, ipa.u are only used by the openings
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VectorCommitmentParam<C: CurveGroup> {
+    pub h: C::Affine,
+    pub ipa: InnerProductParam<C>,
+}
+
+// the opening of a polynomial commitment, the coefficients of f from the constant term up and the blinding random
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PolynomialOpening<C: CurveGroup> {
+    pub coeffs: Vec<C::ScalarField>,
+    pub random: C::ScalarField,
+}
+
+// the evaluation proof of a polynomial commitment, an IPA between the committed vector (coeffs, random)
+// and the powers of the evaluation point
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
+pub struct EvalProof<C: CurveGroup> {
+    pub ipa: InnerProductProof<C>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RangeProof<C: CurveGroup> {
+    // commitments to the bits (A) and to the masking vectors (S)
+    pub com_A: C::Affine,
+    pub com_S: C::Affine,
+    // commitments to the coefficients of t(X)
+    pub com_T1: C::Affine,
+    pub com_T2: C::Affine,
+    // openings t(x), blinding of t(x) and blinding of A, S
+    pub hat_t: C::ScalarField,
+    pub taux: C::ScalarField,
+    pub mu: C::ScalarField,
+    // the Bulletproofs compression proof on <l(x), r(x)>
+    pub ipa_proof: InnerProductProof<C>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::VariableBaseMSM;
+    use ark_secp256k1::{Affine, Fr, Projective};
+    use ark_std::UniformRand;
+    use toolbox::vec::{hadamard_product, inner_product};
+    use crate::ipa::{InnerProductProtocol, IPA_DOMAIN};
+
+    fn ipa_instance(n: usize) -> (InnerProductParam<Projective>, InnerProductProof<Projective>, Projective) {
+        let mut rng = ark_std::test_rng();
+        let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let params = InnerProductParam::<Projective> {
+            factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            u: Affine::rand(&mut rng),
+            vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            arity: FoldingArity::Two,
+        };
+        let proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+
+        let mut exp = hadamard_product(&vec_a, &params.factors_G);
+        exp.extend(hadamard_product(&vec_b, &params.factors_H));
+        let mut base = params.vec_G.clone();
+        base.extend(params.vec_H.clone());
+        let P = Projective::msm(&base, &exp).unwrap() + params.u*inner_product(&vec_a, &vec_b);
+        (params, proof, P)
+    }
+
+    #[test]
+    fn test_ipa_param_builder() {
+        let (params, _, _) = ipa_instance(8);
+        let built = InnerProductParam::<Projective>::builder()
+            .with_vec_G(params.vec_G.clone())
+            .with_vec_H(params.vec_H.clone())
+            .with_u(params.u)
+            .with_factors_G(params.factors_G.clone())
+            .with_factors_H(params.factors_H.clone())
+            .build()
+            .unwrap();
+        assert_eq!(built, params);
+
+        // the factors default to all-ones as in uniform
+        let uniform = InnerProductParam::<Projective>::uniform(params.vec_G.clone(), params.vec_H.clone(), params.u);
+        let built = InnerProductParam::<Projective>::builder()
+            .with_vec_G(params.vec_G.clone())
+            .with_vec_H(params.vec_H.clone())
+            .with_u(params.u)
+            .build()
+            .unwrap();
+        assert_eq!(built, uniform);
+        assert_eq!(uniform.factors_G, vec![Fr::from(1u64); 8]);
+
+        // with_factors sets both factor vectors
+        let built = InnerProductParam::<Projective>::builder()
+            .with_vec_G(params.vec_G.clone())
+            .with_vec_H(params.vec_H.clone())
+            .with_u(params.u)
+            .with_factors(params.factors_G.clone(), params.factors_H.clone())
+            .build()
+            .unwrap();
+        assert_eq!(built, params);
+
+        // each validation failure is rejected: a missing u, mismatched vec_H, factors_G or factors_H,
+        // and an empty or non power of two vec_G
+        let builder = InnerProductParam::<Projective>::builder()
+            .with_vec_G(params.vec_G.clone())
+            .with_vec_H(params.vec_H.clone());
+        assert!(builder.clone().build().is_err());
+        let builder = builder.with_u(params.u);
+        assert!(builder.clone().with_vec_H(params.vec_H[..4].to_vec()).build().is_err());
+        assert!(builder.clone().with_factors_G(params.factors_G[..4].to_vec()).build().is_err());
+        assert!(builder.clone().with_factors_H(params.factors_H[..4].to_vec()).build().is_err());
+        assert!(builder.clone().with_vec_G(vec![]).with_vec_H(vec![]).build().is_err());
+        assert!(builder.with_vec_G(params.vec_G[..6].to_vec()).with_vec_H(params.vec_H[..6].to_vec()).build().is_err());
+    }
+
+    #[test]
+    fn test_ipa_param_new() {
+        let (params, _, _) = ipa_instance(8);
+        let new = |factors_G: &[Fr], factors_H: &[Fr], vec_G: &[Affine], vec_H: &[Affine]| {
+            InnerProductParam::<Projective>::new(factors_G.to_vec(), factors_H.to_vec(), params.u, vec_G.to_vec(), vec_H.to_vec())
+        };
+        assert_eq!(new(&params.factors_G, &params.factors_H, &params.vec_G, &params.vec_H).unwrap(), params);
+        let ones = vec![Fr::from(1u64); 8];
+        assert_eq!(
+            new(&ones, &ones, &params.vec_G, &params.vec_H).unwrap(),
+            InnerProductParam::uniform(params.vec_G.clone(), params.vec_H.clone(), params.u),
+        );
+
+        // vec_H, factors_G or factors_H differing in length from vec_G
+        assert!(new(&params.factors_G, &params.factors_H, &params.vec_G, &params.vec_H[..4]).is_err());
+        assert!(new(&params.factors_G[..4], &params.factors_H, &params.vec_G, &params.vec_H).is_err());
+        assert!(new(&params.factors_G, &params.factors_H[..4], &params.vec_G, &params.vec_H).is_err());
+        // an empty or non power of two vec_G, even when all lengths agree
+        assert!(new(&[], &[], &[], &[]).is_err());
+        assert!(new(&params.factors_G[..6], &params.factors_H[..6], &params.vec_G[..6], &params.vec_H[..6]).is_err());
+        // a length of one is a power of two
+        assert!(new(&params.factors_G[..1], &params.factors_H[..1], &params.vec_G[..1], &params.vec_H[..1]).is_ok());
+    }
+
+    #[test]
+    fn test_ipa_serialization_round_trip() {
+        let (params, proof, _) = ipa_instance(8);
+
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(InnerProductProof::<Projective>::deserialize_compressed(&bytes[..]).unwrap(), proof);
+        let mut bytes = vec![];
+        proof.serialize_uncompressed(&mut bytes).unwrap();
+        assert_eq!(InnerProductProof::<Projective>::deserialize_uncompressed(&bytes[..]).unwrap(), proof);
+
+        let mut bytes = vec![];
+        params.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(InnerProductParam::<Projective>::deserialize_compressed(&bytes[..]).unwrap(), params);
+        let mut bytes = vec![];
+        params.serialize_uncompressed(&mut bytes).unwrap();
+        assert_eq!(InnerProductParam::<Projective>::deserialize_uncompressed(&bytes[..]).unwrap(), params);
+    }
+
+    #[test]
+    fn test_ipa_deserialize_malformed() {
+        let (_, proof, _) = ipa_instance(8);
+
+        // truncated bytes
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert!(InnerProductProof::<Projective>::deserialize_compressed(&bytes[..bytes.len()-1]).is_err());
+
+        // vec_L[0] is moved off the curve by changing its y-coordinate
+        // (the first 8 bytes encode the length of vec_L)
+        let mut bytes = vec![];
+        proof.serialize_uncompressed(&mut bytes).unwrap();
+        bytes[8 + 32] ^= 1;
+        assert!(InnerProductProof::<Projective>::deserialize_uncompressed(&bytes[..]).is_err());
+    }
+
+    #[test]
+    fn test_ipa_verify_deserialized_proof() {
+        let (params, proof, P) = ipa_instance(16);
+
+        let mut proof_bytes = vec![];
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        let mut param_bytes = vec![];
+        params.serialize_compressed(&mut param_bytes).unwrap();
+
+        let proof = InnerProductProof::<Projective>::deserialize_compressed(&proof_bytes[..]).unwrap();
+        let params = InnerProductParam::<Projective>::deserialize_compressed(&param_bytes[..]).unwrap();
+        InnerProductProtocol::verify(IPA_DOMAIN, 16, P, &params, &proof).unwrap();
+    }
+}
@@ -0,0 +1,149 @@
+use alloc::{string::ToString, vec};
+use ark_ec::CurveGroup;
+use ark_std::{end_timer, rand::Rng, start_timer, One, UniformRand, Zero};
+use toolbox::errors::SigmaErrors;
+use toolbox::vec::ct_eq_affine;
+use crate::ipa::InnerProductProtocol;
+use crate::structs::*;
+
+// the transcript domain of the index openings
+const VC_OPEN_DOMAIN: &[u8] = b"VectorCommitmentOpening";
+
+// Index opening of com = vec_g^m h^r: m[i] = v is the inner product of m with the unit vector e_i,
+// so the blinded IPA proves the knowledge of (m, e_i, r) for the target
+// com * H_i * u^v = vec_g^m vec_H^{e_i} u^{<m, e_i>} h^r
+// which the verifier computes from the commitment, the index and the claimed value,
+// and the masking of the blinded IPA hides the other entries of m
+
+/// Setup algorithm generates the commitment generators vec_g, h for vectors of length n, a power of two,
+/// and the generators vec_H, u of the openings
+pub fn setup<C: CurveGroup, R: Rng>(
+    rng: &mut R,
+    n: usize,
+) -> Result<VectorCommitmentParam<C>, SigmaErrors> {
+    if !n.is_power_of_two() {
+        return Err(SigmaErrors::InvalidParameters(
+            "vector length is not power of two".to_string(),
+        ));
+    }
+    let ipa = InnerProductParam::new(
+        vec![C::ScalarField::one(); n],
+        vec![C::ScalarField::one(); n],
+        C::Affine::rand(rng),
+        (0..n).map(|_| C::Affine::rand(rng)).collect(),
+        (0..n).map(|_| C::Affine::rand(rng)).collect(),
+    )?;
+    Ok(VectorCommitmentParam {
+        h: C::Affine::rand(rng),
+        ipa,
+    })
+}
+
+/// Commit algorithm computes com = vec_g^m h^r
+pub fn commit<C: CurveGroup>(
+    params: &VectorCommitmentParam<C>,
+    m: &[C::ScalarField],
+    r: &C::ScalarField,
+) -> Result<C, SigmaErrors> {
+    if m.len() != params.ipa.vec_G.len() {
+        return Err(SigmaErrors::InvalidParameters(
+            "message length should equal to the generator length".to_string(),
+        ));
+    }
+    let msm = C::msm(&params.ipa.vec_G, m)
+        .map_err(|_| SigmaErrors::InvalidParameters("msm length mismatch".to_string()))?;
+    Ok(msm + params.h * r)
+}
+
+/// Proves that the entry i of the vector m committed in com = vec_g^m h^r is m[i],
+/// without revealing the other entries
+pub fn prove_index<C: CurveGroup, R: Rng>(
+    rng: &mut R,
+    params: &VectorCommitmentParam<C>,
+    com: &C,
+    m: &[C::ScalarField],
+    r: &C::ScalarField,
+    i: usize,
+) -> Result<BlindedInnerProductProof<C>, SigmaErrors> {
+    let n = params.ipa.vec_G.len();
+    if i >= n {
+        return Err(SigmaErrors::InvalidParameters(
+            "the index is out of the vector".to_string(),
+        ));
+    }
+    if !ct_eq_affine(commit(params, m, r)?, *com) {
+        return Err(SigmaErrors::InvalidProver(
+            "the opening does not match the commitment".to_string(),
+        ));
+    }
+    let start = start_timer!(|| "running vector commitment index opening prove algorithm...");
+    let mut unit = vec![C::ScalarField::zero(); n];
+    unit[i] = C::ScalarField::one();
+    let proof = InnerProductProtocol::<C>::prove_blinded(rng, VC_OPEN_DOMAIN, &params.ipa, params.h, m.to_vec(), unit, *r);
+    end_timer!(start);
+    proof
+}
+
+/// Verifies that the entry i of the vector committed in com is v
+pub fn verify_index<C: CurveGroup>(
+    params: &VectorCommitmentParam<C>,
+    com: &C,
+    i: usize,
+    v: &C::ScalarField,
+    proof: &BlindedInnerProductProof<C>,
+) -> Result<(), SigmaErrors> {
+    let n = params.ipa.vec_G.len();
+    if i >= n || params.ipa.vec_H.len() != n {
+        return Err(SigmaErrors::InvalidParameters(
+            "the index is out of the vector".to_string(),
+        ));
+    }
+    let target_P = *com + params.ipa.vec_H[i] + params.ipa.u * v;
+    InnerProductProtocol::<C>::verify_blinded(VC_OPEN_DOMAIN, n, target_P, &params.ipa, params.h, proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use ark_secp256k1::{Fr, Projective};
+
+    #[test]
+    fn test_vc_open() {
+        let mut rng = ark_std::test_rng();
+        let n = 16;
+        let params = setup::<Projective, _>(&mut rng, n).unwrap();
+        let m: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let com = commit(&params, &m, &r).unwrap();
+
+        // the first, the last and a middle index of the same commitment
+        for i in [0, n-1, 5] {
+            let proof = prove_index(&mut rng, &params, &com, &m, &r, i).unwrap();
+            verify_index(&params, &com, i, &m[i], &proof).unwrap();
+
+            // a wrong claimed value, another index or another commitment fail
+            assert!(verify_index(&params, &com, i, &(m[i] + Fr::from(1u64)), &proof).is_err());
+            assert!(verify_index(&params, &com, (i + 1) % n, &m[i], &proof).is_err());
+            assert!(verify_index(&params, &com, (i + 1) % n, &m[(i + 1) % n], &proof).is_err());
+            assert!(verify_index(&params, &(com + params.h), i, &m[i], &proof).is_err());
+        }
+        assert!(verify_index(&params, &com, n, &m[0], &prove_index(&mut rng, &params, &com, &m, &r, 0).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_vc_open_invalid() {
+        let mut rng = ark_std::test_rng();
+        let n = 4;
+        let params = setup::<Projective, _>(&mut rng, n).unwrap();
+        let m: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let com = commit(&params, &m, &r).unwrap();
+
+        // the index should be in the vector and the opening should match the commitment
+        assert!(prove_index(&mut rng, &params, &com, &m, &r, n).is_err());
+        assert!(prove_index(&mut rng, &params, &com, &m, &(r + Fr::from(1u64)), 0).is_err());
+        assert!(prove_index(&mut rng, &params, &com, &m[1..], &r, 0).is_err());
+        assert!(setup::<Projective, _>(&mut rng, 6).is_err());
+    }
+}
@@ -0,0 +1,409 @@
+#![allow(non_snake_case)]
+
+use alloc::{string::{String, ToString}, vec, vec::Vec};
+use core::marker::PhantomData;
+use ark_ec::CurveGroup;
+use ark_ff::{Field, PrimeField};
+use ark_std::{end_timer, start_timer, Zero};
+use toolbox::sigma::transcript::ProofTranscript;
+use toolbox::errors::SigmaErrors;
+use toolbox::vec::{ct_eq_affine, generate_powers, scalar_product};
+use crate::ipa::{fold_generators, fold_scalars, msm, nonzero_challenge, rounds_to_length};
+use crate::structs::*;
+
+pub const WIP_DOMAIN: &[u8] = b"WeightedInnerProductArgument";
+
+#[derive(Clone, Debug)]
+pub struct WeightedInnerProductProtocol<C: CurveGroup> {
+    phantom: PhantomData<C>,
+}
+
+// Weighted inner product relation (Bulletproofs+, Section 3):
+// vec_G^{factors_G * vec_a} * vec_H^{factors_H * vec_b} * u^<vec_a, vec_b>_y = P
+// where <vec_a, vec_b>_y = sum_i a_i * b_i * y^{i+1}
+// at each round with n' = n/2, the halves satisfy <a, b>_y = <a_1, b_1>_y + y^{n'} * <a_2, b_2>_y,
+// so the cross terms L, R absorb the weight y^{n'} and the second half of vec_G is folded with y^{-n'}
+impl<C: CurveGroup> WeightedInnerProductProtocol<C>
+{
+    pub fn prove(
+        domain: &'static [u8],
+        params: &InnerProductParam<C>,
+        y: C::ScalarField,
+        vec_a: Vec<C::ScalarField>,
+        vec_b: Vec<C::ScalarField>,
+    ) -> Result<WeightedInnerProductProof<C>, SigmaErrors> {
+        let n = params.vec_G.len();
+        if vec_a.len() != n || vec_b.len() != n {
+            return Err(SigmaErrors::InvalidParameters(
+                "vectors length are different".to_string(),
+            ));
+        }
+        check_params(params, n, y, SigmaErrors::InvalidParameters)?;
+        // the factors apply to the whole vectors, so they are moved into the generators once
+        let scaled = InnerProductParam::with_scaled_generators(
+            params.u, &params.vec_G, &params.vec_H, &params.factors_G, &params.factors_H,
+        )?;
+        let mut transcript = wip_transcript(domain, n, &y)?;
+
+        let start = start_timer!(|| "running weighted inner product argument prove algorithm...");
+        let result = Self::prove_rounds(&mut transcript, scaled, y, vec_a, vec_b);
+        end_timer!(start);
+        result
+    }
+
+    // runs the rounds of the prover on the generators scaled by the factors,
+    // the caller closes the timer on every return
+    fn prove_rounds(
+        transcript: &mut ProofTranscript<C::ScalarField>,
+        params: InnerProductParam<C>,
+        y: C::ScalarField,
+        mut vec_a: Vec<C::ScalarField>,
+        mut vec_b: Vec<C::ScalarField>,
+    ) -> Result<WeightedInnerProductProof<C>, SigmaErrors> {
+        let mut n = vec_a.len();
+        let y_inv = y.inverse().unwrap();
+        let mut vec_G = params.vec_G;
+        let mut vec_H = params.vec_H;
+        let u = params.u;
+        let log_n = n.trailing_zeros() as usize;
+        let mut vec_L = Vec::with_capacity(log_n);
+        let mut vec_R = Vec::with_capacity(log_n);
+        let mut challenges = Vec::with_capacity(log_n);
+
+        while n > 1 {
+            let n_half = n/2;
+            let powers_y = generate_powers(y, n_half);
+            let y_n = powers_y[n_half-1];
+            let y_n_inv = y_inv.pow([n_half as u64]);
+            let (a_1, a_2) = vec_a.split_at(n_half);
+            let (b_1, b_2) = vec_b.split_at(n_half);
+            let (G_1, G_2) = vec_G.split_at(n_half);
+            let (H_1, H_2) = vec_H.split_at(n_half);
+
+            // c_L = <a_1, b_2>_y and c_R = y^{n'} * <a_2, b_1>_y
+            let c_L = weighted_inner_product(a_1, b_2, &powers_y);
+            let c_R = y_n * weighted_inner_product(a_2, b_1, &powers_y);
+
+            // L = G_2^{y^{-n'} * a_1} * H_1^{b_2} * u^{c_L}
+            let mut base = [G_2, H_1].concat();
+            base.push(u);
+            let mut exp = scalar_product(a_1, &y_n_inv);
+            exp.extend_from_slice(b_2);
+            exp.push(c_L);
+            let L = msm::<C>(&base, &exp, SigmaErrors::InvalidParameters)?;
+
+            // R = G_1^{y^{n'} * a_2} * H_2^{b_1} * u^{c_R}
+            let mut base = [G_1, H_2].concat();
+            base.push(u);
+            let mut exp = scalar_product(a_2, &y_n);
+            exp.extend_from_slice(b_1);
+            exp.push(c_R);
+            let R = msm::<C>(&base, &exp, SigmaErrors::InvalidParameters)?;
+
+            let LR = C::normalize_batch(&[L, R]);
+            transcript.append_serializable_element(b"commitments L, R", &[LR[0], LR[1]])?;
+            let (e, e_inv) = nonzero_challenge(transcript, b"challenge")?;
+
+            // a' = e * a_1 + e^{-1} * y^{n'} * a_2, b' = e^{-1} * b_1 + e * b_2
+            // G' = G_1^{e^{-1}} * G_2^{e * y^{-n'}}, H' = H_1^{e} * H_2^{e^{-1}}
+            let new_a = fold_scalars(a_1, a_2, &e, &(e_inv*y_n));
+            let new_b = fold_scalars(b_1, b_2, &e_inv, &e);
+            let new_G = fold_generators::<C>(G_1, G_2, &vec![e_inv; n_half], &vec![e*y_n_inv; n_half]);
+            let new_H = fold_generators::<C>(H_1, H_2, &vec![e; n_half], &vec![e_inv; n_half]);
+            vec_a = new_a;
+            vec_b = new_b;
+            vec_G = new_G;
+            vec_H = new_H;
+
+            vec_L.push(LR[0]);
+            vec_R.push(LR[1]);
+            challenges.push(e);
+            n = n_half;
+        }
+
+        Ok(WeightedInnerProductProof {
+            vec_L,
+            vec_R,
+            a: vec_a[0],
+            b: vec_b[0],
+            challenges,
+        })
+    }
+
+    pub fn verify(
+        domain: &'static [u8],
+        n: usize,
+        target_P: C,
+        params: &InnerProductParam<C>,
+        y: C::ScalarField,
+        proof: &WeightedInnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        let start = start_timer!(|| "running weighted inner product argument verify algorithm...");
+        let result = Self::verify_rounds(domain, n, target_P, params, y, proof);
+        end_timer!(start);
+        result
+    }
+
+    // the checks of verify, the caller closes the timer on every return
+    fn verify_rounds(
+        domain: &'static [u8],
+        n: usize,
+        target_P: C,
+        params: &InnerProductParam<C>,
+        y: C::ScalarField,
+        proof: &WeightedInnerProductProof<C>,
+    ) -> Result<(), SigmaErrors> {
+        check_params(params, n, y, SigmaErrors::InvalidParameters)?;
+        let log_n = proof.vec_L.len();
+        if n != rounds_to_length(log_n, usize::BITS)? || proof.vec_R.len() != log_n || proof.challenges.len() != log_n {
+            return Err(
+                SigmaErrors::InvalidProof("incorrect proof length".to_string())
+            );
+        }
+
+        // check challenges e at each round
+        let mut transcript = wip_transcript(domain, n, &y)?;
+        let mut challenges = Vec::with_capacity(log_n);
+        for i in 0..log_n {
+            transcript.append_serializable_element(b"commitments L, R", &[proof.vec_L[i], proof.vec_R[i]])?;
+            let (e, e_inv) = nonzero_challenge(&mut transcript, b"challenge")?;
+            if e != proof.challenges[i] {
+                return Err(SigmaErrors::InvalidProof("invalid challenge value".to_string()));
+            }
+            challenges.push((e, e_inv));
+        }
+
+        // the folded G and H are vec_G^{vec_s_G} and vec_H^{vec_s_H},
+        // where round i multiplies the first half by e_i^{-1} (resp. e_i) and the second half
+        // by e_i * y^{-n_i} (resp. e_i^{-1}) for n_i = n/2^{i+1},
+        // so the vectors are built from the last round, which splits the lowest bit of the index
+        let y_inv = y.inverse().unwrap();
+        let mut vec_s_G = vec![proof.a];
+        let mut vec_s_H = vec![proof.b];
+        for (i, (e, e_inv)) in challenges.iter().enumerate().rev() {
+            let y_n_inv = y_inv.pow([(n >> (i+1)) as u64]);
+            vec_s_G = [scalar_product(&vec_s_G, e_inv), scalar_product(&vec_s_G, &(*e*y_n_inv))].concat();
+            vec_s_H = [scalar_product(&vec_s_H, e), scalar_product(&vec_s_H, e_inv)].concat();
+        }
+
+        // compute P =
+        // u^{a * b * y} *
+        // vec_G^{factors_G * vec_s_G} *
+        // vec_H^{factors_H * vec_s_H} *
+        // (L_0*...*L_{log_n-1})^{-e^2} *
+        // (R_0*...*R_{log_n-1})^{-e^-2}
+        let mut exp = Vec::with_capacity(2*n + 2*log_n + 1);
+        exp.push(proof.a*proof.b*y);
+        exp.extend(vec_s_G.iter().zip(&params.factors_G).map(|(s, f)| *s * f));
+        exp.extend(vec_s_H.iter().zip(&params.factors_H).map(|(s, f)| *s * f));
+        exp.extend(challenges.iter().map(|(e, _)| -e.square()));
+        exp.extend(challenges.iter().map(|(_, e_inv)| -e_inv.square()));
+
+        let mut base = Vec::with_capacity(2*n + 2*log_n + 1);
+        base.push(params.u);
+        base.extend_from_slice(&params.vec_G);
+        base.extend_from_slice(&params.vec_H);
+        base.extend_from_slice(&proof.vec_L);
+        base.extend_from_slice(&proof.vec_R);
+
+        let expected_P = msm(&base, &exp, SigmaErrors::InvalidProof)?;
+
+        if ct_eq_affine(expected_P, target_P) {
+            Ok(())
+        }
+        else {
+            Err(SigmaErrors::InvalidProof("invalid WIP proof".to_string()))
+        }
+    }
+}
+
+/// <vec_a, vec_b>_y = sum_i a_i * b_i * y^{i+1}, with powers_y = (y, ..., y^n)
+pub fn weighted_inner_product<F: PrimeField>(vec_a: &[F], vec_b: &[F], powers_y: &[F]) -> F {
+    assert_eq!(vec_a.len(), vec_b.len(), "Vectors must be of the same length");
+    vec_a.iter().zip(vec_b).zip(powers_y)
+        .fold(F::zero(), |acc, ((a_i, b_i), y_i)| acc + *a_i * b_i * y_i)
+}
+
+// the parameters must hold n generators and factors for a power of two n with binary folding,
+// and the weight y must be invertible
+fn check_params<C: CurveGroup>(
+    params: &InnerProductParam<C>,
+    n: usize,
+    y: C::ScalarField,
+    error: fn(String) -> SigmaErrors,
+) -> Result<(), SigmaErrors> {
+    if params.vec_G.len() != n || params.vec_H.len() != n || params.factors_G.len() != n || params.factors_H.len() != n {
+        return Err(error("vectors length are different".to_string()));
+    }
+    if !n.is_power_of_two() {
+        return Err(error("vector length is not power of two".to_string()));
+    }
+    if params.arity != FoldingArity::Two {
+        return Err(error("the weighted inner product argument only supports binary folding".to_string()));
+    }
+    if y.is_zero() {
+        return Err(error("zero weight y".to_string()));
+    }
+    Ok(())
+}
+
+// the transcript of prove(domain, ...) for vectors of length n and weight y, up to the first round
+fn wip_transcript<F: PrimeField>(domain: &'static [u8], n: usize, y: &F) -> Result<ProofTranscript<F>, SigmaErrors> {
+    let mut transcript = ProofTranscript::<F>::new(domain);
+    transcript.append_message(b"WIPdomain", domain)?;
+    transcript.append_field_element(b"WIPsize", &F::from(n as u128))?;
+    transcript.append_field_element(b"WIPweight", y)?;
+    Ok(transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use ark_ec::{Group, VariableBaseMSM};
+    use super::*;
+    use ark_secp256k1::{Fr, Projective, Affine};
+    use ark_std::{One, UniformRand};
+    use toolbox::vec::{hadamard_product, inner_product};
+    use crate::ipa::{InnerProductProtocol, IPA_DOMAIN};
+
+    type Wip = WeightedInnerProductProtocol<Projective>;
+
+    // the parameters with random factors, a random weight y, the witness and
+    // P = G^{a * factors_G} H^{b * factors_H} u^{<a, b>_y}
+    fn wip_instance(n: usize) -> (InnerProductParam<Projective>, Fr, Vec<Fr>, Vec<Fr>, Projective) {
+        let mut rng = ark_std::test_rng();
+        let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+        let y = Fr::rand(&mut rng);
+        let params = InnerProductParam::<Projective> {
+            factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+            u: Affine::rand(&mut rng),
+            vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+            arity: FoldingArity::Two,
+        };
+        let P = statement(&params, y, &vec_a, &vec_b);
+        (params, y, vec_a, vec_b, P)
+    }
+
+    fn statement(params: &InnerProductParam<Projective>, y: Fr, vec_a: &[Fr], vec_b: &[Fr]) -> Projective {
+        let mut exp = hadamard_product(vec_a, &params.factors_G);
+        exp.extend(hadamard_product(vec_b, &params.factors_H));
+        let mut base = params.vec_G.clone();
+        base.extend(params.vec_H.clone());
+        let powers_y = generate_powers(y, vec_a.len());
+        Projective::msm(&base, &exp).unwrap() + params.u*weighted_inner_product(vec_a, vec_b, &powers_y)
+    }
+
+    #[test]
+    fn test_wip() {
+        for n in [1, 2, 8, 64] {
+            let (params, y, vec_a, vec_b, P) = wip_instance(n);
+            let proof = Wip::prove(WIP_DOMAIN, &params, y, vec_a.clone(), vec_b.clone()).unwrap();
+            assert_eq!(proof.vec_L.len(), n.trailing_zeros() as usize);
+            Wip::verify(WIP_DOMAIN, n, P, &params, y, &proof).unwrap();
+
+            // with y = 1 the weighted relation is the IPA relation, so both arguments prove the same P
+            let P_one = statement(&params, Fr::one(), &vec_a, &vec_b);
+            let proof = Wip::prove(WIP_DOMAIN, &params, Fr::one(), vec_a.clone(), vec_b.clone()).unwrap();
+            Wip::verify(WIP_DOMAIN, n, P_one, &params, Fr::one(), &proof).unwrap();
+            let ipa_proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+            InnerProductProtocol::verify(IPA_DOMAIN, n, P_one, &params, &ipa_proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_weighted_inner_product() {
+        let mut rng = ark_std::test_rng();
+        let vec_a: Vec<Fr> = (0..8).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_b: Vec<Fr> = (0..8).map(|_| Fr::rand(&mut rng)).collect();
+        let y = Fr::rand(&mut rng);
+        let powers_y = generate_powers(y, 8);
+        let expected = inner_product(&vec_a, &hadamard_product(&vec_b, &powers_y));
+        assert_eq!(weighted_inner_product(&vec_a, &vec_b, &powers_y), expected);
+        // the split used at each round: <a, b>_y = <a_1, b_1>_y + y^4 * <a_2, b_2>_y
+        let split = weighted_inner_product(&vec_a[..4], &vec_b[..4], &powers_y)
+            + powers_y[3]*weighted_inner_product(&vec_a[4..], &vec_b[4..], &powers_y);
+        assert_eq!(split, expected);
+    }
+
+    #[test]
+    fn test_wip_mutations() {
+        let g = Projective::generator();
+        let n = 8;
+        let (params, y, vec_a, vec_b, P) = wip_instance(n);
+        let proof = Wip::prove(WIP_DOMAIN, &params, y, vec_a.clone(), vec_b.clone()).unwrap();
+        Wip::verify(WIP_DOMAIN, n, P, &params, y, &proof).unwrap();
+
+        let mut mutations = vec![];
+        for i in 0..proof.vec_L.len() {
+            let mut mutated = proof.clone();
+            mutated.vec_L[i] = (mutated.vec_L[i] + g).into_affine();
+            mutations.push((format!("L_{}", i), mutated));
+            let mut mutated = proof.clone();
+            mutated.vec_R[i] = (mutated.vec_R[i] + g).into_affine();
+            mutations.push((format!("R_{}", i), mutated));
+            let mut mutated = proof.clone();
+            mutated.challenges[i] += Fr::one();
+            mutations.push((format!("challenge_{}", i), mutated));
+        }
+        let mut mutated = proof.clone();
+        mutated.a += Fr::one();
+        mutations.push(("a".to_string(), mutated));
+        let mut mutated = proof.clone();
+        mutated.b += Fr::one();
+        mutations.push(("b".to_string(), mutated));
+        for (field, mutated) in mutations {
+            assert!(Wip::verify(WIP_DOMAIN, n, P, &params, y, &mutated).is_err(), "mutated {} verified", field);
+        }
+
+        // the proof is bound to the weight and the statement
+        assert!(Wip::verify(WIP_DOMAIN, n, P, &params, y + Fr::one(), &proof).is_err());
+        assert!(Wip::verify(WIP_DOMAIN, n, P + g, &params, y, &proof).is_err());
+        assert!(Wip::verify(b"OtherDomain", n, P, &params, y, &proof).is_err());
+
+        // a witness with a wrong weighted inner product does not prove P
+        let mut vec_b_wrong = vec_b.clone();
+        vec_b_wrong[0] += Fr::one();
+        let proof_wrong = Wip::prove(WIP_DOMAIN, &params, y, vec_a, vec_b_wrong).unwrap();
+        assert!(Wip::verify(WIP_DOMAIN, n, P, &params, y, &proof_wrong).is_err());
+
+        // a truncated proof is rejected as invalid rather than panicking on a length mismatch
+        let mut truncated = proof.clone();
+        truncated.vec_L.pop();
+        truncated.vec_R.pop();
+        truncated.challenges.pop();
+        assert!(matches!(Wip::verify(WIP_DOMAIN, n, P, &params, y, &truncated), Err(SigmaErrors::InvalidProof(_))));
+    }
+
+    #[test]
+    fn test_wip_invalid_parameters() {
+        let (params, y, vec_a, vec_b, P) = wip_instance(8);
+        let proof = Wip::prove(WIP_DOMAIN, &params, y, vec_a.clone(), vec_b.clone()).unwrap();
+        let is_invalid_parameters = |result: Result<(), SigmaErrors>| matches!(result, Err(SigmaErrors::InvalidParameters(_)));
+        assert!(Wip::prove(WIP_DOMAIN, &params, Fr::zero(), vec_a.clone(), vec_b.clone()).is_err());
+        assert!(Wip::prove(WIP_DOMAIN, &params, y, vec_a[..4].to_vec(), vec_b[..4].to_vec()).is_err());
+        let params_quaternary = InnerProductParam { arity: FoldingArity::Four, ..params.clone() };
+        assert!(Wip::prove(WIP_DOMAIN, &params_quaternary, y, vec_a, vec_b).is_err());
+        assert!(is_invalid_parameters(Wip::verify(WIP_DOMAIN, 8, P, &params, Fr::zero(), &proof)));
+        assert!(is_invalid_parameters(Wip::verify(WIP_DOMAIN, 8, P, &params_quaternary, y, &proof)));
+        assert!(is_invalid_parameters(Wip::verify(WIP_DOMAIN, 4, P, &params, y, &proof)));
+    }
+
+    #[test]
+    fn test_wip_proof_size() {
+        // at n = 64 the WIP proof has the layout of the IPA proof: 6 rounds of L, R, the folded a, b
+        // and the 6 challenges, the saving of Bulletproofs+ comes from the range proof, which no longer
+        // needs T_1, T_2, tau_x, mu and t_hat once its inner product is weighted
+        let n = 64;
+        let (params, y, vec_a, vec_b, _) = wip_instance(n);
+        let wip_proof = Wip::prove(WIP_DOMAIN, &params, y, vec_a.clone(), vec_b.clone()).unwrap();
+        let ipa_proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+        assert_eq!(wip_proof.vec_L.len(), 6);
+        assert_eq!(wip_proof.vec_L.len(), ipa_proof.vec_L.len());
+        assert_eq!(wip_proof.size_in_bytes(), ipa_proof.size_in_bytes());
+        // 12 compressed points of 33 bytes, 2 + 6 scalars of 32 bytes and the three length prefixes
+        assert_eq!(wip_proof.size_in_bytes(), 12*33 + 8*32 + 3*8);
+    }
+}
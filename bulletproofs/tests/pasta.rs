@@ -0,0 +1,53 @@
+#![allow(non_snake_case)]
+
+// cargo test -p bulletproofs --features pasta --test pasta
+// the IPA over the Pasta cycle, where the base field of Pallas is the scalar field of Vesta and vice versa,
+// so that an outer IPA on one curve can verify the arithmetic of an inner IPA on the other
+use ark_ec::CurveGroup;
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use toolbox::vec::{hadamard_product, inner_product, rand_affine_vec};
+
+// proves and verifies <vec_a, vec_b> at size n, and checks that a proof does not verify for another target
+fn check_ipa<C: CurveGroup>(n: usize) {
+    let mut rng = ark_std::test_rng();
+    let vec_a: Vec<C::ScalarField> = (0..n).map(|_| C::ScalarField::rand(&mut rng)).collect();
+    let vec_b: Vec<C::ScalarField> = (0..n).map(|_| C::ScalarField::rand(&mut rng)).collect();
+    let params = InnerProductParam::<C> {
+        factors_G: (0..n).map(|_| C::ScalarField::rand(&mut rng)).collect(),
+        factors_H: (0..n).map(|_| C::ScalarField::rand(&mut rng)).collect(),
+        u: C::rand(&mut rng).into_affine(),
+        vec_G: rand_affine_vec::<C, _>(&mut rng, n),
+        vec_H: rand_affine_vec::<C, _>(&mut rng, n),
+        arity: FoldingArity::Two,
+    };
+
+    type Ipa<C> = InnerProductProtocol<C>;
+    let proof = Ipa::<C>::prove(IPA_DOMAIN, &params, vec_a.clone(), vec_b.clone()).unwrap();
+    let mut exp = hadamard_product(&vec_a, &params.factors_G);
+    exp.extend(hadamard_product(&vec_b, &params.factors_H));
+    let mut base = params.vec_G.clone();
+    base.extend(params.vec_H.clone());
+    let P = C::msm(&base, &exp).unwrap() + params.u * inner_product(&vec_a, &vec_b);
+    Ipa::<C>::verify(IPA_DOMAIN, n, P, &params, &proof).unwrap();
+    assert!(Ipa::<C>::verify(IPA_DOMAIN, n, P + params.u, &params, &proof).is_err());
+}
+
+#[test]
+fn test_ipa_pallas() {
+    check_ipa::<ark_pallas::Projective>(16);
+}
+
+#[test]
+fn test_ipa_vesta() {
+    check_ipa::<ark_vesta::Projective>(16);
+}
+
+#[test]
+fn test_pasta_cycle() {
+    use ark_ff::PrimeField;
+    // the scalar field of each curve is the base field of the other
+    assert_eq!(ark_pallas::Fr::MODULUS, ark_vesta::Fq::MODULUS);
+    assert_eq!(ark_vesta::Fr::MODULUS, ark_pallas::Fq::MODULUS);
+}
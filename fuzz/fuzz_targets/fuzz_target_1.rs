@@ -0,0 +1,44 @@
+#![no_main]
+#![allow(non_snake_case)]
+
+// Feeds arbitrary bytes into InnerProductProof deserialization and verification.
+// The first byte selects the statement from ringct_fuzz::IPA_SIZES, the rest is the proof.
+// Malformed bytes must be rejected while deserializing, and any proof that deserializes
+// must be accepted or rejected with SigmaErrors::InvalidProof, never panic.
+
+use std::sync::OnceLock;
+use ark_secp256k1::Projective;
+use ark_serialize::CanonicalDeserialize;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{InnerProductParam, InnerProductProof};
+use libfuzzer_sys::fuzz_target;
+use ringct_fuzz::{ipa_instance, IPA_SIZES};
+use toolbox::errors::SigmaErrors;
+
+type Instance = (usize, InnerProductParam<Projective>, Projective);
+
+fn instances() -> &'static Vec<Instance> {
+    static INSTANCES: OnceLock<Vec<Instance>> = OnceLock::new();
+    INSTANCES.get_or_init(|| {
+        IPA_SIZES.iter()
+            .map(|&n| {
+                let (params, P, _, _) = ipa_instance(n);
+                (n, params, P)
+            })
+            .collect()
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, bytes)) = data.split_first() else {
+        return;
+    };
+    let (n, params, P) = &instances()[selector as usize % IPA_SIZES.len()];
+    let Ok(proof) = InnerProductProof::<Projective>::deserialize_compressed(bytes) else {
+        return;
+    };
+    match InnerProductProtocol::verify(IPA_DOMAIN, *n, *P, params, &proof) {
+        Ok(()) | Err(SigmaErrors::InvalidProof(_)) => {}
+        Err(e) => panic!("unexpected verifier error: {}", e),
+    }
+});
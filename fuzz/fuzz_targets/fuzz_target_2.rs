@@ -0,0 +1,29 @@
+#![no_main]
+
+// Feeds arbitrary bytes into LogarithmicRingSignature deserialization and the ring
+// signature verifier. The ring is sampled once per process.
+
+use std::sync::OnceLock;
+use ark_secp256k1::Projective;
+use ark_serialize::CanonicalDeserialize;
+use libfuzzer_sys::fuzz_target;
+use ringct_fuzz::ring_instance;
+use ringsignature::ringsig::protocol_compressed::RingSignatureScheme;
+use ringsignature::ringsig::structs::{LogarithmicRingSignature, RingSignatureParams};
+use toolbox::errors::SigmaErrors;
+use toolbox::sigma::SigmaProtocol;
+
+fn params() -> &'static RingSignatureParams<Projective> {
+    static PARAMS: OnceLock<RingSignatureParams<Projective>> = OnceLock::new();
+    PARAMS.get_or_init(|| ring_instance().0)
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(signature) = LogarithmicRingSignature::<Projective>::deserialize_compressed(data) else {
+        return;
+    };
+    match RingSignatureScheme::verify(params(), &signature) {
+        Ok(_) | Err(SigmaErrors::InvalidProof(_)) => {}
+        Err(e) => panic!("unexpected verifier error: {}", e),
+    }
+});
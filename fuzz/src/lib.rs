@@ -0,0 +1,85 @@
+#![allow(non_snake_case)]
+
+use std::fs;
+use std::path::Path;
+use ark_ec::VariableBaseMSM;
+use ark_secp256k1::{Affine, Fr, Projective};
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+use bulletproofs::ipa::{InnerProductProtocol, IPA_DOMAIN};
+use bulletproofs::structs::{FoldingArity, InnerProductParam};
+use ringsignature::ringsig::protocol_compressed::RingSignatureScheme;
+use ringsignature::ringsig::structs::RingSignatureParams;
+use toolbox::sigma::SigmaProtocol;
+use toolbox::vec::{hadamard_product, inner_product};
+
+// the IPA sizes exercised by fuzz_target_1, selected by the first input byte
+pub const IPA_SIZES: [usize; 4] = [1, 2, 4, 8];
+
+pub const RING_SIZE: usize = 4;
+pub const MESSAGE: &str = "Welcome to the world of Zero Knowledge!";
+
+// a deterministic IPA statement of size n: the parameters, the target P and the witness
+pub fn ipa_instance(n: usize) -> (InnerProductParam<Projective>, Projective, Vec<Fr>, Vec<Fr>) {
+    let mut rng = ark_std::test_rng();
+    let vec_a: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let vec_b: Vec<Fr> = (0..n).map(|_| Fr::rand(&mut rng)).collect();
+    let params = InnerProductParam::<Projective> {
+        factors_G: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        factors_H: (0..n).map(|_| Fr::rand(&mut rng)).collect(),
+        u: Affine::rand(&mut rng),
+        vec_G: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        vec_H: (0..n).map(|_| Affine::rand(&mut rng)).collect(),
+        arity: FoldingArity::Two,
+    };
+    let mut exp = hadamard_product(&vec_a, &params.factors_G);
+    exp.extend(hadamard_product(&vec_b, &params.factors_H));
+    let mut base = params.vec_G.clone();
+    base.extend(params.vec_H.clone());
+    let P = Projective::msm(&base, &exp).unwrap() + params.u*inner_product(&vec_a, &vec_b);
+    (params, P, vec_a, vec_b)
+}
+
+// ring parameters and the signer's witness
+pub fn ring_instance() -> (RingSignatureParams<Projective>, Vec<Fr>) {
+    let mut rng = ark_std::test_rng();
+    let mut wit = vec![Fr::rand(&mut rng)];
+    let params = RingSignatureScheme::<Projective>::setup(&mut rng, &mut wit, MESSAGE, RING_SIZE).unwrap();
+    (params, wit)
+}
+
+// writes valid proofs into corpus/fuzz_target_1 and well-formed signatures into corpus/fuzz_target_2
+pub fn write_seed_corpus(dir: &Path) {
+    let dir_1 = dir.join("fuzz_target_1");
+    fs::create_dir_all(&dir_1).unwrap();
+    for (i, &n) in IPA_SIZES.iter().enumerate() {
+        let (params, _, vec_a, vec_b) = ipa_instance(n);
+        let proof = InnerProductProtocol::prove(IPA_DOMAIN, &params, vec_a, vec_b).unwrap();
+        let mut bytes = vec![i as u8];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        fs::write(dir_1.join(format!("ipa_n{}", n)), bytes).unwrap();
+    }
+
+    let dir_2 = dir.join("fuzz_target_2");
+    fs::create_dir_all(&dir_2).unwrap();
+    let mut rng = ark_std::test_rng();
+    let (params, wit) = ring_instance();
+    for i in 0..2 {
+        let signature = RingSignatureScheme::<Projective>::prove(&mut rng, &params, &wit).unwrap();
+        let mut bytes = vec![];
+        signature.serialize_compressed(&mut bytes).unwrap();
+        fs::write(dir_2.join(format!("signature_{}", i)), bytes).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cargo test -- --ignored write_corpus
+    #[test]
+    #[ignore]
+    fn write_corpus() {
+        write_seed_corpus(&Path::new(env!("CARGO_MANIFEST_DIR")).join("corpus"));
+    }
+}
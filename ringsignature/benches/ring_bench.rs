@@ -0,0 +1,87 @@
+use ark_secp256k1::{Fr, Projective};
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, Criterion};
+use ringsignature::ringsig::{protocol_compressed_modification, protocol_linear};
+use toolbox::sigma::SigmaProtocol;
+
+type Linear = protocol_linear::RingSignatureScheme<Projective>;
+type Logarithmic = protocol_compressed_modification::RingSignatureScheme<Projective>;
+
+const MESSAGE: &str = "Welcome to the world of Zero Knowledge!";
+const RING_SIZES: [usize; 7] = [8, 16, 32, 64, 128, 256, 512];
+
+// the logarithmic scheme requires the ring size to be less than the modulus bit size (256 for secp256k1),
+// so it is only run for the ring sizes up to 128
+fn log_supported(n: usize) -> bool {
+    n < 256
+}
+
+fn bench_ring_signature(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    // baseline comparison of the proof sizes, the verifier times are compared by
+    // ring_linear_verify_n{n} against ring_log_verify_n{n} in the criterion report
+    let mut crossover = None;
+    println!("{:>6} {:>14} {:>14}", "n", "linear (bytes)", "log (bytes)");
+    for n in RING_SIZES {
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let params = Linear::setup(&mut rng, &mut wit, MESSAGE, n).unwrap();
+        let size_linear = Linear::prove(&mut rng, &params, &wit).unwrap().proof_size_bytes();
+        if !log_supported(n) {
+            println!("{:>6} {:>14} {:>14}", n, size_linear, "-");
+            continue;
+        }
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let params = Logarithmic::setup(&mut rng, &mut wit, MESSAGE, n).unwrap();
+        let size_log = Logarithmic::prove(&mut rng, &params, &wit).unwrap().proof_size_bytes();
+        println!("{:>6} {:>14} {:>14}", n, size_linear, size_log);
+        if size_log < size_linear && crossover.is_none() {
+            crossover = Some(n);
+        }
+    }
+    if let Some(n) = crossover {
+        println!("the logarithmic proof is smaller from ring size {}", n);
+    }
+
+    let mut group = c.benchmark_group("ring_signature");
+    group.sample_size(10);
+    for n in RING_SIZES {
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let params = Linear::setup(&mut rng, &mut wit, MESSAGE, n).unwrap();
+        let proof = Linear::prove(&mut rng, &params, &wit).unwrap();
+        group.bench_function(format!("ring_linear_prove_n{}", n), |b| {
+            b.iter(|| Linear::prove(&mut ark_std::test_rng(), &params, &wit).unwrap())
+        });
+        group.bench_function(format!("ring_linear_verify_n{}", n), |b| {
+            b.iter(|| Linear::verify(&params, &proof).unwrap())
+        });
+
+        if !log_supported(n) {
+            continue;
+        }
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let params = Logarithmic::setup(&mut rng, &mut wit, MESSAGE, n).unwrap();
+        let proof = Logarithmic::prove(&mut rng, &params, &wit).unwrap();
+        group.bench_function(format!("ring_log_prove_n{}", n), |b| {
+            b.iter(|| Logarithmic::prove(&mut ark_std::test_rng(), &params, &wit).unwrap())
+        });
+        group.bench_function(format!("ring_log_verify_n{}", n), |b| {
+            b.iter(|| Logarithmic::verify(&params, &proof).unwrap())
+        });
+
+        // repeated verification of 8 signatures of one ring, with the ring prepared per signature or once
+        let proofs: Vec<_> = (0..8).map(|_| Logarithmic::prove(&mut rng, &params, &wit).unwrap()).collect();
+        group.bench_function(format!("ring_log_verify8_n{}", n), |b| {
+            b.iter(|| proofs.iter().all(|proof| Logarithmic::verify(&params, proof).unwrap()))
+        });
+        group.bench_function(format!("ring_log_verify8_prepared_n{}", n), |b| {
+            b.iter(|| {
+                let prepared = Logarithmic::prepare(&params).unwrap();
+                proofs.iter().all(|proof| Logarithmic::verify_prepared(&prepared, proof).unwrap())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ring_signature);
+criterion_main!(benches);
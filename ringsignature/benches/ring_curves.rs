@@ -0,0 +1,41 @@
+// cargo bench -p ringsignature --features bench --bench ring_curves
+// the logarithmic ring signature at ring size 64 over secp256k1 and the G1 group of BLS12-381,
+// to help choosing a curve; the proof sizes are printed before the timings
+use ark_ec::CurveGroup;
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, Criterion};
+use ringsignature::ringsig::protocol_compressed::RingSignatureScheme;
+use toolbox::sigma::SigmaProtocol;
+
+const MESSAGE: &str = "Welcome to the world of Zero Knowledge!";
+const RING_SIZE: usize = 64;
+
+fn bench_curve<C: CurveGroup>(c: &mut Criterion, curve: &str) {
+    type Ring<C> = RingSignatureScheme<C>;
+    let mut rng = ark_std::test_rng();
+    let mut wit = vec![C::ScalarField::rand(&mut rng)];
+    let params = Ring::<C>::setup(&mut rng, &mut wit, MESSAGE, RING_SIZE).unwrap();
+    let proof = Ring::<C>::prove(&mut rng, &params, &wit).unwrap();
+    println!("{}: {} bytes at ring size {}", curve, proof.proof_size_bytes(), RING_SIZE);
+
+    let mut group = c.benchmark_group(format!("ring_log_{}", curve));
+    group.sample_size(10);
+    group.bench_function(format!("prove_n{}", RING_SIZE), |b| {
+        b.iter(|| Ring::<C>::prove(&mut ark_std::test_rng(), &params, &wit).unwrap())
+    });
+    group.bench_function(format!("verify_n{}", RING_SIZE), |b| {
+        b.iter(|| Ring::<C>::verify(&params, &proof).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_secp256k1(c: &mut Criterion) {
+    bench_curve::<ark_secp256k1::Projective>(c, "secp256k1");
+}
+
+fn bench_bls12_381(c: &mut Criterion) {
+    bench_curve::<ark_bls12_381::G1Projective>(c, "bls12_381");
+}
+
+criterion_group!(benches, bench_secp256k1, bench_bls12_381);
+criterion_main!(benches);
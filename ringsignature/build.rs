@@ -0,0 +1,21 @@
+// With the ffi feature, generates the C header ringct.h of src/ffi.rs with cbindgen into OUT_DIR,
+// so that building never writes to the source tree, and compiles the C program ffi/test.c against it,
+// which the tests of src/ffi.rs call; they also compare it with the checked-in include/ringct.h
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        cbindgen::generate(&crate_dir)
+            .expect("generating the C header")
+            .write_to_file(format!("{out_dir}/ringct.h"));
+        cc::Build::new()
+            .file("ffi/test.c")
+            .include(&out_dir)
+            .compile("ringct_ffi_test");
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=ffi/test.c");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
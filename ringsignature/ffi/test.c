@@ -0,0 +1,25 @@
+/* Calls the C bindings of include/ringct.h, run by the test_c_program test of src/ffi.rs */
+#include <string.h>
+#include "ringct.h"
+
+int ringct_c_test(void) {
+    /* a compressed secp256k1 scalar is 32 little-endian bytes below the group order */
+    uint8_t sk[32] = {0};
+    sk[0] = 42;
+    const char *msg = "Welcome to the world of Zero Knowledge!";
+
+    void *params = ringct_setup(8, sk, sizeof(sk), (const uint8_t *)msg, (uint32_t)strlen(msg));
+    if (params == NULL) {
+        return -2;
+    }
+    void *proof = ringct_prove(params, sk, sizeof(sk));
+    if (proof == NULL) {
+        ringct_free_params(params);
+        return -3;
+    }
+    int result = ringct_verify(params, proof);
+
+    ringct_free_proof(proof);
+    ringct_free_params(params);
+    return result;
+}
@@ -0,0 +1,59 @@
+/*
+ * Memory ownership: the parameters of ringct_setup and the proofs of ringct_prove are owned by the caller,
+ * who must free them exactly once with ringct_free_params and ringct_free_proof.
+ */
+
+#ifndef RINGCT_H
+#define RINGCT_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define VERSION 1
+
+// Sets up a ring of ring_size members including the public key of sk for the message msg,
+// where ring_size should be a power of two of at most 128 and msg valid UTF-8.
+// Returns the parameters, to be freed with ringct_free_params, or null on invalid input
+//
+// # Safety
+// sk and msg must point to sk_len and msg_len readable bytes
+void *ringct_setup(uint32_t ring_size,
+                   const uint8_t *sk,
+                   uint32_t sk_len,
+                   const uint8_t *msg,
+                   uint32_t msg_len);
+
+// Signs on behalf of the ring of params with the secret key wit, whose public key should be a member of the ring.
+// Returns the proof, to be freed with ringct_free_proof, or null on invalid input
+//
+// # Safety
+// params must be null or returned by ringct_setup and not yet freed, wit must point to wit_len readable bytes
+void *ringct_prove(const void *params,
+                   const uint8_t *wit,
+                   uint32_t wit_len);
+
+// Returns 1 if proof is a valid signature for params, 0 if it is not, and -1 if either pointer is null
+//
+// # Safety
+// params and proof must be null or returned by ringct_setup and ringct_prove and not yet freed
+int ringct_verify(const void *params,
+                  const void *proof);
+
+// Frees parameters returned by ringct_setup, null is ignored
+//
+// # Safety
+// params must be null or returned by ringct_setup and not yet freed
+void ringct_free_params(void *params);
+
+// Frees a proof returned by ringct_prove, null is ignored
+//
+// # Safety
+// proof must be null or returned by ringct_prove and not yet freed
+void ringct_free_proof(void *proof);
+
+#endif  /* RINGCT_H */
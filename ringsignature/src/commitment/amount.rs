@@ -0,0 +1,138 @@
+use ark_ec::CurveGroup;
+use ark_std::{rand::Rng, UniformRand, Zero};
+use bulletproofs::range::RANGE_BITS;
+use bulletproofs::structs::{RangeProof, RangeProofParam};
+use toolbox::errors::SigmaErrors;
+use crate::commitment::PedersenParams;
+use crate::commitment::pedersen::PedersenCommitmentScheme;
+
+// the domain of the nothing-up-my-sleeve amount commitment key
+const AMOUNT_DOMAIN: &[u8] = b"RingCT-SP23 amount commitments";
+
+/// The parameters of the amount commitments V = g^v h^gamma and of their range proofs:
+/// the Pedersen commitment key (h, [g]) and the range proof generators, whose g and h are the key
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AmountParams<C: CurveGroup> {
+    // the commitment key with the blinding generator h and the value generator g
+    pub key: PedersenParams<C>,
+    // the range proof generators for a single 64-bit amount
+    pub range: RangeProofParam<C>,
+}
+
+impl<C: CurveGroup> AmountParams<C> {
+    /// Setup algorithm hashes the commitment key to the curve, and samples the bit-commitment generators
+    /// of the range proof
+    pub fn setup<R: Rng>(rng: &mut R) -> Result<Self, SigmaErrors> {
+        let key = PedersenCommitmentScheme::<C>::setup_deterministic(AMOUNT_DOMAIN, 1)?;
+        let mut range = RangeProof::<C>::setup(rng, RANGE_BITS, 1)?;
+        range.g = key.vec_gen[0];
+        range.h = key.generator.into_affine();
+        Ok(AmountParams { key, range })
+    }
+
+    // the range proof should be about commitments under the commitment key
+    fn check(&self) -> Result<(), SigmaErrors> {
+        if self.key.vec_gen.len() != 1 || self.range.g != self.key.vec_gen[0] || self.range.h != self.key.generator.into_affine() {
+            return Err(SigmaErrors::InvalidParameters(
+                "the range proof generators do not match the commitment key".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Commits to the amount value with a fresh nonzero blinding gamma and proves that it lies in [0, 2^64);
+/// outputs the commitment V = g^value h^gamma, the range proof and gamma
+pub fn commit_and_prove_range<C: CurveGroup, R: Rng>(
+    rng: &mut R,
+    params: &AmountParams<C>,
+    value: u64,
+) -> Result<(C, RangeProof<C>, C::ScalarField), SigmaErrors> {
+    let mut blinding = C::ScalarField::rand(rng);
+    while blinding.is_zero() {
+        blinding = C::ScalarField::rand(rng);
+    }
+    let (commitment, proof) = prove_range(rng, params, value, &blinding)?;
+    Ok((commitment, proof, blinding))
+}
+
+/// Same as commit_and_prove_range with the blinding given by the caller, e.g., to balance the amounts
+/// of a transaction; a zero blinding is rejected, since it reveals the amount
+pub fn prove_range<C: CurveGroup, R: Rng>(
+    rng: &mut R,
+    params: &AmountParams<C>,
+    value: u64,
+    blinding: &C::ScalarField,
+) -> Result<(C, RangeProof<C>), SigmaErrors> {
+    params.check()?;
+    if blinding.is_zero() {
+        return Err(SigmaErrors::InvalidProver(
+            "the blinding of an amount should be nonzero".to_string(),
+        ));
+    }
+    let commitment = PedersenCommitmentScheme::<C>::commit(&params.key, &[C::ScalarField::from(value)], blinding, "amount")?;
+    let (proof, _) = RangeProof::<C>::prove(rng, &params.range, &[value], &[*blinding])?;
+    Ok((commitment, proof))
+}
+
+/// Verifies that the commitment hides an amount in [0, 2^64)
+pub fn verify_range<C: CurveGroup>(
+    params: &AmountParams<C>,
+    commitment: &C,
+    proof: &RangeProof<C>,
+) -> Result<(), SigmaErrors> {
+    params.check()?;
+    RangeProof::<C>::verify(&params.range, &[*commitment], proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::Field;
+    use ark_secp256k1::{Fr, Projective};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+    #[test]
+    fn test_amount_range_proof() {
+        let mut rng = ark_std::test_rng();
+        let params = AmountParams::<Projective>::setup(&mut rng).unwrap();
+        for value in [0, 1, 1000, u64::MAX] {
+            let (commitment, proof, blinding) = commit_and_prove_range(&mut rng, &params, value).unwrap();
+            assert_eq!(commitment, PedersenCommitmentScheme::commit(&params.key, &[Fr::from(value)], &blinding, "amount").unwrap());
+            verify_range(&params, &commitment, &proof).unwrap();
+
+            // the proof and the commitment survive a serialization round-trip
+            let mut bytes = Vec::new();
+            (commitment.into_affine(), proof.clone()).serialize_compressed(&mut bytes).unwrap();
+            let (commitment_prime, proof_prime) =
+                <(ark_secp256k1::Affine, RangeProof<Projective>)>::deserialize_compressed(&bytes[..]).unwrap();
+            assert_eq!(proof_prime, proof);
+            verify_range(&params, &commitment_prime.into(), &proof_prime).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_amount_range_proof_invalid() {
+        let mut rng = ark_std::test_rng();
+        let params = AmountParams::<Projective>::setup(&mut rng).unwrap();
+        let (commitment, proof, _) = commit_and_prove_range(&mut rng, &params, 42).unwrap();
+
+        // the commitment of another amount, and a commitment shifted out of u64 by g^{2^64}
+        let (commitment_prime, _, _) = commit_and_prove_range(&mut rng, &params, 42).unwrap();
+        assert!(verify_range(&params, &commitment_prime, &proof).is_err());
+        let shift = params.key.vec_gen[0] * Fr::from(2u64).pow([64]);
+        assert!(verify_range(&params, &(commitment + shift), &proof).is_err());
+
+        // a zero blinding
+        assert!(matches!(prove_range(&mut rng, &params, 42, &Fr::zero()), Err(SigmaErrors::InvalidProver(_))));
+
+        // range proof generators that do not match the commitment key
+        let mut params_prime = params.clone();
+        params_prime.range.h = params.range.u;
+        assert!(matches!(commit_and_prove_range(&mut rng, &params_prime, 42), Err(SigmaErrors::InvalidParameters(_))));
+        assert!(matches!(verify_range(&params_prime, &commitment, &proof), Err(SigmaErrors::InvalidParameters(_))));
+        // and a commitment under another key
+        let params_other = AmountParams::<Projective>::setup(&mut rng).unwrap();
+        assert!(verify_range(&params_other, &commitment, &proof).is_err());
+    }
+}
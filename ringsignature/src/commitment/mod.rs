@@ -1,15 +1,190 @@
+pub mod amount;
 pub mod pedersen;
-use ark_ec::CurveGroup;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{end_timer, rand::Rng, start_timer};
 use std::fmt::Debug;
+use std::path::Path;
+use crate::params_file;
+use toolbox::errors::CommitmentErrors;
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+/// A commitment scheme, where commit binds a message under a random hiding element
+/// and an opening reveals both to the verifier
+pub trait CommitmentScheme {
+    /// public parameters
+    type Params;
+    /// committed message
+    type Message: ?Sized;
+    /// random element for hiding
+    type Random;
+    /// commitment
+    type Commitment;
+    /// opening of a commitment
+    type Opening;
+
+    /// Setup algorithm generates the public parameters for messages of supported_size
+    fn setup<R: Rng>(rng: &mut R, supported_size: usize) -> Result<Self::Params, CommitmentErrors>;
+
+    /// Commit algorithm commits to m with the random element r
+    fn commit(
+        params: &Self::Params,
+        m: &Self::Message,
+        r: &Self::Random,
+    ) -> Result<Self::Commitment, CommitmentErrors>;
+
+    /// Open algorithm outputs the opening of the commitment to m with r
+    fn open(m: &Self::Message, r: &Self::Random) -> Result<Self::Opening, CommitmentErrors>;
+
+    /// Verify algorithm checks that cm is a commitment to the opening
+    fn verify(
+        params: &Self::Params,
+        cm: &Self::Commitment,
+        open: &Self::Opening,
+    ) -> Result<bool, CommitmentErrors>;
+}
+
+/// The parameters of a Pedersen commitment, with generator for the random element and vec_gen for the message
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
 pub struct PedersenParams<C: CurveGroup> {
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
     pub generator: C,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
     pub vec_gen: Vec<C::Affine>,
 }
 
+impl<C: CurveGroup> PedersenParams<C> {
+    /// Returns the length of the message vectors these parameters commit to
+    pub fn supported_size(&self) -> usize {
+        self.vec_gen.len()
+    }
+
+    /// Writes the parameters to the file at path, in the format of params_file
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), CommitmentErrors> {
+        Ok(params_file::write(path, params_file::PEDERSEN_TAG, self)?)
+    }
+
+    /// Reads parameters written by write_to_file, rejecting a corrupted file or points off the curve
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self, CommitmentErrors> {
+        Ok(params_file::read(path, params_file::PEDERSEN_TAG)?)
+    }
+}
+
+/// Fixed-base tables of PedersenParams for committing many messages under the same parameters,
+/// holding for each generator g and window j the shifted generator 2^{window_bits * j} g,
+/// so that a commitment adds every shifted generator into the bucket of its window digit without doublings
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PedersenCommitterKey<C: CurveGroup> {
+    pub window_bits: usize,
+    // the number of windows covering a scalar
+    pub num_windows: usize,
+    // the shifts of vec_gen[i] at num_windows * i..num_windows * (i + 1)
+    pub vec_gen_table: Vec<C::Affine>,
+    // the shifts of the blinding generator
+    pub generator_table: Vec<C::Affine>,
+}
+
+impl<C: CurveGroup> PedersenCommitterKey<C> {
+    /// Precomputes the tables of params for windows of window_bits bits, between 1 and 16,
+    /// where wider windows need fewer additions per commitment but 2^window_bits buckets
+    /// and MODULUS_BIT_SIZE / window_bits points per generator
+    pub fn from_params(params: &PedersenParams<C>, window_bits: usize) -> Result<Self, CommitmentErrors> {
+        if !(1..=16).contains(&window_bits) {
+            return Err(CommitmentErrors::InvalidParameters(
+                "window size should be between 1 and 16 bits".to_string(),
+            ));
+        }
+        let num_windows = (C::ScalarField::MODULUS_BIT_SIZE as usize).div_ceil(window_bits);
+        let start = start_timer!(|| format!("precomputing the pedersen tables of {} generators", params.vec_gen.len() + 1));
+        let shifts = |mut g: C| {
+            let mut table = Vec::with_capacity(num_windows);
+            table.push(g);
+            for _ in 1..num_windows {
+                for _ in 0..window_bits {
+                    g.double_in_place();
+                }
+                table.push(g);
+            }
+            table
+        };
+        let vec_gen_table: Vec<C> = params.vec_gen.iter().flat_map(|g| shifts(g.into_group())).collect();
+        let key = PedersenCommitterKey {
+            window_bits,
+            num_windows,
+            vec_gen_table: C::normalize_batch(&vec_gen_table),
+            generator_table: C::normalize_batch(&shifts(params.generator)),
+        };
+        end_timer!(start);
+        Ok(key)
+    }
+
+    /// Returns the length of the message vectors this key commits to
+    pub fn supported_size(&self) -> usize {
+        self.vec_gen_table.len() / self.num_windows
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct PedersenOpening<C: CurveGroup> {
     pub message: Vec<C::ScalarField>,
     pub random: C::ScalarField,
 }
+
+// a commitment A = g^{b_0}h^{b_1}u^{random} to a bit vector b_0,
+// holding the complement b_1 = 1^n - b_0 and the randomness for the bit-ness argument
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitCommitment<C: CurveGroup> {
+    pub com: C,
+    pub complement: Vec<C::ScalarField>,
+    pub random: C::ScalarField,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::commitment::pedersen::PedersenCommitmentScheme;
+    use ark_std::UniformRand;
+
+    // the properties every commitment scheme should have, checked on m and r against m_prime and r_prime,
+    // where m differs from m_prime and r from r_prime
+    pub(crate) fn check_commitment_scheme<S: CommitmentScheme>(
+        params: &S::Params,
+        m: &S::Message,
+        m_prime: &S::Message,
+        r: &S::Random,
+        r_prime: &S::Random,
+    ) where
+        S::Commitment: PartialEq + Debug,
+    {
+        // an honest opening verifies, and commit is deterministic in (m, r)
+        let cm = S::commit(params, m, r).unwrap();
+        assert!(S::verify(params, &cm, &S::open(m, r).unwrap()).unwrap());
+        assert_eq!(cm, S::commit(params, m, r).unwrap());
+
+        // openings to another message or random element are rejected
+        assert!(!S::verify(params, &cm, &S::open(m_prime, r).unwrap()).unwrap());
+        assert!(!S::verify(params, &cm, &S::open(m, r_prime).unwrap()).unwrap());
+        // and another random element hides the message in another commitment
+        assert_ne!(cm, S::commit(params, m, r_prime).unwrap());
+    }
+
+    fn check_pedersen<C: CurveGroup>() {
+        let mut rng = ark_std::test_rng();
+        let params = <PedersenCommitmentScheme<C> as CommitmentScheme>::setup(&mut rng, 4).unwrap();
+        let m: Vec<C::ScalarField> = (0..4).map(|_| C::ScalarField::rand(&mut rng)).collect();
+        let mut m_prime = m.clone();
+        m_prime[3] += C::ScalarField::from(1u64);
+        let (r, r_prime) = (C::ScalarField::rand(&mut rng), C::ScalarField::rand(&mut rng));
+        check_commitment_scheme::<PedersenCommitmentScheme<C>>(&params, &m, &m_prime, &r, &r_prime);
+
+        // messages of another length than the generators are errors
+        assert!(<PedersenCommitmentScheme<C> as CommitmentScheme>::commit(&params, &m[..3], &r).is_err());
+    }
+
+    #[test]
+    fn test_pedersen_commitment_scheme() {
+        check_pedersen::<ark_secp256k1::Projective>();
+        check_pedersen::<ark_bls12_381::G1Projective>();
+    }
+}
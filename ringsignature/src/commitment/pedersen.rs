@@ -1,10 +1,17 @@
-use ark_ec::CurveGroup;
-use ark_std::{end_timer, marker::PhantomData, rand::Rng, start_timer, UniformRand};
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ff::{BigInteger, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use ark_std::{end_timer, marker::PhantomData, rand::Rng, start_timer, One, UniformRand, Zero};
 
 use std::fmt::Debug;
 use toolbox::errors::CommitmentErrors;
-use toolbox::vec::convert;
-use crate::commitment::{PedersenOpening, PedersenParams};
+use merlin::Transcript;
+use toolbox::hash::hash_to_curve;
+use toolbox::vec::{rand_affine_vec, scalar_product, vec_add, vec_neg};
+use crate::commitment::{BitCommitment, CommitmentScheme, PedersenCommitterKey, PedersenOpening, PedersenParams};
+
+// the (base, scalar) pairs of a multi-scalar multiplication
+pub type MsmTerms<C> = Vec<(<C as CurveGroup>::Affine, <C as Group>::ScalarField)>;
 
 /// Pedersen (Vector) Commitment with form
 /// com(vec_m, r) = vec_g^vec_m + h^r (perfectly hiding)
@@ -24,8 +31,8 @@ impl<C: CurveGroup> PedersenCommitmentScheme<C> {
         // h_scalar should be dropped
         let h_scalar = C::ScalarField::rand(rng);
         let g = C::generator();
-        // generator vector with unknown DL relation
-        let generators = vec![C::Affine::rand(rng); supported_size];
+        // generator vector with unknown DL relation, each generator sampled independently
+        let generators = rand_affine_vec::<C, R>(rng, supported_size);
         let pp = PedersenParams {
             generator: g.mul(h_scalar),
             vec_gen: generators,
@@ -33,42 +40,288 @@ impl<C: CurveGroup> PedersenCommitmentScheme<C> {
         Ok(pp)
     }
 
+    /// Setup-deterministic algorithm generates nothing-up-my-sleeve public parameters, where
+    /// - h: a generator hashed to the curve from label and "generator"
+    /// - vec_g: the generators hashed to the curve from label and "vec_gen" || i
+    ///
+    /// so that nobody knows a discrete log relation between any two of them,
+    /// and a verifier re-derives the parameters from the short label instead of receiving them
+    pub fn setup_deterministic(
+        label: &[u8],
+        supported_size: usize,
+    ) -> Result<PedersenParams<C>, CommitmentErrors> {
+        let start = start_timer!(|| "hashing pedersen generators to the curve...");
+        let generator = hash_generator::<C>(label, b"generator", 0).into_group();
+        let vec_gen = (0..supported_size)
+            .map(|i| hash_generator::<C>(label, b"vec_gen", i as u64))
+            .collect();
+        end_timer!(start);
+        Ok(PedersenParams {
+            generator,
+            vec_gen,
+        })
+    }
+
     /// Commit algorithm takes inputs as
     /// - PublicParams
     /// - m: message vector
     /// - r: random element for hiding
+    ///
     /// then outputs
     /// - cm: a pedersen vector commitment
+    // info only labels the timer
+    #[cfg_attr(not(feature = "print-trace"), allow(unused_variables))]
     pub fn commit(
         params: &PedersenParams<C>,
-        m: &Vec<C::ScalarField>,
+        m: &[C::ScalarField],
         r: &C::ScalarField,
         info: &str,
     ) -> Result<C, CommitmentErrors> {
-        let log_info = "generating pedersen commitment ".to_owned() + info;
-        let start = start_timer!(|| log_info);
-        let params = params;
         if m.len() != params.vec_gen.len() {
             return Err(CommitmentErrors::InvalidParameters(
                 "message length should equal to the generator length".to_string(),
             ));
         }
+        let start = start_timer!(|| "generating pedersen commitment ".to_owned() + info);
+        toolbox::metrics::count_msm();
         let msm = C::msm(&params.vec_gen, m).unwrap();
         let cm: C = params.generator.mul(r) + msm;
         end_timer!(start);
         Ok(cm)
     }
 
+    /// Commit algorithm with the fixed-base tables of key, which outputs the same commitment as commit
+    /// under the parameters of the key without doublings, for committing many messages under the same parameters
+    pub fn commit_with_key(
+        key: &PedersenCommitterKey<C>,
+        m: &[C::ScalarField],
+        r: &C::ScalarField,
+    ) -> Result<C, CommitmentErrors> {
+        if m.len() != key.supported_size() {
+            return Err(CommitmentErrors::InvalidParameters(
+                "message length should equal to the generator length".to_string(),
+            ));
+        }
+        let start = start_timer!(|| "generating pedersen commitment with precomputed tables");
+        toolbox::metrics::count_msm();
+        // buckets[k] sums the shifted generators whose window digit is k
+        let mut buckets = vec![C::zero(); 1 << key.window_bits];
+        let tables = key.vec_gen_table.chunks(key.num_windows).chain([key.generator_table.as_slice()]);
+        for (s, table) in m.iter().chain([r]).zip(tables) {
+            let s = s.into_bigint();
+            for (j, shifted) in table.iter().enumerate() {
+                let digit = window_digit(&s, j * key.window_bits, key.window_bits);
+                if digit != 0 {
+                    buckets[digit] += shifted;
+                }
+            }
+        }
+        // sum_k k * buckets[k] as the sum of the running sums from the top bucket down
+        let mut running = C::zero();
+        let mut cm = C::zero();
+        for bucket in buckets[1..].iter().rev() {
+            running += bucket;
+            cm += running;
+        }
+        end_timer!(start);
+        Ok(cm)
+    }
+
+    /// Same as commit, in affine form
+    pub fn commit_affine(
+        params: &PedersenParams<C>,
+        m: &[C::ScalarField],
+        r: &C::ScalarField,
+    ) -> Result<C::Affine, CommitmentErrors> {
+        Ok(Self::commit(params, m, r, "in affine form")?.into_affine())
+    }
+
+    /// Batch-commit algorithm commits to each message msgs[i] with the random element rands[i]
+    /// under the same parameters, converting the scalars into one reused buffer
+    /// and normalizing all the commitments with a single inversion
+    pub fn batch_commit(
+        params: &PedersenParams<C>,
+        msgs: &[Vec<C::ScalarField>],
+        rands: &[C::ScalarField],
+    ) -> Result<Vec<C::Affine>, CommitmentErrors> {
+        if msgs.len() != rands.len() {
+            return Err(CommitmentErrors::InvalidParameters(
+                "the number of messages should equal the number of random elements".to_string(),
+            ));
+        }
+        if msgs.iter().any(|m| m.len() != params.vec_gen.len()) {
+            return Err(CommitmentErrors::InvalidParameters(
+                "message length should equal to the generator length".to_string(),
+            ));
+        }
+        let start = start_timer!(|| format!("generating {} pedersen commitments", msgs.len()));
+        let mut bigints = Vec::with_capacity(params.vec_gen.len());
+        let coms: Vec<C> = msgs.iter().zip(rands)
+            .map(|(m, r)| {
+                bigints.clear();
+                bigints.extend(m.iter().map(|m_i| m_i.into_bigint()));
+                toolbox::metrics::count_msm();
+                C::msm_bigint(&params.vec_gen, &bigints) + params.generator.mul(r)
+            })
+            .collect();
+        end_timer!(start);
+        Ok(C::normalize_batch(&coms))
+    }
+
+    /// Commit-multi algorithm outputs the product of the commitments to messages[i] with randoms[i]
+    /// under param_sets[i], flattened into a single msm over all the generators
+    pub fn commit_multi(
+        param_sets: &[&PedersenParams<C>],
+        messages: &[Vec<C::ScalarField>],
+        randoms: &[C::ScalarField],
+    ) -> Result<C, CommitmentErrors> {
+        let (bases, scalars): (Vec<C::Affine>, Vec<C::ScalarField>) =
+            Self::commit_multi_terms(param_sets, messages, randoms)?.into_iter().unzip();
+        let start = start_timer!(|| format!("generating the product of {} pedersen commitments", param_sets.len()));
+        toolbox::metrics::count_msm();
+        let cm = C::msm_unchecked(&bases, &scalars);
+        end_timer!(start);
+        Ok(cm)
+    }
+
+    /// Outputs the generators and scalars of commit_multi, so that a caller merges them into a larger msm;
+    /// the generators of zero random elements are skipped
+    pub fn commit_multi_terms(
+        param_sets: &[&PedersenParams<C>],
+        messages: &[Vec<C::ScalarField>],
+        randoms: &[C::ScalarField],
+    ) -> Result<MsmTerms<C>, CommitmentErrors> {
+        if messages.len() != param_sets.len() || randoms.len() != param_sets.len() {
+            return Err(CommitmentErrors::InvalidParameters(
+                "the numbers of parameter sets, messages and random elements should be equal".to_string(),
+            ));
+        }
+        if param_sets.iter().zip(messages).any(|(params, m)| m.len() != params.vec_gen.len()) {
+            return Err(CommitmentErrors::InvalidParameters(
+                "message length should equal to the generator length".to_string(),
+            ));
+        }
+        let (generators, randoms): (Vec<C>, Vec<C::ScalarField>) = param_sets.iter()
+            .zip(randoms)
+            .filter(|(_, r)| !r.is_zero())
+            .map(|(params, r)| (params.generator, *r))
+            .unzip();
+        let mut terms: Vec<(C::Affine, C::ScalarField)> = C::normalize_batch(&generators).into_iter().zip(randoms).collect();
+        for (params, m) in param_sets.iter().zip(messages) {
+            terms.extend(params.vec_gen.iter().copied().zip(m.iter().copied()));
+        }
+        Ok(terms)
+    }
+
+    /// Commit-chunked algorithm commits to a message longer than vec_g, it splits m into chunks of
+    /// vec_g.len() entries, the last one padded with zeros, and outputs one commitment per chunk,
+    /// where chunk i is blinded by the i-th entry of chunk_blindings(r, number of chunks),
+    /// so that the product of the chunk commitments is com(sum of the chunks, r)
+    pub fn commit_chunked(
+        params: &PedersenParams<C>,
+        m: &[C::ScalarField],
+        r: &C::ScalarField,
+    ) -> Result<Vec<C>, CommitmentErrors> {
+        let size = params.vec_gen.len();
+        if size == 0 || m.is_empty() {
+            return Err(CommitmentErrors::InvalidParameters(
+                "message and generators should be non-empty".to_string(),
+            ));
+        }
+        let chunks: Vec<&[C::ScalarField]> = m.chunks(size).collect();
+        let blindings = Self::chunk_blindings(r, chunks.len())?;
+        chunks.iter().zip(&blindings)
+            .map(|(chunk, r_i)| {
+                let mut chunk = chunk.to_vec();
+                chunk.resize(size, C::ScalarField::zero());
+                Self::commit(params, &chunk, r_i, "on chunk")
+            })
+            .collect()
+    }
+
+    /// Outputs the blindings r_0, ..., r_{k-1} of k chunks derived from r, where r_i = hash(r, k, i)
+    /// for i < k - 1 and r_{k-1} = r - (r_0 + ... + r_{k-2}), i.e., the blindings are pseudorandom
+    /// to anyone who does not know r and sum up to r
+    pub fn chunk_blindings(
+        r: &C::ScalarField,
+        num_chunks: usize,
+    ) -> Result<Vec<C::ScalarField>, CommitmentErrors> {
+        let mut r_bytes = Vec::new();
+        r.serialize_compressed(&mut r_bytes)?;
+        let mut blindings: Vec<C::ScalarField> = (0..num_chunks.saturating_sub(1))
+            .map(|i| {
+                let mut transcript = Transcript::new(b"Pedersen chunk blinding");
+                transcript.append_message(b"blinding", &r_bytes);
+                transcript.append_u64(b"number of chunks", num_chunks as u64);
+                transcript.append_u64(b"chunk", i as u64);
+                let mut bytes = [0u8; 64];
+                transcript.challenge_bytes(b"chunk blinding", &mut bytes);
+                C::ScalarField::from_le_bytes_mod_order(&bytes)
+            })
+            .collect();
+        if num_chunks > 0 {
+            let sum = blindings.iter().fold(C::ScalarField::zero(), |acc, r_i| acc + r_i);
+            blindings.push(*r - sum);
+        }
+        Ok(blindings)
+    }
+
+    /// Commit-to-constant algorithm outputs com(c*1^n, r) = (prod vec_g)^c * h^r,
+    /// the same commitment as commit on the all-c vector without its msm
+    pub fn commit_constant(
+        params: &PedersenParams<C>,
+        c: &C::ScalarField,
+        r: &C::ScalarField,
+    ) -> C {
+        let cm = params.generator.mul(r);
+        if c.is_zero() {
+            return cm;
+        }
+        let sum = params.vec_gen.iter().fold(C::zero(), |acc, g_i| acc + g_i);
+        if c.is_one() { cm + sum } else { cm + sum.mul(c) }
+    }
+
+    /// Commit-to-bits algorithm takes inputs as
+    /// - param_g: PublicParams with vec_g and u
+    /// - param_h: PublicParams with vec_h
+    /// - bits: a bit vector b_0
+    ///
+    /// then outputs A = vec_g^{b_0} * vec_h^{b_1} * u^{random} with the complement b_1 = 1^n - b_0
+    /// and the randomness, or an error if an entry of bits is neither 0 nor 1
+    pub fn commit_to_bits<R: Rng>(
+        rng: &mut R,
+        param_g: &PedersenParams<C>,
+        param_h: &PedersenParams<C>,
+        bits: &[C::ScalarField],
+    ) -> Result<BitCommitment<C>, CommitmentErrors> {
+        if bits.iter().any(|b_i| !b_i.is_zero() && !b_i.is_one()) {
+            return Err(CommitmentErrors::InvalidParameters(
+                "the committed vector should be binary".to_string(),
+            ));
+        }
+        let complement: Vec<C::ScalarField> = bits.iter()
+            .map(|&b_i| C::ScalarField::one() - b_i)
+            .collect();
+        let random = C::ScalarField::rand(rng);
+        let com = Self::commit(param_g, bits, &random, "on bits")?
+            + Self::commit(param_h, &complement, &C::ScalarField::zero(), "on complement bits")?;
+        Ok(BitCommitment {
+            com,
+            complement,
+            random,
+        })
+    }
+
     /// Open algorithm outputs the following as the opening of commitment
     /// - m: message vector
     /// - r: random element for hiding
     pub fn open(
-        m: &Vec<C::ScalarField>,
+        m: &[C::ScalarField],
         r: &C::ScalarField,
     ) -> Result<PedersenOpening<C>, CommitmentErrors> {
         Ok(PedersenOpening {
-            message: m.clone(),
-            random: r.clone(),
+            message: m.to_vec(),
+            random: *r,
         })
     }
 
@@ -76,6 +329,7 @@ impl<C: CurveGroup> PedersenCommitmentScheme<C> {
     /// - PublicParams
     /// - cm: commitment
     /// - open: opening includes m and r
+    ///
     /// then outputs
     /// - cm: a pedersen vector commitment
     pub fn verify(
@@ -84,37 +338,435 @@ impl<C: CurveGroup> PedersenCommitmentScheme<C> {
         open: &PedersenOpening<C>,
     ) -> Result<bool, CommitmentErrors> {
         let start = start_timer!(|| "checking pedersen commitment...");
-        let params = params;
+        toolbox::metrics::count_msm();
         let msm = C::msm(&params.vec_gen, &open.message).unwrap();
         let cm_prime = params.generator.mul(open.random) + msm;
         end_timer!(start);
         Ok(&cm_prime == cm)
     }
+
+    /// Add algorithm outputs the product of the commitments with the sum of their openings,
+    /// i.e., a commitment to the sum of the messages with the sum of the randoms;
+    /// the messages should have the same length
+    pub fn add_commitments(
+        coms: &[(C, PedersenOpening<C>)],
+    ) -> Result<(C, PedersenOpening<C>), CommitmentErrors> {
+        let Some(((first, first_open), rest)) = coms.split_first() else {
+            return Err(CommitmentErrors::InvalidParameters(
+                "no commitment to add".to_string(),
+            ));
+        };
+        rest.iter().try_fold((*first, first_open.clone()), |(cm, open), (cm_i, open_i)| {
+            Ok((cm + cm_i, add_openings(&open, open_i, false)?))
+        })
+    }
+
+    /// Scale algorithm outputs com^k with the opening (k*m, k*r)
+    pub fn scale(
+        cm: &C,
+        open: &PedersenOpening<C>,
+        k: &C::ScalarField,
+    ) -> (C, PedersenOpening<C>) {
+        let scaled = PedersenOpening {
+            message: scalar_product(&open.message, k),
+            random: open.random * k,
+        };
+        (cm.mul(k), scaled)
+    }
+
+    /// Sub algorithm outputs lhs / rhs with the opening (m_lhs - m_rhs, r_lhs - r_rhs);
+    /// the messages should have the same length
+    pub fn sub(
+        lhs: &(C, PedersenOpening<C>),
+        rhs: &(C, PedersenOpening<C>),
+    ) -> Result<(C, PedersenOpening<C>), CommitmentErrors> {
+        Ok((lhs.0 - rhs.0, add_openings(&lhs.1, &rhs.1, true)?))
+    }
+}
+
+// the opening of the product (or the quotient, if negate) of two commitments
+fn add_openings<C: CurveGroup>(
+    lhs: &PedersenOpening<C>,
+    rhs: &PedersenOpening<C>,
+    negate: bool,
+) -> Result<PedersenOpening<C>, CommitmentErrors> {
+    if lhs.message.len() != rhs.message.len() {
+        return Err(CommitmentErrors::InvalidParameters(
+            "the committed messages have different lengths".to_string(),
+        ));
+    }
+    let (message, random) = if negate {
+        (vec_add(&lhs.message, &vec_neg(&rhs.message)), lhs.random - rhs.random)
+    } else {
+        (vec_add(&lhs.message, &rhs.message), lhs.random + rhs.random)
+    };
+    Ok(PedersenOpening { message, random })
+}
+
+// the inherent methods take precedence over the trait ones, so callers naming the scheme keep the labelled commit
+impl<C: CurveGroup> CommitmentScheme for PedersenCommitmentScheme<C> {
+    type Params = PedersenParams<C>;
+    type Message = [C::ScalarField];
+    type Random = C::ScalarField;
+    type Commitment = C;
+    type Opening = PedersenOpening<C>;
+
+    fn setup<R: Rng>(rng: &mut R, supported_size: usize) -> Result<Self::Params, CommitmentErrors> {
+        PedersenCommitmentScheme::setup(rng, supported_size)
+    }
+
+    fn commit(params: &Self::Params, m: &Self::Message, r: &Self::Random) -> Result<Self::Commitment, CommitmentErrors> {
+        PedersenCommitmentScheme::commit(params, m, r, "via CommitmentScheme")
+    }
+
+    fn open(m: &Self::Message, r: &Self::Random) -> Result<Self::Opening, CommitmentErrors> {
+        PedersenCommitmentScheme::open(m, r)
+    }
+
+    fn verify(params: &Self::Params, cm: &Self::Commitment, open: &Self::Opening) -> Result<bool, CommitmentErrors> {
+        PedersenCommitmentScheme::verify(params, cm, open)
+    }
+}
+
+// the width bits of s from the bit offset, where width is at most 16
+fn window_digit<B: BigInteger>(s: &B, offset: usize, width: usize) -> usize {
+    let limbs = s.as_ref();
+    let (limb, shift) = (offset / 64, offset % 64);
+    if limb >= limbs.len() {
+        return 0;
+    }
+    let mut bits = limbs[limb] >> shift;
+    if shift + width > 64 && limb + 1 < limbs.len() {
+        bits |= limbs[limb + 1] << (64 - shift);
+    }
+    (bits & ((1 << width) - 1)) as usize
+}
+
+// hashes the generator with the given label and index to the curve
+fn hash_generator<C: CurveGroup>(domain: &[u8], label: &[u8], index: u64) -> C::Affine {
+    let input = [label, &index.to_le_bytes()].concat();
+    hash_to_curve::<C>(domain, &input)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use toolbox::vec::convert;
+    use toolbox::vec::{convert, vec_add};
+    use ark_ff::PrimeField;
+    use proptest::array::{uniform4, uniform32};
+    use proptest::prelude::*;
     use ark_bls12_381::{Fr as G1Fr, G1Projective};
-    use ark_secp256k1::{Fr, Projective};
+    use ark_secp256k1::{Affine, Fr, Projective};
     use test::Bencher;
 
+    type Pedersen = PedersenCommitmentScheme<Projective>;
+
+    fn params() -> PedersenParams<Projective> {
+        let mut rng = ark_std::test_rng();
+        Pedersen::setup(&mut rng, 4).unwrap()
+    }
+
+    fn random(bytes: [u8; 32]) -> Fr {
+        Fr::from_le_bytes_mod_order(&bytes)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_pedersen_binding(
+            m in uniform4(any::<u64>()),
+            i in 0usize..4,
+            delta in 1u64..,
+            r in uniform32(any::<u8>()),
+        ) {
+            // m_prime differs from m in the i-th entry
+            let params = params();
+            let field_m: Vec<Fr> = convert(&m);
+            let mut field_m_prime = field_m.clone();
+            field_m_prime[i] += Fr::from(delta);
+            let r = random(r);
+            let cm = Pedersen::commit(&params, &field_m, &r, "cm").unwrap();
+            let cm_prime = Pedersen::commit(&params, &field_m_prime, &r, "cm").unwrap();
+            prop_assert_ne!(cm, cm_prime);
+        }
+
+        #[test]
+        fn prop_pedersen_hiding(
+            m in uniform4(any::<u64>()),
+            r1 in uniform32(any::<u8>()),
+            r2 in uniform32(any::<u8>()),
+        ) {
+            let (r1, r2) = (random(r1), random(r2));
+            prop_assume!(r1 != r2);
+            let params = params();
+            let field_m: Vec<Fr> = convert(&m);
+            let cm1 = Pedersen::commit(&params, &field_m, &r1, "cm").unwrap();
+            let cm2 = Pedersen::commit(&params, &field_m, &r2, "cm").unwrap();
+            prop_assert_ne!(cm1, cm2);
+        }
+
+        #[test]
+        fn prop_pedersen_commit_verify(
+            m in uniform4(any::<u64>()),
+            r in uniform32(any::<u8>()),
+        ) {
+            let params = params();
+            let field_m: Vec<Fr> = convert(&m);
+            let r = random(r);
+            let cm = Pedersen::commit(&params, &field_m, &r, "cm").unwrap();
+            let opening = Pedersen::open(&field_m, &r).unwrap();
+            prop_assert!(Pedersen::verify(&params, &cm, &opening).unwrap());
+        }
+
+        #[test]
+        fn prop_pedersen_homomorphic(
+            m1 in uniform4(any::<u64>()),
+            m2 in uniform4(any::<u64>()),
+            r1 in uniform32(any::<u8>()),
+            r2 in uniform32(any::<u8>()),
+        ) {
+            let params = params();
+            let (field_m1, field_m2): (Vec<Fr>, Vec<Fr>) = (convert(&m1), convert(&m2));
+            let (r1, r2) = (random(r1), random(r2));
+            let field_m_sum: Vec<Fr> = vec_add(&field_m1, &field_m2);
+            let cm_sum = Pedersen::commit(&params, &field_m_sum, &(r1 + r2), "cm").unwrap();
+            let cm1 = Pedersen::commit(&params, &field_m1, &r1, "cm").unwrap();
+            let cm2 = Pedersen::commit(&params, &field_m2, &r2, "cm").unwrap();
+            prop_assert_eq!(cm_sum, cm1 + cm2);
+        }
+    }
+
     #[test]
-    fn test_pedersen() {
+    fn test_commit_constant() {
         let mut rng = ark_std::test_rng();
-        let supported_size = 10;
-        let params =
-            PedersenCommitmentScheme::<Projective>::setup(&mut rng, supported_size).unwrap();
-        let m: [u64; 10] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
-        let field_m: Vec<Fr> = convert(&m);
+        let params = params();
+        let r = Fr::rand(&mut rng);
+        for c in [Fr::zero(), Fr::one(), Fr::rand(&mut rng)] {
+            let cm = Pedersen::commit(&params, &[c; 4], &r, "cm").unwrap();
+            assert_eq!(Pedersen::commit_constant(&params, &c, &r), cm);
+        }
+    }
+
+    #[test]
+    fn test_commit_chunked() {
+        // a message of 25 entries is committed in chunks of 10, 10 and 5 padded with zeros
+        let mut rng = ark_std::test_rng();
+        let params = Pedersen::setup(&mut rng, 10).unwrap();
+        let m: Vec<Fr> = (0..25).map(|_| Fr::rand(&mut rng)).collect();
+        let r = Fr::rand(&mut rng);
+        let cms = Pedersen::commit_chunked(&params, &m, &r).unwrap();
+        assert_eq!(cms.len(), 3);
+
+        let blindings = Pedersen::chunk_blindings(&r, 3).unwrap();
+        assert_eq!(blindings.iter().sum::<Fr>(), r);
+        for (i, (cm, r_i)) in cms.iter().zip(&blindings).enumerate() {
+            let mut chunk = m[10*i..(10*i + 10).min(25)].to_vec();
+            chunk.resize(10, Fr::zero());
+            assert!(Pedersen::verify(&params, cm, &Pedersen::open(&chunk, r_i).unwrap()).unwrap());
+            // another chunk does not open it
+            let mut chunk_prime = chunk.clone();
+            chunk_prime[0] += Fr::one();
+            assert!(!Pedersen::verify(&params, cm, &Pedersen::open(&chunk_prime, r_i).unwrap()).unwrap());
+        }
+        // the chunk blindings differ, and their commitments multiply to a commitment with blinding r
+        assert!(blindings[0] != blindings[1] && blindings[1] != blindings[2]);
+        let sum: Vec<Fr> = (0..10).map(|j| m.iter().skip(j).step_by(10).sum()).collect();
+        assert_eq!(cms.iter().sum::<Projective>(), Pedersen::commit(&params, &sum, &r, "cm").unwrap());
+
+        // a message within the generators is a single plain commitment
+        assert_eq!(Pedersen::commit_chunked(&params, &m[..10], &r).unwrap(), vec![Pedersen::commit(&params, &m[..10], &r, "cm").unwrap()]);
+        assert!(Pedersen::commit_chunked(&params, &[], &r).is_err());
+    }
+
+    #[test]
+    fn test_homomorphic_arithmetic() {
+        let mut rng = ark_std::test_rng();
+        let params = params();
+        let commit = |m: Vec<Fr>, rng: &mut _| {
+            let r = Fr::rand(rng);
+            (Pedersen::commit(&params, &m, &r, "cm").unwrap(), Pedersen::open(&m, &r).unwrap())
+        };
+        let coms: Vec<_> = (0..3).map(|_| commit((0..4).map(|_| Fr::rand(&mut rng)).collect(), &mut rng)).collect();
+
+        // the combined openings verify against the combined commitments
+        let (cm_sum, open_sum) = Pedersen::add_commitments(&coms).unwrap();
+        assert!(Pedersen::verify(&params, &cm_sum, &open_sum).unwrap());
+        assert_eq!(open_sum.message, vec_add(&vec_add(&coms[0].1.message, &coms[1].1.message), &coms[2].1.message));
+        let k = Fr::rand(&mut rng);
+        let (cm_scaled, open_scaled) = Pedersen::scale(&coms[0].0, &coms[0].1, &k);
+        assert!(Pedersen::verify(&params, &cm_scaled, &open_scaled).unwrap());
+        let (cm_diff, open_diff) = Pedersen::sub(&coms[0], &coms[1]).unwrap();
+        assert!(Pedersen::verify(&params, &cm_diff, &open_diff).unwrap());
+        assert_eq!(Pedersen::add_commitments(&[(cm_diff, open_diff), coms[1].clone()]).unwrap(), coms[0]);
+
+        // the amounts of two inputs and three outputs balance, so sum(inputs) - sum(outputs) commits to zero
+        let inputs = [commit(convert(&[30, 0, 0, 0]), &mut rng), commit(convert(&[12, 0, 0, 0]), &mut rng)];
+        let outputs = [
+            commit(convert(&[20, 0, 0, 0]), &mut rng),
+            commit(convert(&[15, 0, 0, 0]), &mut rng),
+            commit(convert(&[7, 0, 0, 0]), &mut rng),
+        ];
+        let balance = Pedersen::sub(
+            &Pedersen::add_commitments(&inputs).unwrap(),
+            &Pedersen::add_commitments(&outputs).unwrap(),
+        ).unwrap();
+        assert_eq!(balance.1.message, vec![Fr::zero(); 4]);
+        assert!(Pedersen::verify(&params, &balance.0, &balance.1).unwrap());
+        // only the blinding generator remains
+        assert_eq!(balance.0, params.generator * balance.1.random);
+
+        // messages of different lengths and empty sums are rejected
+        let short = (coms[0].0, Pedersen::open(&convert(&[1, 2, 3]), &Fr::rand(&mut rng)).unwrap());
+        assert!(Pedersen::add_commitments(&[coms[0].clone(), short.clone()]).is_err());
+        assert!(Pedersen::sub(&coms[0], &short).is_err());
+        assert!(Pedersen::add_commitments(&[]).is_err());
+    }
+
+    #[test]
+    fn test_batch_commit() {
+        let mut rng = ark_std::test_rng();
+        let params = params();
+        let msgs: Vec<Vec<Fr>> = (0..5).map(|_| (0..4).map(|_| Fr::rand(&mut rng)).collect()).collect();
+        let rands: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+
+        // the batch agrees with commit and commit_affine on every message
+        let coms = Pedersen::batch_commit(&params, &msgs, &rands).unwrap();
+        assert_eq!(coms.len(), 5);
+        for ((cm, m), r) in coms.iter().zip(&msgs).zip(&rands) {
+            assert_eq!(*cm, Pedersen::commit(&params, m, r, "cm").unwrap().into_affine());
+            assert_eq!(*cm, Pedersen::commit_affine(&params, m, r).unwrap());
+        }
+        assert!(Pedersen::batch_commit(&params, &[], &[]).unwrap().is_empty());
+
+        // mismatched numbers of randoms and messages of the wrong length are rejected
+        assert!(Pedersen::batch_commit(&params, &msgs, &rands[..4]).is_err());
+        let mut msgs_prime = msgs.clone();
+        msgs_prime[2].pop();
+        assert!(Pedersen::batch_commit(&params, &msgs_prime, &rands).is_err());
+        assert!(Pedersen::commit_affine(&params, &msgs_prime[2], &rands[2]).is_err());
+    }
+
+    #[test]
+    fn test_commit_multi() {
+        let mut rng = ark_std::test_rng();
+        let param_sets: Vec<PedersenParams<Projective>> = [4, 2, 4].iter().map(|n| Pedersen::setup(&mut rng, *n).unwrap()).collect();
+        let param_refs: Vec<&PedersenParams<Projective>> = param_sets.iter().collect();
+        let messages: Vec<Vec<Fr>> = param_sets.iter().map(|p| (0..p.supported_size()).map(|_| Fr::rand(&mut rng)).collect()).collect();
+        let randoms = vec![Fr::rand(&mut rng), Fr::zero(), Fr::rand(&mut rng)];
+
+        // the single msm equals the sum of the separate commitments
+        let sum: Projective = param_sets.iter().zip(&messages).zip(&randoms)
+            .map(|((params, m), r)| Pedersen::commit(params, m, r, "cm").unwrap())
+            .sum();
+        assert_eq!(Pedersen::commit_multi(&param_refs, &messages, &randoms).unwrap(), sum);
+        assert_eq!(Pedersen::commit_multi(&[], &[], &[]).unwrap(), Projective::zero());
+        // the generator of the zero random element is left out of the terms
+        assert_eq!(Pedersen::commit_multi_terms(&param_refs, &messages, &randoms).unwrap().len(), 2 + 10);
+
+        // mismatched numbers of sets, messages and randoms, and messages of the wrong length are rejected
+        assert!(Pedersen::commit_multi(&param_refs, &messages[..2], &randoms).is_err());
+        assert!(Pedersen::commit_multi(&param_refs, &messages, &randoms[..2]).is_err());
+        let mut messages_prime = messages.clone();
+        messages_prime[1].push(Fr::one());
+        assert!(Pedersen::commit_multi(&param_refs, &messages_prime, &randoms).is_err());
+    }
+
+    #[test]
+    fn test_commit_with_key() {
+        let mut rng = ark_std::test_rng();
+        let params = Pedersen::setup(&mut rng, 10).unwrap();
+        let m: Vec<Fr> = (0..10).map(|_| Fr::rand(&mut rng)).collect();
         let r = Fr::rand(&mut rng);
-        let cm = PedersenCommitmentScheme::<Projective>::commit(&params, &field_m, &r, "cm").unwrap();
-        let opening = PedersenCommitmentScheme::<Projective>::open(&field_m, &r).unwrap();
+        let cm = Pedersen::commit(&params, &m, &r, "cm").unwrap();
+        // every window size gives the commitment of the non-precomputed path, including windows straddling limbs
+        for window_bits in [1, 3, 4, 7, 8, 13, 16] {
+            let key = PedersenCommitterKey::from_params(&params, window_bits).unwrap();
+            assert_eq!(key.supported_size(), 10);
+            assert_eq!(Pedersen::commit_with_key(&key, &m, &r).unwrap(), cm);
+        }
+
+        // as well as the edge scalars and the curve with a 255-bit scalar field
+        let key = PedersenCommitterKey::from_params(&params, 8).unwrap();
+        for s in [Fr::zero(), Fr::one(), -Fr::one()] {
+            assert_eq!(Pedersen::commit_with_key(&key, &[s; 10], &s).unwrap(), Pedersen::commit(&params, &[s; 10], &s, "cm").unwrap());
+        }
+        let params = PedersenCommitmentScheme::<G1Projective>::setup(&mut rng, 4).unwrap();
+        let key = PedersenCommitterKey::from_params(&params, 6).unwrap();
+        let m: Vec<G1Fr> = (0..4).map(|_| G1Fr::rand(&mut rng)).collect();
+        let r = G1Fr::rand(&mut rng);
         assert_eq!(
-            PedersenCommitmentScheme::<Projective>::verify(&params, &cm, &opening).unwrap(),
-            true
+            PedersenCommitmentScheme::<G1Projective>::commit_with_key(&key, &m, &r).unwrap(),
+            PedersenCommitmentScheme::<G1Projective>::commit(&params, &m, &r, "cm").unwrap(),
         );
+
+        // windows outside 1..=16 bits and messages of the wrong length are rejected
+        assert!(PedersenCommitterKey::from_params(&params, 0).is_err());
+        assert!(PedersenCommitterKey::from_params(&params, 17).is_err());
+        assert!(PedersenCommitmentScheme::<G1Projective>::commit_with_key(&key, &m[..3], &r).is_err());
+    }
+
+    #[test]
+    fn test_commit_to_bits() {
+        let mut rng = ark_std::test_rng();
+        let (param_g, param_h) = (params(), Pedersen::setup(&mut rng, 4).unwrap());
+        let bits: Vec<Fr> = convert(&[0, 1, 1, 0]);
+        let bit_com = Pedersen::commit_to_bits(&mut rng, &param_g, &param_h, &bits).unwrap();
+        assert_eq!(bit_com.complement, convert::<Fr>(&[1, 0, 0, 1]));
+        let expected = Pedersen::commit(&param_g, &bits, &bit_com.random, "on bits").unwrap()
+            + Pedersen::commit(&param_h, &bit_com.complement, &Fr::from(0u64), "on complement bits").unwrap();
+        assert_eq!(bit_com.com, expected);
+
+        // entries other than 0 and 1, and vectors of the wrong length are rejected
+        for bits in [convert(&[0, 2, 1, 0]), vec![-Fr::from(1u64); 4], convert(&[0, 1, 1])] {
+            assert!(Pedersen::commit_to_bits(&mut rng, &param_g, &param_h, &bits).is_err());
+        }
+    }
+
+    #[test]
+    fn test_setup_distinct_generators() {
+        // every generator is sampled on its own, so that the commitment binds the order of the message
+        let mut rng = ark_std::test_rng();
+        let params = Pedersen::setup(&mut rng, 16).unwrap();
+        for (i, g_i) in params.vec_gen.iter().enumerate() {
+            assert!(params.vec_gen[i+1..].iter().all(|g_j| g_j != g_i));
+        }
+        let r = Fr::rand(&mut rng);
+        let m: Vec<Fr> = convert(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+        let mut m_prime = m.clone();
+        m_prime.swap(0, 1);
+        assert_ne!(Pedersen::commit(&params, &m, &r, "cm").unwrap(), Pedersen::commit(&params, &m_prime, &r, "cm").unwrap());
+        // nor do two messages of the same sum commit to the same point
+        let m_prime: Vec<Fr> = convert(&[136, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_ne!(Pedersen::commit(&params, &m, &r, "cm").unwrap(), Pedersen::commit(&params, &m_prime, &r, "cm").unwrap());
+    }
+
+    #[test]
+    fn test_setup_deterministic() {
+        // the generators are a function of the label only, so independent calls agree
+        let params = Pedersen::setup_deterministic(b"test_setup_deterministic", 16).unwrap();
+        assert_eq!(params, Pedersen::setup_deterministic(b"test_setup_deterministic", 16).unwrap());
+        assert_eq!(params.vec_gen[..4], Pedersen::setup_deterministic(b"test_setup_deterministic", 4).unwrap().vec_gen);
+        // while another label gives unrelated generators
+        let params_2 = Pedersen::setup_deterministic(b"test_setup_deterministic_2", 16).unwrap();
+        assert_ne!(params, params_2);
+        assert_ne!(params.generator, params_2.generator);
+        assert!(params_2.vec_gen.iter().all(|g_i| !params.vec_gen.contains(g_i)));
+
+        // h and every vec_g[i] are distinct, and none is the identity or the standard generator
+        let mut all = params.vec_gen.clone();
+        all.push(params.generator.into_affine());
+        for (i, g_i) in all.iter().enumerate() {
+            assert!(!g_i.is_zero());
+            assert_ne!(*g_i, Affine::generator());
+            assert!(all[i+1..].iter().all(|g_j| g_j != g_i));
+        }
+
+        // on curves with a cofactor the generators land in the prime order subgroup
+        let params = PedersenCommitmentScheme::<G1Projective>::setup_deterministic(b"test_setup_deterministic", 4).unwrap();
+        assert!(params.vec_gen.iter().all(|g_i| g_i.is_on_curve() && g_i.is_in_correct_subgroup_assuming_on_curve()));
+        let m: Vec<G1Fr> = convert(&[1, 2, 3, 4]);
+        let cm = PedersenCommitmentScheme::<G1Projective>::commit(&params, &m, &G1Fr::from(5u64), "cm").unwrap();
+        let opening = PedersenCommitmentScheme::<G1Projective>::open(&m, &G1Fr::from(5u64)).unwrap();
+        assert!(PedersenCommitmentScheme::<G1Projective>::verify(&params, &cm, &opening).unwrap());
     }
 
     #[bench]
@@ -129,4 +781,45 @@ mod tests {
 
         b.iter(|| PedersenCommitmentScheme::<G1Projective>::commit(&params, &m, &r, "cm").unwrap());
     }
+
+    // four commitments of length 256, computed separately by bench_commit_separate and with a single msm by bench_commit_multi
+    fn commit_multi_inputs() -> (Vec<PedersenParams<G1Projective>>, Vec<Vec<G1Fr>>, Vec<G1Fr>) {
+        let mut rng = ark_std::test_rng();
+        let param_sets: Vec<_> = (0..4).map(|_| PedersenCommitmentScheme::<G1Projective>::setup(&mut rng, 256).unwrap()).collect();
+        let messages = (0..4).map(|_| (0..256).map(|_| G1Fr::rand(&mut rng)).collect()).collect();
+        let randoms = (0..4).map(|_| G1Fr::rand(&mut rng)).collect();
+        (param_sets, messages, randoms)
+    }
+
+    #[bench]
+    fn bench_commit_separate(b: &mut Bencher) {
+        let (param_sets, messages, randoms) = commit_multi_inputs();
+        b.iter(|| {
+            param_sets.iter().zip(&messages).zip(&randoms)
+                .map(|((params, m), r)| PedersenCommitmentScheme::<G1Projective>::commit(params, m, r, "cm").unwrap())
+                .sum::<G1Projective>()
+        });
+    }
+
+    #[bench]
+    fn bench_commit_multi(b: &mut Bencher) {
+        let (param_sets, messages, randoms) = commit_multi_inputs();
+        let param_refs: Vec<_> = param_sets.iter().collect();
+        b.iter(|| PedersenCommitmentScheme::<G1Projective>::commit_multi(&param_refs, &messages, &randoms).unwrap());
+    }
+
+    #[bench]
+    fn bench_group_with_key(b: &mut Bencher) {
+        // the tables are built once, outside of the timed commitments
+        let mut rng = ark_std::test_rng();
+        let supported_size = 4096;
+        let params =
+            PedersenCommitmentScheme::<G1Projective>::setup(&mut rng, supported_size).unwrap();
+        let key = PedersenCommitterKey::from_params(&params, 12).unwrap();
+
+        let m: Vec<G1Fr> = (0..supported_size).map(|_| G1Fr::rand(&mut rng)).collect();
+        let r = G1Fr::rand(&mut rng);
+
+        b.iter(|| PedersenCommitmentScheme::<G1Projective>::commit_with_key(&key, &m, &r).unwrap());
+    }
 }
@@ -0,0 +1,202 @@
+//! C bindings of the modified compressed ring signature over secp256k1, declared in include/ringct.h.
+//! The header is checked in; after changing this file, regenerate it with
+//! `cbindgen --config cbindgen.toml --output include/ringct.h` from the crate directory.
+//!
+//! Memory ownership: ringct_setup and ringct_prove return heap objects owned by the caller, which must
+//! release them with ringct_free_params and ringct_free_proof respectively, exactly once; the byte buffers
+//! passed in stay owned by the caller and are only read during the call.
+//! Secret keys are 32-byte compressed secp256k1 scalars, i.e., little-endian and below the group order.
+use std::ffi::{c_int, c_void};
+use std::ptr;
+use ark_secp256k1::{Fr, Projective};
+use ark_serialize::CanonicalDeserialize;
+use crate::public_key;
+use crate::ringsig::protocol_compressed_modification::RingSignatureScheme;
+use crate::ringsig::structs::{CompressedRingSignature, RingSignatureParams};
+use toolbox::errors::SigmaErrors;
+use toolbox::sigma::SigmaProtocol;
+
+// the bindings are fixed to secp256k1, since C has no generics
+type Ring = RingSignatureScheme<Projective>;
+
+/// Sets up a ring of ring_size members including the public key of sk for the message msg,
+/// where ring_size should be a power of two of at most 128 and msg valid UTF-8.
+/// Returns the parameters, to be freed with ringct_free_params, or null on invalid input
+///
+/// # Safety
+/// sk and msg must point to sk_len and msg_len readable bytes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ringct_setup(
+    ring_size: u32,
+    sk: *const u8,
+    sk_len: u32,
+    msg: *const u8,
+    msg_len: u32,
+) -> *mut c_void {
+    let (Some(sk), Some(msg)) = (unsafe { bytes(sk, sk_len) }, unsafe { bytes(msg, msg_len) }) else {
+        return ptr::null_mut();
+    };
+    match setup(ring_size as usize, sk, msg) {
+        Ok(params) => Box::into_raw(Box::new(params)) as *mut c_void,
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Signs on behalf of the ring of params with the secret key wit, whose public key should be a member of the ring.
+/// Returns the proof, to be freed with ringct_free_proof, or null on invalid input
+///
+/// # Safety
+/// params must be null or returned by ringct_setup and not yet freed, wit must point to wit_len readable bytes
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ringct_prove(params: *const c_void, wit: *const u8, wit_len: u32) -> *mut c_void {
+    let (Some(params), Some(sk)) = (
+        unsafe { (params as *const RingSignatureParams<Projective>).as_ref() },
+        unsafe { bytes(wit, wit_len) },
+    ) else {
+        return ptr::null_mut();
+    };
+    match prove(params, sk) {
+        Ok(proof) => Box::into_raw(Box::new(proof)) as *mut c_void,
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Returns 1 if proof is a valid signature for params, 0 if it is not, and -1 if either pointer is null
+///
+/// # Safety
+/// params and proof must be null or returned by ringct_setup and ringct_prove and not yet freed
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ringct_verify(params: *const c_void, proof: *const c_void) -> c_int {
+    let (Some(params), Some(proof)) = (
+        unsafe { (params as *const RingSignatureParams<Projective>).as_ref() },
+        unsafe { (proof as *const CompressedRingSignature<Projective>).as_ref() },
+    ) else {
+        return -1;
+    };
+    Ring::verify(params, proof).unwrap_or(false) as c_int
+}
+
+/// Frees parameters returned by ringct_setup, null is ignored
+///
+/// # Safety
+/// params must be null or returned by ringct_setup and not yet freed
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ringct_free_params(params: *mut c_void) {
+    if !params.is_null() {
+        drop(unsafe { Box::from_raw(params as *mut RingSignatureParams<Projective>) });
+    }
+}
+
+/// Frees a proof returned by ringct_prove, null is ignored
+///
+/// # Safety
+/// proof must be null or returned by ringct_prove and not yet freed
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ringct_free_proof(proof: *mut c_void) {
+    if !proof.is_null() {
+        drop(unsafe { Box::from_raw(proof as *mut CompressedRingSignature<Projective>) });
+    }
+}
+
+// a buffer of the caller as a slice, a null pointer is only accepted for an empty buffer
+unsafe fn bytes<'a>(data: *const u8, len: u32) -> Option<&'a [u8]> {
+    match (data.is_null(), len) {
+        (true, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(unsafe { std::slice::from_raw_parts(data, len as usize) }),
+    }
+}
+
+// a secret key of exactly 32 bytes, since deserialize_compressed ignores the bytes after the scalar
+fn secret_key(sk: &[u8]) -> Result<Fr, SigmaErrors> {
+    if sk.len() != 32 {
+        return Err(SigmaErrors::InvalidParameters(
+            "secret key should be 32 bytes".to_string(),
+        ));
+    }
+    Ok(Fr::deserialize_compressed(sk)?)
+}
+
+fn setup(ring_size: usize, sk: &[u8], msg: &[u8]) -> Result<RingSignatureParams<Projective>, SigmaErrors> {
+    if !ring_size.is_power_of_two() {
+        return Err(SigmaErrors::InvalidParameters(
+            "ring size is not power of two".to_string(),
+        ));
+    }
+    let msg = std::str::from_utf8(msg)
+        .map_err(|_| SigmaErrors::InvalidParameters("message is not valid UTF-8".to_string()))?;
+    let mut wit = vec![secret_key(sk)?];
+    Ring::setup(&mut rand::thread_rng(), &mut wit, msg, ring_size)
+}
+
+// the witness is sk followed by the indicator vector of its public key in the ring, as for setup
+fn prove(params: &RingSignatureParams<Projective>, sk: &[u8]) -> Result<CompressedRingSignature<Projective>, SigmaErrors> {
+    let sk = secret_key(sk)?;
+    let pk = public_key::<Projective>(&sk);
+    if !params.vec_pk.contains(&pk) {
+        return Err(SigmaErrors::InvalidProver(
+            "the public key is not a member of the ring".to_string(),
+        ));
+    }
+    let mut wit = vec![sk];
+    wit.extend(params.vec_pk.iter().map(|pk_i| Fr::from((*pk_i == pk) as u64)));
+    Ring::prove(&mut rand::thread_rng(), params, &wit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "C" {
+        // ffi/test.c, compiled by build.rs
+        fn ringct_c_test() -> c_int;
+    }
+
+    #[test]
+    fn test_c_program() {
+        assert_eq!(unsafe { ringct_c_test() }, 1);
+    }
+
+    #[test]
+    fn test_header_up_to_date() {
+        // build.rs generates the header into OUT_DIR, the checked-in copy must match it
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/ringct.h"));
+        let checked_in = include_str!("../include/ringct.h");
+        assert!(generated == checked_in, "include/ringct.h is stale, regenerate it with cbindgen");
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let sk = [42u8; 32];
+        let other_sk = [7u8; 32];
+        let msg = b"message";
+        unsafe {
+            // invalid ring sizes, secret keys and messages give null
+            assert!(ringct_setup(6, sk.as_ptr(), 32, msg.as_ptr(), 7).is_null());
+            assert!(ringct_setup(256, sk.as_ptr(), 32, msg.as_ptr(), 7).is_null());
+            assert!(ringct_setup(8, sk.as_ptr(), 31, msg.as_ptr(), 7).is_null());
+            assert!(ringct_setup(8, [42u8; 33].as_ptr(), 33, msg.as_ptr(), 7).is_null());
+            assert!(ringct_setup(8, ptr::null(), 32, msg.as_ptr(), 7).is_null());
+            assert!(ringct_setup(8, sk.as_ptr(), 32, [0xffu8].as_ptr(), 1).is_null());
+
+            let params = ringct_setup(8, sk.as_ptr(), 32, ptr::null(), 0);
+            assert!(!params.is_null());
+            // a key outside the ring cannot sign
+            assert!(ringct_prove(params, other_sk.as_ptr(), 32).is_null());
+            assert!(ringct_prove(ptr::null(), sk.as_ptr(), 32).is_null());
+            assert!(ringct_prove(params, [42u8; 33].as_ptr(), 33).is_null());
+            let proof = ringct_prove(params, sk.as_ptr(), 32);
+            assert_eq!(ringct_verify(params, proof), 1);
+            assert_eq!(ringct_verify(ptr::null(), proof), -1);
+
+            // the proof does not verify for the parameters of another setup
+            let other_params = ringct_setup(8, sk.as_ptr(), 32, ptr::null(), 0);
+            assert_eq!(ringct_verify(other_params, proof), 0);
+
+            ringct_free_proof(proof);
+            ringct_free_params(params);
+            ringct_free_params(other_params);
+            ringct_free_params(ptr::null_mut());
+        }
+    }
+}
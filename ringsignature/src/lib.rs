@@ -3,8 +3,226 @@
 #![feature(test)]
 extern crate test;
 
-mod commitment;
+pub mod commitment;
+pub mod params_file;
 mod schnorr;
-mod ringsig;
+pub mod ringsig;
+pub mod transaction;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 
 pub use merlin::Transcript;
+
+use ark_ec::CurveGroup;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use crate::commitment::PedersenParams;
+use crate::commitment::pedersen::PedersenCommitmentScheme;
+use crate::ringsig::protocol_compressed_modification::RingSignatureScheme;
+use crate::ringsig::structs::{CompressedRingSignature, RingSignatureParams, SessionNonce, RING_KEY_LABEL};
+use toolbox::errors::SigmaErrors;
+use toolbox::sigma::SigmaProtocol;
+
+/// A self-contained ring signature: the ring it was produced for and the
+/// logarithmic-size proof of the modified compressed scheme, which also proves that the
+/// indicator vector is not zero. The commitment generators are not carried, since
+/// both the signer and the verifier derive them from the ring size.
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Signature<C: CurveGroup> {
+    pub ring: Vec<C::Affine>,
+    pub proof: CompressedRingSignature<C>,
+}
+
+/// Outputs the public key pk = g^sk, where g is the fixed key generator
+pub fn public_key<C: CurveGroup>(sk: &C::ScalarField) -> C::Affine {
+    let key_params = key_parameters::<C>();
+    (key_params.vec_gen[0] * sk).into_affine()
+}
+
+/// Signs msg on behalf of the ring with the secret key sk,
+/// the public key of sk should be a member of the ring and the ring size should be a power of two
+/// below the modulus bit size of the scalar field, i.e., at most 128 members on secp256k1
+///
+/// ```
+/// use ark_secp256k1::{Fr, Projective};
+/// use ark_std::UniformRand;
+/// use ringsignature::{public_key, sign, verify};
+///
+/// let mut rng = ark_std::test_rng();
+/// let sk = Fr::rand(&mut rng);
+/// let mut ring: Vec<_> = (0..3).map(|_| public_key::<Projective>(&Fr::rand(&mut rng))).collect();
+/// ring.insert(1, public_key::<Projective>(&sk));
+///
+/// let sig = sign::<Projective>(&sk, &ring, "hello").unwrap();
+/// assert!(verify(&ring, "hello", &sig));
+/// assert!(!verify(&ring, "goodbye", &sig));
+/// ```
+pub fn sign<C: CurveGroup>(
+    sk: &C::ScalarField,
+    ring: &[C::Affine],
+    msg: &str,
+) -> Result<Signature<C>, SigmaErrors> {
+    sign_with_rng(&mut rand::thread_rng(), sk, ring, msg)
+}
+
+/// Same as sign, but derives all the nonces from hash(sk || msg || ring),
+/// so that signing the same inputs always outputs the same signature, e.g., for test vectors
+pub fn sign_deterministic<C: CurveGroup>(
+    sk: &C::ScalarField,
+    ring: &[C::Affine],
+    msg: &str,
+) -> Result<Signature<C>, SigmaErrors> {
+    let mut transcript = Transcript::new(b"RingCT-SP23 nonces");
+    transcript.append_message(b"secret key", &to_bytes(sk)?);
+    transcript.append_message(b"message", msg.as_bytes());
+    transcript.append_message(b"ring", &to_bytes(&ring.to_vec())?);
+    let mut seed = [0u8; 32];
+    transcript.challenge_bytes(b"seed", &mut seed);
+    sign_with_rng(&mut StdRng::from_seed(seed), sk, ring, msg)
+}
+
+fn sign_with_rng<C: CurveGroup, R: Rng>(
+    rng: &mut R,
+    sk: &C::ScalarField,
+    ring: &[C::Affine],
+    msg: &str,
+) -> Result<Signature<C>, SigmaErrors> {
+    let params = public_parameters::<C>(ring, msg)?;
+
+    // the witness is sk followed by the indicator vector of pk in the ring
+    let pk = public_key::<C>(sk);
+    if !ring.contains(&pk) {
+        return Err(SigmaErrors::InvalidProver(
+            "the public key is not a member of the ring".to_string(),
+        ));
+    }
+    let mut wit = vec![*sk];
+    wit.extend(ring.iter().map(|pk_i| C::ScalarField::from((*pk_i == pk) as u64)));
+
+    let proof = RingSignatureScheme::<C>::prove(rng, &params, &wit)?;
+    Ok(Signature {
+        ring: ring.to_vec(),
+        proof,
+    })
+}
+
+/// Verifies that sig is a signature on msg by a member of the ring
+pub fn verify<C: CurveGroup>(
+    ring: &[C::Affine],
+    msg: &str,
+    sig: &Signature<C>,
+) -> bool {
+    if sig.ring != ring {
+        return false;
+    }
+    match public_parameters::<C>(ring, msg) {
+        Ok(params) => RingSignatureScheme::<C>::verify(&params, &sig.proof).unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+// the public parameters for a ring are hashed to the curve from its size,
+// so that the signer and the verifier agree on them without running setup
+fn public_parameters<C: CurveGroup>(
+    ring: &[C::Affine],
+    msg: &str,
+) -> Result<RingSignatureParams<C>, SigmaErrors> {
+    let n = ring.len();
+    if !n.is_power_of_two() {
+        return Err(SigmaErrors::InvalidParameters(
+            "ring size is not power of two".to_string(),
+        ));
+    }
+    Ok(RingSignatureParams {
+        num_witness: 1 + n,
+        num_pub_inputs: n,
+        com_parameters: RingSignatureParams::<C>::deterministic_com_parameters(4, n)?,
+        message: msg.to_string(),
+        vec_pk: ring.to_vec(),
+        session_nonce: session_nonce::<C>(ring, msg)?,
+    })
+}
+
+// without a setup the session is the pair (ring, msg), so its nonce is derived from them
+fn session_nonce<C: CurveGroup>(ring: &[C::Affine], msg: &str) -> Result<SessionNonce, SigmaErrors> {
+    let mut transcript = Transcript::new(b"RingCT-SP23 session");
+    transcript.append_message(b"ring", &to_bytes(&ring.to_vec())?);
+    transcript.append_message(b"message", msg.as_bytes());
+    let mut nonce = [0u8; 32];
+    transcript.challenge_bytes(b"session nonce", &mut nonce);
+    Ok(SessionNonce(nonce))
+}
+
+// the public key parameters (g) do not depend on the ring
+fn key_parameters<C: CurveGroup>() -> PedersenParams<C> {
+    PedersenCommitmentScheme::<C>::setup_deterministic(RING_KEY_LABEL, 1).unwrap()
+}
+
+fn to_bytes<S: CanonicalSerialize>(s: &S) -> Result<Vec<u8>, SigmaErrors> {
+    let mut bytes = Vec::new();
+    s.serialize_compressed(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Fr, Projective};
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = ark_std::test_rng();
+        let sk = Fr::rand(&mut rng);
+        let mut ring: Vec<_> = (0..7).map(|_| public_key::<Projective>(&Fr::rand(&mut rng))).collect();
+        ring.insert(5, public_key::<Projective>(&sk));
+
+        let sig = sign::<Projective>(&sk, &ring, "message").unwrap();
+        assert!(verify(&ring, "message", &sig));
+        // a different message or ring is rejected
+        assert!(!verify(&ring, "another message", &sig));
+        let mut ring_prime = ring.clone();
+        ring_prime.swap(0, 1);
+        assert!(!verify(&ring_prime, "message", &sig));
+
+        // the signer must be a member of a ring of size power of two
+        assert!(sign::<Projective>(&Fr::rand(&mut rng), &ring, "message").is_err());
+        assert!(sign::<Projective>(&sk, &ring[..6], "message").is_err());
+    }
+
+    #[test]
+    fn test_zero_indicator_forgery() {
+        // without any key of the ring, a forger signs with the zero indicator vector,
+        // the witness of b_0 = 0^n is rejected by sign and its proof by verify
+        let mut rng = ark_std::test_rng();
+        let ring: Vec<_> = (0..4).map(|_| public_key::<Projective>(&Fr::rand(&mut rng))).collect();
+        let params = public_parameters::<Projective>(&ring, "message").unwrap();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        wit.extend(vec![Fr::from(0u64); 4]);
+        assert!(RingSignatureScheme::<Projective>::prove(&mut rng, &params, &wit).is_err());
+
+        let (vec_b0, vec_b2) = (vec![Fr::from(0u64); 4], vec![Fr::from(1u64); 4]);
+        let proof = RingSignatureScheme::<Projective>::prove_with_bits(&mut rng, &params, &[], &vec_b0, &vec_b2).unwrap();
+        let sig = Signature { ring: ring.clone(), proof };
+        assert!(!verify(&ring, "message", &sig));
+    }
+
+    #[test]
+    fn test_sign_deterministic() {
+        let mut rng = ark_std::test_rng();
+        let sk = Fr::rand(&mut rng);
+        let mut ring: Vec<_> = (0..3).map(|_| public_key::<Projective>(&Fr::rand(&mut rng))).collect();
+        ring.insert(2, public_key::<Projective>(&sk));
+
+        // the same inputs give byte-identical signatures
+        let sig = sign_deterministic::<Projective>(&sk, &ring, "message").unwrap();
+        let sig_prime = sign_deterministic::<Projective>(&sk, &ring, "message").unwrap();
+        assert_eq!(to_bytes(&sig).unwrap(), to_bytes(&sig_prime).unwrap());
+        assert!(verify(&ring, "message", &sig));
+        // and the nonces change with the message
+        let sig_prime = sign_deterministic::<Projective>(&sk, &ring, "another message").unwrap();
+        assert_ne!(sig.proof.commitments, sig_prime.proof.commitments);
+    }
+}
@@ -0,0 +1,147 @@
+//! Files of the public parameters, so that a CRS is generated once and distributed:
+//! a file is MAGIC, the format VERSION and the tag of the parameter type, followed by the compressed
+//! canonical serialization of the parameters, whose points are checked to be on the curve and in the
+//! prime order subgroup on load
+use std::fs;
+use std::path::Path;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+
+pub const MAGIC: &[u8; 6] = b"RCTCRS";
+pub const VERSION: u8 = 1;
+
+// the tags of the parameter types, so that a file of one type is not loaded as another
+pub(crate) const PEDERSEN_TAG: u8 = 0;
+pub(crate) const RING_TAG: u8 = 1;
+
+// the length of the magic, version and tag
+const HEADER_LEN: usize = MAGIC.len() + 2;
+
+pub(crate) fn write<T: CanonicalSerialize>(path: impl AsRef<Path>, tag: u8, value: &T) -> Result<(), SerializationError> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + value.compressed_size());
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.push(tag);
+    value.serialize_compressed(&mut bytes)?;
+    Ok(fs::write(path, bytes)?)
+}
+
+pub(crate) fn read<T: CanonicalDeserialize>(path: impl AsRef<Path>, tag: u8) -> Result<T, SerializationError> {
+    let bytes = fs::read(path)?;
+    if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(SerializationError::InvalidData);
+    }
+    if bytes[MAGIC.len()] != VERSION || bytes[MAGIC.len() + 1] != tag {
+        return Err(SerializationError::InvalidData);
+    }
+    let mut reader = &bytes[HEADER_LEN..];
+    let value = T::deserialize_compressed(&mut reader)?;
+    // trailing bytes are a corruption as well
+    if !reader.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use ark_secp256k1::{Affine, Fr, Projective};
+    use ark_std::UniformRand;
+    use crate::commitment::PedersenParams;
+    use crate::commitment::pedersen::PedersenCommitmentScheme;
+    use crate::ringsig::protocol_compressed::RingSignatureScheme;
+    use crate::ringsig::structs::RingSignatureParams;
+    use toolbox::sigma::SigmaProtocol;
+
+    type Ring = RingSignatureScheme<Projective>;
+
+    // a file of the system temporary directory, unique per test so that the tests run in parallel
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ringct-{}-{}.crs", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_params_file_round_trip() {
+        let mut rng = ark_std::test_rng();
+        let params = PedersenCommitmentScheme::<Projective>::setup(&mut rng, 8).unwrap();
+        let path = temp_path("pedersen");
+        params.write_to_file(&path).unwrap();
+        assert_eq!(PedersenParams::<Projective>::read_from_file(&path).unwrap(), params);
+        // the header is followed by the compressed points
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!((&bytes[..6], bytes[6], bytes[7]), (&MAGIC[..], VERSION, PEDERSEN_TAG));
+        assert_eq!(bytes.len(), HEADER_LEN + params.compressed_size());
+        // a file of Pedersen parameters is not loaded as ring parameters
+        assert!(RingSignatureParams::<Projective>::read_from_file(&path).is_err());
+        fs::remove_file(&path).unwrap();
+
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let ring_params = Ring::setup(&mut rng, &mut wit, "message", 8).unwrap();
+        let path = temp_path("ring");
+        ring_params.write_to_file(&path).unwrap();
+        assert_eq!(RingSignatureParams::<Projective>::read_from_file(&path).unwrap(), ring_params);
+        fs::remove_file(&path).unwrap();
+        assert!(RingSignatureParams::<Projective>::read_from_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_params_file_corrupted() {
+        let mut rng = ark_std::test_rng();
+        let params = PedersenCommitmentScheme::<Projective>::setup(&mut rng, 4).unwrap();
+        let path = temp_path("corrupted");
+        params.write_to_file(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        // another magic, another version, a truncated file and trailing bytes are rejected
+        let mut corrupted = vec![];
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] ^= 1;
+        corrupted.push(bad_magic);
+        let mut bad_version = bytes.clone();
+        bad_version[6] = VERSION + 1;
+        corrupted.push(bad_version);
+        corrupted.push(bytes[..bytes.len() - 1].to_vec());
+        corrupted.push(bytes[..4].to_vec());
+        corrupted.push([bytes.clone(), vec![0]].concat());
+        // as well as an x-coordinate off the curve: the last point is its compressed x-coordinate and a flags byte,
+        // so that a flipped bit of x lands off the curve for about half of the flips
+        let last = bytes.len() - params.vec_gen[3].compressed_size();
+        let off_curve = (0..8).map(|bit| {
+            let mut flipped = bytes.clone();
+            flipped[last] ^= 1 << bit;
+            flipped
+        });
+        corrupted.extend(off_curve.filter(|flipped| {
+            let mut x = &flipped[last..];
+            Affine::deserialize_with_mode(&mut x, ark_serialize::Compress::Yes, ark_serialize::Validate::No).is_err()
+        }).take(1));
+        assert_eq!(corrupted.len(), 6);
+        for bad in corrupted {
+            fs::write(&path, bad).unwrap();
+            assert!(PedersenParams::<Projective>::read_from_file(&path).is_err());
+        }
+
+        // nor are ring parameters whose ring is shorter than the number of public inputs
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let mut ring_params = Ring::setup(&mut rng, &mut wit, "message", 4).unwrap();
+        ring_params.vec_pk.pop();
+        ring_params.write_to_file(&path).unwrap();
+        assert!(RingSignatureParams::<Projective>::read_from_file(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_params_file_verify() {
+        // a signature produced with the parameters in memory verifies against the parameters loaded from disk
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let ring_params = Ring::setup(&mut rng, &mut wit, "Welcome to the world of Zero Knowledge!", 16).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        let path = temp_path("verify");
+        ring_params.write_to_file(&path).unwrap();
+        let loaded = RingSignatureParams::<Projective>::read_from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(Ring::verify(&loaded, &proof).unwrap());
+    }
+}
@@ -0,0 +1,272 @@
+use std::marker::PhantomData;
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::CanonicalSerialize;
+use ark_std::{end_timer, rand::Rng, start_timer};
+use rand::seq::SliceRandom;
+use toolbox::errors::SigmaErrors;
+use toolbox::hash::{Digest, Sha256};
+use toolbox::sigma::SigmaProtocol;
+use crate::schnorr::dlog::DLogProof;
+
+// Merkle ring membership: the ring vec_pk is hashed into a Merkle tree of sha256 digests and only the
+// root is public, the signer sends pk with its authentication path and a Schnorr proof of sk for pk = g^sk
+// bound to the root and the message. The signature is constant in the ring size up to the log(n) path,
+// but it reveals pk and its position in the ring: it proves membership, not anonymity, which would need
+// a zero-knowledge proof of the path (sha256 is not algebraic over the group)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerkleRingScheme<C: CurveGroup> {
+    phantom: PhantomData<C>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerkleRingParams<C: CurveGroup> {
+    // the root of the Merkle tree over the ring
+    pub root: String,
+    // the depth of the tree, i.e., the length of every path
+    pub depth: usize,
+    // the public key generator g, pk = g^sk
+    pub key_generator: C::Affine,
+    // the signed message
+    pub message: String,
+}
+
+// the layers of the Merkle tree, from the leaves to the root
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerkleWitness {
+    pub layers: Vec<Vec<String>>,
+}
+
+// the authentication path of the leaf at index, with the siblings from the leaf to the root
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerklePath {
+    pub index: usize,
+    pub siblings: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MerkleRingProof<C: CurveGroup> {
+    // the public key of the signer
+    pub pk: C::Affine,
+    // the path from pk to the root
+    pub path: MerklePath,
+    // the proof of knowledge of sk
+    pub dlog: DLogProof<C>,
+}
+
+impl<C: CurveGroup> MerkleRingParams<C> {
+    /// Builds the Merkle tree over vec_pk, padded with empty leaves to a power of two, for the standard
+    /// generator of C as key generator and an empty message; outputs the public root and the tree,
+    /// from which the signer takes its path
+    pub fn from_vec_pk(vec_pk: &[C::Affine]) -> (MerkleRingParams<C>, MerkleWitness) {
+        let start = start_timer!(|| "building the merkle tree of the ring...");
+        let mut leaves: Vec<String> = vec_pk.iter().map(hash_leaf::<C>).collect();
+        leaves.resize(vec_pk.len().next_power_of_two(), hash_empty());
+        let mut layers = vec![leaves];
+        while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+            let parents = layer.chunks(2).map(|pair| hash_node(&pair[0], &pair[1])).collect();
+            layers.push(parents);
+        }
+        end_timer!(start);
+        let params = MerkleRingParams {
+            root: layers[layers.len()-1][0].clone(),
+            depth: layers.len() - 1,
+            key_generator: C::Affine::generator(),
+            message: String::new(),
+        };
+        (params, MerkleWitness { layers })
+    }
+
+    // the Schnorr proof is bound to the root and the message
+    fn dlog_message(&self) -> Vec<u8> {
+        [self.root.as_bytes(), self.message.as_bytes()].concat()
+    }
+}
+
+impl MerkleWitness {
+    /// Returns the authentication path of the leaf at index, if any
+    pub fn path(&self, index: usize) -> Option<MerklePath> {
+        if index >= self.layers[0].len() {
+            return None;
+        }
+        let siblings = self.layers[..self.layers.len()-1].iter()
+            .enumerate()
+            .map(|(level, layer)| layer[(index >> level) ^ 1].clone())
+            .collect();
+        Some(MerklePath { index, siblings })
+    }
+}
+
+impl MerklePath {
+    /// Recomputes the root from the leaf and the path
+    pub fn root(&self, leaf: &str) -> String {
+        self.siblings.iter()
+            .enumerate()
+            .fold(leaf.to_string(), |node, (level, sibling)| {
+                if (self.index >> level) & 1 == 0 {
+                    hash_node(&node, sibling)
+                } else {
+                    hash_node(sibling, &node)
+                }
+            })
+    }
+}
+
+/// Implement a sigma protocol as a Merkle ring membership proof:
+/// Relation: P knows a sk to a pk among the leaves of the Merkle tree with the public root
+/// P->V: pk and the path from pk to the root, A = g^r
+/// V->P: challenge c
+/// P->V: s = r + c*sk
+impl<C> SigmaProtocol<C> for MerkleRingScheme<C>
+where
+    C: CurveGroup,
+{
+    /// public parameters
+    type PublicParams = MerkleRingParams<C>;
+    /// witness: the secret key and the Merkle tree
+    type Witness = (C::ScalarField, MerkleWitness);
+    /// commitment
+    type Commitments = C::Affine;
+    /// challenge
+    type Challenge = C::ScalarField;
+    /// proof
+    type Proof = MerkleRingProof<C>;
+
+    fn setup<R: Rng>(
+        rng: &mut R,
+        wit: &mut Self::Witness, // secret key, the tree is filled in
+        msg: &str,
+        supported_size: usize, // ring size
+    ) -> Result<Self::PublicParams, SigmaErrors> {
+        if supported_size == 0 {
+            return Err(SigmaErrors::InvalidParameters(
+                "the ring should not be empty".to_string(),
+            ));
+        }
+        let pk = (C::Affine::generator() * wit.0).into_affine();
+        let mut vec_pk: Vec<C::Affine> = (1..supported_size).map(|_| C::rand(rng).into_affine()).collect();
+        vec_pk.push(pk);
+        vec_pk.shuffle(rng);
+        let (mut params, tree) = MerkleRingParams::<C>::from_vec_pk(&vec_pk);
+        params.message = msg.to_string();
+        wit.1 = tree;
+        Ok(params)
+    }
+
+    fn prove<R: Rng>(
+        rng: &mut R,
+        params: &Self::PublicParams,
+        wit: &Self::Witness,
+    ) -> Result<Self::Proof, SigmaErrors> {
+        let (sk, tree) = wit;
+        let pk = (params.key_generator * sk).into_affine();
+        let leaf = hash_leaf::<C>(&pk);
+        let path = tree.layers[0].iter()
+            .position(|leaf_i| *leaf_i == leaf)
+            .and_then(|index| tree.path(index))
+            .filter(|path| path.siblings.len() == params.depth && path.root(&leaf) == params.root)
+            .ok_or(SigmaErrors::InvalidProver(
+                "the public key is not a member of the ring".to_string(),
+            ))?;
+        let start = start_timer!(|| "running merkle ring prove algorithm...");
+        let dlog = DLogProof::prove(rng, params.key_generator, pk.into_group(), *sk, &params.dlog_message());
+        end_timer!(start);
+        Ok(MerkleRingProof { pk, path, dlog })
+    }
+
+    fn verify(
+        params: &Self::PublicParams,
+        proof: &Self::Proof
+    ) -> Result<bool, SigmaErrors> {
+        if proof.path.siblings.len() != params.depth || proof.path.index >> params.depth != 0 {
+            return Err(SigmaErrors::InvalidProof(
+                "the path does not match the depth of the tree".to_string(),
+            ));
+        }
+        let start = start_timer!(|| "running merkle ring verify algorithm...");
+        let result = proof.path.root(&hash_leaf::<C>(&proof.pk)) == params.root
+            && DLogProof::verify(params.key_generator, proof.pk.into_group(), &proof.dlog, &params.dlog_message());
+        end_timer!(start);
+        Ok(result)
+    }
+}
+
+// the leaves, the padding leaves and the inner nodes are hashed with distinct prefixes,
+// so that no inner node can be presented as a leaf
+fn hash_leaf<C: CurveGroup>(pk: &C::Affine) -> String {
+    let mut bytes = vec![0u8];
+    pk.serialize_compressed(&mut bytes).expect("serializing a point into a vector");
+    Sha256::digest(&bytes)
+}
+
+fn hash_empty() -> String {
+    Sha256::digest(&[2u8])
+}
+
+fn hash_node(left: &str, right: &str) -> String {
+    Sha256::digest(&[&[1u8], left.as_bytes(), right.as_bytes()].concat())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Fr, Projective};
+    use ark_std::UniformRand;
+
+    type Merkle = MerkleRingScheme<Projective>;
+
+    #[test]
+    fn test_merkle_ring() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = (Fr::rand(&mut rng), MerkleWitness::default());
+        let params = Merkle::setup(&mut rng, &mut wit, "merkle ring", 256).unwrap();
+        let proof = Merkle::prove(&mut rng, &params, &wit).unwrap();
+        assert_eq!(proof.path.siblings.len(), 8);
+        assert!(Merkle::verify(&params, &proof).unwrap());
+
+        // a tampered path, a path to another leaf, and another message fail
+        let mut proof_prime = proof.clone();
+        proof_prime.path.siblings[3] = hash_empty();
+        assert!(!Merkle::verify(&params, &proof_prime).unwrap());
+        let mut proof_prime = proof.clone();
+        proof_prime.path.index ^= 1;
+        assert!(!Merkle::verify(&params, &proof_prime).unwrap());
+        let mut proof_prime = proof.clone();
+        proof_prime.path.siblings.pop();
+        assert!(Merkle::verify(&params, &proof_prime).is_err());
+        let mut params_prime = params.clone();
+        params_prime.message = String::from("another message");
+        assert!(!Merkle::verify(&params_prime, &proof).unwrap());
+
+        // a key outside of the ring cannot prove, nor present the path of a member
+        let sk_outside = Fr::rand(&mut rng);
+        assert!(Merkle::prove(&mut rng, &params, &(sk_outside, wit.1.clone())).is_err());
+        let mut proof_prime = Merkle::prove(&mut rng, &params, &wit).unwrap();
+        proof_prime.pk = (params.key_generator * sk_outside).into_affine();
+        assert!(!Merkle::verify(&params, &proof_prime).unwrap());
+    }
+
+    #[test]
+    fn test_merkle_ring_from_vec_pk() {
+        // a ring that is not a power of two is padded with empty leaves
+        let mut rng = ark_std::test_rng();
+        let vec_sk: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let vec_pk: Vec<_> = vec_sk.iter().map(|sk| (ark_secp256k1::Affine::generator() * sk).into_affine()).collect();
+        let (mut params, tree) = MerkleRingParams::<Projective>::from_vec_pk(&vec_pk);
+        params.message = String::from("message");
+        assert_eq!(params.depth, 3);
+        for (i, pk) in vec_pk.iter().enumerate() {
+            assert_eq!(tree.path(i).unwrap().root(&hash_leaf::<Projective>(pk)), params.root);
+        }
+        assert!(tree.path(8).is_none());
+        for sk in vec_sk {
+            let proof = Merkle::prove(&mut rng, &params, &(sk, tree.clone())).unwrap();
+            assert!(Merkle::verify(&params, &proof).unwrap());
+        }
+
+        // a ring of one key has an empty path
+        let (params, tree) = MerkleRingParams::<Projective>::from_vec_pk(&vec_pk[..1]);
+        assert_eq!(params.depth, 0);
+        assert_eq!(tree.path(0).unwrap().root(&hash_leaf::<Projective>(&vec_pk[0])), params.root);
+    }
+}
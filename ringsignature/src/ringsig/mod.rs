@@ -1,3 +1,7 @@
-mod structs;
-mod protocol_linear;
-mod protocol_compressed;
\ No newline at end of file
+pub mod structs;
+pub mod protocol_linear;
+pub mod protocol_compressed;
+pub mod protocol_compressed_modification;
+pub mod ring_vrf;
+pub mod merkle_ring;
+pub mod sanity;
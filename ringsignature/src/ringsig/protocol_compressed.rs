@@ -1,34 +1,36 @@
-use std::io::Write;
 use std::marker::PhantomData;
 
-use ark_ec::CurveGroup;
+use ark_ec::{AffineRepr, CurveGroup};
 use ark_ff::Field;
-use ark_std::{end_timer, rand::Rng, start_timer, UniformRand, Zero, One};
-use sha256::digest;
+use ark_std::{end_timer, rand::Rng, start_timer, UniformRand, Zero};
 
 use bulletproofs::ipa::*;
 use bulletproofs::structs::*;
 use crate::commitment::pedersen::PedersenCommitmentScheme;
 use crate::commitment::PedersenParams;
-use crate::ringsig::structs::{LogarithmicRingSignature, RingSignatureParams, Openings};
-use toolbox::sigma::{transcript::ProofTranscript, SigmaProtocol};
+use crate::ringsig::structs::{LogarithmicRingSignature, RingSignatureParams, LinearOpenings, SessionNonce, LOGARITHMIC_DOMAIN};
+use toolbox::sigma::SigmaProtocol;
 use toolbox::errors::SigmaErrors;
+use toolbox::hash::{Digest, Sha256};
+use toolbox::metrics;
 use toolbox::vec::*;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct RingSignatureScheme<C>
+pub struct RingSignatureScheme<C, D = Sha256>
 where
     C: CurveGroup,
+    D: Digest,
 {
-    phantom: PhantomData<C>,
+    phantom: PhantomData<(C, D)>,
 }
 
 /// Implement a sigma protocol as a ring signature scheme (without compression), including 5-move:
 /// Relation: P knows a sk to a pk among the vector vec_pk
 /// Formalized Relation: P knows a sk satisfying <vec_pk, vec_b> = com(sk)
-impl<C> SigmaProtocol<C> for RingSignatureScheme<C>
+impl<C, D> SigmaProtocol<C> for RingSignatureScheme<C, D>
 where
     C: CurveGroup,
+    D: Digest,
 {
     /// public parameters
     type PublicParams = RingSignatureParams<C>;
@@ -44,31 +46,39 @@ where
     fn setup<R: Rng>(
         rng: &mut R,
         wit: &mut Self::Witness, // secret key
-        msg: &String,
-        supported_size: usize, // ring size
+        msg: &str,
+        supported_size: usize, // ring size, padded to the next power of two
     ) -> Result<Self::PublicParams, SigmaErrors> {
-        // generate commitment scheme parameters (vec_g, u)
-        let com_params_1 = PedersenCommitmentScheme::<C>::setup(rng, supported_size)?;
-        // generate commitment scheme parameters (vec_h, v)
-        let com_params_2 = PedersenCommitmentScheme::<C>::setup(rng, supported_size)?;
-
-        // generate public key parameters (g)
-        let key_params = PedersenCommitmentScheme::<C>::setup(rng, 1)?;
+        if supported_size == 0 {
+            return Err(SigmaErrors::InvalidParameters(
+                "ring size should be positive".to_string(),
+            ));
+        }
+        // the IPA vectors are as long as the padded ring
+        let num_pub_inputs = supported_size.next_power_of_two();
+        // derive commitment scheme parameters (vec_g, u), (vec_h, v) and public key parameters (g)
+        let com_parameters = RingSignatureParams::<C>::deterministic_com_parameters(2, num_pub_inputs)?;
+        let key_params = &com_parameters[2];
 
         // generate pk vectors
-        let pk:C::Affine = PedersenCommitmentScheme::commit(&key_params, wit, &C::ScalarField::zero(), "as pk")?.into_affine();
-        let mut vec_pk = vec![C::Affine::rand(rng); supported_size-1];
+        let pk:C::Affine = PedersenCommitmentScheme::commit(key_params, wit, &C::ScalarField::zero(), "as pk")?.into_affine();
+        let mut vec_pk = rand_affine_vec::<C, R>(rng, supported_size-1);
         // add pk to the vector and shuffle it
         vec_pk.push(pk);
-        let vec_b = shuffle::<C>(&mut vec_pk, pk);
+        let (mut vec_b, index) = shuffle_indexed::<C, R>(&mut vec_pk, pk, rng);
+        debug_assert_eq!(vec_pk[index], pk);
+        // the indicator is zero on the padding
+        RingSignatureParams::pad_ring(&mut vec_pk, key_params);
+        vec_b.resize(num_pub_inputs, C::ScalarField::zero());
         wit.extend(vec_b);
 
         Ok(RingSignatureParams {
             num_witness: wit.len(),
-            num_pub_inputs: supported_size,
-            com_parameters: vec![com_params_1, com_params_2, key_params],
-            message: msg.clone(),
+            num_pub_inputs,
+            com_parameters,
+            message: msg.to_string(),
             vec_pk,
+            session_nonce: SessionNonce::rand(rng),
         })
     }
 
@@ -79,7 +89,8 @@ where
     ) -> Result<Self::Proof, SigmaErrors> {
         // initialization
         let start = start_timer!(|| "running sigma protocol prove algorithm...");
-        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingSignature");
+        metrics::phase("commitments");
+        let mut transcript = params.session_transcript(LOGARITHMIC_DOMAIN)?;
         transcript.append_serializable_element(b"public list", &params.vec_pk)?;
 
         // parse commitment parameters
@@ -90,32 +101,16 @@ where
         let vec_sk = wit[0..wit.len()-params.num_pub_inputs].to_vec();
         let vec_b = wit[wit.len()-params.num_pub_inputs..].to_vec();
 
-        // denote b_0 = b, b_1 = 1^n - b_0
-        let vec_b0 = vec_b.clone();
-        let vec_b1: Vec<C::ScalarField> = vec_b.iter()
-            .map(|&b_i| C::ScalarField::one() - b_i)
-            .collect();
-
-        // sanity check
-        // b_0 + b_1 = 1^n
-        // b_0 \circ b_1 = 0^n
-        let constraint_1 = vec_b0.iter()
-            .zip(vec_b1.iter())
-            .all(|(&b0_i, &b1_i)| b0_i + b1_i == C::ScalarField::one());
-        let constraint_2 = vec_b0.iter()
-            .zip(vec_b1.iter())
-            .all(|(&b0_i, &b1_i)| b0_i * b1_i == C::ScalarField::zero());
-        assert!(constraint_1 && constraint_2);
-
+        // denote b_0 = b, b_1 = 1^n - b_0, where b_0 must be binary
         // computes A = g^{b_0}h^{b_1}u^{alpha}, B = g^{r_0}h^{r_1}u^{beta}
-        let alpha = C::ScalarField::rand(rng);
+        let vec_b0 = vec_b.clone();
+        let bits_A = PedersenCommitmentScheme::commit_to_bits(rng, param_g_u, param_h_v, &vec_b0)?;
+        let (com_A, vec_b1, alpha) = (bits_A.com, bits_A.complement, bits_A.random);
         let beta = C::ScalarField::rand(rng);
-        let vec_r0 = vec![C::ScalarField::rand(rng); vec_b0.len()];
-        let vec_r1 = vec![C::ScalarField::rand(rng); vec_b1.len()];
-        let com_A = PedersenCommitmentScheme::commit(&param_g_u, &vec_b0, &alpha, "on b0")?
-            + PedersenCommitmentScheme::commit(&param_h_v, &vec_b1, &C::ScalarField::zero(), "on b1")?;
-        let com_B = PedersenCommitmentScheme::commit(&param_g_u, &vec_r0, &beta, "on r0")?
-            + PedersenCommitmentScheme::commit(&param_h_v, &vec_r1, &C::ScalarField::zero(), "on r1")?;
+        let vec_r0: Vec<C::ScalarField> = (0..vec_b0.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let vec_r1: Vec<C::ScalarField> = (0..vec_b1.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let com_B = PedersenCommitmentScheme::commit(param_g_u, &vec_r0, &beta, "on r0")?
+            + PedersenCommitmentScheme::commit(param_h_v, &vec_r1, &C::ScalarField::zero(), "on r1")?;
 
         // P->V: A,B
         transcript.append_serializable_element(b"commitments A,B", &[com_A, com_B])?;
@@ -139,26 +134,26 @@ where
         // T1 = v^{t1}u^{tau1}
         // T2 = v^{t2}u^{tau2}
         let rs = C::ScalarField::rand(rng);
-        let neg_rs = -rs.clone();
+        let neg_rs = -rs;
         let tau1 = C::ScalarField::rand(rng);
         let tau2 = C::ScalarField::rand(rng);
 
-        let com_E = C::msm(&params.vec_pk, &vec_r0_yn).unwrap() + PedersenCommitmentScheme::commit(&param_key, &vec![neg_rs], &C::ScalarField::zero(), "E")?;
+        metrics::count_msm();
+        let com_E = C::msm(&params.vec_pk, &vec_r0_yn).unwrap() + PedersenCommitmentScheme::commit(param_key, &[neg_rs], &C::ScalarField::zero(), "E")?;
         let param_u_v = PedersenParams {
-            generator: param_h_v.generator.clone(),
-            vec_gen: vec![param_g_u.generator.into_affine().clone()],
+            generator: param_h_v.generator,
+            vec_gen: vec![param_g_u.generator.into_affine()],
         };
-        let com_T1 = PedersenCommitmentScheme::commit(&param_u_v, &vec![tau1], &t1, "T1")?;
-        let com_T2 = PedersenCommitmentScheme::commit(&param_u_v, &vec![tau2], &t2, "T2")?;
+        let com_T1 = PedersenCommitmentScheme::commit(&param_u_v, &[tau1], &t1, "T1")?;
+        let com_T2 = PedersenCommitmentScheme::commit(&param_u_v, &[tau2], &t2, "T2")?;
 
         // P->V: E, T1, T2
         transcript.append_serializable_element(b"commitments A,B", &[com_E, com_T1, com_T2])?;
 
         // append the message digest to the transcript
-        let h = digest(&params.message);
-        let mut h_msg: &mut [u8] = &mut [0; 32];
-        h_msg.write(h.as_bytes()).unwrap();
-        transcript.append_message(b"message digest", &h_msg)?;
+        metrics::phase("openings");
+        let h = D::digest(params.message.as_bytes());
+        transcript.append_message_digest::<D>(&h)?;
 
         // V->P: challenges x
         let x = transcript.get_and_append_challenge(b"challenge x")?;
@@ -172,8 +167,7 @@ where
         let hat_t = inner_product(&zeta, &eta);
 
         // // sanity check
-        // let vec_1n = vec![C::ScalarField::one(); params.num_pub_inputs];
-        // let delta = inner_product(&vec_1n, &powers_yn) * (z+z*z);
+        // let delta = vec_sum(&powers_yn) * (z+z*z);
         // let t_prime = delta + t1*x + t2*x*x;
         // if t_prime == hat_t {println!("delta equality passes")}
         // else {println!("delta equality fails")}
@@ -195,31 +189,12 @@ where
         let fs = sum + rs*x;
 
         // Bulletproofs Compression
-        let powers_yn_inverse = generate_powers(y.inverse().unwrap(), params.num_pub_inputs);
-        let mut vec_g_yn = Vec::with_capacity(param_g_u.vec_gen.len());
-        for i in 0..param_g_u.vec_gen.len() {
-            vec_g_yn.push((param_g_u.vec_gen[i]*powers_yn_inverse[i]).into_affine());
-        }
-        let n = vec_g_yn.len();
-        let mut vec_G:Vec<C::Affine> = Vec::with_capacity(n);
-        for i in 0..n {
-            vec_G.push((vec_g_yn[i] + params.vec_pk[i]).into_affine());
-        }
-        let vec_H = param_h_v.vec_gen.clone();
-        let v = param_h_v.generator.clone().into_affine();
-        let factors_G = vec![C::ScalarField::from(1u64); n];
-        let factors_H = vec![C::ScalarField::from(1u64); n];
-        let param = InnerProductParam {
-            factors_G,
-            factors_H,
-            u: v,
-            vec_G,
-            vec_H,
-        };
+        metrics::phase("compression");
+        let param = for_ring_signature(params, y)?;
 
-        let proof = InnerProductProtocol::<C>::prove(&param, zeta.clone(), eta.clone())?;
+        let proof = InnerProductProtocol::<C>::prove(LOGARITHMIC_DOMAIN, &param, zeta.clone(), eta.clone())?;
 
-        let openings = Openings {
+        let openings = LinearOpenings {
             zeta: vec![proof.a],
             eta: vec![proof.b],
             hat_t,
@@ -232,11 +207,12 @@ where
         // proving ends
         end_timer!(start);
         Ok(LogarithmicRingSignature {
-            commitments: vec![com_A, com_B, com_E, com_T1, com_T2],
+            commitments: C::normalize_batch(&[com_A, com_B, com_E, com_T1, com_T2]),
             openings,
             compression_proof: proof,
             challenges: vec![y,z,x],
             digest: h.clone(),
+            session_nonce: params.session_nonce,
         })
     }
 
@@ -244,27 +220,33 @@ where
         params: &Self::PublicParams,
         proof: &Self::Proof
     ) -> Result<bool, SigmaErrors> {
+        // a malformed proof or a desynchronized transcript is rejected before any group arithmetic
+        metrics::phase("precheck");
+        Self::precheck_challenges(params, proof)?;
         // initialization
         let start = start_timer!(|| "preprocessing sigma protocol verify algorithm...");
-        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingSignature");
-        transcript.append_serializable_element(b"public list", &params.vec_pk)?;
+        metrics::phase("preprocessing");
 
         // parse commitment parameters
         let param_g_u = &params.com_parameters[0];
         let param_h_v = &params.com_parameters[1];
         let param_key = &params.com_parameters[2];
+        // commit_constant reads the generators without a length check
+        if param_g_u.supported_size() != params.num_pub_inputs || param_h_v.supported_size() != params.num_pub_inputs {
+            end_timer!(start);
+            return Err(SigmaErrors::InvalidParameters(
+                "commitment parameters do not match the ring size".to_string(),
+            ));
+        }
 
         // parse proof
         let commitments = &proof.commitments;
-        let (com_A, com_B, com_E, com_T1, com_T2) = (commitments[0], commitments[1], commitments[2], commitments[3], commitments[4]);
+        let (com_A, com_B, com_E, com_T1, com_T2) = (commitments[0].into_group(), commitments[1].into_group(),
+            commitments[2].into_group(), commitments[3].into_group(), commitments[4].into_group());
         let openings = &proof.openings;
-        let challenges = &proof.challenges;
-        let digest = &proof.digest;
-
-        let (y,z,x) = (challenges[0],challenges[1],challenges[2]);
+        let (y,z,x) = (proof.challenges[0], proof.challenges[1], proof.challenges[2]);
 
-        let vec_0n = vec![C::ScalarField::zero(); params.num_pub_inputs];
-        let vec_1n = vec![C::ScalarField::one(); params.num_pub_inputs];
+        let zero = C::ScalarField::zero();
         let powers_yn = generate_powers(y, params.num_pub_inputs);
 
         // check validity of T1 T2
@@ -273,97 +255,168 @@ where
         // let t = inner_product(&openings.zeta, &openings.eta);
         // assert_eq!(openings.hat_t, t, "step 1: hat_t check fails");
 
-        let delta = inner_product(&vec_1n, &powers_yn) * (z+z*z);
+        let delta = vec_sum(&powers_yn) * (z+z*z);
 
-        // let lhs_step1 = PedersenCommitmentScheme::commit(param_h_v, &vec_0n, &openings.hat_t, "on hat_t")?;
-        let rhs_step1 = PedersenCommitmentScheme::commit(param_h_v, &vec_0n, &delta, "on delta")?
-            + com_T1.mul(x) + com_T2.mul(x*x) - PedersenCommitmentScheme::commit(&param_g_u, &vec_0n, &openings.taux, "on tau_x")?;
+        // let lhs_step1 = PedersenCommitmentScheme::commit_constant(param_h_v, &zero, &openings.hat_t);
+        let rhs_step1 = PedersenCommitmentScheme::commit_constant(param_h_v, &zero, &delta)
+            + com_T1.mul(x) + com_T2.mul(x*x) - PedersenCommitmentScheme::commit_constant(param_g_u, &zero, &openings.taux);
         // assert_eq!(lhs_step1, rhs_step1, "step 1: T1, T2 checks fail");
 
         // check validity of A B
         // {vec_g'}^{zeta} vec_h^eta = A B^x vec_g^{z1^n} vec_h^{z1^n} u^{-mu}
-        let powers_yn_inverse = generate_powers(y.inverse().unwrap(), params.num_pub_inputs);
-        let mut vec_g_yn = Vec::with_capacity(param_g_u.vec_gen.len());
-        for i in 0..param_g_u.vec_gen.len() {
-            vec_g_yn.push((param_g_u.vec_gen[i]*powers_yn_inverse[i]).into_affine());
-        }
-        let vec_z1n = vec![z; params.num_pub_inputs];
-        let param_g_yn_u = PedersenParams {
-            generator: param_g_u.generator.clone(),
-            vec_gen: vec_g_yn,
-        };
         // let lhs_step2 = PedersenCommitmentScheme::commit(&param_g_yn_u, &openings.zeta, &C::ScalarField::zero(), "on zeta")?
         //    + PedersenCommitmentScheme::commit(&param_h_v, &openings.eta, &C::ScalarField::zero(), "on eta")?;
         let rhs_step2 = com_A + com_B.mul(x)
-            + PedersenCommitmentScheme::commit(&param_g_u, &vec_z1n, &(-openings.mu), "on z1n")?
-            + PedersenCommitmentScheme::commit(&param_h_v, &vec_z1n, &C::ScalarField::zero(), "on z1n")?;
+            + PedersenCommitmentScheme::commit_constant(param_g_u, &z, &(-openings.mu))
+            + PedersenCommitmentScheme::commit_constant(param_h_v, &z, &zero);
         // assert_eq!(lhs_step2, rhs_step2, "step 2: A,B checks fail");
 
         // check pk
         // P^zeta = g^fs E^x P^{z y^n}
         let vec_z_yn = scalar_product(&powers_yn, &z);
         // let lhs_step3 = C::msm(&params.vec_pk, &openings.zeta).unwrap();
-        let rhs_step3 = PedersenCommitmentScheme::commit(&param_key, &vec![openings.fs], &C::ScalarField::zero(), "on fs")?
+        metrics::count_msm();
+        let rhs_step3 = PedersenCommitmentScheme::commit(param_key, &[openings.fs], &C::ScalarField::zero(), "on fs")?
             + com_E.mul(x) + C::msm(&params.vec_pk, &vec_z_yn).unwrap();
         // assert_eq!(lhs_step3, rhs_step3, "step 3: pk check fails");
 
         end_timer!(start);
 
         let start = start_timer!(|| "running sigma protocol verify algorithm...");
-        // check the challenges
-        transcript.append_serializable_element(b"commitments A,B", &[com_A, com_B])?;
-        let y = transcript.get_and_append_challenge(b"challenge y")?;
-        let z = transcript.get_and_append_challenge(b"challenge z")?;
-        transcript.append_serializable_element(b"commitments A,B", &[com_E, com_T1, com_T2])?;
-        let h = sha256::digest(&params.message);
-        assert_eq!(&h, digest);
-        let mut h_msg: &mut [u8] = &mut [0; 32];
-        h_msg.write(h.as_bytes()).unwrap();
-        transcript.append_message(b"message digest", &h_msg)?;
-        let x = transcript.get_and_append_challenge(b"challenge x")?;
-
-        if (y,z,x) != (challenges[0],challenges[1],challenges[2])  {
-            return Err(SigmaErrors::InvalidProof(
-                "invalid challenge value".to_string(),
-            ));
-        }
-
+        metrics::phase("compression");
         // run Bulletproofs Compression
         // consider aggregating the following three equation into one
         // v^{hat_t} = v^delta T1^x T2^{x^2} y^{-taux}
         // {vec_g'}^{zeta} vec_h^eta = A B^x vec_g^{z1^n} vec_h^{z1^n} u^{-mu}
         // P^zeta = g^fs E^x P^{z y^n}
         let RHS = rhs_step1 + rhs_step2 + rhs_step3;
-        let n = param_g_yn_u.vec_gen.len();
-        let mut vec_G:Vec<C::Affine> = Vec::with_capacity(n);
-        for i in 0..n {
-            vec_G.push((param_g_yn_u.vec_gen[i] + params.vec_pk[i]).into_affine());
-        }
-        let vec_H = param_h_v.vec_gen.clone();
-        let v = param_h_v.generator.clone().into_affine();
-        let factors_G = vec![C::ScalarField::from(1u64); n];
-        let factors_H = vec![C::ScalarField::from(1u64); n];
-        let param = InnerProductParam {
-            factors_G,
-            factors_H,
-            u: v,
-            vec_G,
-            vec_H,
-        };
+        let n = params.num_pub_inputs;
+        let param = for_ring_signature(params, y)?;
 
         // call Bulletproofs prover
-        InnerProductProtocol::<C>::verify(n, RHS, &param, &proof.compression_proof)?;
+        InnerProductProtocol::<C>::verify(LOGARITHMIC_DOMAIN, n, RHS, &param, &proof.compression_proof)?;
         let result = true;
         end_timer!(start);
         Ok(result)
     }
 }
 
+impl<C: CurveGroup, D: Digest> RingSignatureScheme<C, D> {
+    /// Verifies proof on msg against a ring held by the verifier, e.g., a sub-ring of a larger set,
+    /// instead of the parameters of the setup: the commitment parameters are derived from the ring size
+    /// and the session nonce is the one the verifier expects, so that the proof only verifies if its transcript
+    /// absorbed exactly this ring, in this order, in this session; a ring of any positive size is padded as by setup.
+    /// This scheme does not prove that the indicator vector is nonzero, see the modified compressed scheme
+    pub fn verify_with_ring(
+        proof: &LogarithmicRingSignature<C>,
+        vec_pk: &[C::Affine],
+        msg: &str,
+        session_nonce: &SessionNonce,
+    ) -> Result<bool, SigmaErrors> {
+        if vec_pk.is_empty() {
+            return Err(SigmaErrors::InvalidParameters(
+                "ring size should be positive".to_string(),
+            ));
+        }
+        let n = vec_pk.len().next_power_of_two();
+        let com_parameters = RingSignatureParams::<C>::deterministic_com_parameters(2, n)?;
+        let mut vec_pk = vec_pk.to_vec();
+        RingSignatureParams::pad_ring(&mut vec_pk, &com_parameters[2]);
+        let params = RingSignatureParams {
+            num_witness: 1 + n,
+            num_pub_inputs: n,
+            com_parameters,
+            message: msg.to_string(),
+            vec_pk,
+            session_nonce: *session_nonce,
+        };
+        Self::verify(&params, proof)
+    }
+
+    /// Same as prove, but also outputs the MSMs, transcript appends and phase durations it ran
+    #[cfg(feature = "metrics")]
+    pub fn prove_with_stats<R: Rng>(
+        rng: &mut R,
+        params: &RingSignatureParams<C>,
+        wit: &[C::ScalarField],
+    ) -> Result<(LogarithmicRingSignature<C>, metrics::ProveStats), SigmaErrors> {
+        let (proof, stats) = metrics::ProveStats::record(|| Self::prove(rng, params, &wit.to_vec()));
+        Ok((proof?, stats))
+    }
+
+    /// Same as verify, but also outputs the MSMs, transcript appends and phase durations it ran
+    #[cfg(feature = "metrics")]
+    pub fn verify_with_stats(
+        params: &RingSignatureParams<C>,
+        proof: &LogarithmicRingSignature<C>,
+    ) -> Result<(bool, metrics::VerifyStats), SigmaErrors> {
+        let (result, stats) = metrics::VerifyStats::record(|| Self::verify(params, proof));
+        Ok((result?, stats))
+    }
+
+    /// Replays the transcript of a signature and compares the challenges y, z, x it stores,
+    /// after checking the session nonce, the proof length and the message digest,
+    /// so that verify rejects a malformed or desynchronized proof before any group arithmetic
+    pub fn precheck_challenges(
+        params: &RingSignatureParams<C>,
+        proof: &LogarithmicRingSignature<C>,
+    ) -> Result<(), SigmaErrors> {
+        params.check_session_nonce(&proof.session_nonce)?;
+        if proof.commitments.len() != 5 || proof.challenges.len() != 3 {
+            return Err(SigmaErrors::InvalidProof(
+                "incorrect proof length".to_string(),
+            ));
+        }
+        let commitments = &proof.commitments;
+        let mut transcript = params.session_transcript(LOGARITHMIC_DOMAIN)?;
+        transcript.append_serializable_element(b"public list", &params.vec_pk)?;
+        transcript.append_serializable_element(b"commitments A,B", &[commitments[0].into_group(), commitments[1].into_group()])?;
+        let y = transcript.get_and_append_challenge(b"challenge y")?;
+        let z = transcript.get_and_append_challenge(b"challenge z")?;
+        transcript.append_serializable_element(b"commitments A,B", &[commitments[2].into_group(), commitments[3].into_group(), commitments[4].into_group()])?;
+        let h = D::digest(params.message.as_bytes());
+        if h != proof.digest {
+            return Err(SigmaErrors::InvalidProof(
+                "invalid message digest".to_string(),
+            ));
+        }
+        transcript.append_message_digest::<D>(&h)?;
+        let x = transcript.get_and_append_challenge(b"challenge x")?;
+
+        if [y, z, x] != proof.challenges[..] {
+            return Err(SigmaErrors::InvalidProof(
+                "invalid challenge value".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// the IPA parameters vec_G = vec_g \circ y^{-n} + vec_pk, vec_H = vec_h, u = v of the compression,
+// shared by prove and verify so that the two cannot diverge
+fn for_ring_signature<C: CurveGroup>(
+    params: &RingSignatureParams<C>,
+    y: C::ScalarField,
+) -> Result<InnerProductParam<C>, SigmaErrors> {
+    let (param_g_u, param_h_v) = (&params.com_parameters[0], &params.com_parameters[1]);
+    let y_inv = y.inverse().ok_or(SigmaErrors::InvalidProof("invalid challenge value".to_string()))?;
+    let powers_yn_inverse = generate_powers(y_inv, params.num_pub_inputs);
+    let vec_G: Vec<C> = param_g_u.vec_gen.iter().zip(powers_yn_inverse.iter()).zip(params.vec_pk.iter())
+        .map(|((g_i, y_inv_i), pk_i)| *g_i * y_inv_i + pk_i)
+        .collect();
+    InnerProductParam::builder()
+        .with_vec_G(C::normalize_batch(&vec_G))
+        .with_vec_H(param_h_v.vec_gen.clone())
+        .with_u(param_h_v.generator.into_affine())
+        .build()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_secp256k1::{Fr, Projective};
+    use ark_secp256k1::{Affine, Fr, Projective};
     use ark_std::UniformRand;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 
     #[test]
     fn test_ringsignature() {
@@ -380,6 +433,371 @@ mod tests {
         let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
         // verify algorithm
         let result = Ring::verify(&ring_params, &proof).unwrap();
-        assert_eq!(result, true);
+        assert!(result);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ringsignature_serde_json() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+
+        // the parameters and the signature verify after a JSON round trip
+        let params_json = serde_json::to_string(&ring_params).unwrap();
+        let proof_json = serde_json::to_string(&proof).unwrap();
+        let params_prime: RingSignatureParams<Projective> = serde_json::from_str(&params_json).unwrap();
+        let proof_prime: LogarithmicRingSignature<Projective> = serde_json::from_str(&proof_json).unwrap();
+        assert_eq!(params_prime, ring_params);
+        assert_eq!(proof_prime, proof);
+        assert!(Ring::verify(&params_prime, &proof_prime).unwrap());
+
+        // field elements and points are hex strings of their compressed bytes
+        let value: serde_json::Value = serde_json::from_str(&proof_json).unwrap();
+        assert_eq!(value["commitments"][0].as_str().unwrap().len(), 66);
+        assert_eq!(value["compression_proof"]["a"].as_str().unwrap().len(), 64);
+        // and a point off the curve is rejected
+        let mut value = value;
+        value["commitments"][0] = serde_json::Value::from("02".to_string() + &"ff".repeat(32));
+        assert!(serde_json::from_value::<LogarithmicRingSignature<Projective>>(value).is_err());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_ringsignature_stats() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let ring_params = Ring::setup(&mut rng, &mut wit, "Welcome to the world of Zero Knowledge!", 16).unwrap();
+        let (proof, prove_stats) = Ring::prove_with_stats(&mut rng, &ring_params, &wit).unwrap();
+        let (result, verify_stats) = Ring::verify_with_stats(&ring_params, &proof).unwrap();
+        assert!(result);
+
+        // the prover runs 8 MSMs for A, B, E, T1, T2 and 2 per round of the compression, log2(16) = 4 rounds,
+        // and the verifier one for g^fs, one for P^{z y^n} and one for the final check of the compression
+        assert_eq!(prove_stats.msm_count, 8 + 2 * 4);
+        assert_eq!(verify_stats.msm_count, 3);
+        // both run the same transcript
+        assert_eq!(prove_stats.transcript_appends, verify_stats.transcript_appends);
+        let prove_phases: Vec<&str> = prove_stats.phases.iter().map(|(name, _)| *name).collect();
+        let verify_phases: Vec<&str> = verify_stats.phases.iter().map(|(name, _)| *name).collect();
+        assert_eq!(prove_phases, ["commitments", "openings", "compression"]);
+        assert_eq!(verify_phases, ["precheck", "preprocessing", "compression"]);
+    }
+
+    #[test]
+    fn test_verify_with_ring() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 8).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+
+        // the verifier picks the ring of the proof out of a larger set
+        let mut superset = ring_params.vec_pk.clone();
+        superset.extend((0..8).map(|_| Affine::rand(&mut rng)));
+        assert!(Ring::verify_with_ring(&proof, &superset[..8], &message, &ring_params.session_nonce).unwrap());
+
+        // a ring with one substituted key, a reordered ring, the superset and another message are rejected
+        let mut ring_prime = superset[..8].to_vec();
+        ring_prime[3] = superset[12];
+        assert!(Ring::verify_with_ring(&proof, &ring_prime, &message, &ring_params.session_nonce).is_err());
+        let mut ring_prime = superset[..8].to_vec();
+        ring_prime.swap(0, 1);
+        assert!(Ring::verify_with_ring(&proof, &ring_prime, &message, &ring_params.session_nonce).is_err());
+        assert!(Ring::verify_with_ring(&proof, &superset, &message, &ring_params.session_nonce).is_err());
+        assert!(Ring::verify_with_ring(&proof, &superset[..8], "another message", &ring_params.session_nonce).is_err());
+        assert!(Ring::verify_with_ring(&proof, &superset[..6], &message, &ring_params.session_nonce).is_err());
+
+        // a proof of the same ring and message in another session is rejected
+        let mut params_prime = ring_params.clone();
+        params_prime.session_nonce = SessionNonce::rand(&mut rng);
+        let proof_prime = Ring::prove(&mut rng, &params_prime, &wit).unwrap();
+        assert!(Ring::verify_with_ring(&proof_prime, &superset[..8], &message, &params_prime.session_nonce).unwrap());
+        assert!(Ring::verify_with_ring(&proof_prime, &superset[..8], &message, &ring_params.session_nonce).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_padded() {
+        let mut rng = ark_std::test_rng();
+        let sk = Fr::rand(&mut rng);
+        let mut wit = vec![sk];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        // a ring of 100 keys is padded with 28 decoys to the IPA length 128
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 100).unwrap();
+        assert_eq!((ring_params.num_pub_inputs, ring_params.vec_pk.len()), (128, 128));
+        assert_eq!(ring_params.ring_size(), 100);
+        assert!(wit[1 + 100..].iter().all(|b_i| b_i.is_zero()));
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(Ring::verify(&ring_params, &proof).unwrap());
+        // a verifier holding the 100 keys pads them the same way
+        assert!(Ring::verify_with_ring(&proof, &ring_params.vec_pk[..100], &message, &ring_params.session_nonce).unwrap());
+        assert!(Ring::verify_with_ring(&proof, &ring_params.vec_pk[..99], &message, &ring_params.session_nonce).is_err());
+
+        // the decoys are commitments to zero with known randoms, so that no secret key opens them:
+        // an indicator on the padding does not verify, not even for the secret key zero
+        let key_params = &ring_params.com_parameters[2];
+        assert_eq!(ring_params.vec_pk[110], (key_params.generator * Fr::from(110u64)).into_affine());
+        for sk_prime in [sk, Fr::zero()] {
+            let mut wit_prime = vec![sk_prime; 1];
+            wit_prime.extend((0..128).map(|i| Fr::from((i == 110) as u64)));
+            let proof_prime = Ring::prove(&mut rng, &ring_params, &wit_prime).unwrap();
+            assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+        }
+
+        // a ring of a power of two is not padded, and an empty ring is rejected
+        let ring_params = Ring::setup(&mut rng, &mut vec![sk], &message, 8).unwrap();
+        assert_eq!((ring_params.vec_pk.len(), ring_params.ring_size()), (8, 8));
+        assert!(Ring::setup(&mut rng, &mut vec![sk], &message, 0).is_err());
+        assert!(Ring::verify_with_ring(&proof, &[], &message, &ring_params.session_nonce).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_malformed() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+
+        // a serialized signature verifies after deserialization
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let proof_prime = LogarithmicRingSignature::<Projective>::deserialize_compressed(&bytes[..]).unwrap();
+        assert!(Ring::verify(&ring_params, &proof_prime).unwrap());
+
+        // malformed signatures are rejected without panicking
+        let mut proof_prime = proof.clone();
+        proof_prime.commitments.pop();
+        assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+        let mut proof_prime = proof.clone();
+        proof_prime.challenges[0] = Fr::zero();
+        assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+        let mut proof_prime = proof.clone();
+        proof_prime.digest = String::from("digest");
+        assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+        let mut proof_prime = proof.clone();
+        proof_prime.compression_proof.vec_R.pop();
+        assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+    }
+
+    #[test]
+    fn test_message_digest() {
+        use toolbox::hash::Keccak256;
+        type Ring = RingSignatureScheme<Projective>;
+        type KeccakRing = RingSignatureScheme<Projective, Keccak256>;
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let ring_params = Ring::setup(&mut rng, &mut wit, "message", 4).unwrap();
+
+        // the same message and nonces under SHA-256 and Keccak-256 give distinct proofs, each of which verifies
+        let proof = Ring::prove(&mut ark_std::test_rng(), &ring_params, &wit).unwrap();
+        let proof_keccak = KeccakRing::prove(&mut ark_std::test_rng(), &ring_params, &wit).unwrap();
+        assert_ne!(proof.digest, proof_keccak.digest);
+        assert_ne!(proof.challenges, proof_keccak.challenges);
+        assert!(Ring::verify(&ring_params, &proof).unwrap());
+        assert!(KeccakRing::verify(&ring_params, &proof_keccak).unwrap());
+
+        // a proof does not verify under the other digest, even with the digest it carries swapped
+        assert!(Ring::verify(&ring_params, &proof_keccak).is_err());
+        assert!(KeccakRing::verify(&ring_params, &proof).is_err());
+        let mut proof_prime = proof_keccak.clone();
+        proof_prime.digest = proof.digest.clone();
+        assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+    }
+
+    #[test]
+    fn test_message_digest_blake2() {
+        use toolbox::hash::Blake2s256;
+        type Ring = RingSignatureScheme<Projective>;
+        type Blake2Ring = RingSignatureScheme<Projective, Blake2s256>;
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let ring_params = Ring::setup(&mut rng, &mut wit, "message", 4).unwrap();
+
+        // as for Keccak-256, the BLAKE2s-256 proof differs from the SHA-256 one and only verifies under its own digest
+        let proof = Ring::prove(&mut ark_std::test_rng(), &ring_params, &wit).unwrap();
+        let proof_blake2 = Blake2Ring::prove(&mut ark_std::test_rng(), &ring_params, &wit).unwrap();
+        assert_ne!(proof.digest, proof_blake2.digest);
+        assert_ne!(proof.challenges, proof_blake2.challenges);
+        assert!(Blake2Ring::verify(&ring_params, &proof_blake2).unwrap());
+        assert!(Ring::verify(&ring_params, &proof_blake2).is_err());
+        assert!(Blake2Ring::verify(&ring_params, &proof).is_err());
+    }
+
+    #[test]
+    fn test_precheck_challenges() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        Ring::precheck_challenges(&ring_params, &proof).unwrap();
+
+        // a corrupted stored challenge is reported by the precheck, before the IPA would
+        // have failed on its truncated proof, and without reading the commitment parameters
+        let mut proof_prime = proof.clone();
+        proof_prime.challenges[2] += Fr::from(1u64);
+        proof_prime.compression_proof.vec_L.clear();
+        let mut params_prime = ring_params.clone();
+        params_prime.com_parameters[0].vec_gen.clear();
+        for params in [&ring_params, &params_prime] {
+            assert!(matches!(Ring::verify(params, &proof_prime), Err(SigmaErrors::InvalidProof(msg)) if msg == "invalid challenge value"));
+        }
+        assert!(Ring::precheck_challenges(&ring_params, &proof_prime).is_err());
+        // as are a stored challenge missing and the challenges of another signature
+        let mut proof_prime = proof.clone();
+        proof_prime.challenges.pop();
+        assert!(Ring::precheck_challenges(&ring_params, &proof_prime).is_err());
+        let mut proof_prime = proof.clone();
+        proof_prime.challenges = Ring::prove(&mut rng, &ring_params, &wit).unwrap().challenges;
+        assert!(Ring::precheck_challenges(&ring_params, &proof_prime).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_session_nonce() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 8).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert_eq!(proof.session_nonce, ring_params.session_nonce);
+        assert!(Ring::verify(&ring_params, &proof).unwrap());
+
+        // another session over the same ring, generators and message
+        let mut other_params = ring_params.clone();
+        other_params.session_nonce = SessionNonce::rand(&mut rng);
+        assert_ne!(other_params.session_nonce, ring_params.session_nonce);
+        let is_invalid_proof = |result: Result<bool, SigmaErrors>| matches!(result, Err(SigmaErrors::InvalidProof(_)));
+        assert!(is_invalid_proof(Ring::verify(&other_params, &proof)));
+
+        // swapping the nonce of the other session into the proof does not replay it there,
+        // since the challenges were derived from the nonce of the first session
+        let mut replayed = proof.clone();
+        replayed.session_nonce = other_params.session_nonce;
+        assert!(is_invalid_proof(Ring::verify(&other_params, &replayed)));
+        assert!(is_invalid_proof(Ring::verify(&ring_params, &replayed)));
+    }
+
+    #[test]
+    fn test_deterministic_com_parameters() {
+        // independent setups derive the same generators, which a verifier re-derives from the ring size
+        let mut rng = ark_std::test_rng();
+        type Ring = RingSignatureScheme<Projective>;
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let ring_params = Ring::setup(&mut rng, &mut wit, "message", 8).unwrap();
+        let mut wit_other = vec![Fr::rand(&mut rng)];
+        let other_params = Ring::setup(&mut rng, &mut wit_other, "another message", 8).unwrap();
+        assert_eq!(other_params.com_parameters, ring_params.com_parameters);
+        assert_eq!(RingSignatureParams::<Projective>::deterministic_com_parameters(2, 8).unwrap(), ring_params.com_parameters);
+        assert_ne!(other_params.vec_pk, ring_params.vec_pk);
+
+        // the sets are pairwise unrelated, and the key generator does not depend on the ring size
+        let [g_u, h_v, key] = ring_params.com_parameters.as_slice() else { panic!("expected three parameter sets") };
+        assert_ne!(g_u.generator, h_v.generator);
+        assert!(h_v.vec_gen.iter().all(|h_i| !g_u.vec_gen.contains(h_i)));
+        assert_eq!(RingSignatureParams::<Projective>::deterministic_com_parameters(2, 4).unwrap()[2], *key);
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(Ring::verify(&ring_params, &proof).unwrap());
+    }
+
+    #[test]
+    fn test_for_ring_signature() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 8).unwrap();
+        let y = Fr::rand(&mut rng);
+        let param = for_ring_signature(&ring_params, y).unwrap();
+        let y_inv = y.inverse().unwrap();
+        assert_eq!(param.vec_G[0], (ring_params.com_parameters[0].vec_gen[0] * y_inv + ring_params.vec_pk[0]).into_affine());
+        assert_eq!(param.vec_H, ring_params.com_parameters[1].vec_gen);
+
+        // a zero challenge and a ring that does not match the generators are rejected
+        assert!(for_ring_signature(&ring_params, Fr::zero()).is_err());
+        let mut params_prime = ring_params.clone();
+        params_prime.vec_pk.pop();
+        assert!(for_ring_signature(&params_prime, y).is_err());
+    }
+
+    #[test]
+    fn test_proof_size_bytes() {
+        use crate::ringsig::protocol_linear;
+        type Linear = protocol_linear::RingSignatureScheme<Projective>;
+        type Ring = RingSignatureScheme<Projective>;
+        let mut rng = ark_std::test_rng();
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        for ring_size in [32, 64] {
+            let mut wit = vec![Fr::rand(&mut rng)];
+            let params = Linear::setup(&mut rng, &mut wit, &message, ring_size).unwrap();
+            let proof_linear = Linear::prove(&mut rng, &params, &wit).unwrap();
+            let mut wit = vec![Fr::rand(&mut rng)];
+            let params = Ring::setup(&mut rng, &mut wit, &message, ring_size).unwrap();
+            let proof_log = Ring::prove(&mut rng, &params, &wit).unwrap();
+
+            // the sizes match the serialized signatures
+            let mut bytes = vec![];
+            proof_log.serialize_compressed(&mut bytes).unwrap();
+            assert_eq!(proof_log.proof_size_bytes(), bytes.len());
+            assert!(proof_log.proof_size_bytes() < proof_linear.proof_size_bytes());
+        }
+    }
+
+    // asserts the size of a signature for a ring of 2^log_n members:
+    // a signature holds 2*log2(n) points L, R and log2(n) IPA challenges, and a constant part:
+    // 5 commitments, zeta and eta of length 1, hat_t, taux, mu, fs, the challenges y, z, x,
+    // the IPA a and b, the hex digest, the 32-byte session nonce and the length prefixes of the 7 vectors and the digest
+    fn check_proof_size(log_n: usize) {
+        type Ring = RingSignatureScheme<Projective>;
+        let mut rng = ark_std::test_rng();
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let (point, scalar, prefix) = (Affine::default().compressed_size(), Fr::zero().compressed_size(), 8);
+        let constant = 5*point + 11*scalar + 64 + 32 + 8*prefix;
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let params = Ring::setup(&mut rng, &mut wit, &message, 1 << log_n).unwrap();
+        let proof = Ring::prove(&mut rng, &params, &wit).unwrap();
+        assert_eq!(proof.proof_size_bytes(), constant + log_n*(2*point + scalar));
+        assert_eq!(proof.compression_proof.size_in_bytes(), 3*prefix + 2*scalar + log_n*(2*point + scalar));
+    }
+
+    #[test]
+    fn test_proof_size_formula() {
+        for log_n in 6..=8 {
+            check_proof_size(log_n);
+        }
+    }
+
+    // the rings of 2^9 to 2^12 members take minutes to prove in debug builds
+    #[test]
+    #[ignore]
+    fn test_proof_size_formula_large() {
+        for log_n in 9..=12 {
+            check_proof_size(log_n);
+        }
+    }
+
+    #[test]
+    fn test_ringsignature_bls() {
+        use ark_bls12_381::{Fr as G1Fr, G1Projective};
+        // the same flow on the G1 group of a pairing-friendly curve
+        let mut rng = ark_std::test_rng();
+        let ring_size = 16;
+        let sk = G1Fr::rand(&mut rng);
+        let mut wit = vec![sk];
+        type Ring = RingSignatureScheme<G1Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, ring_size).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        let result = Ring::verify(&ring_params, &proof).unwrap();
+        assert!(result);
     }
-}
\ No newline at end of file
+}
@@ -1,561 +1,1082 @@
-use std::fs::Permissions;
-use std::io::Write;
-use std::marker::PhantomData;
-
-use ark_ec::CurveGroup;
-use ark_ff::Field;
-use ark_std::{end_timer, rand::Rng, start_timer, UniformRand, Zero, One};
-use sha256::digest;
-
-use bulletproofs::ipa::*;
-use bulletproofs::structs::*;
-use crate::commitment::pedersen::PedersenCommitmentScheme;
-use crate::commitment::PedersenParams;
-use crate::ringsig::structs::{LogarithmicRingSignature, RingSignatureParams, Openings};
-use toolbox::sigma::{transcript::ProofTranscript, SigmaProtocol};
-use toolbox::errors::SigmaErrors;
-use toolbox::vec::*;
-
-#[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct RingSignatureScheme<C>
-where
-    C: CurveGroup,
-{
-    phantom: PhantomData<C>,
-}
-
-/// Implement a sigma protocol as a ring signature scheme (without compression), including 5-move:
-/// Relation: P knows a sk to a pk among the vector vec_pk
-/// Formalized Relation: P knows a sk satisfying <vec_pk, vec_b> = com(sk)
-impl<C> SigmaProtocol<C> for RingSignatureScheme<C>
-where
-    C: CurveGroup,
-{
-    /// public parameters
-    type PublicParams = RingSignatureParams<C>;
-    /// witness
-    type Witness = Vec<C::ScalarField>;
-    /// witness commitments
-    type Commitments = Vec<C::Affine>;
-    // challenge
-    type Challenge = Vec<C::ScalarField>;
-    /// proof
-    type Proof = LogarithmicRingSignature<C>;
-
-    fn setup<R: Rng>(
-        rng: &mut R,
-        wit: &mut Self::Witness, // secret key
-        msg: &String,
-        supported_size: usize, // ring size
-    ) -> Result<Self::PublicParams, SigmaErrors> {
-        // generate commitment scheme parameters (vec_g, u)
-        let com_params_1 = PedersenCommitmentScheme::<C>::setup(rng, supported_size)?;
-        // generate commitment scheme parameters (vec_h, v)
-        let com_params_2 = PedersenCommitmentScheme::<C>::setup(rng, supported_size)?;
-
-        //生成(vec_g_2,u_2)
-        let com_params_3 = PedersenCommitmentScheme::<C>::setup(rng, supported_size)?;
-        //生成(vec_h_2,v_2)
-        let com_params_4 = PedersenCommitmentScheme::<C>::setup(rng, supported_size)?;
-        
-        // generate public key parameters (g)
-        let key_params = PedersenCommitmentScheme::<C>::setup(rng, 1)?;
-
-        // generate pk vectors
-        let pk:C::Affine = PedersenCommitmentScheme::commit(&key_params, wit, &C::ScalarField::zero(), "as pk")?.into_affine();
-        let mut vec_pk = vec![C::Affine::rand(rng); 2*supported_size-1];
-        // add pk to the vector and shuffle it
-        vec_pk.push(pk);
-        let vec_b = shuffle::<C>(&mut vec_pk, pk);
-        wit.extend(vec_b);
-
-        Ok(RingSignatureParams {
-            num_witness: wit.len(),
-            num_pub_inputs: supported_size,
-            com_parameters: vec![com_params_1, com_params_2, com_params_3,com_params_4,key_params],
-            message: msg.clone(),
-            vec_pk,
-        })
-    }
-
-    fn prove<R: Rng>(
-        rng: &mut R,
-        params: &Self::PublicParams,
-        wit: &Self::Witness,
-    ) -> Result<Self::Proof, SigmaErrors> {
-        // initialization
-        let start = start_timer!(|| "running sigma protocol prove algorithm...");
-        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingSignature");
-        transcript.append_serializable_element(b"public list", &params.vec_pk)?;
-        // parse commitment parameters
-        let param_g_1_u_1 = &params.com_parameters[0];
-        let param_h_1_v_1 = &params.com_parameters[1];
-        let param_g_2_u_2=&params.com_parameters[2];
-        let param_h_2_v_2=&params.com_parameters[3];
-        let param_key = &params.com_parameters[4];
-        // parse wit as vec_sk and vec_b
-        let vec_sk = wit[0..wit.len()-params.num_pub_inputs].to_vec();
-        let vec_b = wit[wit.len()-params.num_pub_inputs..].to_vec();
-         // 定义 b' = b - bits(1)，其中 bits(1) 是第一个元素为 1，其余为 0 的向量
-        let vec_b_prime: Vec<C::ScalarField> = {
-        let n = vec_b.len();
-        // 构造 bits(1) 向量：第一个元素为 1，其余为 0
-        let mut bits = vec![C::ScalarField::zero(); n];
-        if n > 0 {
-            bits[0] = C::ScalarField::one();
-        }
-        // 逐元素相减得到 b'
-        vec_b
-            .iter()
-            .zip(bits.iter())
-            .map(|(&b_i, &bit_i)| b_i - bit_i)
-            .collect()
-        };
-        // denote b_0 = b, b_1 = 1^n - b_0
-        let vec_b0 = vec_b.clone();
-        let vec_b1: Vec<C::ScalarField> = vec_b.iter()
-            .map(|&b_i| C::ScalarField::one() - b_i)
-            .collect();
-        // 定义 b_2 = b'，b_3 = 1^n - b'
-        let (vec_b2, vec_b3): (Vec<C::ScalarField>, Vec<C::ScalarField>) = {
-            //let n = vec_b_prime.len();
-            // 生成全 1 向量 1^n
-            let ones = vec![C::ScalarField::one(); params.num_pub_inputs];
-            // 计算 b_3 = 1^n - b'
-            let vec_b3: Vec<C::ScalarField> = ones
-                .into_iter()
-                .zip(vec_b_prime.iter())
-                .map(|(one_i, &b_prime_i)| one_i - b_prime_i)
-                .collect();
-            // b_2 直接等于 b'
-            let vec_b2 = vec_b_prime.clone();
-            (vec_b2, vec_b3)
-        };
-        // sanity check
-        // b_0 + b_1 = 1^n
-        // b_0 \circ b_1 = 0^n
-        let constraint_1 = vec_b0.iter()
-            .zip(vec_b1.iter())
-            .all(|(&b0_i, &b1_i)| b0_i + b1_i == C::ScalarField::one());
-        let constraint_2 = vec_b0.iter()
-            .zip(vec_b1.iter())
-            .all(|(&b0_i, &b1_i)| b0_i * b1_i == C::ScalarField::zero());
-        assert!(constraint_1 && constraint_2);
-
-
-        //check b_2 b_3
-        // 约束检查：b_2 + b_3 = 1^n 且 b_2 ◦ b_3 = 0^n
-        //let check_sum = vec_b2.iter()
-        // .zip(vec_b3.iter())
-        // .all(|(&b2_i, &b3_i)| b2_i + b3_i == C::ScalarField::one());
-
-        // let check_product = vec_b2.iter()
-        // .zip(vec_b3.iter())
-        // .all(|(&b2_i, &b3_i)| b2_i * b3_i == C::ScalarField::zero());
-
-        // assert!(check_sum && check_product, "b_2/b_3 constraints failed");
-
-        // computes A = g^{b_0}h^{b_1}u^{alpha}, B = g^{r_0}h^{r_1}u^{beta},C D
-        let alpha_1 = C::ScalarField::rand(rng);
-        let alpha_2 = C::ScalarField::rand(rng);
-        let alpha_3 = C::ScalarField::rand(rng);
-        let alpha_4 = C::ScalarField::rand(rng);
-        //let beta = C::ScalarField::rand(rng);
-        let vec_r0 = vec![C::ScalarField::rand(rng); vec_b0.len()];
-        let vec_r1 = vec![C::ScalarField::rand(rng); vec_b1.len()];
-        let vec_r2 = vec![C::ScalarField::rand(rng); vec_b1.len()];
-        let vec_r3 = vec![C::ScalarField::rand(rng); vec_b1.len()];
-        let com_A = PedersenCommitmentScheme::commit(&param_g_1_u_1, &vec_b0, &alpha_1, "on b0")?
-            + PedersenCommitmentScheme::commit(&param_h_1_v_1, &vec_b1, &C::ScalarField::zero(), "on b1")?;
-        let com_B = PedersenCommitmentScheme::commit(&param_g_1_u_1, &vec_r0, &alpha_2, "on r0")?
-            + PedersenCommitmentScheme::commit(&param_h_1_v_1, &vec_r1, &C::ScalarField::zero(), "on r1")?;
-        let com_C = PedersenCommitmentScheme::commit(&param_g_2_u_2, &vec_b2, &alpha_3, "on b2")?
-        + PedersenCommitmentScheme::commit(&param_h_2_v_2, &vec_b3, &C::ScalarField::zero(), "on b3")?;
-        let com_D = PedersenCommitmentScheme::commit(&param_g_2_u_2, &vec_r2, &alpha_4, "on r2")?
-        + PedersenCommitmentScheme::commit(&param_h_2_v_2, &vec_r3, &C::ScalarField::zero(), "on r3")?;
-
-        // P->V: A,B
-        transcript.append_serializable_element(b"commitments A,B,C,D", &[com_A, com_B,com_C,com_D])?;
-
-        // V->P: challenges y,z
-        let y = transcript.get_and_append_challenge(b"challenge y")?;
-        let z = transcript.get_and_append_challenge(b"challenge z")?;
-
-        // t1 = <r_0 \circ y^n, z*1^n + b_1> + <(b0 + z*1^n) \circ y^n, r_1>
-        let powers_yn = generate_powers(y, params.num_pub_inputs);
-        let vec_z1n = vec![z; params.num_pub_inputs];
-        let vec_r0_yn = hadamard_product(&vec_r0, &powers_yn);
-        let mut vec_r0_yn_expanded=vec_r0_yn.clone();
-        vec_r0_yn_expanded.extend(vec![C::ScalarField::zero();vec_r0_yn.len()]);
-        //let vec_z1n_b1 = vec_add(&vec_z1n, &vec_b1);
-        //let vec_b0_z1n_yn = hadamard_product(&vec_add(&vec_z1n, &vec_b0), &powers_yn);
-        //let t1 = inner_product(&vec_r0_yn, &vec_z1n_b1) + inner_product(&vec_b0_z1n_yn, &vec_r1);
-        // t2 = <r0 \circ y^n, r_1>
-        //let t2 = inner_product(&vec_r0_yn, &vec_r1);
-        let z2=z.pow(&[2]);
-        // 计算 z^3
-        let z3 = z.pow(&[3]); // z^3
-        // 计算 z^5
-        let z5 = z.pow(&[5]); // z^5
-        // 计算 z^7
-        let z7 = z.pow(&[7]); // z^7
-        //let two_power_n = vec![C::ScalarField::from(2u64); params.num_pub_inputs];
-        let two_power_n=generate_powers(C::ScalarField::from(2u64), params.num_pub_inputs);
-        let z2_b3: Vec<_>=vec_b3.iter().map(|&x| x * z2).collect(); 
-        let z3_1n= vec![z3; params.num_pub_inputs];
-        let z7_2n: Vec<_> = two_power_n.iter().map(|&x| z7 * x).collect();
-        let z5_2n: Vec<_> = two_power_n.iter().map(|&x| z5 * x).collect();
-        let b1_z2_b3=[vec_b1, z2_b3].concat();
-        let z_1n_z3_1n=[vec_z1n,z3_1n].concat();
-        let z7_2n_z5_2n=[z7_2n,z5_2n].concat();
-        let z2_b2:Vec<_>=vec_b2.iter().map(|&x| x*z2).collect();
-        let b0_z2_b2=[vec_b0,z2_b2].concat();
-        let yn_yn=[powers_yn.clone(),powers_yn.clone()].concat();
-        let t1_part11=hadamard_product(&([vec_r0.clone(), vec_r2.clone()].concat()),&([powers_yn.clone(),powers_yn.clone()].concat()));
-        let t1_part12:Vec<_>=b1_z2_b3.iter()
-            .zip(z_1n_z3_1n.iter())
-            .zip(z7_2n_z5_2n.iter())
-            .map(|((&x1,&x2),&x3)| x1+x2+x3)
-            .collect();
-        let t1_part1=inner_product(&t1_part11,&t1_part12);
-        let t1_part211:Vec<_>=b0_z2_b2.iter()
-            .zip(z_1n_z3_1n.iter())
-            .map(|(&x1,&x2)| x1+x2)
-            .collect();
-        let t1_part21:Vec<_>=hadamard_product(&t1_part211, &yn_yn);
-        let t1_part22:Vec<_>=[vec_r1.clone(),vec_r3.clone()].concat();
-        let t1_part2=inner_product(&t1_part21, &t1_part22);
-        let t1=t1_part1+t1_part2;
-
-        let t2_part11:Vec<_>=[vec_r0.clone(),vec_r2.clone()].concat();
-        let t2_part1:Vec<_>=hadamard_product(&t2_part11, &yn_yn);
-        let t2_part2:Vec<_>=[vec_r1.clone(),vec_r3.clone()].concat();
-        let t2=inner_product(&t2_part1, &t2_part2);
-        // computes
-        // E = P^{y^n \circ r_0} Com_{ck}(0; -r_s)
-        // T1 = v^{t1}u^{tau1}
-        // T2 = v^{t2}u^{tau2}
-        let rs = C::ScalarField::rand(rng);
-        let neg_rs = -rs.clone();
-        let tau1 = C::ScalarField::rand(rng);
-        let tau2 = C::ScalarField::rand(rng);
-
-        let com_E = C::msm(&params.vec_pk, &vec_r0_yn_expanded).unwrap() + PedersenCommitmentScheme::commit(&param_key, &vec![neg_rs], &C::ScalarField::zero(), "E")?;
-        
-        let param_u_v = PedersenParams {
-            generator: param_h_1_v_1.generator.clone(),
-            vec_gen: vec![param_g_1_u_1.generator.into_affine().clone()],
-        };
-        let com_T1 = PedersenCommitmentScheme::commit(&param_u_v, &vec![tau1], &t1, "T1")?;
-        let com_T2 = PedersenCommitmentScheme::commit(&param_u_v, &vec![tau2], &t2, "T2")?;
-        // P->V: E, T1, T2
-        transcript.append_serializable_element(b"commitments E,T1,T2", &[com_E, com_T1, com_T2])?;
-
-        // append the message digest to the transcript
-        let h = digest(&params.message);
-        let mut h_msg: &mut [u8] = &mut [0; 32];
-        h_msg.write(h.as_bytes()).unwrap();
-        
-        transcript.append_message(b"message digest", &h_msg)?;
-        // V->P: challenges x
-        let x = transcript.get_and_append_challenge(b"challenge x")?;
-
-        // computes zeta = (b_0 + z*1^n + r_0*x) \circ y^n, eta = b_1 + z*1^n + r_1*x
-        //let b0_z1n_r0x = vec_add(&vec_b0, &vec_add(&vec_z1n, &scalar_product(&vec_r0, &x)));
-        //let zeta = hadamard_product(&b0_z1n_r0x, &powers_yn);
-        //let eta = vec_add(&vec_b1, &vec_add(&vec_z1n, &scalar_product(&vec_r1, &x)));
-
-        let r1_r3=[vec_r1,vec_r3].concat();
-        let r0_r2=[vec_r0,vec_r2].concat();
-        let r0_r2_x:Vec<_>=r0_r2.iter().map(|&a| a*x).collect();
-        let r1_r3_x:Vec<_>=r1_r3.iter().map(|&a| a*x).collect();
-        let zeta_1=vec_add(&vec_add(&b0_z2_b2, &z_1n_z3_1n),&r0_r2_x);
-        let zeta=hadamard_product(&zeta_1, &yn_yn);
-        
-        let eta_1=vec_add(&b1_z2_b3, &z_1n_z3_1n);
-        let eta_2=vec_add(&z7_2n_z5_2n, &r1_r3_x);
-        let eta=vec_add(&eta_1, &eta_2);
-        // computes hat_t = <zeta, eta>
-        let hat_t = inner_product(&zeta, &eta);
-        // // sanity check
-        // let vec_1n = vec![C::ScalarField::one(); params.num_pub_inputs];
-        // let delta = inner_product(&vec_1n, &powers_yn) * (z+z*z);
-        // let t_prime = delta + t1*x + t2*x*x;
-        // if t_prime == hat_t {println!("delta equality passes")}
-        // else {println!("delta equality fails")}
-
-        // tau_x = tau1*x + tau2*x^2
-        let taux = tau1*x + tau2*x*x;
-        // mu = alpha + beta*x
-        let mu_1 = alpha_1 + alpha_2*x;
-        let mu_2=alpha_2*z*z+alpha_4*x;
-        // fs = \sum_{j=1}^k y^{i_j} s_j + r_s*x
-        let mut j = 1;
-        let mut sum = C::ScalarField::zero();
-        for i in 0..params.num_pub_inputs {
-            let term = powers_yn[i]*vec_b[i];
-            if term != C::ScalarField::zero() {
-                sum += term*vec_sk[j];
-            
-            }
-            j += 1;
-        }
-        let fs = sum + rs*x;
-
-        // Bulletproofs Compression
-        let powers_yn_inverse = generate_powers(y.inverse().unwrap(), params.num_pub_inputs);
-        let mut vec_g1_yn = Vec::with_capacity(param_g_1_u_1.vec_gen.len());
-        let mut vec_g2_yn = Vec::with_capacity(param_g_2_u_2.vec_gen.len());
-        for i in 0..param_g_1_u_1.vec_gen.len() {
-            vec_g1_yn.push((param_g_1_u_1.vec_gen[i]*powers_yn_inverse[i]).into_affine());
-        }
-        for i in 0..param_g_2_u_2.vec_gen.len() {
-            vec_g2_yn.push((param_g_2_u_2.vec_gen[i]*powers_yn_inverse[i]).into_affine());
-        }
-        let vec_g_yn=[vec_g1_yn.clone(),vec_g2_yn.clone()].concat();
-        let n = vec_g_yn.len();
-        let mut vec_G:Vec<C::Affine> = Vec::with_capacity(n);
-        for i in 0..n {
-            vec_G.push((vec_g_yn[i] + params.vec_pk[i]).into_affine());
-        }
-        let vec_H = [param_h_1_v_1.vec_gen.clone(),param_h_2_v_2.vec_gen.clone()].concat();
-        let v = param_h_1_v_1.generator.clone().into_affine();
-        let factors_G = vec![C::ScalarField::from(1u64); n];
-        let factors_H = vec![C::ScalarField::from(1u64); n];
-        let param = InnerProductParam {
-            factors_G,
-            factors_H,
-            u: v,
-            vec_G,
-            vec_H,
-        };
-
-        let proof = InnerProductProtocol::<C>::prove(&param, zeta.clone(), eta.clone())?;
-
-        let openings = Openings {
-            zeta: vec![proof.a],
-            eta: vec![proof.b],
-            hat_t,
-            taux,
-            mu_1,
-            mu_2,
-            fs,
-        };
-        assert_eq!(j, vec_sk.len());
-
-        // proving ends
-        end_timer!(start);
-        Ok(LogarithmicRingSignature {
-            commitments: vec![com_A, com_B,com_C,com_D, com_E, com_T1, com_T2],
-            openings,
-            compression_proof: proof,
-            challenges: vec![y,z,x],
-            digest: h.clone(),
-        })
-    }
-
-    fn verify(
-        params: &Self::PublicParams,
-        proof: &Self::Proof
-    ) -> Result<bool, SigmaErrors> {
-        // initialization
-        let start = start_timer!(|| "preprocessing sigma protocol verify algorithm...");
-        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingSignature");
-        transcript.append_serializable_element(b"public list", &params.vec_pk)?;
-        // parse commitment parameters
-        let param_g_1_u_1 = &params.com_parameters[0];
-        let param_h_1_v_1 = &params.com_parameters[1];
-        let param_g_2_u_2=&params.com_parameters[2];
-        let param_h_2_v_2=&params.com_parameters[3];
-        let param_key = &params.com_parameters[4];
-
-        // parse proof
-        let commitments = &proof.commitments;
-        let (com_A, com_B,com_C ,com_D,com_E, com_T1, com_T2) = (commitments[0], commitments[1], commitments[2], commitments[3], commitments[4],commitments[5],commitments[6]);
-        let openings = &proof.openings;
-        let challenges = &proof.challenges;
-        let digest = &proof.digest;
-
-        let (y,z,x) = (challenges[0],challenges[1],challenges[2]);
-
-        let vec_0n = vec![C::ScalarField::zero(); params.num_pub_inputs];
-        let vec_1n = vec![C::ScalarField::one(); params.num_pub_inputs];
-        let vec_12n = vec![C::ScalarField::one(); 2*params.num_pub_inputs];
-        let powers_yn = generate_powers(y, params.num_pub_inputs);
-
-        // check validity of T1 T2
-        // v^{hat_t} = v^delta T1^x T2^{x^2} y^{-taux}
-        // where hat_t = <zeta, eta>
-        // let t = inner_product(&openings.zeta, &openings.eta);
-        // assert_eq!(openings.hat_t, t, "step 1: hat_t check fails");
-        let z2=z.pow(&[2]);
-        // 计算 z^3
-        let z3 = z.pow(&[3]); // z^3
-        // 计算 z^5
-        let z5 = z.pow(&[5]); // z^5
-        // 计算 z^7
-        let z7 = z.pow(&[7]); // z^7
-        let z6=z.pow(&[6]);
-        let z8=z.pow(&[8]);
-        let two_power_n = vec![C::ScalarField::from(2u64); params.num_pub_inputs];
-        let yn_yn=[powers_yn.clone(),powers_yn.clone()].concat();
-        let delta_1=(z+z2+z5+z6+z7)*inner_product(&vec_1n, &powers_yn.clone());
-        let delta_21=hadamard_product(&vec_12n, &yn_yn);
-        let delta_22=[two_power_n.clone(),two_power_n.clone()].concat();
-        let delta_2=z8*inner_product(&delta_21, &delta_22);
-        let delta=delta_1+delta_2;
-        //let delta = inner_product(&vec_1n, &powers_yn) * (z+z*z);
-
-        //let lhs_step1 = PedersenCommitmentScheme::commit(param_h_1_v_1, &vec_0n, &openings.hat_t, "on hat_t")?;
-        let rhs_step1 = PedersenCommitmentScheme::commit(param_h_1_v_1, &vec_0n, &delta, "on delta")?
-            + com_T1.mul(x) + com_T2.mul(x*x) - PedersenCommitmentScheme::commit(&param_g_1_u_1, &vec_0n, &openings.taux, "on tau_x")?;
-        //assert_eq!(lhs_step1, rhs_step1, "step 1: T1, T2 checks fail");
-
-        // check validity of A B
-        // {vec_g'}^{zeta} vec_h^eta = A B^x vec_g^{z1^n} vec_h^{z1^n} u^{-mu}
-        let powers_yn_inverse = generate_powers(y.inverse().unwrap(), params.num_pub_inputs);
-        let mut vec_g1_yn = Vec::with_capacity(param_g_1_u_1.vec_gen.len());
-        for i in 0..param_g_1_u_1.vec_gen.len() {
-            vec_g1_yn.push((param_g_1_u_1.vec_gen[i]*powers_yn_inverse[i]).into_affine());
-        }
-        let mut vec_g2_yn = Vec::with_capacity(param_g_2_u_2.vec_gen.len());
-        for i in 0..param_g_2_u_2.vec_gen.len() {
-            vec_g2_yn.push((param_g_2_u_2.vec_gen[i]*powers_yn_inverse[i]).into_affine());
-        }
-        let vec_g1_g2=[vec_g1_yn.clone(),vec_g2_yn.clone()].concat();
-        let vec_g12=vec_g1_g2.clone();
-        let vec_h1=param_h_1_v_1.vec_gen.clone();
-        let vec_h2=param_h_2_v_2.vec_gen.clone();
-        let vec_h1_h2=[vec_h1,vec_h2].concat();
-        let vec_z1n = vec![z; params.num_pub_inputs];
-        let vec_z3_1n=vec![z3;params.num_pub_inputs];
-        let vec_z7_2n: Vec<_> = two_power_n.iter().map(|&x| z7 * x).collect();
-        let vec_z1n_z72n=vec_add(&vec_z1n, &vec_z7_2n);
-        let vec_z5_2n:Vec<_>=two_power_n.iter().map(|&x| z5 * x).collect();
-        let vec_z3n_z52n=vec_add(&vec_z3_1n, &vec_z5_2n);
-        //generate zero vector
-        let zeros = vec![C::ScalarField::zero(); vec_g1_g2.len()];
-        let param_g1_yn_u1 = PedersenParams {
-            generator: param_g_1_u_1.generator.clone(),
-            vec_gen: vec_g1_g2,
-        };
-        // let param_g2_yn_u2 = PedersenParams {
-        //     generator: param_g_2_u_2.generator.clone(),
-        //     vec_gen:vec_g12,
-        // };
-        let param_h12_v1 = PedersenParams {
-            generator: param_h_1_v_1.generator.clone(),
-            vec_gen: vec_h1_h2,
-        };
-        // let lhs_step2 = PedersenCommitmentScheme::commit(&param_g_yn_u, &openings.zeta, &C::ScalarField::zero(), "on zeta")?
-        //    + PedersenCommitmentScheme::commit(&param_h_v, &openings.eta, &C::ScalarField::zero(), "on eta")?;
-        //
-
-
-        // let lhs_step2=PedersenCommitmentScheme::commit(&param_g2_yn_u2, &zeros, &openings.mu_2, "on zeros")?
-        //     +PedersenCommitmentScheme::commit(&param_g1_yn_u1, &openings.zeta, &openings.mu_1, "on zeta")?
-        //     +PedersenCommitmentScheme::commit(&param_h12_v1, &openings.eta,&C::ScalarField::zero(),"on eta")?;
-        
-        
-        // let rhs_step2 = com_A + com_B.mul(x)
-        //     + PedersenCommitmentScheme::commit(&param_g_u, &vec_z1n, &(-openings.mu), "on z1n")?
-        //     + PedersenCommitmentScheme::commit(&param_h_v, &vec_z1n, &C::ScalarField::zero(), "on z1n")?;
-        let rhs_step2=com_A+com_B.mul(x)+com_C.mul(z2)+com_D.mul(x)
-            +PedersenCommitmentScheme::commit(&param_g_1_u_1,&vec_z1n,&C::ScalarField::zero() , "on vec_z1n")?
-            +PedersenCommitmentScheme::commit(&param_g_2_u_2, &vec_z3_1n,&C::ScalarField::zero() , "on vec_z3_1n")?
-            +PedersenCommitmentScheme::commit(&param_h_1_v_1,&vec_z1n_z72n , &C::ScalarField::zero(), "on vec_z1n_z7n")?
-            +PedersenCommitmentScheme::commit(&param_h_2_v_2, &vec_z3n_z52n, &C::ScalarField::zero(), "on vec_z3n_z52n")?;
-        //assert_eq!(lhs_step2, rhs_step2, "step 2: A,B checks fail");
-
-        // check pk
-        // P^zeta = g^fs E^x P^{z y^n}
-        let vec_z_yn = scalar_product(&powers_yn, &z);
-        let vec_z_yn_2=vec_z_yn.clone();
-        let vec_z_yn_expanded=[vec_z_yn.clone(),vec_z_yn_2.clone()].concat();
-        // let lhs_step3 = C::msm(&params.vec_pk, &openings.zeta).unwrap();
-
-
-
-        let rhs_step3 = PedersenCommitmentScheme::commit(&param_key, &vec![openings.fs], &C::ScalarField::zero(), "on fs")?
-            + com_E.mul(x) + C::msm(&params.vec_pk, &vec_z_yn_expanded).unwrap();
-        // assert_eq!(lhs_step3, rhs_step3, "step 3: pk check fails");
-
-        end_timer!(start);
-
-        let start = start_timer!(|| "running sigma protocol verify algorithm...");
-        // check the challenges
-        transcript.append_serializable_element(b"commitments A,B,C,D", &[com_A, com_B,com_C,com_D])?;
-        let y = transcript.get_and_append_challenge(b"challenge y")?;
-        let z = transcript.get_and_append_challenge(b"challenge z")?;
-        transcript.append_serializable_element(b"commitments E,T1,T2", &[com_E, com_T1, com_T2])?;
-        let h = sha256::digest(&params.message);
-        assert_eq!(&h, digest);
-        let mut h_msg: &mut [u8] = &mut [0; 32];
-        h_msg.write(h.as_bytes()).unwrap();
-        transcript.append_message(b"message digest", &h_msg)?;
-        let x = transcript.get_and_append_challenge(b"challenge x")?;
-
-        if (y,z,x) != (challenges[0],challenges[1],challenges[2])  {
-            return Err(SigmaErrors::InvalidProof(
-                "invalid challenge value".to_string(),
-            ));
-        }
-
-        // run Bulletproofs Compression
-        // consider aggregating the following three equation into one
-        // v^{hat_t} = v^delta T1^x T2^{x^2} y^{-taux}
-        // {vec_g'}^{zeta} vec_h^eta = A B^x vec_g^{z1^n} vec_h^{z1^n} u^{-mu}
-        // P^zeta = g^fs E^x P^{z y^n}
-        let RHS = rhs_step1 + rhs_step2 + rhs_step3;
-        let n = param_g1_yn_u1.vec_gen.len();
-        let mut vec_G:Vec<C::Affine> = Vec::with_capacity(n);
-        let vec_g=vec_g12.clone();
-        for i in 0..n {
-            vec_G.push((vec_g[i] + params.vec_pk[i]).into_affine());
-        }
-        let vec_H = param_h12_v1.vec_gen.clone();
-        let v = param_h12_v1.generator.clone().into_affine();
-        let factors_G = vec![C::ScalarField::from(1u64); n];
-        let factors_H = vec![C::ScalarField::from(1u64); n];
-        let param = InnerProductParam {
-            factors_G,
-            factors_H,
-            u: v,
-            vec_G,
-            vec_H,
-        };
-
-        // call Bulletproofs prover
-        InnerProductProtocol::<C>::verify(n, RHS, &param, &proof.compression_proof)?;
-        let result = true;
-        end_timer!(start);
-        Ok(result)
-    }
-}
-
-
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use ark_secp256k1::{Fr, Projective};
-    use ark_std::UniformRand;
-
-    #[test]
-    fn test_ringsignature() {
-        // parameter setting
-        let mut rng = ark_std::test_rng();
-        let ring_size = 256;
-        let sk = Fr::rand(&mut rng);
-        let mut wit = vec![sk];
-        type Ring = RingSignatureScheme<Projective>;
-        let message = String::from("Welcome to the world of Zero Knowledge!");
-        // setup algorithm
-        let ring_params = Ring::setup(&mut rng, &mut wit, &message, ring_size).unwrap();
-        // prove algorithm
-        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
-        // verify algorithm
-        let result = Ring::verify(&ring_params, &proof).unwrap();
-        assert_eq!(result, true);
-    }
-}
\ No newline at end of file
+use std::marker::PhantomData;
+
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ff::{Field, PrimeField};
+use ark_std::{end_timer, rand::Rng, start_timer, One, UniformRand, Zero};
+
+use bulletproofs::ipa::*;
+use bulletproofs::structs::*;
+use crate::commitment::pedersen::PedersenCommitmentScheme;
+use crate::commitment::PedersenParams;
+use crate::ringsig::structs::{CompressedOpenings, CompressedRingSignature, PreparedRing, RingSignatureParams, SessionNonce, LOGARITHMIC_DOMAIN};
+use crate::ringsig::sanity::{check_b0_b1_constraints, check_b2_b3_constraints};
+use toolbox::sigma::{transcript::ProofTranscript, SigmaProtocol};
+use toolbox::errors::SigmaErrors;
+use toolbox::hash::{Digest, Sha256};
+use toolbox::vec::*;
+
+/// The ring size n must be a power of two below the modulus bit size of the scalar field,
+/// i.e., at most 128 members on secp256k1 and BLS12-381, since <b_0, 2^n> - <b_2, 2^n> = 1
+/// is only sound when the integers of the n bits do not wrap around the modulus
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RingSignatureScheme<C, D = Sha256>
+where
+    C: CurveGroup,
+    D: Digest,
+{
+    phantom: PhantomData<(C, D)>,
+}
+
+/// Implement a sigma protocol as a ring signature scheme (with compression), including 5-move:
+/// Relation: P knows a sk to a pk among the vector vec_pk
+/// Formalized Relation: P knows a sk satisfying <vec_pk, vec_b> = com(sk) for a binary vec_b != 0^n,
+/// where vec_b != 0^n is shown by b_2 = bits(int(b_0) - 1) being binary
+impl<C, D> SigmaProtocol<C> for RingSignatureScheme<C, D>
+where
+    C: CurveGroup,
+    D: Digest,
+{
+    /// public parameters
+    type PublicParams = RingSignatureParams<C>;
+    /// witness
+    type Witness = Vec<C::ScalarField>;
+    /// witness commitments
+    type Commitments = Vec<C::Affine>;
+    // challenge
+    type Challenge = Vec<C::ScalarField>;
+    /// proof
+    type Proof = CompressedRingSignature<C>;
+
+    /// Fails with InvalidParameters unless supported_size is a power of two less than the modulus bit size
+    fn setup<R: Rng>(
+        rng: &mut R,
+        wit: &mut Self::Witness, // secret key
+        msg: &str,
+        supported_size: usize, // ring size
+    ) -> Result<Self::PublicParams, SigmaErrors> {
+        check_ring_size::<C::ScalarField>(supported_size)?;
+        // derive commitment scheme parameters (vec_g_1, u_1), (vec_h_1, v_1), (vec_g_2, u_2), (vec_h_2, v_2)
+        // and public key parameters (g)
+        let com_parameters = RingSignatureParams::<C>::deterministic_com_parameters(4, supported_size)?;
+        let key_params = &com_parameters[4];
+
+        // generate pk vectors
+        let pk:C::Affine = PedersenCommitmentScheme::commit(key_params, wit, &C::ScalarField::zero(), "as pk")?.into_affine();
+        let mut vec_pk = rand_affine_vec::<C, R>(rng, supported_size-1);
+        // add pk to the vector and shuffle it
+        vec_pk.push(pk);
+        let (vec_b, index) = shuffle_indexed::<C, R>(&mut vec_pk, pk, rng);
+        debug_assert_eq!(vec_pk[index], pk);
+        assert_binary(&vec_b);
+        wit.extend(vec_b);
+
+        Ok(RingSignatureParams {
+            num_witness: wit.len(),
+            num_pub_inputs: supported_size,
+            com_parameters,
+            message: msg.to_string(),
+            vec_pk,
+            session_nonce: SessionNonce::rand(rng),
+        })
+    }
+
+    fn prove<R: Rng>(
+        rng: &mut R,
+        params: &Self::PublicParams,
+        wit: &Self::Witness,
+    ) -> Result<Self::Proof, SigmaErrors> {
+        // initialization
+        let start = start_timer!(|| "running sigma protocol prove algorithm...");
+        check_ring_size::<C::ScalarField>(params.num_pub_inputs)?;
+        if params.vec_pk.len() != params.num_pub_inputs {
+            return Err(SigmaErrors::InvalidProver(
+                "public list does not match the ring size".to_string(),
+            ));
+        }
+        if wit.len() <= params.num_pub_inputs {
+            return Err(SigmaErrors::InvalidProver(
+                "the witness should be the secret keys and the indicator vector".to_string(),
+            ));
+        }
+        // parse wit as vec_sk and vec_b
+        let vec_sk = wit[0..wit.len()-params.num_pub_inputs].to_vec();
+        let vec_b = wit[wit.len()-params.num_pub_inputs..].to_vec();
+
+        // denote b_0 = b, b_1 = 1^n - b_0, where b_0 must be binary
+        let vec_b0 = vec_b.clone();
+        if !is_binary(&vec_b0) {
+            return Err(SigmaErrors::InvalidProver(
+                "the committed vector should be binary".to_string(),
+            ));
+        }
+        // each one of b_0 is matched with a secret key by fs
+        if vec_b0.iter().filter(|b| b.is_one()).count() != vec_sk.len() {
+            return Err(SigmaErrors::InvalidProver(
+                "the indicator vector should select one public key per secret key".to_string(),
+            ));
+        }
+        // denote b_2 = bits(int(b_0) - 1)
+        let vec_b2 = subtract_with_borrow(&vec_b0).ok_or(SigmaErrors::InvalidProver(
+            "the indicator vector should not be zero".to_string(),
+        ))?;
+
+        // b_1, b_2, b_3 are derived from b_0, so this only fails for an indicator the relation cannot prove
+        let n = params.num_pub_inputs;
+        if !check_b0_b1_constraints(&vec_b0, &complement(&vec_b0), n)
+            || !check_b2_b3_constraints(&vec_b2, &complement(&vec_b2), &vec_b0, n)
+        {
+            return Err(SigmaErrors::InvalidProver(
+                "the indicator vector does not satisfy the range relation".to_string(),
+            ));
+        }
+
+        let proof = Self::prove_with_bits(rng, params, &vec_sk, &vec_b0, &vec_b2);
+        // proving ends
+        end_timer!(start);
+        proof
+    }
+
+    fn verify(
+        params: &Self::PublicParams,
+        proof: &Self::Proof
+    ) -> Result<bool, SigmaErrors> {
+        Self::verify_prepared(&Self::prepare(params)?, proof)
+    }
+}
+
+impl<C: CurveGroup, D: Digest> RingSignatureScheme<C, D> {
+    // the prover on the bits b_0 and b_2 = bits(int(b_0) - 1), which prove has checked against the witness,
+    // so that a test can run it on b_0 = 0^n as a forger would
+    pub(crate) fn prove_with_bits<R: Rng>(
+        rng: &mut R,
+        params: &RingSignatureParams<C>,
+        vec_sk: &[C::ScalarField],
+        vec_b0: &[C::ScalarField],
+        vec_b2: &[C::ScalarField],
+    ) -> Result<CompressedRingSignature<C>, SigmaErrors> {
+        let mut transcript = params.session_transcript(LOGARITHMIC_DOMAIN)?;
+        transcript.append_serializable_element(b"public list", &params.vec_pk)?;
+
+        // parse commitment parameters
+        let com_params = params.compressed_com_params()?;
+
+        // b_1 = 1^n - b_0, b_3 = 1^n - b_2
+        let vec_b1 = complement(vec_b0);
+        let alpha_1 = C::ScalarField::rand(rng);
+        let vec_b3 = complement(vec_b2);
+        let alpha_3 = C::ScalarField::rand(rng);
+
+        let alpha_2 = C::ScalarField::rand(rng);
+        let alpha_4 = C::ScalarField::rand(rng);
+        let vec_r0: Vec<C::ScalarField> = (0..vec_b0.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let vec_r1: Vec<C::ScalarField> = (0..vec_b1.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let vec_r2: Vec<C::ScalarField> = (0..vec_b2.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let vec_r3: Vec<C::ScalarField> = (0..vec_b3.len()).map(|_| C::ScalarField::rand(rng)).collect();
+
+        // computes A = g_1^{b_0}h_1^{b_1}u_1^{alpha_1}, B = g_1^{r_0}h_1^{r_1}u_1^{alpha_2}
+        // and C = g_2^{b_2}h_2^{b_3}u_2^{alpha_3}, D = g_2^{r_2}h_2^{r_3}u_2^{alpha_4}
+        // as two batches under the keys (g_1 || h_1, u_1) and (g_2 || h_2, u_2)
+        let coms_AB = PedersenCommitmentScheme::batch_commit(
+            &concat_params(com_params.g1u1, com_params.h1v1),
+            &[[vec_b0, &vec_b1].concat(), [vec_r0.as_slice(), &vec_r1].concat()],
+            &[alpha_1, alpha_2],
+        )?;
+        let coms_CD = PedersenCommitmentScheme::batch_commit(
+            &concat_params(com_params.g2u2, com_params.h2v2),
+            &[[vec_b2, &vec_b3].concat(), [vec_r2.as_slice(), &vec_r3].concat()],
+            &[alpha_3, alpha_4],
+        )?;
+        let (com_A, com_B) = (coms_AB[0].into_group(), coms_AB[1].into_group());
+        let (com_C, com_D) = (coms_CD[0].into_group(), coms_CD[1].into_group());
+
+        // P->V: A,B,C,D
+        transcript.append_serializable_element(b"commitments A,B,C,D", &[com_A, com_B, com_C, com_D])?;
+
+        // V->P: challenges y,z
+        let y = transcript.get_and_append_challenge(b"challenge y")?;
+        let z = transcript.get_and_append_challenge(b"challenge z")?;
+
+        // the two halves of zeta and eta are
+        // zeta_1 = (b_0 + z*1^n + r_0*x) \circ y^n, eta_1 = b_1 + z*1^n + z^7*2^n \circ y^{-n} + r_1*x
+        // zeta_2 = (z^2*b_2 + z^3*1^n + r_2*x) \circ y^n, eta_2 = z^2*b_3 + z^3*1^n - z^5*2^n \circ y^{-n} + r_3*x
+        let powers_z = powers_of(z, 7);
+        let (z2, z3, z5, z7) = (powers_z[2], powers_z[3], powers_z[5], powers_z[7]);
+        let powers_yn = generate_powers(y, params.num_pub_inputs);
+        let y_inv = y.inverse().ok_or(SigmaErrors::InvalidProver("invalid challenge value".to_string()))?;
+        let powers_yn_inverse = generate_powers(y_inv, params.num_pub_inputs);
+        let two_power_n_yn_inverse = hadamard_product(&powers_of_two(params.num_pub_inputs), &powers_yn_inverse);
+        let vec_z1n = vec![z; params.num_pub_inputs];
+        let vec_z3_1n = vec![z3; params.num_pub_inputs];
+        let yn_yn = [powers_yn.clone(), powers_yn.clone()].concat();
+        let zeta_0 = [
+            vec_add(vec_b0, &vec_z1n),
+            vec_add(&scalar_product(vec_b2, &z2), &vec_z3_1n),
+        ].concat();
+        let eta_0 = [
+            vec_add(&vec_add(&vec_b1, &vec_z1n), &scalar_product(&two_power_n_yn_inverse, &z7)),
+            vec_add(&vec_add(&scalar_product(&vec_b3, &z2), &vec_z3_1n), &scalar_product(&two_power_n_yn_inverse, &(-z5))),
+        ].concat();
+        let r0_r2 = [vec_r0.clone(), vec_r2].concat();
+        let r1_r3 = [vec_r1, vec_r3].concat();
+
+        // t1 = <(r_0 || r_2) \circ y^n, eta_0> + <zeta_0 \circ y^n, r_1 || r_3>
+        let r0_r2_yn = hadamard_product(&r0_r2, &yn_yn);
+        let t1 = inner_product(&r0_r2_yn, &eta_0) + inner_product(&hadamard_product(&zeta_0, &yn_yn), &r1_r3);
+        // t2 = <(r_0 || r_2) \circ y^n, r_1 || r_3>
+        let t2 = inner_product(&r0_r2_yn, &r1_r3);
+
+        // computes
+        // E = P^{y^n \circ r_0} Com_{ck}(0; -r_s)
+        // T1 = v_1^{t1}u_1^{tau1}
+        // T2 = v_1^{t2}u_1^{tau2}
+        let rs = C::ScalarField::rand(rng);
+        let neg_rs = -rs;
+        let tau1 = C::ScalarField::rand(rng);
+        let tau2 = C::ScalarField::rand(rng);
+
+        let vec_r0_yn = hadamard_product(&vec_r0, &powers_yn);
+        let com_E = C::msm(&params.vec_pk, &vec_r0_yn)
+            .map_err(|_| SigmaErrors::InvalidParameters("msm length mismatch".to_string()))?
+            + PedersenCommitmentScheme::commit(com_params.key, &[neg_rs], &C::ScalarField::zero(), "E")?;
+        let param_u_v = PedersenParams {
+            generator: com_params.h1v1.generator,
+            vec_gen: vec![com_params.g1u1.generator.into_affine()],
+        };
+        let com_T1 = PedersenCommitmentScheme::commit(&param_u_v, &[tau1], &t1, "T1")?;
+        let com_T2 = PedersenCommitmentScheme::commit(&param_u_v, &[tau2], &t2, "T2")?;
+
+        // P->V: E, T1, T2
+        transcript.append_serializable_element(b"commitments E,T1,T2", &[com_E, com_T1, com_T2])?;
+
+        // append the message digest to the transcript
+        let h = D::digest(params.message.as_bytes());
+        transcript.append_message_digest::<D>(&h)?;
+
+        // V->P: challenges x
+        let x = transcript.get_and_append_challenge(b"challenge x")?;
+
+        // computes zeta = (zeta_0 + (r_0 || r_2)*x) \circ (y^n || y^n), eta = eta_0 + (r_1 || r_3)*x
+        let zeta = hadamard_product(&vec_add(&zeta_0, &scalar_product(&r0_r2, &x)), &yn_yn);
+        let eta = vec_add(&eta_0, &scalar_product(&r1_r3, &x));
+
+        // computes hat_t = <zeta, eta>
+        let hat_t = inner_product(&zeta, &eta);
+
+        // tau_x = tau1*x + tau2*x^2
+        let taux = tau1*x + tau2*x*x;
+        // mu_1 = alpha_1 + alpha_2*x, mu_2 = alpha_3*z^2 + alpha_4*x
+        let mu_1 = alpha_1 + alpha_2*x;
+        let mu_2 = alpha_3*z2 + alpha_4*x;
+        // fs = \sum_{j=1}^k y^{i_j} s_j + r_s*x
+        let mut j = 0;
+        let mut sum = C::ScalarField::zero();
+        for i in 0..params.num_pub_inputs {
+            let term = powers_yn[i]*vec_b0[i];
+            if term != C::ScalarField::zero() {
+                sum += term*vec_sk[j];
+                j += 1;
+            }
+        }
+        let fs = sum + rs*x;
+        debug_assert_eq!(j, vec_sk.len());
+
+        // P->V: openings
+        transcript.append_serializable_element(b"openings", &[hat_t, taux, mu_1, mu_2, fs])?;
+
+        // V->P: challenge w aggregating the three verification equations
+        let w = transcript.get_and_append_challenge(b"challenge w")?;
+
+        // Bulletproofs Compression
+        let commitments = vec![com_A, com_B, com_C, com_D, com_E, com_T1, com_T2];
+        let challenges = vec![y, z, x, w];
+        let mut openings = CompressedOpenings {
+            zeta: vec![],
+            eta: vec![],
+            hat_t,
+            taux,
+            mu_1,
+            mu_2,
+            fs,
+        };
+        let param = compression_param(params, &powers_yn_inverse, w)?;
+
+        // sanity check
+        // G^zeta H^eta is the target the verifier re-derives
+        debug_assert_eq!(
+            C::msm(&param.vec_G, &zeta).unwrap() + C::msm(&param.vec_H, &eta).unwrap(),
+            msm_terms::<C>(&target_terms(params, &C::normalize_batch(&commitments), &openings, &challenges)?)
+        );
+
+        // the IPA continues on the transcript, so its challenges are bound to the commitments and openings,
+        // and proves <zeta, eta> = hat_t for the opened hat_t
+        let proof = InnerProductProtocol::<C>::prove_with_public_product(&mut transcript, &param, zeta, eta)?;
+        openings.zeta = vec![proof.a];
+        openings.eta = vec![proof.b];
+
+        Ok(CompressedRingSignature {
+            commitments,
+            openings,
+            compression_proof: proof,
+            challenges,
+            digest: h.clone(),
+            session_nonce: params.session_nonce,
+        })
+    }
+
+    /// Collects the generators of the IPA verifier that only depend on the ring,
+    /// so that the signatures of the ring are verified by verify_prepared without rebuilding them
+    pub fn prepare(params: &RingSignatureParams<C>) -> Result<PreparedRing<'_, C>, SigmaErrors> {
+        check_ring_size::<C::ScalarField>(params.num_pub_inputs)?;
+        let com_params = params.compressed_com_params()?;
+        if params.vec_pk.len() != params.num_pub_inputs {
+            return Err(SigmaErrors::InvalidParameters(
+                "public list does not match the ring size".to_string(),
+            ));
+        }
+        Ok(PreparedRing {
+            params,
+            vec_G: [com_params.g1u1.vec_gen.clone(), com_params.g2u2.vec_gen.clone()].concat(),
+            vec_H: [com_params.h1v1.vec_gen.clone(), com_params.h2v2.vec_gen.clone()].concat(),
+            u: com_params.h1v1.generator.into_affine(),
+        })
+    }
+
+    /// Verifies proof on msg against a ring held by the verifier, e.g., a sub-ring of a larger set,
+    /// instead of the parameters of the setup: the commitment parameters are derived from the ring size
+    /// and the session nonce is the one the verifier expects, so that the proof only verifies if its transcript
+    /// absorbed exactly this ring, in this order, in this session; the ring is not padded,
+    /// its size must be a power of two below the modulus bit size
+    pub fn verify_with_ring(
+        proof: &CompressedRingSignature<C>,
+        vec_pk: &[C::Affine],
+        msg: &str,
+        session_nonce: &SessionNonce,
+    ) -> Result<bool, SigmaErrors> {
+        let n = vec_pk.len();
+        check_ring_size::<C::ScalarField>(n)?;
+        let params = RingSignatureParams {
+            num_witness: 1 + n,
+            num_pub_inputs: n,
+            com_parameters: RingSignatureParams::<C>::deterministic_com_parameters(4, n)?,
+            message: msg.to_string(),
+            vec_pk: vec_pk.to_vec(),
+            session_nonce: *session_nonce,
+        };
+        Self::verify(&params, proof)
+    }
+
+    /// Same as verify, on the generators of a prepared ring
+    pub fn verify_prepared(
+        prepared: &PreparedRing<'_, C>,
+        proof: &CompressedRingSignature<C>,
+    ) -> Result<bool, SigmaErrors> {
+        let params = prepared.params;
+        params.check_session_nonce(&proof.session_nonce)?;
+        let n = 2*params.num_pub_inputs;
+        if prepared.vec_G.len() != n || prepared.vec_H.len() != n {
+            return Err(SigmaErrors::InvalidParameters(
+                "prepared generators do not match the ring size".to_string(),
+            ));
+        }
+        // initialization
+        let start = start_timer!(|| "preprocessing sigma protocol verify algorithm...");
+
+        // parse proof
+        if proof.commitments.len() != 7 || proof.challenges.len() != 4 {
+            return Err(SigmaErrors::InvalidProof(
+                "incorrect proof length".to_string(),
+            ));
+        }
+
+        // check the challenges
+        let (challenges, mut transcript) = replay_challenges::<C, D>(params, proof)?;
+        if challenges != proof.challenges {
+            return Err(SigmaErrors::InvalidProof(
+                "invalid challenge value".to_string(),
+            ));
+        }
+        let (y, w) = (proof.challenges[0], proof.challenges[3]);
+
+        // re-derive the terms of the IPA target from the commitments and openings
+        let commitments = C::normalize_batch(&proof.commitments);
+        let mut terms = target_terms(params, &commitments, &proof.openings, &proof.challenges)?;
+        // the IPA proves <zeta, eta> = hat_t, and (1) binds hat_t to T1, T2,
+        // it is batched into the same msm as rho*(rhs(1) - v_1^{hat_t}) with a challenge rho drawn
+        // after the whole signature, so that a nonzero difference only cancels out for a single rho
+        let mut batch_transcript = transcript.clone();
+        batch_transcript.append_serializable_element(b"compression proof", &proof.compression_proof)?;
+        let rho = batch_transcript.get_and_append_challenge(b"challenge rho")?;
+        terms.extend(scale_terms::<C>(hat_t_terms(params, &commitments, &proof.openings, &proof.challenges)?, rho));
+
+        // the generators of compression_param, with y^{-n} as factors of vec_g_1 || vec_g_2 and P^w as the shift of vec_g_1
+        let y_inv = y.inverse().ok_or(SigmaErrors::InvalidProof("invalid challenge value".to_string()))?;
+        let powers_yn_inverse = generate_powers(y_inv, params.num_pub_inputs);
+        let param = InnerProductParam::new(
+            [powers_yn_inverse.clone(), powers_yn_inverse].concat(),
+            vec![C::ScalarField::one(); n],
+            prepared.u,
+            prepared.vec_G.clone(),
+            prepared.vec_H.clone(),
+        )?;
+        end_timer!(start);
+
+        let start = start_timer!(|| "running sigma protocol verify algorithm...");
+        // call Bulletproofs verifier on the transcript of the signature, which checks all equations in one msm
+        let result = InnerProductProtocol::<C>::verify_shifted_with_public_product(
+            &mut transcript, n, &terms, proof.openings.hat_t, &param, Some((&params.vec_pk, w)), &proof.compression_proof,
+        );
+        end_timer!(start);
+        // a rejected signature is checked against (1) alone, to report a forged hat_t
+        if result.is_err() && !msm_terms::<C>(&hat_t_terms(params, &commitments, &proof.openings, &proof.challenges)?).is_zero() {
+            return Err(SigmaErrors::InvalidProof(
+                "hat_t is inconsistent with T1, T2".to_string(),
+            ));
+        }
+        result?;
+        Ok(true)
+    }
+}
+
+// the range relation <b_0, 2^n> - <b_2, 2^n> = 1 must not wrap around the modulus,
+// and the compressed vectors of length 2n must have a power of two length
+fn check_ring_size<F: PrimeField>(n: usize) -> Result<(), SigmaErrors> {
+    if !n.is_power_of_two() || n >= F::MODULUS_BIT_SIZE as usize {
+        return Err(SigmaErrors::InvalidParameters(
+            "ring size should be a power of two less than the modulus bit size".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// outputs bits(int(vec_b) - 1) for a little-endian bit vector vec_b, or None if vec_b is zero
+pub(crate) fn subtract_with_borrow<F: PrimeField>(vec_b: &[F]) -> Option<Vec<F>> {
+    let mut borrow = true;
+    let vec_diff = vec_b.iter()
+        .map(|b_i| {
+            let bit = b_i.is_one();
+            let diff = bit != borrow;
+            borrow = borrow && !bit;
+            F::from(diff as u64)
+        })
+        .collect();
+    if borrow { None } else { Some(vec_diff) }
+}
+
+// 1^n - vec_b
+fn complement<F: PrimeField>(vec_b: &[F]) -> Vec<F> {
+    vec_b.iter().map(|b_i| F::one() - b_i).collect()
+}
+
+// the key (vec_g || vec_h, u) of the commitments g^{m_0} h^{m_1} u^r to m_0 || m_1
+fn concat_params<C: CurveGroup>(param_g: &PedersenParams<C>, param_h: &PedersenParams<C>) -> PedersenParams<C> {
+    PedersenParams {
+        generator: param_g.generator,
+        vec_gen: [param_g.vec_gen.as_slice(), &param_h.vec_gen].concat(),
+    }
+}
+
+// 2^n = (1, 2, ..., 2^{n-1})
+pub(crate) fn powers_of_two<F: PrimeField>(n: usize) -> Vec<F> {
+    std::iter::successors(Some(F::one()), |&power| Some(power.double()))
+        .take(n)
+        .collect()
+}
+
+// delta = <zeta_0, eta_0> with the prover's randomness set to zero,
+// i.e., (z + z^2 + z^5 + z^6)*<1^n, y^n> + z^7*(<b_0, 2^n> - <b_2, 2^n>) for <b_0, 2^n> - <b_2, 2^n> = 1
+fn delta<F: PrimeField>(y: F, z: F, n: usize) -> F {
+    let powers_yn = generate_powers(y, n);
+    let powers_z = powers_of(z, 7);
+    (powers_z[1] + powers_z[2] + powers_z[5] + powers_z[6]) * vec_sum(&powers_yn) + powers_z[7]
+}
+
+// the parameters of the aggregated IPA instance
+// vec_G = (vec_g_1 \circ y^{-n} + P^w) || vec_g_2 \circ y^{-n}, vec_H = vec_h_1 || vec_h_2, u = v_1
+fn compression_param<C: CurveGroup>(
+    params: &RingSignatureParams<C>,
+    powers_yn_inverse: &[C::ScalarField],
+    w: C::ScalarField,
+) -> Result<InnerProductParam<C>, SigmaErrors> {
+    let com_params = params.compressed_com_params()?;
+
+    let n = 2*params.num_pub_inputs;
+    let mut vec_G: Vec<C> = Vec::with_capacity(n);
+    for ((g_i, y_inv_i), pk_i) in com_params.g1u1.vec_gen.iter().zip(powers_yn_inverse.iter()).zip(params.vec_pk.iter()) {
+        vec_G.push(*g_i * y_inv_i + *pk_i * w);
+    }
+    for (g_i, y_inv_i) in com_params.g2u2.vec_gen.iter().zip(powers_yn_inverse.iter()) {
+        vec_G.push(*g_i * y_inv_i);
+    }
+    // one field inversion for the whole vector instead of one per generator
+    let vec_G = C::normalize_batch(&vec_G);
+    let vec_H = [com_params.h1v1.vec_gen.clone(), com_params.h2v2.vec_gen.clone()].concat();
+    let u = com_params.h1v1.generator.into_affine();
+    Ok(InnerProductParam::uniform(vec_G, vec_H, u))
+}
+
+// the re-derived challenges and the transcript after them
+type Replay<F> = (Vec<F>, ProofTranscript<F>);
+
+// replays the transcript to re-derive the challenges (y, z, x, w) of a proof,
+// the transcript is returned to carry on with the IPA
+fn replay_challenges<C: CurveGroup, D: Digest>(
+    params: &RingSignatureParams<C>,
+    proof: &CompressedRingSignature<C>,
+) -> Result<Replay<C::ScalarField>, SigmaErrors> {
+    let mut transcript = params.session_transcript(LOGARITHMIC_DOMAIN)?;
+    transcript.append_serializable_element(b"public list", &params.vec_pk)?;
+    let commitments = &proof.commitments;
+    transcript.append_serializable_element(b"commitments A,B,C,D", &[commitments[0], commitments[1], commitments[2], commitments[3]])?;
+    let y = transcript.get_and_append_challenge(b"challenge y")?;
+    let z = transcript.get_and_append_challenge(b"challenge z")?;
+    transcript.append_serializable_element(b"commitments E,T1,T2", &[commitments[4], commitments[5], commitments[6]])?;
+    let h = D::digest(params.message.as_bytes());
+    if h != proof.digest {
+        return Err(SigmaErrors::InvalidProof(
+            "invalid message digest".to_string(),
+        ));
+    }
+    transcript.append_message_digest::<D>(&h)?;
+    let x = transcript.get_and_append_challenge(b"challenge x")?;
+    let openings = &proof.openings;
+    transcript.append_serializable_element(b"openings", &[openings.hat_t, openings.taux, openings.mu_1, openings.mu_2, openings.fs])?;
+    let w = transcript.get_and_append_challenge(b"challenge w")?;
+    Ok((vec![y, z, x, w], transcript))
+}
+
+// the terms (point, scalar) of a multi-scalar multiplication
+type Terms<C> = Vec<(<C as CurveGroup>::Affine, <C as Group>::ScalarField)>;
+
+// the verifier checks the following three equations
+// (1) v_1^{hat_t} = v_1^delta T1^x T2^{x^2} u_1^{-taux}
+// (2) {vec_g_1'}^{zeta_1} {vec_g_2'}^{zeta_2} vec_h_1^{eta_1} vec_h_2^{eta_2}
+//     = A B^x C^{z^2} D^x vec_g_1^{z1^n} vec_g_2^{z^3 1^n} vec_h_1^{z1^n + z^7 2^n \circ y^{-n}}
+//       vec_h_2^{z^3 1^n - z^5 2^n \circ y^{-n}} u_1^{-mu_1} u_2^{-mu_2}
+// (3) P^{zeta_1} = g^fs E^x P^{z y^n}
+// where hat_t = <zeta, eta>, by aggregating (2) and (3) with the challenge w into one IPA instance
+// (vec_g_1' + P^w)^{zeta_1} {vec_g_2'}^{zeta_2} vec_h^eta = rhs(2) + w*rhs(3) with the public inner product hat_t,
+// this outputs the terms of the right side target_P, which the IPA verifier merges into its final msm,
+// (1) is checked on the opened hat_t by hat_t_terms
+fn target_terms<C: CurveGroup>(
+    params: &RingSignatureParams<C>,
+    commitments: &[C::Affine],
+    openings: &CompressedOpenings<C>,
+    challenges: &[C::ScalarField],
+) -> Result<Terms<C>, SigmaErrors> {
+    let (y, z, x, w) = (challenges[0], challenges[1], challenges[2], challenges[3]);
+    if params.vec_pk.len() != params.num_pub_inputs {
+        return Err(SigmaErrors::InvalidParameters(
+            "public list does not match the ring size".to_string(),
+        ));
+    }
+
+    // check validity of A B C D
+    let mut terms = rhs_step2_terms(params, commitments, openings, y, z, x)?;
+    // check pk
+    terms.extend(scale_terms::<C>(rhs_step3_terms(params, commitments, openings, y, z, x)?, w));
+    Ok(terms)
+}
+
+// the right side of equation (1), v_1^delta T1^x T2^{x^2} u_1^{-taux}
+fn rhs_step1_terms<C: CurveGroup>(
+    params: &RingSignatureParams<C>,
+    commitments: &[C::Affine],
+    openings: &CompressedOpenings<C>,
+    y: C::ScalarField,
+    z: C::ScalarField,
+    x: C::ScalarField,
+) -> Result<Terms<C>, SigmaErrors> {
+    let com_params = params.compressed_com_params()?;
+    let (com_T1, com_T2) = (commitments[5], commitments[6]);
+    let generators = C::normalize_batch(&[com_params.h1v1.generator, com_params.g1u1.generator]);
+    let delta = delta(y, z, params.num_pub_inputs);
+    Ok(vec![(generators[0], delta), (com_T1, x), (com_T2, x*x), (generators[1], -openings.taux)])
+}
+
+// the difference rhs(1) - v_1^{hat_t} of equation (1), which is the identity for a valid hat_t
+fn hat_t_terms<C: CurveGroup>(
+    params: &RingSignatureParams<C>,
+    commitments: &[C::Affine],
+    openings: &CompressedOpenings<C>,
+    challenges: &[C::ScalarField],
+) -> Result<Terms<C>, SigmaErrors> {
+    let (y, z, x) = (challenges[0], challenges[1], challenges[2]);
+    let mut terms = rhs_step1_terms(params, commitments, openings, y, z, x)?;
+    let v_1 = params.compressed_com_params()?.h1v1.generator.into_affine();
+    terms.push((v_1, -openings.hat_t));
+    Ok(terms)
+}
+
+// the right side of equation (2), which opens A B^x C^{z^2} D^x with mu_1 = alpha_1 + alpha_2*x
+// under u_1 and mu_2 = alpha_3*z^2 + alpha_4*x under u_2
+fn rhs_step2_terms<C: CurveGroup>(
+    params: &RingSignatureParams<C>,
+    commitments: &[C::Affine],
+    openings: &CompressedOpenings<C>,
+    y: C::ScalarField,
+    z: C::ScalarField,
+    x: C::ScalarField,
+) -> Result<Terms<C>, SigmaErrors> {
+    let com_params = params.compressed_com_params()?;
+    let (com_A, com_B, com_C, com_D) = (commitments[0], commitments[1], commitments[2], commitments[3]);
+    let powers_z = powers_of(z, 7);
+    let (z2, z3, z5, z7) = (powers_z[2], powers_z[3], powers_z[5], powers_z[7]);
+
+    let y_inv = y.inverse().ok_or(SigmaErrors::InvalidProof("invalid challenge value".to_string()))?;
+    let powers_yn_inverse = generate_powers(y_inv, params.num_pub_inputs);
+    let two_power_n_yn_inverse = hadamard_product(&powers_of_two(params.num_pub_inputs), &powers_yn_inverse);
+    let vec_z1n_z7_2n: Vec<C::ScalarField> = two_power_n_yn_inverse.iter().map(|s| z + *s * z7).collect();
+    let vec_z3_1n_z5_2n: Vec<C::ScalarField> = two_power_n_yn_inverse.iter().map(|s| z3 - *s * z5).collect();
+
+    let n = params.num_pub_inputs;
+    let mut terms = vec![(com_A, C::ScalarField::one()), (com_B, x), (com_C, z2), (com_D, x)];
+    // the commitments to z*1^n, z^3*1^n under (vec_g_1, u_1), (vec_g_2, u_2) with the randoms -mu_1, -mu_2
+    // and to the vectors above under vec_h_1, vec_h_2, as the terms of one msm
+    terms.extend(PedersenCommitmentScheme::commit_multi_terms(
+        &[com_params.g1u1, com_params.g2u2, com_params.h1v1, com_params.h2v2],
+        &[vec![z; n], vec![z3; n], vec_z1n_z7_2n, vec_z3_1n_z5_2n],
+        &[-openings.mu_1, -openings.mu_2, C::ScalarField::zero(), C::ScalarField::zero()],
+    )?);
+    Ok(terms)
+}
+
+// the right side of equation (3), g^fs E^x P^{z y^n}
+fn rhs_step3_terms<C: CurveGroup>(
+    params: &RingSignatureParams<C>,
+    commitments: &[C::Affine],
+    openings: &CompressedOpenings<C>,
+    y: C::ScalarField,
+    z: C::ScalarField,
+    x: C::ScalarField,
+) -> Result<Terms<C>, SigmaErrors> {
+    let com_params = params.compressed_com_params()?;
+    let com_E = commitments[4];
+    let vec_z_yn = scalar_product(&generate_powers(y, params.num_pub_inputs), &z);
+    let mut terms = vec![(com_params.key.vec_gen[0], openings.fs), (com_E, x)];
+    terms.extend(params.vec_pk.iter().copied().zip(vec_z_yn));
+    Ok(terms)
+}
+
+// multiplies every scalar of the terms by c
+fn scale_terms<C: CurveGroup>(terms: Terms<C>, c: C::ScalarField) -> Terms<C> {
+    terms.into_iter().map(|(point, scalar)| (point, scalar * c)).collect()
+}
+
+// computes prod_i point_i^{scalar_i} of the terms with one msm
+fn msm_terms<C: CurveGroup>(terms: &[(C::Affine, C::ScalarField)]) -> C {
+    let (bases, scalars): (Vec<C::Affine>, Vec<C::ScalarField>) = terms.iter().copied().unzip();
+    C::msm_unchecked(&bases, &scalars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Fr, Projective};
+    use ark_std::UniformRand;
+    use test::Bencher;
+
+    #[test]
+    fn test_ringsignature() {
+        // parameter setting
+        let mut rng = ark_std::test_rng();
+        let ring_size = 16;
+        let sk = Fr::rand(&mut rng);
+        let mut wit = vec![sk];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        // setup algorithm
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, ring_size).unwrap();
+        // prove algorithm
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        // verify algorithm
+        assert!(Ring::verify(&ring_params, &proof).is_ok());
+
+        // ring sizes that wrap <b_0, 2^n> around the modulus are rejected
+        assert!(Ring::setup(&mut rng, &mut vec![sk], &message, 256).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_serialization() {
+        use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 16).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+
+        // the verifier receives the parameters and the signature as bytes
+        let mut params_bytes = Vec::new();
+        ring_params.serialize_compressed(&mut params_bytes).unwrap();
+        let mut proof_bytes = Vec::new();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        let params_prime = RingSignatureParams::<Projective>::deserialize_compressed(&params_bytes[..]).unwrap();
+        let proof_prime = CompressedRingSignature::<Projective>::deserialize_compressed(&proof_bytes[..]).unwrap();
+        assert_eq!(params_prime, ring_params);
+        assert_eq!(proof_prime, proof);
+        assert!(Ring::verify(&params_prime, &proof_prime).is_ok());
+
+        // truncated parameters are rejected
+        assert!(RingSignatureParams::<Projective>::deserialize_compressed(&params_bytes[..params_bytes.len()-1]).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_swapped_ipa() {
+        let mut rng = ark_std::test_rng();
+        let sk = Fr::rand(&mut rng);
+        let mut wit = vec![sk];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 8).unwrap();
+        let proof_1 = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        let proof_2 = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(Ring::verify(&ring_params, &proof_1).is_ok());
+        assert!(Ring::verify(&ring_params, &proof_2).is_ok());
+
+        // the IPA challenges are bound to the signature it was produced for
+        let mut proof_prime = proof_1.clone();
+        proof_prime.compression_proof = proof_2.compression_proof.clone();
+        assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+
+        // and the IPA does not verify on its own, with a fresh transcript
+        let (y, w) = (proof_1.challenges[0], proof_1.challenges[3]);
+        let powers_yn_inverse = generate_powers(y.inverse().unwrap(), 8);
+        let param = compression_param(&ring_params, &powers_yn_inverse, w).unwrap();
+        let commitments = Projective::normalize_batch(&proof_1.commitments);
+        let terms = target_terms(&ring_params, &commitments, &proof_1.openings, &proof_1.challenges).unwrap();
+        let mut transcript = ProofTranscript::new(LOGARITHMIC_DOMAIN);
+        let hat_t = proof_1.openings.hat_t;
+        assert!(InnerProductProtocol::verify_with_public_product(&mut transcript, 16, &terms, hat_t, &param, &proof_1.compression_proof).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_prepared() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 8).unwrap();
+        let prepared = Ring::prepare(&ring_params).unwrap();
+
+        // one prepared ring verifies every signature of the ring
+        for _ in 0..2 {
+            let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+            assert!(Ring::verify_prepared(&prepared, &proof).is_ok());
+
+            // the prepared generators with the factors y^{-n} and the shift P^w are the generators of the prover
+            let (y, w) = (proof.challenges[0], proof.challenges[3]);
+            let powers_yn_inverse = generate_powers(y.inverse().unwrap(), 8);
+            let param = compression_param(&ring_params, &powers_yn_inverse, w).unwrap();
+            let factors = [powers_yn_inverse.clone(), powers_yn_inverse].concat();
+            let mut vec_G: Vec<Projective> = prepared.vec_G.iter().zip(&factors).map(|(g_i, y_i)| *g_i * y_i).collect();
+            vec_G.iter_mut().zip(&ring_params.vec_pk).for_each(|(g_i, pk_i)| *g_i += *pk_i * w);
+            assert_eq!(Projective::normalize_batch(&vec_G), param.vec_G);
+            assert_eq!(prepared.vec_H, param.vec_H);
+            assert_eq!(prepared.u, param.u);
+        }
+
+        // a signature of another ring is rejected by the prepared ring
+        let mut wit_prime = vec![wit[0]];
+        let ring_params_prime = Ring::setup(&mut rng, &mut wit_prime, &message, 8).unwrap();
+        let proof_prime = Ring::prove(&mut rng, &ring_params_prime, &wit_prime).unwrap();
+        assert!(Ring::verify_prepared(&prepared, &proof_prime).is_err());
+
+        // generators that do not match the ring size are rejected instead of panicking
+        let mut prepared = Ring::prepare(&ring_params).unwrap();
+        prepared.vec_G.pop();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(Ring::verify_prepared(&prepared, &proof).is_err());
+    }
+
+    #[test]
+    fn test_verify_with_ring() {
+        use ark_secp256k1::Affine;
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 8).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        let nonce = ring_params.session_nonce;
+
+        // the verifier picks the ring of the proof out of a larger set
+        let mut superset = ring_params.vec_pk.clone();
+        superset.extend((0..8).map(|_| Affine::rand(&mut rng)));
+        assert!(Ring::verify_with_ring(&proof, &superset[..8], &message, &nonce).is_ok());
+
+        // a ring with one substituted key, the superset, a ring that is not a power of two,
+        // another message and another session are rejected
+        let mut ring_prime = superset[..8].to_vec();
+        ring_prime[3] = superset[12];
+        assert!(Ring::verify_with_ring(&proof, &ring_prime, &message, &nonce).is_err());
+        assert!(Ring::verify_with_ring(&proof, &superset, &message, &nonce).is_err());
+        assert!(Ring::verify_with_ring(&proof, &superset[..6], &message, &nonce).is_err());
+        assert!(Ring::verify_with_ring(&proof, &superset[..8], "another message", &nonce).is_err());
+        assert!(Ring::verify_with_ring(&proof, &superset[..8], &message, &SessionNonce::rand(&mut rng)).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_wrong_params() {
+        let mut rng = ark_std::test_rng();
+        let sk = Fr::rand(&mut rng);
+        let mut wit = vec![sk];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 8).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(ring_params.compressed_com_params().is_ok());
+
+        // a missing parameter set is rejected instead of being misindexed
+        let mut params_prime = ring_params.clone();
+        params_prime.com_parameters.pop();
+        assert!(Ring::prove(&mut rng, &params_prime, &wit).is_err());
+        assert!(Ring::verify(&params_prime, &proof).is_err());
+
+        // parameter sets of the wrong size are rejected
+        let mut params_prime = ring_params.clone();
+        params_prime.com_parameters[2].vec_gen.pop();
+        assert_eq!(params_prime.com_parameters[2].supported_size(), 7);
+        assert!(Ring::prove(&mut rng, &params_prime, &wit).is_err());
+        assert!(Ring::verify(&params_prime, &proof).is_err());
+        let mut params_prime = ring_params.clone();
+        params_prime.com_parameters[4] = params_prime.com_parameters[0].clone();
+        assert!(Ring::verify(&params_prime, &proof).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_forged_openings() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+
+        // a forged opening is rejected, even with the challenges re-derived for it
+        let forgeries: [fn(&mut CompressedOpenings<Projective>); 4] = [
+            |openings| openings.fs += Fr::one(),
+            |openings| openings.taux += Fr::one(),
+            |openings| openings.mu_1 += Fr::one(),
+            |openings| openings.mu_2 += Fr::one(),
+        ];
+        for forge in forgeries {
+            let mut proof_prime = proof.clone();
+            forge(&mut proof_prime.openings);
+            assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+            proof_prime.challenges = replay_challenges::<Projective, Sha256>(&ring_params, &proof_prime).unwrap().0;
+            assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+        }
+
+        // the prover cannot sign with the zero indicator vector
+        let mut wit_zero = wit.clone();
+        wit_zero[1..].iter_mut().for_each(|b_i| *b_i = Fr::zero());
+        assert!(Ring::prove(&mut rng, &ring_params, &wit_zero).is_err());
+        // nor with a witness without secret key, more ones than secret keys, or a truncated ring
+        assert!(Ring::prove(&mut rng, &ring_params, &wit[1..].to_vec()).is_err());
+        let mut wit_ones = wit.clone();
+        wit_ones[1..].iter_mut().for_each(|b_i| *b_i = Fr::one());
+        assert!(Ring::prove(&mut rng, &ring_params, &wit_ones).is_err());
+        let mut params_prime = ring_params.clone();
+        params_prime.vec_pk.pop();
+        assert!(Ring::prove(&mut rng, &params_prime, &wit).is_err());
+    }
+
+    #[test]
+    fn test_ringsignature_forged_hat_t() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+
+        let mut proof_prime = proof.clone();
+        proof_prime.openings.hat_t += Fr::one();
+        assert!(Ring::verify(&ring_params, &proof_prime).is_err());
+        // with the challenges re-derived, the hat_t check itself rejects the proof
+        proof_prime.challenges = replay_challenges::<Projective, Sha256>(&ring_params, &proof_prime).unwrap().0;
+        let result = Ring::verify(&ring_params, &proof_prime);
+        assert!(matches!(result, Err(SigmaErrors::InvalidProof(ref msg)) if msg.contains("hat_t")));
+    }
+
+    #[test]
+    fn test_verify_failures() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(Ring::verify(&ring_params, &proof).is_ok());
+
+        // each field of the signature is corrupted so that exactly one check fails, and the challenges
+        // are re-derived after the openings where the check comes after the transcript,
+        // the openings of equations (2) and (3) are bound to the round challenges of the aggregated IPA,
+        // so they are rejected by it, while a forged IPA scalar reaches the final msm
+        type Corruption = fn(&mut CompressedRingSignature<Projective>);
+        let corruptions: [(&str, bool, Corruption); 8] = [
+            ("incorrect proof length", false, |proof| { proof.commitments.pop(); }),
+            ("the proof belongs to another session", false, |proof| proof.session_nonce = SessionNonce::default()),
+            ("invalid message digest", false, |proof| proof.digest.push('0')),
+            ("invalid challenge value", false, |proof| proof.challenges[1] += Fr::one()),
+            ("hat_t is inconsistent with T1, T2", true, |proof| proof.openings.taux += Fr::one()),
+            ("invalid challenge value", true, |proof| proof.openings.mu_1 += Fr::one()),
+            ("invalid challenge value", true, |proof| proof.openings.fs += Fr::one()),
+            ("invalid IPA proof", false, |proof| proof.compression_proof.a += Fr::one()),
+        ];
+        for (error, replay, corrupt) in corruptions {
+            let mut proof_prime = proof.clone();
+            corrupt(&mut proof_prime);
+            if replay {
+                proof_prime.challenges = replay_challenges::<Projective, Sha256>(&ring_params, &proof_prime).unwrap().0;
+            }
+            let result = Ring::verify(&ring_params, &proof_prime);
+            assert!(matches!(result, Err(SigmaErrors::InvalidProof(ref msg)) if msg == error), "{error}: {result:?}");
+        }
+    }
+
+    #[test]
+    fn test_rhs_step2() {
+        use ark_ec::VariableBaseMSM;
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let n = 4;
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, n).unwrap();
+        let com_params = ring_params.compressed_com_params().unwrap();
+
+        // A, C commit to (b_0, b_1), (b_2, b_3) and B, D to random (r_0, r_1), (r_2, r_3) as in prove
+        let vec_b0 = wit[wit.len()-n..].to_vec();
+        let vec_b2 = subtract_with_borrow(&vec_b0).unwrap();
+        let bits_A = PedersenCommitmentScheme::commit_to_bits(&mut rng, com_params.g1u1, com_params.h1v1, &vec_b0).unwrap();
+        let bits_C = PedersenCommitmentScheme::commit_to_bits(&mut rng, com_params.g2u2, com_params.h2v2, &vec_b2).unwrap();
+        let (alpha_2, alpha_4) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let vec_r: Vec<Vec<Fr>> = (0..4).map(|_| (0..n).map(|_| Fr::rand(&mut rng)).collect()).collect();
+        let com_B = PedersenCommitmentScheme::commit(com_params.g1u1, &vec_r[0], &alpha_2, "on r0").unwrap()
+            + PedersenCommitmentScheme::commit(com_params.h1v1, &vec_r[1], &Fr::zero(), "on r1").unwrap();
+        let com_D = PedersenCommitmentScheme::commit(com_params.g2u2, &vec_r[2], &alpha_4, "on r2").unwrap()
+            + PedersenCommitmentScheme::commit(com_params.h2v2, &vec_r[3], &Fr::zero(), "on r3").unwrap();
+        let commitments = vec![bits_A.com, com_B, bits_C.com, com_D];
+
+        // the prover commits to A, B and C, D as batches under the concatenated keys
+        let coms_AB = PedersenCommitmentScheme::batch_commit(
+            &concat_params(com_params.g1u1, com_params.h1v1),
+            &[[vec_b0.as_slice(), &bits_A.complement].concat(), vec_r[0..2].concat()],
+            &[bits_A.random, alpha_2],
+        ).unwrap();
+        let coms_CD = PedersenCommitmentScheme::batch_commit(
+            &concat_params(com_params.g2u2, com_params.h2v2),
+            &[[vec_b2.as_slice(), &bits_C.complement].concat(), vec_r[2..4].concat()],
+            &[bits_C.random, alpha_4],
+        ).unwrap();
+        assert_eq!([coms_AB, coms_CD].concat(), Projective::normalize_batch(&commitments));
+
+        // zeta and eta with the y^n factor of zeta moved onto the generators g_1, g_2
+        let (y, z, x) = (Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng));
+        let (z2, z3, z5, z7) = (z.pow([2]), z.pow([3]), z.pow([5]), z.pow([7]));
+        let two_power_n_yn_inverse = hadamard_product(&powers_of_two(n), &generate_powers(y.inverse().unwrap(), n));
+        let add = |vec: &[Fr], c: Fr| -> Vec<Fr> { vec.iter().map(|v_i| *v_i + c).collect() };
+        let zeta_1 = vec_add(&add(&vec_b0, z), &scalar_product(&vec_r[0], &x));
+        let zeta_2 = vec_add(&add(&scalar_product(&vec_b2, &z2), z3), &scalar_product(&vec_r[2], &x));
+        let eta_1 = vec_add(&vec_add(&add(&bits_A.complement, z), &scalar_product(&two_power_n_yn_inverse, &z7)), &scalar_product(&vec_r[1], &x));
+        let eta_2 = vec_add(&vec_add(&add(&scalar_product(&bits_C.complement, &z2), z3), &scalar_product(&two_power_n_yn_inverse, &(-z5))), &scalar_product(&vec_r[3], &x));
+        let lhs = Projective::msm(&com_params.g1u1.vec_gen, &zeta_1).unwrap()
+            + Projective::msm(&com_params.g2u2.vec_gen, &zeta_2).unwrap()
+            + Projective::msm(&com_params.h1v1.vec_gen, &eta_1).unwrap()
+            + Projective::msm(&com_params.h2v2.vec_gen, &eta_2).unwrap();
+        let lhs_multi = PedersenCommitmentScheme::commit_multi(
+            &[com_params.g1u1, com_params.g2u2, com_params.h1v1, com_params.h2v2],
+            &[zeta_1, zeta_2, eta_1, eta_2],
+            &[Fr::zero(); 4],
+        ).unwrap();
+        assert_eq!(lhs_multi, lhs);
+
+        // mu_1 = alpha_1 + alpha_2*x, mu_2 = alpha_3*z^2 + alpha_4*x
+        let mut openings = CompressedOpenings::<Projective> {
+            mu_1: bits_A.random + alpha_2*x,
+            mu_2: bits_C.random*z2 + alpha_4*x,
+            ..Default::default()
+        };
+        let commitments = Projective::normalize_batch(&commitments);
+        let rhs_step2 = |openings: &CompressedOpenings<Projective>| {
+            msm_terms::<Projective>(&rhs_step2_terms(&ring_params, &commitments, openings, y, z, x).unwrap())
+        };
+        assert_eq!(rhs_step2(&openings), lhs);
+
+        // mu_2 must scale alpha_3 by z^2 as C is raised to z^2
+        openings.mu_2 = bits_C.random + alpha_4*x;
+        assert_ne!(rhs_step2(&openings), lhs);
+    }
+
+    #[test]
+    fn test_delta() {
+        // delta equals <zeta_0 \circ (y^n || y^n), eta_0> of the prover with zero randomness,
+        // for every non-zero indicator vector b_0 of length n
+        let (y, z, n) = (Fr::from(2u64), Fr::from(3u64), 4);
+        let (z2, z3, z5, z7) = (z.pow([2]), z.pow([3]), z.pow([5]), z.pow([7]));
+        let powers_yn = generate_powers(y, n);
+        let yn_yn = [powers_yn.clone(), powers_yn.clone()].concat();
+        let two_power_n_yn_inverse = hadamard_product(&powers_of_two(n), &generate_powers(y.inverse().unwrap(), n));
+        let complement = |vec_b: &[Fr]| -> Vec<Fr> { vec_b.iter().map(|&b_i| Fr::one() - b_i).collect() };
+        for index in 1..(1u64 << n) {
+            let vec_b0: Vec<Fr> = (0..n).map(|i| Fr::from((index >> i) & 1)).collect();
+            let vec_b2 = subtract_with_borrow(&vec_b0).unwrap();
+            let (vec_b1, vec_b3) = (complement(&vec_b0), complement(&vec_b2));
+            let zeta_0 = [
+                vec_add(&vec_b0, &vec![z; n]),
+                vec_add(&scalar_product(&vec_b2, &z2), &vec![z3; n]),
+            ].concat();
+            let eta_0 = [
+                vec_add(&vec_add(&vec_b1, &vec![z; n]), &scalar_product(&two_power_n_yn_inverse, &z7)),
+                vec_add(&vec_add(&scalar_product(&vec_b3, &z2), &vec![z3; n]), &scalar_product(&two_power_n_yn_inverse, &(-z5))),
+            ].concat();
+            let expected = inner_product(&hadamard_product(&zeta_0, &yn_yn), &eta_0);
+            assert_eq!(delta(y, z, n), expected, "delta differs for b_0 = {:04b}", index);
+        }
+
+        // the z^8 term of the original formula does not satisfy the identity
+        let sum_yn = vec_sum(&powers_yn);
+        assert_ne!(delta(y, z, n), (z + z2 + z.pow([5]) + z.pow([6]))*sum_yn + z.pow([8]));
+    }
+
+    #[test]
+    fn test_subtract_with_borrow() {
+        let vec_b: Vec<Fr> = convert(&[0, 0, 1, 0]);
+        assert_eq!(subtract_with_borrow(&vec_b), Some(convert(&[1, 1, 0, 0])));
+        let vec_b: Vec<Fr> = convert(&[1, 0, 1, 0]);
+        assert_eq!(subtract_with_borrow(&vec_b), Some(convert(&[0, 0, 1, 0])));
+        assert_eq!(subtract_with_borrow(&[Fr::zero(); 4]), None);
+    }
+
+    #[test]
+    fn test_ringsignature_bls() {
+        use ark_bls12_381::{Fr as G1Fr, G1Projective};
+        // the same flow on the G1 group of a pairing-friendly curve
+        let mut rng = ark_std::test_rng();
+        let ring_size = 16;
+        let sk = G1Fr::rand(&mut rng);
+        let mut wit = vec![sk];
+        type Ring = RingSignatureScheme<G1Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, ring_size).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(Ring::verify(&ring_params, &proof).is_ok());
+
+        // the modulus of BLS12-381 has 255 bits, so ring sizes up to 128 are supported
+        assert!(Ring::setup(&mut rng, &mut vec![sk], &message, 128).is_ok());
+        assert!(Ring::setup(&mut rng, &mut vec![sk], &message, 256).is_err());
+    }
+
+    #[bench]
+    fn bench_verify(b: &mut Bencher) {
+        // the largest supported ring, a power of two below the 256-bit modulus of secp256k1
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 128).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        b.iter(|| Ring::verify(&ring_params, &proof).unwrap());
+    }
+}
@@ -1,31 +1,32 @@
-use std::io::Write;
 use std::marker::PhantomData;
 
 use ark_ec::CurveGroup;
 use ark_ff::Field;
-use ark_std::{end_timer, rand::Rng, start_timer, UniformRand, Zero, One};
-use sha256::digest;
+use ark_std::{end_timer, rand::Rng, start_timer, UniformRand, Zero};
 use crate::commitment::pedersen::PedersenCommitmentScheme;
 use crate::commitment::PedersenParams;
-use crate::ringsig::structs::{LinearRingSignature, RingSignatureParams, Openings};
-use toolbox::sigma::{transcript::ProofTranscript, SigmaProtocol};
+use crate::ringsig::structs::{LinearRingSignature, RingSignatureParams, LinearOpenings, SessionNonce, LINEAR_DOMAIN};
+use toolbox::sigma::SigmaProtocol;
 use toolbox::errors::SigmaErrors;
+use toolbox::hash::{Digest, Sha256};
 use toolbox::vec::*;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct RingSignatureScheme<C>
+pub struct RingSignatureScheme<C, D = Sha256>
 where
     C: CurveGroup,
+    D: Digest,
 {
-    phantom: PhantomData<C>,
+    phantom: PhantomData<(C, D)>,
 }
 
 /// Implement a sigma protocol as a ring signature scheme (without compression), including 5-move:
 /// Relation: P knows a sk to a pk among the vector vec_pk
 /// Formalized Relation: P knows a sk satisfying <vec_pk, vec_b> = com(sk)
-impl<C> SigmaProtocol<C> for RingSignatureScheme<C>
+impl<C, D> SigmaProtocol<C> for RingSignatureScheme<C, D>
 where
     C: CurveGroup,
+    D: Digest,
 {
     /// public parameters
     type PublicParams = RingSignatureParams<C>;
@@ -41,31 +42,30 @@ where
     fn setup<R: Rng>(
         rng: &mut R,
         wit: &mut Self::Witness, // secret key
-        msg: &String,
+        msg: &str,
         supported_size: usize, // ring size
     ) -> Result<Self::PublicParams, SigmaErrors> {
-        // generate commitment scheme parameters (vec_g, u)
-        let com_params_1 = PedersenCommitmentScheme::<C>::setup(rng, supported_size)?;
-        // generate commitment scheme parameters (vec_h, v)
-        let com_params_2 = PedersenCommitmentScheme::<C>::setup(rng, supported_size)?;
-
-        // generate public key parameters (g)
-        let key_params = PedersenCommitmentScheme::<C>::setup(rng, 1)?;
+        // derive commitment scheme parameters (vec_g, u), (vec_h, v) and public key parameters (g)
+        let com_parameters = RingSignatureParams::<C>::deterministic_com_parameters(2, supported_size)?;
+        let key_params = &com_parameters[2];
 
         // generate pk vectors
-        let pk:C::Affine = PedersenCommitmentScheme::commit(&key_params, wit, &C::ScalarField::zero(), "as pk")?.into_affine();
-        let mut vec_pk = vec![C::Affine::rand(rng); supported_size-1];
+        let pk:C::Affine = PedersenCommitmentScheme::commit(key_params, wit, &C::ScalarField::zero(), "as pk")?.into_affine();
+        let mut vec_pk = rand_affine_vec::<C, R>(rng, supported_size-1);
         // add pk to the vector and shuffle it
         vec_pk.push(pk);
-        let vec_b = shuffle::<C>(&mut vec_pk, pk);
+        let (vec_b, index) = shuffle_indexed::<C, R>(&mut vec_pk, pk, rng);
+        debug_assert_eq!(vec_pk[index], pk);
+        assert_binary(&vec_b);
         wit.extend(vec_b);
 
         Ok(RingSignatureParams {
             num_witness: wit.len(),
             num_pub_inputs: supported_size,
-            com_parameters: vec![com_params_1, com_params_2, key_params],
-            message: msg.clone(),
+            com_parameters,
+            message: msg.to_string(),
             vec_pk,
+            session_nonce: SessionNonce::rand(rng),
         })
     }
 
@@ -76,7 +76,7 @@ where
     ) -> Result<Self::Proof, SigmaErrors> {
         // initialization
         let start = start_timer!(|| "running sigma protocol prove algorithm...");
-        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingSignature");
+        let mut transcript = params.session_transcript(LINEAR_DOMAIN)?;
         transcript.append_serializable_element(b"public list", &params.vec_pk)?;
 
         // parse commitment parameters
@@ -87,32 +87,16 @@ where
         let vec_sk = wit[0..wit.len()-params.num_pub_inputs].to_vec();
         let vec_b = wit[wit.len()-params.num_pub_inputs..].to_vec();
 
-        // denote b_0 = b, b_1 = 1^n - b_0
-        let vec_b0 = vec_b.clone();
-        let vec_b1: Vec<C::ScalarField> = vec_b.iter()
-            .map(|&b_i| C::ScalarField::one() - b_i)
-            .collect();
-
-        // sanity check
-        // b_0 + b_1 = 1^n
-        // b_0 \circ b_1 = 0^n
-        let constraint_1 = vec_b0.iter()
-            .zip(vec_b1.iter())
-            .all(|(&b0_i, &b1_i)| b0_i + b1_i == C::ScalarField::one());
-        let constraint_2 = vec_b0.iter()
-            .zip(vec_b1.iter())
-            .all(|(&b0_i, &b1_i)| b0_i * b1_i == C::ScalarField::zero());
-        assert!(constraint_1 && constraint_2);
-
+        // denote b_0 = b, b_1 = 1^n - b_0, where b_0 must be binary
         // computes A = g^{b_0}h^{b_1}u^{alpha}, B = g^{r_0}h^{r_1}u^{beta}
-        let alpha = C::ScalarField::rand(rng);
+        let vec_b0 = vec_b.clone();
+        let bits_A = PedersenCommitmentScheme::commit_to_bits(rng, param_g_u, param_h_v, &vec_b0)?;
+        let (com_A, vec_b1, alpha) = (bits_A.com, bits_A.complement, bits_A.random);
         let beta = C::ScalarField::rand(rng);
-        let vec_r0 = vec![C::ScalarField::rand(rng); vec_b0.len()];
-        let vec_r1 = vec![C::ScalarField::rand(rng); vec_b1.len()];
-        let com_A = PedersenCommitmentScheme::commit(&param_g_u, &vec_b0, &alpha, "on b0")?
-            + PedersenCommitmentScheme::commit(&param_h_v, &vec_b1, &C::ScalarField::zero(), "on b1")?;
-        let com_B = PedersenCommitmentScheme::commit(&param_g_u, &vec_r0, &beta, "on r0")?
-            + PedersenCommitmentScheme::commit(&param_h_v, &vec_r1, &C::ScalarField::zero(), "on r1")?;
+        let vec_r0: Vec<C::ScalarField> = (0..vec_b0.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let vec_r1: Vec<C::ScalarField> = (0..vec_b1.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let com_B = PedersenCommitmentScheme::commit(param_g_u, &vec_r0, &beta, "on r0")?
+            + PedersenCommitmentScheme::commit(param_h_v, &vec_r1, &C::ScalarField::zero(), "on r1")?;
 
         // P->V: A,B
         transcript.append_serializable_element(b"commitments A,B", &[com_A, com_B])?;
@@ -136,26 +120,24 @@ where
         // T1 = v^{t1}u^{tau1}
         // T2 = v^{t2}u^{tau2}
         let rs = C::ScalarField::rand(rng);
-        let neg_rs = -rs.clone();
+        let neg_rs = -rs;
         let tau1 = C::ScalarField::rand(rng);
         let tau2 = C::ScalarField::rand(rng);
 
-        let com_E = C::msm(&params.vec_pk, &vec_r0_yn).unwrap() + PedersenCommitmentScheme::commit(&param_key, &vec![neg_rs], &C::ScalarField::zero(), "E")?;
+        let com_E = C::msm(&params.vec_pk, &vec_r0_yn).unwrap() + PedersenCommitmentScheme::commit(param_key, &[neg_rs], &C::ScalarField::zero(), "E")?;
         let param_u_v = PedersenParams {
-            generator: param_h_v.generator.clone(),
-            vec_gen: vec![param_g_u.generator.into_affine().clone()],
+            generator: param_h_v.generator,
+            vec_gen: vec![param_g_u.generator.into_affine()],
         };
-        let com_T1 = PedersenCommitmentScheme::commit(&param_u_v, &vec![tau1], &t1, "T1")?;
-        let com_T2 = PedersenCommitmentScheme::commit(&param_u_v, &vec![tau2], &t2, "T2")?;
+        let com_T1 = PedersenCommitmentScheme::commit(&param_u_v, &[tau1], &t1, "T1")?;
+        let com_T2 = PedersenCommitmentScheme::commit(&param_u_v, &[tau2], &t2, "T2")?;
 
         // P->V: E, T1, T2
         transcript.append_serializable_element(b"commitments A,B", &[com_E, com_T1, com_T2])?;
 
         // append the message digest to the transcript
-        let h = digest(&params.message);
-        let mut h_msg: &mut [u8] = &mut [0; 32];
-        h_msg.write(h.as_bytes()).unwrap();
-        transcript.append_message(b"message digest", &h_msg)?;
+        let h = D::digest(params.message.as_bytes());
+        transcript.append_message_digest::<D>(&h)?;
 
         // V->P: challenges x
         let x = transcript.get_and_append_challenge(b"challenge x")?;
@@ -169,8 +151,7 @@ where
         let hat_t = inner_product(&zeta, &eta);
 
         // // sanity check
-        // let vec_1n = vec![C::ScalarField::one(); params.num_pub_inputs];
-        // let delta = inner_product(&vec_1n, &powers_yn) * (z+z*z);
+        // let delta = vec_sum(&powers_yn) * (z+z*z);
         // let t_prime = delta + t1*x + t2*x*x;
         // if t_prime == hat_t {println!("delta equality passes")}
         // else {println!("delta equality fails")}
@@ -190,7 +171,7 @@ where
             }
         }
         let fs = sum + rs*x;
-        let openings = Openings {
+        let openings = LinearOpenings {
             zeta,
             eta,
             hat_t,
@@ -207,6 +188,7 @@ where
             openings,
             challenges: vec![y,z,x],
             digest: h.clone(),
+            session_nonce: params.session_nonce,
         })
     }
 
@@ -214,80 +196,133 @@ where
         params: &Self::PublicParams,
         proof: &Self::Proof
     ) -> Result<bool, SigmaErrors> {
+        // a malformed proof or a desynchronized transcript is rejected before any group arithmetic
+        Self::precheck_challenges(params, proof)?;
         // initialization
-        let start = start_timer!(|| "running sigma protocol prove algorithm...");
-        let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingSignature");
-        transcript.append_serializable_element(b"public list", &params.vec_pk)?;
+        let start = start_timer!(|| "running sigma protocol verify algorithm...");
 
         // parse commitment parameters
         let param_g_u = &params.com_parameters[0];
         let param_h_v = &params.com_parameters[1];
         let param_key = &params.com_parameters[2];
+        // commit_constant reads the generators without a length check
+        if param_g_u.supported_size() != params.num_pub_inputs || param_h_v.supported_size() != params.num_pub_inputs {
+            end_timer!(start);
+            return Err(SigmaErrors::InvalidParameters(
+                "commitment parameters do not match the ring size".to_string(),
+            ));
+        }
 
         // parse proof
         let commitments = &proof.commitments;
         let (com_A, com_B, com_E, com_T1, com_T2) = (commitments[0], commitments[1], commitments[2], commitments[3], commitments[4]);
         let openings = &proof.openings;
-        let challenges = &proof.challenges;
-        let digest = &proof.digest;
-
-        // check the challenges
-        transcript.append_serializable_element(b"commitments A,B", &[com_A, com_B])?;
-        let y = transcript.get_and_append_challenge(b"challenge y")?;
-        let z = transcript.get_and_append_challenge(b"challenge z")?;
-        transcript.append_serializable_element(b"commitments A,B", &[com_E, com_T1, com_T2])?;
-        let h = sha256::digest(&params.message);
-        assert_eq!(&h, digest);
-        let mut h_msg: &mut [u8] = &mut [0; 32];
-        h_msg.write(h.as_bytes()).unwrap();
-        transcript.append_message(b"message digest", &h_msg)?;
-        let x = transcript.get_and_append_challenge(b"challenge x")?;
-
-        if (y,z,x) != (challenges[0],challenges[1],challenges[2])  {
-            return Err(SigmaErrors::InvalidProof(
-                "invalid challenge value".to_string(),
-            ));
-        }
+        let (y,z,x) = (proof.challenges[0], proof.challenges[1], proof.challenges[2]);
 
         // check validity of T1 T2
         // v^{hat_t} y^taux = v^delta T1^x T2^{x^2}
-        let vec_0n = vec![C::ScalarField::zero(); params.num_pub_inputs];
-        let vec_1n = vec![C::ScalarField::one(); params.num_pub_inputs];
         let powers_yn = generate_powers(y, params.num_pub_inputs);
-        let delta = inner_product(&vec_1n, &powers_yn) * (z+z*z);
-        let lhs = PedersenCommitmentScheme::commit(param_h_v, &vec_0n, &openings.hat_t, "on hat_t")?
-            + PedersenCommitmentScheme::commit(&param_g_u, &vec_0n, &openings.taux, "on tau_x")?;
-        let rhs = PedersenCommitmentScheme::commit(param_h_v, &vec_0n, &delta, "on delta")?
+        let delta = vec_sum(&powers_yn) * (z+z*z);
+        let zero = C::ScalarField::zero();
+        let lhs = PedersenCommitmentScheme::commit_constant(param_h_v, &zero, &openings.hat_t)
+            + PedersenCommitmentScheme::commit_constant(param_g_u, &zero, &openings.taux);
+        let rhs = PedersenCommitmentScheme::commit_constant(param_h_v, &zero, &delta)
             + com_T1.mul(x) + com_T2.mul(x*x);
-        assert_eq!(lhs, rhs, "step 1: T1, T2 checks fail");
+        if lhs != rhs {
+            end_timer!(start);
+            return Err(SigmaErrors::InvalidProof(
+                "step 1: T1, T2 checks fail".to_string(),
+            ));
+        }
 
         // check validity of A B
         // g^{zeta \circ y^n} h^eta u^mu = A B^x g^{z1^n} h^{z1^n}
-        let powers_yn_inverse = generate_powers(y.inverse().unwrap(), params.num_pub_inputs);
+        let y_inv = y.inverse().ok_or(SigmaErrors::InvalidProof("invalid challenge value".to_string()))?;
+        let powers_yn_inverse = generate_powers(y_inv, params.num_pub_inputs);
         // assert_eq!(hadamard_product(&powers_yn, &powers_yn_inverse), vec![C::ScalarField::one(); params.num_pub_inputs]);
         let zeta_yn = hadamard_product(&openings.zeta, &powers_yn_inverse);
-        let vec_z1n = vec![z; params.num_pub_inputs];
-        let lhs = PedersenCommitmentScheme::commit(&param_g_u, &zeta_yn, &openings.mu, "on zeta")?
-            + PedersenCommitmentScheme::commit(&param_h_v, &openings.eta, &C::ScalarField::zero(), "on eta")?;
+        let lhs = PedersenCommitmentScheme::commit(param_g_u, &zeta_yn, &openings.mu, "on zeta")?
+            + PedersenCommitmentScheme::commit(param_h_v, &openings.eta, &C::ScalarField::zero(), "on eta")?;
         let rhs = com_A + com_B.mul(x)
-            + PedersenCommitmentScheme::commit(&param_g_u, &vec_z1n, &C::ScalarField::zero(), "on z1n")?
-            + PedersenCommitmentScheme::commit(&param_h_v, &vec_z1n, &C::ScalarField::zero(), "on z1n")?;
-        assert_eq!(lhs, rhs, "step 2: A,B checks fail");
+            + PedersenCommitmentScheme::commit_constant(param_g_u, &z, &zero)
+            + PedersenCommitmentScheme::commit_constant(param_h_v, &z, &zero);
+        if lhs != rhs {
+            end_timer!(start);
+            return Err(SigmaErrors::InvalidProof(
+                "step 2: A,B checks fail".to_string(),
+            ));
+        }
 
         // check pk
         // P^zeta = g^fs E^x P^{z y^n}
         let vec_z_yn = scalar_product(&powers_yn, &z);
-        let lhs = C::msm(&params.vec_pk, &openings.zeta).unwrap();
-        let rhs = PedersenCommitmentScheme::commit(&param_key, &vec![openings.fs], &C::ScalarField::zero(), "on fs")?
-            + com_E.mul(x) + C::msm(&params.vec_pk, &vec_z_yn).unwrap();
-        assert_eq!(lhs, rhs, "step 3: pk check fails");
+        let lhs = C::msm(&params.vec_pk, &openings.zeta)
+            .map_err(|_| SigmaErrors::InvalidParameters("msm length mismatch".to_string()))?;
+        let rhs = PedersenCommitmentScheme::commit(param_key, &[openings.fs], &C::ScalarField::zero(), "on fs")?
+            + com_E.mul(x)
+            + C::msm(&params.vec_pk, &vec_z_yn)
+                .map_err(|_| SigmaErrors::InvalidParameters("msm length mismatch".to_string()))?;
+        if lhs != rhs {
+            end_timer!(start);
+            return Err(SigmaErrors::InvalidProof(
+                "step 3: pk check fails".to_string(),
+            ));
+        }
 
         // check inner product hat_t = <zeta, eta>
         let t = inner_product(&openings.zeta, &openings.eta);
-        assert_eq!(openings.hat_t, t, "step 4: hat_t check fails");
-        let result = true;
+        if openings.hat_t != t {
+            end_timer!(start);
+            return Err(SigmaErrors::InvalidProof(
+                "step 4: hat_t check fails".to_string(),
+            ));
+        }
         end_timer!(start);
-        Ok(result)
+        Ok(true)
+    }
+}
+
+impl<C: CurveGroup, D: Digest> RingSignatureScheme<C, D> {
+    /// Replays the transcript of a signature and compares the challenges y, z, x it stores,
+    /// after checking the session nonce, the proof length and the message digest,
+    /// so that verify rejects a malformed or desynchronized proof before any group arithmetic
+    pub fn precheck_challenges(
+        params: &RingSignatureParams<C>,
+        proof: &LinearRingSignature<C>,
+    ) -> Result<(), SigmaErrors> {
+        params.check_session_nonce(&proof.session_nonce)?;
+        if proof.commitments.len() != 5 || proof.challenges.len() != 3 {
+            return Err(SigmaErrors::InvalidProof(
+                "incorrect proof length".to_string(),
+            ));
+        }
+        if proof.openings.zeta.len() != params.num_pub_inputs || proof.openings.eta.len() != params.num_pub_inputs {
+            return Err(SigmaErrors::InvalidProof(
+                "openings do not match the ring size".to_string(),
+            ));
+        }
+        let commitments = &proof.commitments;
+        let mut transcript = params.session_transcript(LINEAR_DOMAIN)?;
+        transcript.append_serializable_element(b"public list", &params.vec_pk)?;
+        transcript.append_serializable_element(b"commitments A,B", &[commitments[0], commitments[1]])?;
+        let y = transcript.get_and_append_challenge(b"challenge y")?;
+        let z = transcript.get_and_append_challenge(b"challenge z")?;
+        transcript.append_serializable_element(b"commitments A,B", &[commitments[2], commitments[3], commitments[4]])?;
+        let h = D::digest(params.message.as_bytes());
+        if h != proof.digest {
+            return Err(SigmaErrors::InvalidProof(
+                "invalid message digest".to_string(),
+            ));
+        }
+        transcript.append_message_digest::<D>(&h)?;
+        let x = transcript.get_and_append_challenge(b"challenge x")?;
+
+        if [y, z, x] != proof.challenges[..] {
+            return Err(SigmaErrors::InvalidProof(
+                "invalid challenge value".to_string(),
+            ));
+        }
+        Ok(())
     }
 }
 
@@ -311,7 +346,113 @@ mod tests {
         // prove algorithm
         let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
         // verify algorithm
-        let result = Ring::verify(&ring_params, &proof).unwrap();
-        assert_eq!(result, true);
+        assert!(Ring::verify(&ring_params, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_ringsignature_bls() {
+        use ark_bls12_381::{Fr as G1Fr, G1Projective};
+        // the same flow on the G1 group of a pairing-friendly curve
+        let mut rng = ark_std::test_rng();
+        let ring_size = 10;
+        let sk = G1Fr::rand(&mut rng);
+        let mut wit = vec![sk];
+        type Ring = RingSignatureScheme<G1Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, ring_size).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(Ring::verify(&ring_params, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_precheck_challenges() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        Ring::precheck_challenges(&ring_params, &proof).unwrap();
+
+        // a corrupted stored challenge is reported by the precheck, without reading the commitment parameters
+        let mut proof_prime = proof.clone();
+        proof_prime.challenges[0] += Fr::from(1u64);
+        let mut params_prime = ring_params.clone();
+        params_prime.com_parameters[0].vec_gen.clear();
+        for params in [&ring_params, &params_prime] {
+            assert!(matches!(Ring::verify(params, &proof_prime), Err(SigmaErrors::InvalidProof(msg)) if msg == "invalid challenge value"));
+        }
+        // a signature on another message is rejected instead of panicking
+        let mut params_prime = ring_params.clone();
+        params_prime.message = String::from("another message");
+        assert!(Ring::verify(&params_prime, &proof).is_err());
+        let mut proof_prime = proof.clone();
+        proof_prime.challenges.pop();
+        assert!(Ring::precheck_challenges(&ring_params, &proof_prime).is_err());
+    }
+
+    #[test]
+    fn test_cross_protocol_replay() {
+        use crate::ringsig::protocol_compressed;
+        use crate::ringsig::structs::LogarithmicRingSignature;
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        type LogRing = protocol_compressed::RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        Ring::precheck_challenges(&ring_params, &proof).unwrap();
+
+        // the logarithmic scheme runs the same moves on the same parameters, but its transcript has another domain,
+        // so that the challenges of the linear proof do not replay into the logarithmic verifier
+        let replayed = LogarithmicRingSignature {
+            commitments: Projective::normalize_batch(&proof.commitments),
+            openings: proof.openings.clone(),
+            challenges: proof.challenges.clone(),
+            compression_proof: Default::default(),
+            digest: proof.digest.clone(),
+            session_nonce: proof.session_nonce,
+        };
+        assert!(matches!(LogRing::precheck_challenges(&ring_params, &replayed), Err(SigmaErrors::InvalidProof(msg)) if msg == "invalid challenge value"));
+        assert!(LogRing::verify(&ring_params, &replayed).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_verify_failures() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        type Ring = RingSignatureScheme<Projective>;
+        let message = String::from("Welcome to the world of Zero Knowledge!");
+        let mut ring_params = Ring::setup(&mut rng, &mut wit, &message, 4).unwrap();
+        // a trapdoor h_1 = u^k between the generators of eta and mu lets the test shift eta without failing step 2,
+        // which is the only way to reach step 4 without breaking an earlier equation
+        let k = Fr::rand(&mut rng);
+        ring_params.com_parameters[1].vec_gen[0] = (ring_params.com_parameters[0].generator * k).into_affine();
+        let proof = Ring::prove(&mut rng, &ring_params, &wit).unwrap();
+        assert!(Ring::verify(&ring_params, &proof).is_ok());
+
+        // each opening is corrupted so that exactly one equation fails, which is reported instead of panicking
+        type Corruption = fn(&mut LinearOpenings<Projective>, Fr);
+        let corruptions: [(&str, Corruption); 4] = [
+            ("step 1: T1, T2 checks fail", |openings, _| openings.taux += Fr::from(1u64)),
+            ("step 2: A,B checks fail", |openings, _| openings.mu += Fr::from(1u64)),
+            ("step 3: pk check fails", |openings, _| openings.fs += Fr::from(1u64)),
+            ("step 4: hat_t check fails", |openings, k| {
+                openings.eta[0] += Fr::from(1u64);
+                openings.mu -= k;
+            }),
+        ];
+        for (step, corrupt) in corruptions {
+            let mut proof_prime = proof.clone();
+            corrupt(&mut proof_prime.openings, k);
+            assert!(matches!(Ring::verify(&ring_params, &proof_prime), Err(SigmaErrors::InvalidProof(msg)) if msg == step));
+        }
+        // a padded or short zeta is rejected before the vector arithmetic
+        for len in [3, 5] {
+            let mut proof_prime = proof.clone();
+            proof_prime.openings.zeta.resize(len, Fr::from(1u64));
+            assert!(matches!(Ring::verify(&ring_params, &proof_prime), Err(SigmaErrors::InvalidProof(msg)) if msg == "openings do not match the ring size"));
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::marker::PhantomData;
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::rand::Rng;
+use toolbox::errors::SigmaErrors;
+use toolbox::hash::hash_to_curve;
+use toolbox::sigma::{transcript::ProofTranscript, SigmaProtocol};
+use crate::ringsig::protocol_compressed_modification::RingSignatureScheme;
+use crate::ringsig::structs::{CompressedRingSignature, RingSignatureParams};
+
+// the domain of the VRF base H_p(input) = hash_to_curve(RING_VRF_DOMAIN, input)
+const RING_VRF_DOMAIN: &[u8] = b"RingCT-SP23 ring VRF";
+
+// Ring-VRF: the output Y = H_p(input)^sk is the key image of sk for the base H_p(input),
+// so it is unique per key-input pair, and the ring signature proves knowledge of sk with R_i = g*^sk for
+// R_j = pk_j Y^w and g* = g H_p(input)^w with a challenge w on the ring, the input and Y,
+// which for a random w holds only if pk_i = g^sk and Y = H_p(input)^sk for the same hidden member i,
+// as the modified compressed scheme proves that the indicator vector selects a member
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RingVRF<C: CurveGroup> {
+    phantom: PhantomData<C>,
+}
+
+impl<C: CurveGroup> RingVRF<C> {
+    /// Outputs the VRF output Y = H_p(vrf_input)^sk of the signer and a ring signature proving that Y
+    /// is computed with the key of a member of the ring, where ring_wit is sk followed by the indicator
+    /// vector of pk in the ring, as output by the setup of the modified compressed ring signature
+    pub fn prove<R: Rng>(
+        rng: &mut R,
+        ring_params: &RingSignatureParams<C>,
+        ring_wit: &[C::ScalarField],
+        vrf_input: &[u8],
+    ) -> Result<(C::Affine, CompressedRingSignature<C>), SigmaErrors> {
+        if ring_wit.len() != 1 + ring_params.num_pub_inputs {
+            return Err(SigmaErrors::InvalidProver(
+                "the witness should be one secret key and the indicator vector".to_string(),
+            ));
+        }
+        let vrf_output = (hash_to_curve::<C>(RING_VRF_DOMAIN, vrf_input) * ring_wit[0]).into_affine();
+        let params = vrf_parameters(ring_params, vrf_input, vrf_output)?;
+        let pk = (ring_params.com_parameters[4].vec_gen[0] * ring_wit[0]).into_affine();
+        if !ring_params.vec_pk.contains(&pk) {
+            return Err(SigmaErrors::InvalidProver(
+                "the public key is not a member of the ring".to_string(),
+            ));
+        }
+        let proof = RingSignatureScheme::<C>::prove(rng, &params, &ring_wit.to_vec())?;
+        Ok((vrf_output, proof))
+    }
+
+    /// Verifies that vrf_output is the VRF output of vrf_input under the key of a member of the ring
+    pub fn verify(
+        ring_params: &RingSignatureParams<C>,
+        vrf_input: &[u8],
+        vrf_output: C::Affine,
+        proof: &CompressedRingSignature<C>,
+    ) -> Result<bool, SigmaErrors> {
+        let params = vrf_parameters(ring_params, vrf_input, vrf_output)?;
+        RingSignatureScheme::<C>::verify(&params, proof)
+    }
+}
+
+// the ring signature parameters binding the ring to the VRF output,
+// with the ring R_j = pk_j Y^w and the key generator g* = g H_p(input)^w
+fn vrf_parameters<C: CurveGroup>(
+    ring_params: &RingSignatureParams<C>,
+    vrf_input: &[u8],
+    vrf_output: C::Affine,
+) -> Result<RingSignatureParams<C>, SigmaErrors> {
+    if ring_params.com_parameters.len() != 5 || ring_params.com_parameters[4].vec_gen.len() != 1 {
+        return Err(SigmaErrors::InvalidParameters(
+            "missing key generator".to_string(),
+        ));
+    }
+    let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingVRF");
+    transcript.append_serializable_element(b"ring", &ring_params.vec_pk)?;
+    transcript.append_message(b"VRF input", vrf_input)?;
+    transcript.append_serializable_element(b"VRF output", &vrf_output)?;
+    let w = transcript.get_and_append_challenge(b"challenge w")?;
+
+    let ring: Vec<C> = ring_params.vec_pk.iter()
+        .map(|pk_j| pk_j.into_group() + vrf_output * w)
+        .collect();
+    let mut params = ring_params.clone();
+    params.vec_pk = C::normalize_batch(&ring);
+    let key_params = &mut params.com_parameters[4];
+    key_params.vec_gen = vec![(key_params.vec_gen[0] + hash_to_curve::<C>(RING_VRF_DOMAIN, vrf_input) * w).into_affine()];
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Fr, Projective};
+    use ark_std::UniformRand;
+
+    type Vrf = RingVRF<Projective>;
+    type Ring = RingSignatureScheme<Projective>;
+
+    #[test]
+    fn test_ring_vrf() {
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let ring_params = Ring::setup(&mut rng, &mut wit, "ring VRF", 8).unwrap();
+
+        let (output, proof) = Vrf::prove(&mut rng, &ring_params, &wit, b"input").unwrap();
+        assert!(Vrf::verify(&ring_params, b"input", output, &proof).is_ok());
+        // the same input always gives the same output, with a fresh proof
+        let (output_prime, proof_prime) = Vrf::prove(&mut rng, &ring_params, &wit, b"input").unwrap();
+        assert_eq!(output_prime, output);
+        assert!(Vrf::verify(&ring_params, b"input", output, &proof_prime).is_ok());
+        // and different inputs give different outputs
+        let (output_other, proof_other) = Vrf::prove(&mut rng, &ring_params, &wit, b"another input").unwrap();
+        assert_ne!(output_other, output);
+        assert!(Vrf::verify(&ring_params, b"another input", output_other, &proof_other).is_ok());
+
+        // the proof is bound to the input and the output
+        assert!(Vrf::verify(&ring_params, b"another input", output, &proof).is_err());
+        assert!(Vrf::verify(&ring_params, b"input", output_other, &proof).is_err());
+        assert!(Vrf::verify(&ring_params, b"input", output_other, &proof_other).is_err());
+        let output_forged = (output + hash_to_curve::<Projective>(RING_VRF_DOMAIN, b"input")).into_affine();
+        assert!(Vrf::verify(&ring_params, b"input", output_forged, &proof).is_err());
+    }
+
+    #[test]
+    fn test_ring_vrf_keys() {
+        // another member of a ring outputs another value on the same input,
+        // and a key outside of the ring cannot prove its output
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let ring_params = Ring::setup(&mut rng, &mut wit, "ring VRF", 4).unwrap();
+        let mut wit_other = vec![Fr::rand(&mut rng)];
+        let ring_params_other = Ring::setup(&mut rng, &mut wit_other, "ring VRF", 4).unwrap();
+
+        let (output, _) = Vrf::prove(&mut rng, &ring_params, &wit, b"input").unwrap();
+        let (output_other, proof_other) = Vrf::prove(&mut rng, &ring_params_other, &wit_other, b"input").unwrap();
+        assert_ne!(output_other, output);
+        assert!(Vrf::verify(&ring_params, b"input", output_other, &proof_other).is_err());
+
+        let mut wit_outside = wit.clone();
+        wit_outside[0] = wit_other[0];
+        assert!(Vrf::prove(&mut rng, &ring_params, &wit_outside, b"input").is_err());
+        assert!(Vrf::prove(&mut rng, &ring_params, &wit[..4], b"input").is_err());
+
+        // nor can any key prove an output with the zero indicator vector
+        let params = vrf_parameters(&ring_params, b"input", output_other).unwrap();
+        let (vec_b0, vec_b2) = (vec![Fr::from(0u64); 4], vec![Fr::from(1u64); 4]);
+        let proof_zero = Ring::prove_with_bits(&mut rng, &params, &[], &vec_b0, &vec_b2).unwrap();
+        assert!(Vrf::verify(&ring_params, b"input", output_other, &proof_zero).is_err());
+    }
+}
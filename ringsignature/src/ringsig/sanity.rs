@@ -0,0 +1,60 @@
+use ark_ff::PrimeField;
+use toolbox::vec::inner_product;
+
+use crate::ringsig::protocol_compressed_modification::{powers_of_two, subtract_with_borrow};
+
+/// Checks that b_0, b_1 of length n are complementary bit vectors:
+/// b_0 + b_1 = 1^n and b_0 \circ b_1 = 0^n
+pub fn check_b0_b1_constraints<F: PrimeField>(b0: &[F], b1: &[F], n: usize) -> bool {
+    b0.len() == n && b1.len() == n && complementary_bits(b0, b1)
+}
+
+/// Checks that b_2, b_3 of length n are complementary bit vectors with b_2 = bits(int(b_0) - 1):
+/// b_2 + b_3 = 1^n, b_2 \circ b_3 = 0^n and <b_0, 2^n> - 1 = <b_2, 2^n>
+pub fn check_b2_b3_constraints<F: PrimeField>(b2: &[F], b3: &[F], b0: &[F], n: usize) -> bool {
+    if b2.len() != n || b3.len() != n || b0.len() != n || !complementary_bits(b2, b3) {
+        return false;
+    }
+    // the field relation alone holds for any b_2 once <b_0, 2^n> wraps around the modulus,
+    // so b_2 is also compared with the integer subtraction of b_0
+    let powers = powers_of_two::<F>(n);
+    subtract_with_borrow(b0).is_some_and(|diff| diff == b2)
+        && inner_product(b0, &powers) - F::one() == inner_product(b2, &powers)
+}
+
+// lhs + rhs = 1^n and lhs \circ rhs = 0^n
+fn complementary_bits<F: PrimeField>(lhs: &[F], rhs: &[F]) -> bool {
+    lhs.iter()
+        .zip(rhs.iter())
+        .all(|(&l_i, &r_i)| l_i + r_i == F::one() && l_i * r_i == F::zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::Field;
+    use ark_secp256k1::Fr;
+    use toolbox::vec::convert;
+
+    #[test]
+    fn test_check_constraints() {
+        let b0: Vec<Fr> = convert(&[0, 0, 1, 0]);
+        let b1: Vec<Fr> = convert(&[1, 1, 0, 1]);
+        let b2: Vec<Fr> = convert(&[1, 1, 0, 0]);
+        let b3: Vec<Fr> = convert(&[0, 0, 1, 1]);
+        assert!(check_b0_b1_constraints(&b0, &b1, 4));
+        assert!(check_b2_b3_constraints(&b2, &b3, &b0, 4));
+
+        // wrong lengths, non-binary entries and a wrong b_2 are rejected
+        assert!(!check_b0_b1_constraints(&b0, &b1, 8));
+        assert!(!check_b0_b1_constraints(&b0, &b0, 4));
+        let half = Fr::from(2u64).inverse().unwrap();
+        assert!(!check_b0_b1_constraints(&[half; 4], &[half; 4], 4));
+        assert!(!check_b2_b3_constraints(&b3, &b2, &b0, 4));
+        assert!(!check_b2_b3_constraints(&b2, &b3, &b1, 4));
+        // b_0 = 0^n has no b_2
+        let zero: Vec<Fr> = convert(&[0, 0, 0, 0]);
+        let one: Vec<Fr> = convert(&[1, 1, 1, 1]);
+        assert!(!check_b2_b3_constraints(&one, &zero, &zero, 4));
+    }
+}
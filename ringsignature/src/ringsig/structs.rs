@@ -1,46 +1,183 @@
 use crate::commitment::{PedersenParams};
+use crate::commitment::pedersen::PedersenCommitmentScheme;
+use crate::params_file;
 use ark_ec::CurveGroup;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, SerializationError, Valid, Validate,
+};
+use ark_std::{io::{Read, Write}, rand::Rng, UniformRand};
 use bulletproofs::structs::InnerProductProof;
+use std::path::Path;
+use toolbox::errors::SigmaErrors;
+use toolbox::sigma::transcript::ProofTranscript;
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Openings<C: CurveGroup> {
+// the labels of the generators of the ring signatures, hashed to the curve by setup_deterministic;
+// the key generator g is shared by all rings, so that a key pair can join any ring
+pub const RING_COM_LABEL: &[u8] = b"RingCT-SP23 ring commitment";
+pub const RING_KEY_LABEL: &[u8] = b"RingCT-SP23 public key";
+
+// the transcript domains of the ring signatures, so that the challenges of a proof are bound to its protocol
+// and a proof of the linear scheme is not replayed into the verifier of a logarithmic one
+pub const LINEAR_DOMAIN: &[u8] = b"RingSig-Linear";
+pub const LOGARITHMIC_DOMAIN: &[u8] = b"RingSig-Log";
+
+// the 32 random bytes identifying a setup session, serialized as the raw bytes;
+// the derived impls for [u8; 32] of ark-serialize 0.4 panic on truncated input, so the bytes are read with read_exact
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct SessionNonce(pub [u8; 32]);
+
+impl UniformRand for SessionNonce {
+    fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        SessionNonce(rng.r#gen())
+    }
+}
+
+impl CanonicalSerialize for SessionNonce {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        _compress: Compress,
+    ) -> Result<(), SerializationError> {
+        Ok(writer.write_all(&self.0)?)
+    }
+
+    fn serialized_size(&self, _compress: Compress) -> usize {
+        self.0.len()
+    }
+}
+
+impl Valid for SessionNonce {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for SessionNonce {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        _compress: Compress,
+        _validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let mut bytes = [0u8; 32];
+        reader.read_exact(&mut bytes)?;
+        Ok(SessionNonce(bytes))
+    }
+}
+
+// Openings of the schemes that commit to (b_0, b_1) in a single A with one blinding opening mu,
+// i.e., the linear scheme and the logarithmic scheme built on it
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
+pub struct LinearOpenings<C: CurveGroup> {
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
     pub zeta: Vec<C::ScalarField>,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
     pub eta: Vec<C::ScalarField>,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
     pub hat_t: C::ScalarField,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
     pub taux: C::ScalarField,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
     pub mu: C::ScalarField,
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
     pub fs: C::ScalarField,
 }
 
 // Linear-size Ring Signature tuple without Bulletproofs Compression
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
 pub struct LinearRingSignature<C: CurveGroup> {
     // the intermediate commitment vector generated along the proving
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
     pub commitments: Vec<C>,
     // the opening vector generated along the proving
-    pub openings: Openings<C>,
+    pub openings: LinearOpenings<C>,
     // the challenge vector generated by merlin transcript
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
     pub challenges: Vec<C::ScalarField>,
     // the digest of the message
     pub digest: String,
+    // the session nonce of the parameters the proof was generated for
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
+    pub session_nonce: SessionNonce,
+}
+
+impl<C: CurveGroup> LinearRingSignature<C> {
+    /// Returns the number of bytes of the compressed serialization, the openings grow linearly in the ring size
+    pub fn proof_size_bytes(&self) -> usize {
+        self.compressed_size()
+    }
 }
 
 // Logarithmic-size Ring Signature tuple with Bulletproofs Compression
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
 pub struct LogarithmicRingSignature<C: CurveGroup> {
+    // the intermediate commitment vector generated along the proving, in affine form for a compact encoding
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
+    pub commitments: Vec<C::Affine>,
+    // the opening vector generated along the proving
+    pub openings: LinearOpenings<C>,
+    // the challenge vector generated by merlin transcript
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
+    pub challenges: Vec<C::ScalarField>,
+    // the Bulletproofs compression proof
+    pub compression_proof: InnerProductProof<C>,
+    // the digest of the message
+    pub digest: String,
+    // the session nonce of the parameters the proof was generated for
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
+    pub session_nonce: SessionNonce,
+}
+
+impl<C: CurveGroup> LogarithmicRingSignature<C> {
+    /// Returns the number of bytes of the compressed serialization, only the 2*log2(n) group elements
+    /// of the compression proof grow with the ring size
+    pub fn proof_size_bytes(&self) -> usize {
+        self.compressed_size()
+    }
+}
+
+// Openings of the modified scheme, which commits to (b_0, b_1) and (b_2, b_3) separately
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CompressedOpenings<C: CurveGroup> {
+    pub zeta: Vec<C::ScalarField>,
+    pub eta: Vec<C::ScalarField>,
+    pub hat_t: C::ScalarField,
+    pub taux: C::ScalarField,
+    pub mu_1: C::ScalarField,
+    pub mu_2: C::ScalarField,
+    pub fs: C::ScalarField,
+}
+
+// Logarithmic-size Ring Signature tuple of the modified scheme
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CompressedRingSignature<C: CurveGroup> {
     // the intermediate commitment vector generated along the proving
     pub commitments: Vec<C>,
     // the opening vector generated along the proving
-    pub openings: Openings<C>,
+    pub openings: CompressedOpenings<C>,
     // the challenge vector generated by merlin transcript
     pub challenges: Vec<C::ScalarField>,
     // the Bulletproofs compression proof
     pub compression_proof: InnerProductProof<C>,
     // the digest of the message
     pub digest: String,
+    // the session nonce of the parameters the proof was generated for
+    pub session_nonce: SessionNonce,
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+impl<C: CurveGroup> CompressedRingSignature<C> {
+    /// Returns the number of bytes of the compressed serialization
+    pub fn proof_size_bytes(&self) -> usize {
+        self.compressed_size()
+    }
+}
+
+// the serialized parameters carry the ring and the generators, so that a verifier
+// can check a received signature against exactly the parameters the prover used
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(bound = ""))]
 pub struct RingSignatureParams<C: CurveGroup> {
     // the number of witness elements
     pub num_witness: usize,
@@ -51,5 +188,137 @@ pub struct RingSignatureParams<C: CurveGroup> {
     // the signed message
     pub message: String,
     // public key vector
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical_vec"))]
     pub vec_pk: Vec<C::Affine>,
-}
\ No newline at end of file
+    // the random nonce sampled at setup, absorbed by the transcript before any other element
+    // so that a proof of one session does not verify in another
+    #[cfg_attr(feature = "serde", serde(with = "toolbox::serde_canonical::canonical"))]
+    pub session_nonce: SessionNonce,
+}
+
+// the decoy Com(0; i) = h^i at position i of a padded ring, never the identity since i is at least 1
+fn padding_key<C: CurveGroup>(key_params: &PedersenParams<C>, i: usize) -> C {
+    key_params.generator * C::ScalarField::from(i as u64)
+}
+
+// the commitment parameter sets of the modified compressed scheme, in the order of setup
+pub struct CompressedComParams<'a, C: CurveGroup> {
+    // (vec_g_1, u_1)
+    pub g1u1: &'a PedersenParams<C>,
+    // (vec_h_1, v_1)
+    pub h1v1: &'a PedersenParams<C>,
+    // (vec_g_2, u_2)
+    pub g2u2: &'a PedersenParams<C>,
+    // (vec_h_2, v_2)
+    pub h2v2: &'a PedersenParams<C>,
+    // the generator g of the public keys
+    pub key: &'a PedersenParams<C>,
+}
+
+// the generators of the IPA verifier of the modified compressed scheme that only depend on the ring,
+// built once to verify many signatures of the ring; the generators of a signature
+// vec_G = (vec_g_1 \circ y^{-n} + P^w) || vec_g_2 \circ y^{-n} depend on its challenges y, w,
+// which the verifier applies as the factors y^{-n} and the shift P^w
+pub struct PreparedRing<'a, C: CurveGroup> {
+    pub params: &'a RingSignatureParams<C>,
+    // vec_g_1 || vec_g_2
+    pub vec_G: Vec<C::Affine>,
+    // vec_h_1 || vec_h_2
+    pub vec_H: Vec<C::Affine>,
+    // v_1
+    pub u: C::Affine,
+}
+
+impl<C: CurveGroup> RingSignatureParams<C> {
+    /// Derives the commitment parameter sets (vec_g_i, u_i) for i < num_sets from the labels
+    /// RING_COM_LABEL || i, followed by the key parameters (g) from RING_KEY_LABEL,
+    /// so that anyone re-derives the generators of a ring from its size
+    pub fn deterministic_com_parameters(
+        num_sets: usize,
+        supported_size: usize,
+    ) -> Result<Vec<PedersenParams<C>>, SigmaErrors> {
+        let mut com_parameters = (0..num_sets)
+            .map(|i| {
+                let label = [RING_COM_LABEL, &(i as u64).to_le_bytes()].concat();
+                PedersenCommitmentScheme::<C>::setup_deterministic(&label, supported_size)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        com_parameters.push(PedersenCommitmentScheme::<C>::setup_deterministic(RING_KEY_LABEL, 1)?);
+        Ok(com_parameters)
+    }
+
+    /// Pads the ring vec_pk with decoys up to the next power of two, the length of the IPA vectors,
+    /// where the decoy at position i is the commitment to zero Com(0; i) = h^i under the key parameters (g, h):
+    /// nobody knows the discrete log of h to g, so that no secret key opens a decoy
+    /// and the indicator of every valid witness is zero on the padding
+    pub fn pad_ring(vec_pk: &mut Vec<C::Affine>, key_params: &PedersenParams<C>) {
+        let padding: Vec<C> = (vec_pk.len()..vec_pk.len().next_power_of_two())
+            .map(|i| padding_key(key_params, i))
+            .collect();
+        vec_pk.extend(C::normalize_batch(&padding));
+    }
+
+    /// Returns the number of public keys of the ring before its padding by pad_ring
+    pub fn ring_size(&self) -> usize {
+        let Some(key_params) = self.com_parameters.last() else {
+            return self.vec_pk.len();
+        };
+        let mut size = self.vec_pk.len();
+        while size > 0 && self.vec_pk[size - 1] == padding_key(key_params, size - 1).into_affine() {
+            size -= 1;
+        }
+        size
+    }
+
+    /// Writes the parameters to the file at path, in the format of params_file
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> Result<(), SigmaErrors> {
+        Ok(params_file::write(path, params_file::RING_TAG, self)?)
+    }
+
+    /// Reads parameters written by write_to_file, rejecting a corrupted file, points off the curve
+    /// or a ring whose length is not the number of public inputs
+    pub fn read_from_file(path: impl AsRef<Path>) -> Result<Self, SigmaErrors> {
+        let params: Self = params_file::read(path, params_file::RING_TAG)?;
+        if params.vec_pk.len() != params.num_pub_inputs {
+            return Err(SigmaErrors::InvalidParameters(
+                "the ring does not match the number of public inputs".to_string(),
+            ));
+        }
+        Ok(params)
+    }
+
+    /// Parses com_parameters as the five parameter sets of the modified compressed scheme,
+    /// the vector sets must support the ring size and the key set a single generator
+    pub fn compressed_com_params(&self) -> Result<CompressedComParams<'_, C>, SigmaErrors> {
+        let [g1u1, h1v1, g2u2, h2v2, key] = self.com_parameters.as_slice() else {
+            return Err(SigmaErrors::InvalidParameters(
+                "expected five commitment parameter sets".to_string(),
+            ));
+        };
+        if [g1u1, h1v1, g2u2, h2v2].iter().any(|p| p.supported_size() != self.num_pub_inputs)
+            || key.supported_size() != 1
+        {
+            return Err(SigmaErrors::InvalidParameters(
+                "commitment parameters do not match the ring size".to_string(),
+            ));
+        }
+        Ok(CompressedComParams { g1u1, h1v1, g2u2, h2v2, key })
+    }
+
+    // the transcript of a signature of the protocol domain in this session, starting with the session nonce
+    pub(crate) fn session_transcript(&self, domain: &'static [u8]) -> Result<ProofTranscript<C::ScalarField>, SigmaErrors> {
+        let mut transcript = ProofTranscript::<C::ScalarField>::new(domain);
+        transcript.append_message(b"session nonce", &self.session_nonce.0)?;
+        Ok(transcript)
+    }
+
+    // a proof carries the nonce of its session, which must be the nonce of these parameters
+    pub(crate) fn check_session_nonce(&self, session_nonce: &SessionNonce) -> Result<(), SigmaErrors> {
+        if session_nonce != &self.session_nonce {
+            return Err(SigmaErrors::InvalidProof(
+                "the proof belongs to another session".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use ark_ec::CurveGroup;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{rand::Rng, UniformRand};
+use toolbox::errors::TranscriptError;
+use toolbox::sigma::transcript::ProofTranscript;
+use toolbox::vec::ct_eq_affine;
+
+/// Schnorr proof of knowledge of a discrete log, bound to a message:
+/// Relation: P knows x such that Y = G^x
+/// P->V: A = G^r
+/// V->P: challenge c
+/// P->V: s = r + c*x
+/// V checks G^s = A Y^c
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DLogProof<C: CurveGroup> {
+    // the masking commitment A
+    pub com: C::Affine,
+    // the response s
+    pub response: C::ScalarField,
+}
+
+impl<C: CurveGroup> DLogProof<C> {
+    /// Proves the knowledge of x such that Y = G^x, the challenge is bound to G, Y and msg
+    pub fn prove<R: Rng>(
+        rng: &mut R,
+        G: C::Affine,
+        Y: C,
+        x: C::ScalarField,
+        msg: &[u8],
+    ) -> DLogProof<C> {
+        let r = C::ScalarField::rand(rng);
+        let com = (G * r).into_affine();
+        let c = challenge::<C>(G, Y, com, msg);
+        DLogProof {
+            com,
+            response: r + c * x,
+        }
+    }
+
+    /// Verifies that the prover knows the discrete log of Y to the base G, for the message msg
+    pub fn verify(
+        G: C::Affine,
+        Y: C,
+        proof: &DLogProof<C>,
+        msg: &[u8],
+    ) -> bool {
+        let c = challenge::<C>(G, Y, proof.com, msg);
+        ct_eq_affine(G * proof.response, Y * c + proof.com)
+    }
+}
+
+// the Fiat-Shamir challenge c on the statement (G, Y), the message and the masking commitment A
+fn challenge<C: CurveGroup>(G: C::Affine, Y: C, com: C::Affine, msg: &[u8]) -> C::ScalarField {
+    let mut transcript = ProofTranscript::<C::ScalarField>::new(b"DLogProof");
+    // the transcript only fails to serialize its input, which does not happen for points written to a vector
+    let absorb = |transcript: &mut ProofTranscript<C::ScalarField>| -> Result<C::ScalarField, TranscriptError> {
+        transcript.append_message(b"message", msg)?;
+        transcript.append_serializable_element(b"statement G, Y", &[G, Y.into_affine()])?;
+        transcript.append_serializable_element(b"masking commitment", &com)?;
+        transcript.get_and_append_challenge(b"challenge c")
+    };
+    absorb(&mut transcript).expect("absorbing points into the transcript")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Affine, Fr, Projective};
+    use ark_std::UniformRand;
+
+    type DLog = DLogProof<Projective>;
+
+    #[test]
+    fn test_dlog() {
+        let mut rng = ark_std::test_rng();
+        let G = Affine::rand(&mut rng);
+        let x = Fr::rand(&mut rng);
+        let Y = G * x;
+        let proof = DLog::prove(&mut rng, G, Y, x, b"message");
+        assert!(DLog::verify(G, Y, &proof, b"message"));
+
+        // the proof is bound to the message, the base and the public element
+        assert!(!DLog::verify(G, Y, &proof, b"another message"));
+        assert!(!DLog::verify(Affine::rand(&mut rng), Y, &proof, b"message"));
+        assert!(!DLog::verify(G, Y + G, &proof, b"message"));
+        let mut proof_prime = proof.clone();
+        proof_prime.response += Fr::from(1u64);
+        assert!(!DLog::verify(G, Y, &proof_prime, b"message"));
+        assert!(!DLog::verify(G, Y, &DLog::default(), b"message"));
+    }
+
+    #[test]
+    fn test_dlog_soundness() {
+        // a random x that is not the discrete log of Y does not verify
+        let mut rng = ark_std::test_rng();
+        let G = Affine::rand(&mut rng);
+        let Y = G * Fr::rand(&mut rng);
+        for _ in 0..16 {
+            let x_prime = Fr::rand(&mut rng);
+            let proof = DLog::prove(&mut rng, G, Y, x_prime, b"message");
+            assert!(!DLog::verify(G, Y, &proof, b"message"));
+        }
+    }
+}
@@ -1,2 +1,3 @@
+pub mod dlog;
 pub mod protocol;
 pub(crate) mod structs;
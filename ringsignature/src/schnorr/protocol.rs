@@ -1,18 +1,20 @@
 use ark_ec::CurveGroup;
 use ark_std::{end_timer, rand::Rng, start_timer, UniformRand};
-use sha256::digest;
-use std::{fmt::Debug, io::Write, marker::PhantomData};
+use std::{fmt::Debug, marker::PhantomData};
 use crate::commitment::pedersen::PedersenCommitmentScheme;
 use toolbox::errors::SigmaErrors;
+use toolbox::hash::{Digest, Sha256};
 use crate::schnorr::structs::{SchnorrParams, SchnorrProof};
 use toolbox::sigma::{transcript::ProofTranscript, SigmaProtocol};
+use toolbox::vec::ct_eq_affine;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct SchnorrProtocol<C>
+pub struct SchnorrProtocol<C, D = Sha256>
 where
     C: CurveGroup,
+    D: Digest,
 {
-    phantom: PhantomData<C>,
+    phantom: PhantomData<(C, D)>,
 }
 
 /// Implement a sigma protocol as a schnorr protocol, including 3-move:
@@ -20,9 +22,10 @@ where
 /// P->V: commitment com(mask)
 /// V->P: challenge c
 /// P->V: openings z = wit + c*mask, z_r = r_wit + c*r_mask
-impl<C> SigmaProtocol<C> for SchnorrProtocol<C>
+impl<C, D> SigmaProtocol<C> for SchnorrProtocol<C, D>
 where
     C: CurveGroup,
+    D: Digest,
 {
     /// public parameters
     type PublicParams = SchnorrParams<C>;
@@ -39,13 +42,13 @@ where
     /// Inputs:
     /// - rng: RngCore palys a role as the random tape
     /// - max: the maximum length of the witness supported
+    ///
     /// Outputs:
     /// - Params<C>: Pedersen commitment parameter as a tuple (h, generators)
-    ///
     fn setup<R: Rng>(
         rng: &mut R,
         wit: &mut Self::Witness,
-        msg: &String,
+        msg: &str,
         supported_size: usize,
     ) -> Result<Self::PublicParams, SigmaErrors> {
         let start = start_timer!(|| "running schnorr protocol setup algorithm...");
@@ -60,7 +63,7 @@ where
             num_witness: wit.len(),
             num_pub_inputs: 1,
             com_parameters: com_params,
-            message: msg.clone(),
+            message: msg.to_string(),
         };
         end_timer!(start);
         Ok(schnorr_params)
@@ -86,16 +89,14 @@ where
         let r_wit = witness[witness.len()-1];
 
         // sample the masking vector and compute its commitment
-        let mask = vec![C::ScalarField::rand(rng); params.num_witness-1];
+        let mask: Vec<C::ScalarField> = (0..params.num_witness-1).map(|_| C::ScalarField::rand(rng)).collect();
         let r_mask = C::ScalarField::rand(rng);
         let com_mask = PedersenCommitmentScheme::commit(&params.com_parameters, &mask, &r_mask, "on masking")?;
         transcript.append_serializable_element(b"masking commitment", &com_mask)?;
 
         // append the message digest to the transcript
-        let h = digest(&params.message);
-        let mut h_msg: &mut [u8] = &mut [0; 32];
-        h_msg.write(h.as_bytes()).unwrap();
-        transcript.append_message(b"message digest", &h_msg)?;
+        let h = D::digest(params.message.as_bytes());
+        transcript.append_message_digest::<D>(&h)?;
 
         // generate the challenge c
         let c = transcript.get_and_append_challenge(b"challenge")?;
@@ -122,6 +123,12 @@ where
     }
 
     fn verify(params: &Self::PublicParams, proof: &Self::Proof) -> Result<bool, SigmaErrors> {
+        // parse proof
+        if proof.commitments.len() != 1 || proof.challenge.len() != 1 || proof.opening.len() != params.num_witness {
+            return Err(SigmaErrors::InvalidProof(
+                "incorrect proof length".to_string(),
+            ));
+        }
         // initialization
         let start = start_timer!(|| "running schnorr protocol verify algorithm...");
         let mut transcript = ProofTranscript::<C::ScalarField>::new(b"SchnorrSignature");
@@ -131,11 +138,13 @@ where
         transcript.append_serializable_element(b"masking commitment", &proof.commitments[0])?;
 
         // append the message digest to the transcript
-        let h = digest(&params.message);
-        assert_eq!(h, proof.digest);
-        let mut h_msg: &mut [u8] = &mut [0; 32];
-        h_msg.write(h.as_bytes()).unwrap();
-        transcript.append_message(b"message digest", &h_msg)?;
+        let h = D::digest(params.message.as_bytes());
+        if h != proof.digest {
+            return Err(SigmaErrors::InvalidProof(
+                "invalid message digest".to_string(),
+            ));
+        }
+        transcript.append_message_digest::<D>(&h)?;
 
         // generate the challenge
         let c = transcript.get_and_append_challenge(b"challenge")?;
@@ -146,12 +155,12 @@ where
         }
 
         // check the validity of opening
-        let lhs = params.com_witness[0].mul(c) + proof.commitments[0].clone();
+        let lhs = params.com_witness[0].mul(c) + proof.commitments[0];
 
         let z = proof.opening[0..params.num_witness-1].to_vec();
         let zr = proof.opening[params.num_witness-1];
         let rhs = PedersenCommitmentScheme::commit(&params.com_parameters, &z, &zr, "on opening")?;
-        if lhs != rhs {
+        if !ct_eq_affine(lhs, rhs) {
             return Err(SigmaErrors::InvalidProof("verification failed".to_string()));
         }
 
@@ -182,6 +191,13 @@ mod tests {
         // verify algorithm
         let result = Schnorr::verify(&params, &proof).unwrap();
 
-        assert_eq!(result, true);
+        assert!(result);
+
+        // malformed proofs and another message are rejected without panicking
+        let mut proof_prime = proof.clone();
+        proof_prime.opening.pop();
+        assert!(Schnorr::verify(&params, &proof_prime).is_err());
+        let params_prime = SchnorrParams { message: String::from("another message"), ..params.clone() };
+        assert!(Schnorr::verify(&params_prime, &proof).is_err());
     }
 }
@@ -0,0 +1,460 @@
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
+use ark_std::{rand::Rng, UniformRand};
+use bulletproofs::structs::{RangeProof, RangeProofParam};
+use toolbox::errors::SigmaErrors;
+use toolbox::hash::{hash_to_curve, Digest, Sha256};
+use toolbox::sigma::{transcript::ProofTranscript, SigmaProtocol};
+use crate::commitment::PedersenParams;
+use crate::ringsig::protocol_compressed_modification::RingSignatureScheme;
+use crate::ringsig::structs::{CompressedRingSignature, RingSignatureParams};
+use crate::schnorr::protocol::SchnorrProtocol;
+use crate::schnorr::structs::SchnorrParams;
+use crate::{public_key, public_parameters, to_bytes};
+
+pub use crate::schnorr::structs::SchnorrProof;
+
+// the domain of the key image base H = hash_to_curve(KEY_IMAGE_DOMAIN, "")
+const KEY_IMAGE_DOMAIN: &[u8] = b"RingCT-SP23 key image";
+
+/// The generators of the transactions: the range proof generators, whose g and h also
+/// commit to the amounts as C = g^v h^r, and the base H of the key images I = H^sk
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TransactionParams<C: CurveGroup> {
+    // the range proof generators for up to max_outputs outputs
+    pub range: RangeProofParam<C>,
+    // the bit length of the amounts
+    pub n_bits: usize,
+    // the key image base H
+    pub key_image_base: C::Affine,
+}
+
+// an output of a transaction, which later transactions spend as a member of a ring
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TransactionOutput<C: CurveGroup> {
+    // the public key pk = g^sk of the owner
+    pub public_key: C::Affine,
+    // the amount commitment C = g^v h^r
+    pub commitment: C::Affine,
+}
+
+// an input of a transaction, the ring signature proves that the key image and the pseudo-output
+// belong to the same hidden member of the ring
+#[derive(Clone, Debug, Default, Eq, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct TransactionInput<C: CurveGroup> {
+    // the ring of outputs, one of which is spent
+    pub ring: Vec<TransactionOutput<C>>,
+    // the key image I = H^sk of the spent output
+    pub key_image: C::Affine,
+    // the pseudo-output C' = g^v h^{r - sk} re-committing the spent amount
+    pub pseudo_output: C::Affine,
+    // the ring signature on the transaction digest
+    pub signature: CompressedRingSignature<C>,
+}
+
+// the secrets of a spent output
+#[derive(Clone, Debug)]
+pub struct SpendInput<C: CurveGroup> {
+    // the ring, which contains the spent output
+    pub ring: Vec<TransactionOutput<C>>,
+    // the secret key of the spent output
+    pub sk: C::ScalarField,
+    // the opening (v, r) of the amount commitment of the spent output
+    pub amount: u64,
+    pub blinding: C::ScalarField,
+}
+
+// RingCT transaction: the inputs with their key images and ring signatures,
+// the outputs with an aggregated range proof and the proof that the amounts balance
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Transaction<C: CurveGroup> {
+    pub inputs: Vec<TransactionInput<C>>,
+    pub outputs: Vec<TransactionOutput<C>>,
+    // the public fee, the amounts balance as sum v_in = sum v_out + fee
+    pub fee: u64,
+    // the message signed along with the transaction
+    pub message: String,
+    // the aggregated range proof of the output amounts
+    pub range_proof: RangeProof<C>,
+    // the proof of knowledge of e with sum C'_in - sum C_out - g^fee = h^e
+    pub balance_proof: SchnorrProof<C>,
+}
+
+impl<C: CurveGroup> TransactionParams<C> {
+    /// Setup algorithm generates the generators for at most max_outputs outputs of n_bits each
+    pub fn setup<R: Rng>(
+        rng: &mut R,
+        n_bits: usize,
+        max_outputs: usize,
+    ) -> Result<Self, SigmaErrors> {
+        Ok(TransactionParams {
+            range: RangeProof::<C>::setup(rng, n_bits, max_outputs)?,
+            n_bits,
+            key_image_base: hash_to_curve::<C>(KEY_IMAGE_DOMAIN, b""),
+        })
+    }
+
+    /// Commits to an amount as C = g^v h^r
+    pub fn commit(&self, amount: u64, blinding: &C::ScalarField) -> C::Affine {
+        RangeProof::<C>::commit(&self.range, amount, blinding).into_affine()
+    }
+
+    /// Outputs the key image I = H^sk of a secret key
+    pub fn key_image(&self, sk: &C::ScalarField) -> C::Affine {
+        (self.key_image_base * sk).into_affine()
+    }
+}
+
+impl<C: CurveGroup> Transaction<C> {
+    /// Creates a transaction spending the inputs to outputs of the given (public key, amount) pairs,
+    /// the inputs must pay exactly the outputs and the fee;
+    /// outputs the transaction and the blinding factors of the output commitments
+    pub fn create<R: Rng>(
+        rng: &mut R,
+        params: &TransactionParams<C>,
+        spends: &[SpendInput<C>],
+        outputs: &[(C::Affine, u64)],
+        fee: u64,
+        message: &str,
+    ) -> Result<(Self, Vec<C::ScalarField>), SigmaErrors> {
+        if spends.is_empty() || outputs.is_empty() {
+            return Err(SigmaErrors::InvalidParameters(
+                "a transaction spends at least one input to at least one output".to_string(),
+            ));
+        }
+        let total_in = spends.iter().try_fold(0u64, |acc, spend| acc.checked_add(spend.amount));
+        let total_out = outputs.iter().try_fold(fee, |acc, (_, amount)| acc.checked_add(*amount));
+        if total_in.is_none() || total_in != total_out {
+            return Err(SigmaErrors::InvalidProver(
+                "the input amounts do not pay the outputs and the fee".to_string(),
+            ));
+        }
+
+        // the output commitments come out of the aggregated range proof
+        let amounts: Vec<u64> = outputs.iter().map(|(_, amount)| *amount).collect();
+        let blindings: Vec<C::ScalarField> = (0..outputs.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let (range_proof, commitments) = RangeProof::<C>::prove_aggregated(rng, &params.range, &amounts, &blindings, params.n_bits)?;
+        let outputs = outputs.iter().zip(C::normalize_batch(&commitments))
+            .map(|((public_key, _), commitment)| TransactionOutput { public_key: *public_key, commitment })
+            .collect();
+
+        // the pseudo-output re-commits the spent amount with blinding r - sk, so that C - C' = h^sk
+        let mut inputs = Vec::with_capacity(spends.len());
+        let mut witnesses = Vec::with_capacity(spends.len());
+        let mut excess = -blindings.iter().fold(C::ScalarField::from(0u64), |acc, r| acc + r);
+        for spend in spends {
+            let pk = public_key::<C>(&spend.sk);
+            let Some(index) = spend.ring.iter().position(|output| output.public_key == pk) else {
+                return Err(SigmaErrors::InvalidProver(
+                    "the public key is not a member of the ring".to_string(),
+                ));
+            };
+            if spend.ring[index].commitment != params.commit(spend.amount, &spend.blinding) {
+                return Err(SigmaErrors::InvalidProver(
+                    "the amount does not open the commitment of the spent output".to_string(),
+                ));
+            }
+            let pseudo_blinding = spend.blinding - spend.sk;
+            excess += pseudo_blinding;
+            inputs.push(TransactionInput {
+                ring: spend.ring.clone(),
+                key_image: params.key_image(&spend.sk),
+                pseudo_output: params.commit(spend.amount, &pseudo_blinding),
+                signature: CompressedRingSignature::default(),
+            });
+            // the witness is sk followed by the indicator vector of the spent output in the ring
+            let mut wit = vec![spend.sk];
+            wit.extend((0..spend.ring.len()).map(|i| C::ScalarField::from((i == index) as u64)));
+            witnesses.push(wit);
+        }
+
+        let mut transaction = Transaction {
+            inputs,
+            outputs,
+            fee,
+            message: message.to_string(),
+            range_proof,
+            balance_proof: SchnorrProof::default(),
+        };
+        let digest = transaction.digest()?;
+        for (input, wit) in transaction.inputs.iter_mut().zip(&witnesses) {
+            let ring_params = input_parameters(params, input, &digest)?;
+            input.signature = RingSignatureScheme::<C>::prove(rng, &ring_params, wit)?;
+        }
+        let balance_params = balance_parameters(params, &transaction, &digest);
+        transaction.balance_proof = SchnorrProtocol::<C>::prove(rng, &balance_params, &vec![excess])?;
+        Ok((transaction, blindings))
+    }
+
+    /// Verifies the ring signature of every input, the range proof of the outputs and the balance proof,
+    /// and that no key image is spent twice within the transaction
+    pub fn verify(&self, params: &TransactionParams<C>) -> Result<(), SigmaErrors> {
+        if self.inputs.is_empty() || self.outputs.is_empty() {
+            return Err(SigmaErrors::InvalidProof(
+                "a transaction spends at least one input to at least one output".to_string(),
+            ));
+        }
+        for (i, input) in self.inputs.iter().enumerate() {
+            if !verify_key_image_wellformed(input) {
+                return Err(SigmaErrors::InvalidProof(
+                    "malformed key image".to_string(),
+                ));
+            }
+            if self.inputs[..i].iter().any(|previous| previous.key_image == input.key_image) {
+                return Err(SigmaErrors::InvalidProof(
+                    "repeated key image".to_string(),
+                ));
+            }
+        }
+
+        let digest = self.digest()?;
+        for input in &self.inputs {
+            let ring_params = input_parameters(params, input, &digest)?;
+            if !RingSignatureScheme::<C>::verify(&ring_params, &input.signature)? {
+                return Err(SigmaErrors::InvalidProof(
+                    "invalid ring signature".to_string(),
+                ));
+            }
+        }
+
+        let commitments: Vec<C> = self.outputs.iter().map(|output| output.commitment.into_group()).collect();
+        RangeProof::<C>::verify_aggregated(&params.range, &commitments, params.n_bits, &self.range_proof)?;
+
+        let balance_params = balance_parameters(params, self, &digest);
+        if !SchnorrProtocol::<C>::verify(&balance_params, &self.balance_proof)? {
+            return Err(SigmaErrors::InvalidProof(
+                "invalid balance proof".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the key images of the inputs, by which a node indexes the spent outputs before verifying
+    pub fn key_images(&self) -> Vec<C::Affine> {
+        self.inputs.iter().map(|input| input.key_image).collect()
+    }
+
+    // the digest signed by the ring signatures and the balance proof, covering everything but the proofs
+    fn digest(&self) -> Result<String, SigmaErrors> {
+        let mut bytes = self.message.as_bytes().to_vec();
+        bytes.extend(self.fee.to_le_bytes());
+        for input in &self.inputs {
+            bytes.extend(to_bytes(&input.ring)?);
+            bytes.extend(to_bytes(&[input.key_image, input.pseudo_output])?);
+        }
+        bytes.extend(to_bytes(&self.outputs)?);
+        Ok(Sha256::digest(&bytes))
+    }
+}
+
+/// Checks the key image of an input without its ring signature, so that a mempool rejects a malformed image
+/// before the full verification: the image is a point of the prime order subgroup other than the identity,
+/// the ring has a size of a power of two, and the image is not the public key of a member.
+/// That the image is H^sk for the key of a member is only shown by the ring signature, see Transaction::verify
+pub fn verify_key_image_wellformed<C: CurveGroup>(input: &TransactionInput<C>) -> bool {
+    input.key_image.check().is_ok()
+        && !input.key_image.is_zero()
+        && input.ring.len().is_power_of_two()
+        && input.ring.iter().all(|output| output.public_key != input.key_image)
+}
+
+// the ring signature of an input proves knowledge of sk with R_i = g*^sk for a member i selected
+// by a binary indicator vector, which the modified compressed scheme also proves to be nonzero,
+// where R_j = pk_j I^{w_1} (C_j - C')^{w_2} and g* = g H^{w_1} h^{w_2} for challenges w_1, w_2,
+// which for random w_1, w_2 holds only if pk_i = g^sk, I = H^sk and C_i - C' = h^sk,
+// i.e., the key image and the pseudo-output belong to the same hidden member of the ring;
+// the rings are at most 128 outputs, the limit of the modified scheme
+fn input_parameters<C: CurveGroup>(
+    params: &TransactionParams<C>,
+    input: &TransactionInput<C>,
+    digest: &str,
+) -> Result<RingSignatureParams<C>, SigmaErrors> {
+    let mut transcript = ProofTranscript::<C::ScalarField>::new(b"RingCT input");
+    transcript.append_message(b"transaction digest", digest.as_bytes())?;
+    transcript.append_serializable_element(b"ring", &input.ring)?;
+    transcript.append_serializable_element(b"key image, pseudo-output", &[input.key_image, input.pseudo_output])?;
+    let w_1 = transcript.get_and_append_challenge(b"challenge w_1")?;
+    let w_2 = transcript.get_and_append_challenge(b"challenge w_2")?;
+
+    let ring: Vec<C> = input.ring.iter()
+        .map(|output| output.public_key.into_group() + input.key_image * w_1 + (output.commitment.into_group() - input.pseudo_output) * w_2)
+        .collect();
+    let mut ring_params = public_parameters::<C>(&C::normalize_batch(&ring), digest)?;
+    // the key parameters (g) follow the sets (vec_g_1, u_1), (vec_h_1, v_1), (vec_g_2, u_2), (vec_h_2, v_2)
+    let key_params = &mut ring_params.com_parameters[4];
+    key_params.vec_gen = vec![(key_params.vec_gen[0] + params.key_image_base * w_1 + params.range.h * w_2).into_affine()];
+    Ok(ring_params)
+}
+
+// the statement of the balance proof: the excess sum C'_in - sum C_out - g^fee is h^e,
+// i.e., a commitment to a zero amount
+fn balance_parameters<C: CurveGroup>(
+    params: &TransactionParams<C>,
+    transaction: &Transaction<C>,
+    digest: &str,
+) -> SchnorrParams<C> {
+    let excess = transaction.inputs.iter().fold(C::zero(), |acc, input| acc + input.pseudo_output)
+        - transaction.outputs.iter().fold(C::zero(), |acc, output| acc + output.commitment)
+        - params.range.g * C::ScalarField::from(transaction.fee);
+    SchnorrParams {
+        com_witness: vec![excess],
+        num_witness: 1,
+        num_pub_inputs: 1,
+        com_parameters: PedersenParams {
+            generator: params.range.h.into_group(),
+            vec_gen: vec![],
+        },
+        message: digest.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Fr, Projective};
+
+    type Tx = Transaction<Projective>;
+
+    // a ring of 4 outputs with random keys and amounts, and the spend of the output at index
+    fn spend_input<R: Rng>(
+        rng: &mut R,
+        params: &TransactionParams<Projective>,
+        amount: u64,
+        index: usize,
+    ) -> SpendInput<Projective> {
+        let sk = Fr::rand(rng);
+        let blinding = Fr::rand(rng);
+        let mut ring: Vec<TransactionOutput<Projective>> = (0..3)
+            .map(|_| TransactionOutput {
+                public_key: public_key::<Projective>(&Fr::rand(rng)),
+                commitment: params.commit(rng.gen_range(0..1000), &Fr::rand(rng)),
+            })
+            .collect();
+        ring.insert(index, TransactionOutput {
+            public_key: public_key::<Projective>(&sk),
+            commitment: params.commit(amount, &blinding),
+        });
+        SpendInput { ring, sk, amount, blinding }
+    }
+
+    #[test]
+    fn test_transaction() {
+        // 2 inputs of 30 and 20 pay 2 outputs of 25 and 20 and a fee of 5
+        let mut rng = ark_std::test_rng();
+        let params = TransactionParams::<Projective>::setup(&mut rng, 64, 2).unwrap();
+        let spends = [spend_input(&mut rng, &params, 30, 1), spend_input(&mut rng, &params, 20, 3)];
+        let recipients = [(public_key::<Projective>(&Fr::rand(&mut rng)), 25), (public_key::<Projective>(&Fr::rand(&mut rng)), 20)];
+        let (tx, blindings) = Tx::create(&mut rng, &params, &spends, &recipients, 5, "transaction").unwrap();
+        tx.verify(&params).unwrap();
+        // the recipients can open their outputs
+        for ((_, amount), (output, blinding)) in recipients.iter().zip(tx.outputs.iter().zip(&blindings)) {
+            assert_eq!(output.commitment, params.commit(*amount, blinding));
+        }
+
+        // the amounts must balance
+        assert!(Tx::create(&mut rng, &params, &spends, &recipients, 6, "transaction").is_err());
+        // the fee, the message and the outputs are signed
+        let tx_prime = Transaction { fee: 6, ..tx.clone() };
+        assert!(tx_prime.verify(&params).is_err());
+        let tx_prime = Transaction { message: "another transaction".to_string(), ..tx.clone() };
+        assert!(tx_prime.verify(&params).is_err());
+        let mut tx_prime = tx.clone();
+        tx_prime.outputs.swap(0, 1);
+        assert!(tx_prime.verify(&params).is_err());
+        // a pseudo-output re-committing another amount breaks its ring signature
+        let mut tx_prime = tx.clone();
+        tx_prime.inputs[0].pseudo_output = (tx_prime.inputs[0].pseudo_output + params.range.g).into_affine();
+        assert!(tx_prime.verify(&params).is_err());
+        // a key image of another key breaks its ring signature
+        let mut tx_prime = tx.clone();
+        tx_prime.inputs[0].key_image = params.key_image(&Fr::rand(&mut rng));
+        assert!(tx_prime.verify(&params).is_err());
+        // the balance proof of another transaction does not verify
+        let (tx_other, _) = Tx::create(&mut rng, &params, &spends, &recipients, 5, "transaction").unwrap();
+        let tx_prime = Transaction { balance_proof: tx_other.balance_proof, ..tx.clone() };
+        assert!(tx_prime.verify(&params).is_err());
+    }
+
+    #[test]
+    fn test_transaction_zero_indicator() {
+        // an input signed with the zero indicator vector, which selects no member and needs no key,
+        // is rejected by the check that <b_0, 2^n> - <b_2, 2^n> = 1
+        let mut rng = ark_std::test_rng();
+        let params = TransactionParams::<Projective>::setup(&mut rng, 64, 1).unwrap();
+        let spends = [spend_input(&mut rng, &params, 30, 1)];
+        let recipients = [(public_key::<Projective>(&Fr::rand(&mut rng)), 25)];
+        let (mut tx, _) = Tx::create(&mut rng, &params, &spends, &recipients, 5, "zero indicator").unwrap();
+        tx.verify(&params).unwrap();
+
+        let ring_params = input_parameters(&params, &tx.inputs[0], &tx.digest().unwrap()).unwrap();
+        let n = ring_params.num_pub_inputs;
+        let (vec_b0, vec_b2) = (vec![Fr::from(0u64); n], vec![Fr::from(1u64); n]);
+        tx.inputs[0].signature = RingSignatureScheme::<Projective>::prove_with_bits(&mut rng, &ring_params, &[], &vec_b0, &vec_b2).unwrap();
+        assert!(matches!(tx.verify(&params), Err(SigmaErrors::InvalidProof(msg)) if msg == "hat_t is inconsistent with T1, T2"));
+    }
+
+    #[test]
+    fn test_transaction_double_spend() {
+        // the same output spent twice within one transaction repeats its key image
+        let mut rng = ark_std::test_rng();
+        let params = TransactionParams::<Projective>::setup(&mut rng, 64, 2).unwrap();
+        let spend = spend_input(&mut rng, &params, 30, 2);
+        let recipients = [(public_key::<Projective>(&Fr::rand(&mut rng)), 40), (public_key::<Projective>(&Fr::rand(&mut rng)), 15)];
+        let spends = [spend.clone(), spend];
+        let (tx, _) = Tx::create(&mut rng, &params, &spends, &recipients, 5, "double spend").unwrap();
+        assert_eq!(tx.inputs[0].key_image, tx.inputs[1].key_image);
+        assert!(matches!(tx.verify(&params), Err(SigmaErrors::InvalidProof(msg)) if msg == "repeated key image"));
+    }
+
+    #[test]
+    fn test_key_image_wellformed() {
+        let mut rng = ark_std::test_rng();
+        let params = TransactionParams::<Projective>::setup(&mut rng, 64, 2).unwrap();
+        let spends = [spend_input(&mut rng, &params, 30, 0)];
+        let recipients = [(public_key::<Projective>(&Fr::rand(&mut rng)), 25)];
+        let (tx, _) = Tx::create(&mut rng, &params, &spends, &recipients, 5, "key image").unwrap();
+        assert_eq!(tx.key_images(), vec![params.key_image(&spends[0].sk)]);
+        assert!(verify_key_image_wellformed(&tx.inputs[0]));
+
+        // the identity, the public key of a member and a ring that is not a power of two are rejected,
+        // and verify rejects the identity before the ring signature
+        let mut tx_prime = tx.clone();
+        tx_prime.inputs[0].key_image = ark_secp256k1::Affine::zero();
+        assert!(!verify_key_image_wellformed(&tx_prime.inputs[0]));
+        assert!(matches!(tx_prime.verify(&params), Err(SigmaErrors::InvalidProof(msg)) if msg == "malformed key image"));
+        let mut input = tx.inputs[0].clone();
+        input.key_image = input.ring[2].public_key;
+        assert!(!verify_key_image_wellformed(&input));
+        let mut input = tx.inputs[0].clone();
+        input.ring.pop();
+        assert!(!verify_key_image_wellformed(&input));
+    }
+
+    #[test]
+    fn test_key_image_subgroup() {
+        use ark_bls12_381::{Fq, G1Affine, G1Projective};
+        use ark_ff::One;
+        // on a curve with a cofactor, a point on the curve out of the prime order subgroup is rejected
+        let mut rng = ark_std::test_rng();
+        let params = TransactionParams::<G1Projective>::setup(&mut rng, 8, 1).unwrap();
+        let mut input = TransactionInput::<G1Projective> {
+            ring: (0..4).map(|_| TransactionOutput {
+                public_key: G1Affine::rand(&mut rng),
+                commitment: G1Affine::rand(&mut rng),
+            }).collect(),
+            key_image: params.key_image(&ark_bls12_381::Fr::rand(&mut rng)),
+            ..Default::default()
+        };
+        assert!(verify_key_image_wellformed(&input));
+
+        let mut x = Fq::one();
+        input.key_image = loop {
+            match G1Affine::get_point_from_x_unchecked(x, false) {
+                Some(point) if !point.is_in_correct_subgroup_assuming_on_curve() => break point,
+                _ => x += Fq::one(),
+            }
+        };
+        assert!(input.key_image.is_on_curve());
+        assert!(!verify_key_image_wellformed(&input));
+    }
+}
@@ -0,0 +1,140 @@
+use ark_secp256k1::{Fr, Projective};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use js_sys::{Array, Uint8Array};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use wasm_bindgen::prelude::*;
+use crate::ringsig::protocol_compressed_modification::RingSignatureScheme;
+use crate::ringsig::structs::{CompressedRingSignature, RingSignatureParams};
+use toolbox::errors::SigmaErrors;
+use toolbox::sigma::SigmaProtocol;
+
+// the bindings are fixed to secp256k1, since wasm-bindgen does not export generic functions
+type Ring = RingSignatureScheme<Projective>;
+
+/// Sets up a ring of ring_size members including the public key of sk_bytes, a compressed secp256k1 scalar,
+/// where ring_size is a power of two of at most 128, and signs message on behalf of the ring with the
+/// modified compressed scheme; outputs the serialized pair (RingSignatureParams, CompressedRingSignature),
+/// which split_signed splits into the inputs of ring_verify
+#[wasm_bindgen]
+pub fn ring_sign(ring_size: usize, sk_bytes: &[u8], message: &str) -> Result<Uint8Array, JsValue> {
+    let mut rng = entropy_rng()?;
+    let signed = sign_to_bytes(&mut rng, ring_size, sk_bytes, message).map_err(to_js_error)?;
+    Ok(Uint8Array::from(signed.as_slice()))
+}
+
+/// Splits the output of ring_sign into the serialized parameters and the serialized proof
+#[wasm_bindgen]
+pub fn split_signed(signed: &[u8]) -> Result<Array, JsValue> {
+    let (params_bytes, proof_bytes) = split(signed).map_err(to_js_error)?;
+    Ok(Array::of2(&Uint8Array::from(params_bytes), &Uint8Array::from(proof_bytes)))
+}
+
+/// Verifies a serialized proof against serialized parameters, where the generators of the parameters must be
+/// the ones derived from the ring size, so that a signer cannot choose generators with known discrete logs
+#[wasm_bindgen]
+pub fn ring_verify(params_bytes: &[u8], proof_bytes: &[u8]) -> bool {
+    let (Ok(params), Ok(proof)) = (
+        RingSignatureParams::<Projective>::deserialize_compressed(params_bytes),
+        CompressedRingSignature::<Projective>::deserialize_compressed(proof_bytes),
+    ) else {
+        return false;
+    };
+    // the ring bounds the number of generators to derive
+    if params.num_pub_inputs != params.vec_pk.len() {
+        return false;
+    }
+    match RingSignatureParams::<Projective>::deterministic_com_parameters(4, params.num_pub_inputs) {
+        Ok(com_parameters) if com_parameters == params.com_parameters => Ring::verify(&params, &proof).unwrap_or(false),
+        _ => false,
+    }
+}
+
+// the signing of ring_sign without the JavaScript types
+fn sign_to_bytes<R: Rng>(
+    rng: &mut R,
+    ring_size: usize,
+    sk_bytes: &[u8],
+    message: &str,
+) -> Result<Vec<u8>, SigmaErrors> {
+    if !ring_size.is_power_of_two() {
+        return Err(SigmaErrors::InvalidParameters(
+            "ring size is not power of two".to_string(),
+        ));
+    }
+    let mut wit = vec![Fr::deserialize_compressed(sk_bytes)?];
+    let params = Ring::setup(rng, &mut wit, message, ring_size)?;
+    let proof = Ring::prove(rng, &params, &wit)?;
+    let mut signed = Vec::new();
+    (params, proof).serialize_compressed(&mut signed)?;
+    Ok(signed)
+}
+
+// the serialized parameters are followed by the serialized proof, whose offset is found by reading the parameters
+fn split(signed: &[u8]) -> Result<(&[u8], &[u8]), SigmaErrors> {
+    let mut proof_bytes = signed;
+    RingSignatureParams::<Projective>::deserialize_compressed(&mut proof_bytes)?;
+    Ok((&signed[..signed.len() - proof_bytes.len()], proof_bytes))
+}
+
+// seeds the rng from the entropy of the host, which is crypto.getRandomValues in browsers and Node.js
+fn entropy_rng() -> Result<StdRng, JsValue> {
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed).map_err(to_js_error)?;
+    Ok(StdRng::from_seed(seed))
+}
+
+fn to_js_error<E: std::fmt::Display>(e: E) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+
+    fn sk_bytes(sk: &Fr) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        sk.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_sign_and_verify_bytes() {
+        let mut rng = ark_std::test_rng();
+        let sk = sk_bytes(&Fr::rand(&mut rng));
+        let signed = sign_to_bytes(&mut rng, 8, &sk, "hello").unwrap();
+        let (params_bytes, proof_bytes) = split(&signed).unwrap();
+        assert!(ring_verify(params_bytes, proof_bytes));
+
+        // a proof of another signature does not verify, nor do truncated bytes
+        let other = sign_to_bytes(&mut rng, 8, &sk, "hello").unwrap();
+        let (_, other_proof) = split(&other).unwrap();
+        assert!(!ring_verify(params_bytes, other_proof));
+        assert!(!ring_verify(&params_bytes[1..], proof_bytes));
+        assert!(!ring_verify(params_bytes, &proof_bytes[..proof_bytes.len() - 1]));
+        assert!(split(&signed[..8]).is_err());
+
+        // invalid ring sizes and secret keys are errors
+        assert!(sign_to_bytes(&mut rng, 6, &sk, "hello").is_err());
+        assert!(sign_to_bytes(&mut rng, 0, &sk, "hello").is_err());
+        assert!(sign_to_bytes(&mut rng, 256, &sk, "hello").is_err());
+        assert!(sign_to_bytes(&mut rng, 8, &[0xff; 32], "hello").is_err());
+        assert!(sign_to_bytes(&mut rng, 8, &sk[..31], "hello").is_err());
+    }
+
+    #[test]
+    fn test_verify_derived_generators() {
+        // a proof under generators chosen by the signer verifies with Ring::verify but not with ring_verify
+        let mut rng = ark_std::test_rng();
+        let mut wit = vec![Fr::rand(&mut rng)];
+        let mut params = Ring::setup(&mut rng, &mut wit, "hello", 4).unwrap();
+        params.com_parameters[0] = crate::commitment::pedersen::PedersenCommitmentScheme::<Projective>::setup(&mut rng, 4).unwrap();
+        let proof = Ring::prove(&mut rng, &params, &wit).unwrap();
+        assert!(Ring::verify(&params, &proof).unwrap());
+
+        let (mut params_bytes, mut proof_bytes) = (Vec::new(), Vec::new());
+        params.serialize_compressed(&mut params_bytes).unwrap();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+        assert!(!ring_verify(&params_bytes, &proof_bytes));
+    }
+}
@@ -0,0 +1,46 @@
+// the ring signature schemes instantiated with the G1 group of BLS12-381 instead of secp256k1
+use ark_bls12_381::{Fr, G1Projective};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::UniformRand;
+use ringsignature::ringsig::{protocol_compressed, protocol_compressed_modification, protocol_linear};
+use toolbox::sigma::SigmaProtocol;
+
+const MESSAGE: &str = "Welcome to the world of Zero Knowledge!";
+
+// setup, prove and verify with a fresh key, and check that the proof survives serialization
+// and does not verify for the parameters of another setup
+fn check_ringsignature<S, P>(ring_size: usize)
+where
+    S: SigmaProtocol<G1Projective, Witness = Vec<Fr>, Proof = P>,
+    P: CanonicalSerialize + CanonicalDeserialize,
+{
+    let mut rng = ark_std::test_rng();
+    let mut wit = vec![Fr::rand(&mut rng)];
+    let params = S::setup(&mut rng, &mut wit, MESSAGE, ring_size).unwrap();
+    let proof = S::prove(&mut rng, &params, &wit).unwrap();
+    assert!(S::verify(&params, &proof).unwrap());
+
+    let mut bytes = vec![];
+    proof.serialize_compressed(&mut bytes).unwrap();
+    let proof_prime = P::deserialize_compressed(&bytes[..]).unwrap();
+    assert!(S::verify(&params, &proof_prime).unwrap());
+
+    let mut wit_prime = vec![Fr::rand(&mut rng)];
+    let params_prime = S::setup(&mut rng, &mut wit_prime, MESSAGE, ring_size).unwrap();
+    assert!(!S::verify(&params_prime, &proof).unwrap_or(false));
+}
+
+#[test]
+fn test_ringsignature_linear() {
+    check_ringsignature::<protocol_linear::RingSignatureScheme<G1Projective>, _>(16);
+}
+
+#[test]
+fn test_ringsignature_logarithmic() {
+    check_ringsignature::<protocol_compressed::RingSignatureScheme<G1Projective>, _>(16);
+}
+
+#[test]
+fn test_ringsignature_logarithmic_modification() {
+    check_ringsignature::<protocol_compressed_modification::RingSignatureScheme<G1Projective>, _>(16);
+}
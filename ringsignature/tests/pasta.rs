@@ -0,0 +1,48 @@
+// cargo test -p ringsignature --features pasta --test pasta
+// the Pedersen commitments and the ring signature schemes instantiated with the Pasta curves Pallas and Vesta
+use ark_ec::CurveGroup;
+use ark_std::UniformRand;
+use ringsignature::commitment::pedersen::PedersenCommitmentScheme;
+use ringsignature::ringsig::{protocol_compressed, protocol_compressed_modification, protocol_linear};
+use toolbox::sigma::SigmaProtocol;
+
+const MESSAGE: &str = "Welcome to the world of Zero Knowledge!";
+
+fn check_pedersen<C: CurveGroup>() {
+    let mut rng = ark_std::test_rng();
+    let params = PedersenCommitmentScheme::<C>::setup(&mut rng, 8).unwrap();
+    let m: Vec<C::ScalarField> = (0..8).map(|_| C::ScalarField::rand(&mut rng)).collect();
+    let r = C::ScalarField::rand(&mut rng);
+    let cm = PedersenCommitmentScheme::<C>::commit(&params, &m, &r, "pasta").unwrap();
+    let open = PedersenCommitmentScheme::<C>::open(&m, &r).unwrap();
+    assert!(PedersenCommitmentScheme::<C>::verify(&params, &cm, &open).unwrap());
+}
+
+fn check_ringsignature<C, S>(ring_size: usize)
+where
+    C: CurveGroup,
+    S: SigmaProtocol<C, Witness = Vec<C::ScalarField>>,
+{
+    let mut rng = ark_std::test_rng();
+    let mut wit = vec![C::ScalarField::rand(&mut rng)];
+    let params = S::setup(&mut rng, &mut wit, MESSAGE, ring_size).unwrap();
+    let proof = S::prove(&mut rng, &params, &wit).unwrap();
+    assert!(S::verify(&params, &proof).unwrap());
+}
+
+fn check_curve<C: CurveGroup>() {
+    check_pedersen::<C>();
+    check_ringsignature::<C, protocol_linear::RingSignatureScheme<C>>(8);
+    check_ringsignature::<C, protocol_compressed::RingSignatureScheme<C>>(8);
+    check_ringsignature::<C, protocol_compressed_modification::RingSignatureScheme<C>>(8);
+}
+
+#[test]
+fn test_pallas() {
+    check_curve::<ark_pallas::Projective>();
+}
+
+#[test]
+fn test_vesta() {
+    check_curve::<ark_vesta::Projective>();
+}
@@ -0,0 +1,10 @@
+#!/usr/bin/env sh
+# Builds the Node.js package of the wasm module into ringsignature/wasm/pkg and runs wasm/test.js against it.
+# Needs wasm-pack and the wasm32-unknown-unknown target (rustup target add wasm32-unknown-unknown);
+# print-trace is left out, since the timers of ark-std read the system clock, which panics in wasm.
+set -e
+cd "$(dirname "$0")/.."
+# the crate enables #![feature(test)] for its benchmarks
+export RUSTC_BOOTSTRAP=1
+wasm-pack build --target nodejs --out-dir wasm/pkg -- --no-default-features --features wasm
+node wasm/test.js
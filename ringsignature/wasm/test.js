@@ -0,0 +1,24 @@
+// Signs with the wasm module built by build.sh and checks that the proof verifies from Node.js
+const assert = require("assert");
+const { ring_sign, split_signed, ring_verify } = require("./pkg/ringsignature.js");
+
+// a compressed secp256k1 scalar is 32 little-endian bytes below the group order
+const sk = new Uint8Array(32);
+sk[0] = 42;
+
+const signed = ring_sign(8, sk, "Welcome to the world of Zero Knowledge!");
+const [params, proof] = split_signed(signed);
+assert.strictEqual(params.length + proof.length, signed.length);
+assert.ok(ring_verify(params, proof), "the proof should verify");
+
+// a proof of another signing does not verify against these parameters, nor does a modified proof
+const [, otherProof] = split_signed(ring_sign(8, sk, "Welcome to the world of Zero Knowledge!"));
+assert.ok(!ring_verify(params, otherProof));
+const tampered = proof.slice();
+tampered[tampered.length - 1] ^= 1;
+assert.ok(!ring_verify(params, tampered));
+
+// ring sizes must be powers of two
+assert.throws(() => ring_sign(6, sk, "message"));
+
+console.log("ok");
@@ -0,0 +1,111 @@
+use alloc::string::ToString;
+use ark_ec::CurveGroup;
+use ark_ff::Zero;
+use ark_std::rand::Rng;
+use crate::errors::SigmaErrors;
+use crate::vec::{ct_eq_affine, vec_product};
+
+/// Elliptic-curve accumulator of a set {m_1,...,m_k} of nonzero scalars:
+/// the accumulator value is A = G^{prod(m_i)} and the membership witness of m_j is W_j = G^{prod_{i!=j}(m_i)},
+/// which is checked by W_j^{m_j} = A without knowing the rest of the set.
+/// The order of the group is public, so anyone can compute W = A^{1/m} for any m:
+/// the witnesses are only meaningful when they are handed out by whoever accumulated the set
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Accumulator<C: CurveGroup> {
+    // the base G of the accumulator
+    pub G: C::Affine,
+}
+
+impl<C: CurveGroup> Accumulator<C> {
+    /// Samples the base of the accumulator
+    pub fn setup<R: Rng>(rng: &mut R) -> Self {
+        Accumulator {
+            G: C::rand(rng).into_affine(),
+        }
+    }
+
+    /// Computes the accumulator value A = G^{prod(m_i)} of the set
+    pub fn accumulate(&self, set: &[C::ScalarField]) -> Result<C::Affine, SigmaErrors> {
+        check_set::<C>(set)?;
+        Ok((self.G * vec_product(set)).into_affine())
+    }
+
+    /// Computes the membership witness W_j = G^{prod_{i!=j}(m_i)} of the element set[index]
+    pub fn witness(&self, set: &[C::ScalarField], index: usize) -> Result<C::Affine, SigmaErrors> {
+        check_set::<C>(set)?;
+        if index >= set.len() {
+            return Err(SigmaErrors::InvalidParameters(
+                "the index is out of the set".to_string(),
+            ));
+        }
+        let product: C::ScalarField = set.iter()
+            .enumerate()
+            .filter(|(i, _)| *i != index)
+            .map(|(_, m_i)| m_i)
+            .product();
+        Ok((self.G * product).into_affine())
+    }
+
+    /// Checks that elem is accumulated in A with the membership witness W, i.e. W^elem = A
+    pub fn verify(A: C::Affine, elem: C::ScalarField, witness: C::Affine) -> bool {
+        // a zero element would accept the identity as the accumulator of any set
+        !elem.is_zero() && ct_eq_affine(witness * elem, A.into())
+    }
+}
+
+// a zero element sends the accumulator value to the identity, which accumulates every element
+fn check_set<C: CurveGroup>(set: &[C::ScalarField]) -> Result<(), SigmaErrors> {
+    if set.iter().any(|m_i| m_i.is_zero()) {
+        return Err(SigmaErrors::InvalidParameters(
+            "the accumulated elements should be nonzero".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use ark_secp256k1::{Fr, Projective};
+    use ark_std::UniformRand;
+
+    type Acc = Accumulator<Projective>;
+
+    #[test]
+    fn test_accumulator() {
+        let mut rng = ark_std::test_rng();
+        let acc = Acc::setup(&mut rng);
+        let set: Vec<Fr> = (0..8).map(|_| Fr::rand(&mut rng)).collect();
+        let A = acc.accumulate(&set).unwrap();
+
+        // every element of the set is a member with its witness
+        let witnesses: Vec<_> = (0..set.len()).map(|j| acc.witness(&set, j).unwrap()).collect();
+        for (m_j, W_j) in set.iter().zip(&witnesses) {
+            assert!(Acc::verify(A, *m_j, *W_j));
+        }
+        // an element out of the set is not a member with any of the witnesses
+        let elem = Fr::rand(&mut rng);
+        assert!(witnesses.iter().all(|W_j| !Acc::verify(A, elem, *W_j)));
+        // nor is an element of the set with the witness of another element
+        assert!(!Acc::verify(A, set[0], witnesses[1]));
+        // nor an element of the set in the accumulator of another set
+        let A_prime = acc.accumulate(&set[1..]).unwrap();
+        assert!(!Acc::verify(A_prime, set[0], witnesses[0]));
+        assert!(Acc::verify(A_prime, set[2], acc.witness(&set[1..], 1).unwrap()));
+    }
+
+    #[test]
+    fn test_accumulator_invalid() {
+        let mut rng = ark_std::test_rng();
+        let acc = Acc::setup(&mut rng);
+        let mut set: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+        assert!(acc.witness(&set, 4).is_err());
+        let A = acc.accumulate(&set).unwrap();
+        assert!(!Acc::verify(A, Fr::zero(), acc.witness(&set, 0).unwrap()));
+
+        set[2] = Fr::zero();
+        assert!(acc.accumulate(&set).is_err());
+        assert!(acc.witness(&set, 0).is_err());
+    }
+}
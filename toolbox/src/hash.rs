@@ -0,0 +1,139 @@
+use alloc::{format, string::String, vec, vec::Vec};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_serialize::CanonicalSerialize;
+use merlin::Transcript;
+
+/// Hashes input to a curve point by try-and-increment: (domain, input, counter) is hashed to the bytes
+/// of a candidate x coordinate, and counter is incremented until the bytes decode to a point,
+/// whose cofactor is then cleared. The output is deterministic and nobody knows its discrete log
+/// with respect to any other generator
+pub fn hash_to_curve<C: CurveGroup>(domain: &[u8], input: &[u8]) -> C::Affine {
+    let mut bytes = vec![0u8; C::Affine::generator().compressed_size()];
+    for counter in 0u64.. {
+        let mut transcript = Transcript::new(b"HashToCurve");
+        transcript.append_message(b"domain", domain);
+        transcript.append_message(b"input", input);
+        transcript.append_u64(b"counter", counter);
+        transcript.challenge_bytes(b"point", &mut bytes);
+        if let Some(point) = C::Affine::from_random_bytes(&bytes) {
+            let point = point.clear_cofactor();
+            if !point.is_zero() {
+                return point;
+            }
+        }
+    }
+    unreachable!("the counter space is not exhausted before a point is found")
+}
+
+/// The hash function of the message digest of the signatures, output in lowercase hex;
+/// the protocols append NAME to their transcript next to the digest, so that a proof only verifies
+/// with the function it was made with
+pub trait Digest {
+    const NAME: &'static [u8];
+    fn digest(msg: &[u8]) -> String;
+}
+
+/// SHA-256, the default message digest
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sha256;
+
+impl Digest for Sha256 {
+    const NAME: &'static [u8] = b"SHA-256";
+    fn digest(msg: &[u8]) -> String {
+        to_hex(&<sha2::Sha256 as sha2::Digest>::digest(msg))
+    }
+}
+
+/// Keccak-256 as used by Ethereum, i.e., with the original Keccak padding rather than the SHA-3 one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Keccak256;
+
+impl Digest for Keccak256 {
+    const NAME: &'static [u8] = b"Keccak-256";
+    fn digest(msg: &[u8]) -> String {
+        // sponge with a rate of 136 bytes, the message is padded with 0x01 ... 0x80
+        const RATE: usize = 136;
+        let mut padded = msg.to_vec();
+        padded.push(0x01);
+        padded.resize(padded.len().next_multiple_of(RATE), 0);
+        *padded.last_mut().expect("the padding is not empty") |= 0x80;
+
+        let mut state = [0u64; 25];
+        for block in padded.chunks(RATE) {
+            for (lane, word) in state.iter_mut().zip(block.chunks(8)) {
+                *lane ^= u64::from_le_bytes(word.try_into().expect("the rate is a multiple of 8"));
+            }
+            keccak::f1600(&mut state);
+        }
+        let bytes: Vec<u8> = state[..4].iter().flat_map(|lane| lane.to_le_bytes()).collect();
+        to_hex(&bytes)
+    }
+}
+
+/// BLAKE2s with a 256-bit output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Blake2s256;
+
+impl Digest for Blake2s256 {
+    const NAME: &'static [u8] = b"BLAKE2s-256";
+    fn digest(msg: &[u8]) -> String {
+        to_hex(&<blake2::Blake2s256 as blake2::Digest>::digest(msg))
+    }
+}
+
+// lowercase hex of bytes
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Affine, Projective};
+
+    fn to_hex(point: &Affine) -> String {
+        let mut bytes = vec![];
+        point.serialize_compressed(&mut bytes).unwrap();
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_hash_to_curve() {
+        // the output is pinned, so that it stays stable across runs and releases
+        let point = hash_to_curve::<Projective>(b"test_hash_to_curve", b"input");
+        assert_eq!(to_hex(&point), "d90f0a1043044979f9efc2099323fdebb9f668430617a11d8040fbeddd5e4b2f80");
+        assert!(point.is_on_curve() && !point.is_zero());
+
+        // distinct inputs and distinct domains map to distinct points
+        let points: Vec<Affine> = (0u64..32)
+            .map(|i| hash_to_curve::<Projective>(b"test_hash_to_curve", &i.to_le_bytes()))
+            .collect();
+        for (i, p_i) in points.iter().enumerate() {
+            assert!(points[i+1..].iter().all(|p_j| p_j != p_i));
+        }
+        assert_ne!(point, hash_to_curve::<Projective>(b"test_hash_to_curve_2", b"input"));
+
+        // on curves with a cofactor the point lands in the prime order subgroup
+        let point = hash_to_curve::<ark_bls12_381::G1Projective>(b"test_hash_to_curve", b"input");
+        assert!(point.is_on_curve() && point.is_in_correct_subgroup_assuming_on_curve() && !point.is_zero());
+    }
+
+    #[test]
+    fn test_digest() {
+        assert_eq!(Sha256::digest(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(Keccak256::digest(b""), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(Keccak256::digest(b"abc"), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+        assert_eq!(Blake2s256::digest(b""), "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9");
+        assert_eq!(Blake2s256::digest(b"abc"), "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982");
+        // messages of one rate minus one byte, one rate and more than one rate, pinned from the sponge,
+        // which outputs SHA3-256 on the same messages when run with the SHA-3 padding 0x06
+        let vectors = [
+            (135, "34367dc248bbd832f4e3e69dfaac2f92638bd0bbd18f2912ba4ef454919cf446"),
+            (136, "a6c4d403279fe3e0af03729caada8374b5ca54d8065329a3ebcaeb4b60aa386e"),
+            (300, "5b7e0e47a96f32a88b4f14ca177982790807c40e1a105742ba0fc1babe1ef826"),
+        ];
+        for (len, hex) in vectors {
+            assert_eq!(Keccak256::digest(&vec![b'a'; len]), hex);
+        }
+    }
+}
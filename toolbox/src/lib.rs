@@ -1,6 +1,17 @@
+#![no_std]
 #![allow(dead_code)]
 #![allow(non_snake_case)]
 
+extern crate alloc;
+// the standard library is only linked with the std feature, and by the tests
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
 pub mod sigma;
 pub mod errors;
+pub mod hash;
 pub mod vec;
+pub mod accumulator;
+pub mod metrics;
+#[cfg(feature = "serde")]
+pub mod serde_canonical;
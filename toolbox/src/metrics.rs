@@ -0,0 +1,149 @@
+//! Counters of the multi-scalar multiplications, transcript appends and phase durations of a prove or verify call,
+//! recorded on the calling thread by ProveStats::record and VerifyStats::record.
+//! The hooks are called unconditionally by the protocols and compile to nothing without the metrics feature,
+//! so that the hot paths are not affected unless a researcher opts in
+#[cfg(feature = "metrics")]
+pub use recording::*;
+
+/// Counts a multi-scalar multiplication of the current recording
+#[inline(always)]
+pub fn count_msm() {
+    #[cfg(feature = "metrics")]
+    recording::with_counters(|counters| counters.msm_count += 1);
+}
+
+/// Counts an append to a proof transcript of the current recording
+#[inline(always)]
+pub fn count_transcript_append() {
+    #[cfg(feature = "metrics")]
+    recording::with_counters(|counters| counters.transcript_appends += 1);
+}
+
+/// Ends the current phase of the recording, if any, and starts the phase name
+#[inline(always)]
+pub fn phase(name: &'static str) {
+    #[cfg(feature = "metrics")]
+    recording::with_counters(|counters| counters.enter_phase(Some(name)));
+    #[cfg(not(feature = "metrics"))]
+    let _ = name;
+}
+
+#[cfg(feature = "metrics")]
+mod recording {
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
+    use std::time::{Duration, Instant};
+
+    std::thread_local! {
+        static COUNTERS: RefCell<Option<Counters>> = const { RefCell::new(None) };
+    }
+
+    #[derive(Default)]
+    pub(super) struct Counters {
+        pub(super) msm_count: usize,
+        pub(super) transcript_appends: usize,
+        phases: Vec<(&'static str, Duration)>,
+        current: Option<(&'static str, Instant)>,
+    }
+
+    impl Counters {
+        pub(super) fn enter_phase(&mut self, name: Option<&'static str>) {
+            if let Some((name, start)) = self.current.take() {
+                self.phases.push((name, start.elapsed()));
+            }
+            self.current = name.map(|name| (name, Instant::now()));
+        }
+    }
+
+    pub(super) fn with_counters(f: impl FnOnce(&mut Counters)) {
+        COUNTERS.with(|counters| {
+            if let Some(counters) = counters.borrow_mut().as_mut() {
+                f(counters)
+            }
+        });
+    }
+
+    // runs f with fresh counters, restoring the counters of an enclosing recording afterwards
+    fn record<T>(f: impl FnOnce() -> T) -> (T, Counters) {
+        let outer = COUNTERS.with(|counters| counters.replace(Some(Counters::default())));
+        let output = f();
+        let mut counters = COUNTERS
+            .with(|counters| counters.replace(outer))
+            .expect("the recording is only taken here");
+        counters.enter_phase(None);
+        (output, counters)
+    }
+
+    /// The operations of a prove call
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct ProveStats {
+        pub msm_count: usize,
+        pub transcript_appends: usize,
+        // the duration of each phase in order
+        pub phases: Vec<(&'static str, Duration)>,
+    }
+
+    impl ProveStats {
+        /// Runs f, which should prove on the calling thread, and outputs its result with the operations it ran
+        pub fn record<T>(f: impl FnOnce() -> T) -> (T, Self) {
+            let (output, counters) = record(f);
+            let stats = ProveStats {
+                msm_count: counters.msm_count,
+                transcript_appends: counters.transcript_appends,
+                phases: counters.phases,
+            };
+            (output, stats)
+        }
+    }
+
+    /// The operations of a verify call
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct VerifyStats {
+        pub msm_count: usize,
+        pub transcript_appends: usize,
+        // the duration of each phase in order
+        pub phases: Vec<(&'static str, Duration)>,
+    }
+
+    impl VerifyStats {
+        /// Runs f, which should verify on the calling thread, and outputs its result with the operations it ran
+        pub fn record<T>(f: impl FnOnce() -> T) -> (T, Self) {
+            let (output, counters) = record(f);
+            let stats = VerifyStats {
+                msm_count: counters.msm_count,
+                transcript_appends: counters.transcript_appends,
+                phases: counters.phases,
+            };
+            (output, stats)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_record() {
+        let ((), stats) = ProveStats::record(|| {
+            phase("first");
+            count_msm();
+            count_transcript_append();
+            phase("second");
+            count_msm();
+            // an inner recording neither sees nor leaks into the outer counts
+            let ((), inner) = VerifyStats::record(count_msm);
+            assert_eq!(inner.msm_count, 1);
+            assert!(inner.phases.is_empty());
+        });
+        assert_eq!((stats.msm_count, stats.transcript_appends), (2, 1));
+        let names: Vec<_> = stats.phases.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["first", "second"]);
+
+        // outside a recording the hooks do nothing
+        count_msm();
+        let ((), stats) = VerifyStats::record(|| ());
+        assert_eq!(stats, VerifyStats::default());
+    }
+}
@@ -0,0 +1,90 @@
+//! serde adapters encoding arkworks values as the lowercase hex of their compressed canonical bytes,
+//! for human-readable transport such as JSON; use as #[serde(with = "toolbox::serde_canonical::canonical")]
+//! on a single value and #[serde(with = "toolbox::serde_canonical::canonical_vec")] on a vector of values
+use alloc::{format, string::String, vec::Vec};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::de::Error;
+
+fn to_hex<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::with_capacity(value.compressed_size());
+    value
+        .serialize_compressed(&mut bytes)
+        .expect("serializing into a vector");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex<T: CanonicalDeserialize, E: Error>(hex: &str) -> Result<T, E> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(E::custom("hex string of odd length or with non-ascii characters"));
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(E::custom)?;
+    T::deserialize_compressed(bytes.as_slice()).map_err(|e| E::custom(format!("{:?}", e)))
+}
+
+/// A single value as one hex string
+pub mod canonical {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: CanonicalSerialize, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_hex(value))
+    }
+
+    pub fn deserialize<'de, T: CanonicalDeserialize, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        from_hex(&String::deserialize(deserializer)?)
+    }
+}
+
+/// A vector of values as a sequence of hex strings, one per element
+pub mod canonical_vec {
+    use super::*;
+    use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T: CanonicalSerialize, S: Serializer>(values: &[T], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&to_hex(value))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, T: CanonicalDeserialize, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<T>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hex| from_hex(hex))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Affine, Fr};
+    use ark_std::UniformRand;
+    use serde::de::value::{Error as ValueError, StrDeserializer};
+    use serde::de::IntoDeserializer;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let mut rng = ark_std::test_rng();
+        let s = Fr::rand(&mut rng);
+        let p = Affine::rand(&mut rng);
+        assert_eq!(from_hex::<Fr, ValueError>(&to_hex(&s)).unwrap(), s);
+        assert_eq!(from_hex::<Affine, ValueError>(&to_hex(&p)).unwrap(), p);
+        // a compressed secp256k1 point is 33 bytes
+        assert_eq!(to_hex(&p).len(), 66);
+
+        let hex = to_hex(&s);
+        let de: StrDeserializer<ValueError> = hex.as_str().into_deserializer();
+        assert_eq!(canonical::deserialize::<Fr, _>(de).unwrap(), s);
+
+        // odd lengths, non-hex characters and truncated values are rejected
+        assert!(from_hex::<Fr, ValueError>("abc").is_err());
+        assert!(from_hex::<Fr, ValueError>("zz").is_err());
+        assert!(from_hex::<Fr, ValueError>(&to_hex(&s)[2..]).is_err());
+    }
+}
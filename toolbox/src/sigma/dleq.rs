@@ -0,0 +1,159 @@
+use alloc::string::ToString;
+use ark_ec::CurveGroup;
+use ark_std::{rand::Rng, UniformRand};
+use crate::errors::SigmaErrors;
+use crate::sigma::transcript::ProofTranscript;
+use crate::vec::ct_eq_affine;
+
+// DLEQ relation: the prover knows x with X = G^x and Y = H^x
+// the two statements are batched by a challenge w bound to (G, H, X, Y) into X Y^w = (G H^w)^x,
+// which is proved by a Schnorr proof over the base G H^w:
+// P->V: A = (G H^w)^r
+// V->P: challenge c
+// P->V: s = r + c*x
+// V checks (G H^w)^s = A (X Y^w)^c
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DLEQProof<C: CurveGroup> {
+    // the masking commitment A
+    pub com: C::Affine,
+    // the response s
+    pub response: C::ScalarField,
+}
+
+impl<C: CurveGroup> DLEQProof<C> {
+    /// Proves that log_G(X) = log_H(Y) with the witness x
+    pub fn prove<R: Rng>(
+        rng: &mut R,
+        G: C::Affine,
+        H: C::Affine,
+        X: C,
+        Y: C,
+        x: C::ScalarField,
+    ) -> Result<Self, SigmaErrors> {
+        let mut transcript = statement_transcript(G, H, X, Y)?;
+        let w = transcript.get_and_append_challenge(b"challenge w")?;
+        let base = G + H * w;
+
+        let r = C::ScalarField::rand(rng);
+        let com = (base * r).into_affine();
+        transcript.append_serializable_element(b"masking commitment", &com)?;
+        let c = transcript.get_and_append_challenge(b"challenge c")?;
+
+        Ok(DLEQProof {
+            com,
+            response: r + c * x,
+        })
+    }
+
+    /// Verifies that the proof shows log_G(X) = log_H(Y)
+    pub fn verify(
+        G: C::Affine,
+        H: C::Affine,
+        X: C,
+        Y: C,
+        proof: &Self,
+    ) -> bool {
+        Self::verify_checked(G, H, X, Y, proof).is_ok()
+    }
+
+    // the checks of verify, failing with the reason of the rejection
+    fn verify_checked(
+        G: C::Affine,
+        H: C::Affine,
+        X: C,
+        Y: C,
+        proof: &Self,
+    ) -> Result<(), SigmaErrors> {
+        let mut transcript = statement_transcript(G, H, X, Y)?;
+        let w = transcript.get_and_append_challenge(b"challenge w")?;
+        transcript.append_serializable_element(b"masking commitment", &proof.com)?;
+        let c = transcript.get_and_append_challenge(b"challenge c")?;
+
+        let lhs = (G + H * w) * proof.response;
+        let rhs = (X + Y * w) * c + proof.com;
+        if ct_eq_affine(lhs, rhs) {
+            Ok(())
+        }
+        else {
+            Err(SigmaErrors::InvalidProof("invalid DLEQ proof".to_string()))
+        }
+    }
+}
+
+// the transcript of a DLEQ proof absorbing the statement (G, H, X, Y)
+fn statement_transcript<C: CurveGroup>(
+    G: C::Affine,
+    H: C::Affine,
+    X: C,
+    Y: C,
+) -> Result<ProofTranscript<C::ScalarField>, SigmaErrors> {
+    let mut transcript = ProofTranscript::<C::ScalarField>::new(b"DLEQ");
+    transcript.append_serializable_element(b"bases G, H", &[G, H])?;
+    transcript.append_serializable_element(b"statement X, Y", &C::normalize_batch(&[X, Y]))?;
+    Ok(transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Affine, Fr, Projective};
+    use ark_ec::Group;
+
+    type Dleq = DLEQProof<Projective>;
+
+    #[test]
+    fn test_dleq() {
+        let mut rng = ark_std::test_rng();
+        let G = Affine::rand(&mut rng);
+        let H = Affine::rand(&mut rng);
+        let x = Fr::rand(&mut rng);
+        let (X, Y) = (G * x, H * x);
+        let proof = Dleq::prove(&mut rng, G, H, X, Y, x).unwrap();
+        assert!(Dleq::verify(G, H, X, Y, &proof));
+
+        // the proof is bound to the statement
+        assert!(!Dleq::verify(H, G, Y, X, &proof));
+        assert!(!Dleq::verify(G, H, X, X, &proof));
+        assert!(!Dleq::verify(G, Affine::rand(&mut rng), X, Y, &proof));
+    }
+
+    #[test]
+    fn test_dleq_invalid_witness() {
+        let mut rng = ark_std::test_rng();
+        let G = Affine::rand(&mut rng);
+        let H = Affine::rand(&mut rng);
+        let x = Fr::rand(&mut rng);
+        let (X, Y) = (G * x, H * x);
+
+        // a wrong witness
+        let proof = Dleq::prove(&mut rng, G, H, X, Y, x + Fr::from(1u64)).unwrap();
+        assert!(!Dleq::verify(G, H, X, Y, &proof));
+
+        // different discrete logs with the witness of X
+        let Y_prime = H * Fr::rand(&mut rng);
+        let proof = Dleq::prove(&mut rng, G, H, X, Y_prime, x).unwrap();
+        assert!(!Dleq::verify(G, H, X, Y_prime, &proof));
+        // a Y off by another point cannot be absorbed by the batching challenge
+        let Y_prime = Y + Projective::generator();
+        let proof = Dleq::prove(&mut rng, G, H, X, Y_prime, x).unwrap();
+        assert!(!Dleq::verify(G, H, X, Y_prime, &proof));
+    }
+
+    #[test]
+    fn test_dleq_tampered_proof() {
+        let mut rng = ark_std::test_rng();
+        let G = Affine::rand(&mut rng);
+        let H = Affine::rand(&mut rng);
+        let x = Fr::rand(&mut rng);
+        let (X, Y) = (G * x, H * x);
+        let proof = Dleq::prove(&mut rng, G, H, X, Y, x).unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.com = (tampered.com + G).into_affine();
+        assert!(!Dleq::verify(G, H, X, Y, &tampered));
+        let mut tampered = proof.clone();
+        tampered.response += Fr::from(1u64);
+        assert!(!Dleq::verify(G, H, X, Y, &tampered));
+        assert!(!Dleq::verify(G, H, X, Y, &Dleq::default()));
+    }
+}
@@ -2,6 +2,9 @@ use crate::errors::SigmaErrors;
 use ark_ec::CurveGroup;
 use ark_std::rand::Rng;
 
+pub mod dleq;
+pub mod okamoto;
+pub mod sumcheck;
 pub mod transcript;
 
 pub trait SigmaProtocol<C>
@@ -25,7 +28,7 @@ where
     fn setup<R: Rng>(
         rng: &mut R,
         wit: &mut Self::Witness,
-        msg: &String,
+        msg: &str,
         supported_size: usize,
     ) -> Result<Self::PublicParams, SigmaErrors>;
 
@@ -0,0 +1,173 @@
+use alloc::{string::ToString, vec::Vec};
+use ark_ec::CurveGroup;
+use ark_std::{rand::Rng, UniformRand};
+use crate::errors::SigmaErrors;
+use crate::sigma::transcript::ProofTranscript;
+use crate::vec::ct_eq_affine;
+
+// Okamoto relation: the prover knows a representation (x_1, ..., x_k) of C = G_1^{x_1} * ... * G_k^{x_k},
+// which is a Schnorr proof for k = 1 and an opening proof of a Pedersen commitment for k = 2:
+// P->V: A = G_1^{r_1} * ... * G_k^{r_k}
+// V->P: challenge c
+// P->V: s_i = r_i + c*x_i
+// V checks G_1^{s_1} * ... * G_k^{s_k} = A C^c
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OkamotoProof<C: CurveGroup> {
+    // the masking commitment A
+    pub com: C::Affine,
+    // the responses s_1, ..., s_k
+    pub responses: Vec<C::ScalarField>,
+}
+
+impl<C: CurveGroup> OkamotoProof<C> {
+    /// Proves the knowledge of the witnesses of C = prod_i bases_i^{witnesses_i},
+    /// the transcript absorbs the bases and C before the challenge
+    pub fn prove<R: Rng>(
+        rng: &mut R,
+        bases: &[C::Affine],
+        witnesses: &[C::ScalarField],
+        transcript: &mut ProofTranscript<C::ScalarField>,
+    ) -> Result<Self, SigmaErrors> {
+        if bases.is_empty() || bases.len() != witnesses.len() {
+            return Err(SigmaErrors::InvalidParameters(
+                "bases and witnesses should be non-empty and of the same length".to_string(),
+            ));
+        }
+        let commitment = C::msm_unchecked(bases, witnesses);
+        statement(transcript, bases, commitment)?;
+
+        let masks: Vec<C::ScalarField> = (0..bases.len()).map(|_| C::ScalarField::rand(rng)).collect();
+        let com = C::msm_unchecked(bases, &masks).into_affine();
+        transcript.append_serializable_element(b"masking commitment", &com)?;
+        let c = transcript.get_and_append_challenge(b"challenge c")?;
+
+        Ok(OkamotoProof {
+            com,
+            responses: masks.iter().zip(witnesses).map(|(r_i, x_i)| *r_i + c * x_i).collect(),
+        })
+    }
+
+    /// Verifies that the prover knows a representation of the commitment over the bases,
+    /// the transcript must be in the same state as the transcript passed to prove
+    pub fn verify(
+        bases: &[C::Affine],
+        commitment: C,
+        proof: &Self,
+        transcript: &mut ProofTranscript<C::ScalarField>,
+    ) -> bool {
+        Self::verify_checked(bases, commitment, proof, transcript).is_ok()
+    }
+
+    // the checks of verify, failing with the reason of the rejection
+    fn verify_checked(
+        bases: &[C::Affine],
+        commitment: C,
+        proof: &Self,
+        transcript: &mut ProofTranscript<C::ScalarField>,
+    ) -> Result<(), SigmaErrors> {
+        if bases.is_empty() || proof.responses.len() != bases.len() {
+            return Err(SigmaErrors::InvalidProof("incorrect proof length".to_string()));
+        }
+        statement(transcript, bases, commitment)?;
+        transcript.append_serializable_element(b"masking commitment", &proof.com)?;
+        let c = transcript.get_and_append_challenge(b"challenge c")?;
+
+        let lhs = C::msm_unchecked(bases, &proof.responses);
+        let rhs = commitment * c + proof.com;
+        if ct_eq_affine(lhs, rhs) {
+            Ok(())
+        }
+        else {
+            Err(SigmaErrors::InvalidProof("invalid Okamoto proof".to_string()))
+        }
+    }
+}
+
+// absorbs the statement (G_1, ..., G_k, C) of an Okamoto proof
+fn statement<C: CurveGroup>(
+    transcript: &mut ProofTranscript<C::ScalarField>,
+    bases: &[C::Affine],
+    commitment: C,
+) -> Result<(), SigmaErrors> {
+    transcript.append_serializable_element(b"Okamoto bases", &bases.to_vec())?;
+    transcript.append_serializable_element(b"Okamoto commitment", &commitment.into_affine())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::PrimeField;
+    use ark_secp256k1::{Affine, Fr, Projective};
+    use ark_std::rand::{rngs::StdRng, SeedableRng};
+    use proptest::prelude::*;
+
+    type Okamoto = OkamotoProof<Projective>;
+
+    fn transcript() -> ProofTranscript<Fr> {
+        ProofTranscript::new(b"Okamoto test")
+    }
+
+    // k random bases and witnesses with the commitment of their representation
+    fn instance(rng: &mut StdRng, k: usize) -> (Vec<Affine>, Vec<Fr>, Projective) {
+        let bases: Vec<Affine> = (0..k).map(|_| Affine::rand(rng)).collect();
+        let witnesses: Vec<Fr> = (0..k).map(|_| Fr::rand(rng)).collect();
+        let commitment = bases.iter().zip(&witnesses).map(|(g_i, x_i)| *g_i * x_i).sum();
+        (bases, witnesses, commitment)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_okamoto_completeness(seed in any::<u64>(), k in 1usize..=8) {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let (bases, witnesses, commitment) = instance(&mut rng, k);
+            let proof = Okamoto::prove(&mut rng, &bases, &witnesses, &mut transcript()).unwrap();
+            prop_assert!(Okamoto::verify(&bases, commitment, &proof, &mut transcript()));
+        }
+    }
+
+    #[test]
+    fn test_okamoto_soundness() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (bases, witnesses, commitment) = instance(&mut rng, 4);
+        let proof = Okamoto::prove(&mut rng, &bases, &witnesses, &mut transcript()).unwrap();
+        assert!(Okamoto::verify(&bases, commitment, &proof, &mut transcript()));
+
+        // the proof is bound to the commitment, the bases and the transcript
+        assert!(!Okamoto::verify(&bases, commitment + bases[0], &proof, &mut transcript()));
+        let mut bases_prime = bases.clone();
+        bases_prime.swap(0, 1);
+        assert!(!Okamoto::verify(&bases_prime, commitment, &proof, &mut transcript()));
+        assert!(!Okamoto::verify(&bases, commitment, &proof, &mut ProofTranscript::new(b"another protocol")));
+
+        // tampered or truncated responses are rejected
+        let mut proof_prime = proof.clone();
+        proof_prime.responses[3] += Fr::from(1u64);
+        assert!(!Okamoto::verify(&bases, commitment, &proof_prime, &mut transcript()));
+        let mut proof_prime = proof.clone();
+        proof_prime.responses.pop();
+        assert!(!Okamoto::verify(&bases, commitment, &proof_prime, &mut transcript()));
+        assert!(!Okamoto::verify(&bases, commitment, &Okamoto::default(), &mut transcript()));
+
+        // a wrong witness does not verify
+        let mut witnesses_prime = witnesses.clone();
+        witnesses_prime[2] += Fr::from(1u64);
+        let proof = Okamoto::prove(&mut rng, &bases, &witnesses_prime, &mut transcript()).unwrap();
+        assert!(!Okamoto::verify(&bases, commitment, &proof, &mut transcript()));
+    }
+
+    #[test]
+    fn test_okamoto_invalid_parameters() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (bases, witnesses, _) = instance(&mut rng, 3);
+        assert!(Okamoto::prove(&mut rng, &bases, &witnesses[..2], &mut transcript()).is_err());
+        assert!(Okamoto::prove(&mut rng, &[], &[], &mut transcript()).is_err());
+        // a Pedersen opening (m, r) is the case k = 2
+        let (m, r) = (Fr::from_le_bytes_mod_order(b"message"), Fr::rand(&mut rng));
+        let commitment = bases[0] * m + bases[1] * r;
+        let proof = Okamoto::prove(&mut rng, &bases[..2], &[m, r], &mut transcript()).unwrap();
+        assert!(Okamoto::verify(&bases[..2], commitment, &proof, &mut transcript()));
+    }
+}
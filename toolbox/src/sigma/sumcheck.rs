@@ -0,0 +1,202 @@
+use alloc::{format, string::ToString, vec::Vec};
+use core::marker::PhantomData;
+use ark_ff::PrimeField;
+use crate::errors::SigmaErrors;
+use crate::sigma::transcript::ProofTranscript;
+
+// Sumcheck relation: sum_{x in {0,1}^n} f(x) = c for the multilinear f given by its evaluations on {0,1}^n,
+// where evals[i] is f at the bits of i, x_1 being the most significant bit
+// in round j, with x_1..x_{j-1} bound to the challenges r_1..r_{j-1}:
+// P->V: g_j(X) = sum_{x in {0,1}^{n-j}} f(r_1, ..., r_{j-1}, X, x) as its coefficients (g_j0, g_j1, g_j2)
+// V checks g_j(0) + g_j(1) = g_{j-1}(r_{j-1}), with g_0(r_0) = c
+// V->P: challenge r_j
+// and the verifier is left with the claim f(r_1, ..., r_n) = g_n(r_n), checked against an oracle of f;
+// the round polynomials of a multilinear f are linear, their degree-2 coefficient leaves room for products of two
+// multilinear polynomials as in GKR-style arguments and range proofs
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SumcheckProof<F: PrimeField> {
+    // the coefficients of g_j from the constant term up, per round
+    pub round_polys: Vec<[F; 3]>,
+}
+
+/// The sumcheck protocol, made non-interactive over the transcript of the caller
+pub struct Sumcheck<F: PrimeField> {
+    phantom: PhantomData<F>,
+}
+
+impl<F: PrimeField> Sumcheck<F> {
+    /// Proves that the multilinear polynomial with the evaluations evals, of a power of two length of at least 2,
+    /// sums to claimed_sum on the boolean hypercube
+    pub fn prove(
+        evals: &[F],
+        claimed_sum: F,
+        transcript: &mut ProofTranscript<F>,
+    ) -> Result<SumcheckProof<F>, SigmaErrors> {
+        let num_vars = num_vars(evals.len())?;
+        if evals.iter().sum::<F>() != claimed_sum {
+            return Err(SigmaErrors::InvalidProver(
+                "the evaluations do not sum to the claimed sum".to_string(),
+            ));
+        }
+        absorb_statement(transcript, num_vars, &claimed_sum)?;
+
+        let mut evals = evals.to_vec();
+        let mut round_polys = Vec::with_capacity(num_vars);
+        for _ in 0..num_vars {
+            let half = evals.len() / 2;
+            let (low, high) = evals.split_at(half);
+            // g_j(0), g_j(1) and g_j(2) = sum 2 f(.., 1, x) - f(.., 0, x), by the multilinearity in X
+            let e0: F = low.iter().sum();
+            let e1: F = high.iter().sum();
+            let e2 = e1.double() - e0;
+            let poly = interpolate(e0, e1, e2);
+            transcript.append_serializable_element(b"sumcheck round", &poly.to_vec())?;
+            let r = transcript.get_and_append_challenge(b"sumcheck challenge")?;
+            round_polys.push(poly);
+
+            // bind X to r
+            evals = low.iter().zip(high).map(|(l, h)| *l + r * (*h - l)).collect();
+        }
+        Ok(SumcheckProof { round_polys })
+    }
+
+    /// Verifies the rounds of proof for a polynomial in num_vars variables summing to claimed_sum,
+    /// and outputs the point r and the value v of the remaining claim f(r) = v,
+    /// which the caller checks against its own access to f, e.g., with evaluate_multilinear
+    pub fn verify(
+        num_vars: usize,
+        claimed_sum: F,
+        proof: &SumcheckProof<F>,
+        transcript: &mut ProofTranscript<F>,
+    ) -> Result<(Vec<F>, F), SigmaErrors> {
+        if num_vars == 0 || proof.round_polys.len() != num_vars {
+            return Err(SigmaErrors::InvalidProof(
+                "incorrect proof length".to_string(),
+            ));
+        }
+        absorb_statement(transcript, num_vars, &claimed_sum)?;
+
+        let mut claim = claimed_sum;
+        let mut point = Vec::with_capacity(num_vars);
+        for (j, poly) in proof.round_polys.iter().enumerate() {
+            // g_j(0) + g_j(1) = 2 g_j0 + g_j1 + g_j2
+            if poly[0].double() + poly[1] + poly[2] != claim {
+                return Err(SigmaErrors::InvalidProof(
+                    format!("round {}: sumcheck fails", j + 1),
+                ));
+            }
+            transcript.append_serializable_element(b"sumcheck round", &poly.to_vec())?;
+            let r = transcript.get_and_append_challenge(b"sumcheck challenge")?;
+            claim = poly[0] + r * (poly[1] + r * poly[2]);
+            point.push(r);
+        }
+        Ok((point, claim))
+    }
+}
+
+/// Evaluates the multilinear extension of evals at point, with the variable order of the sumcheck
+pub fn evaluate_multilinear<F: PrimeField>(evals: &[F], point: &[F]) -> Result<F, SigmaErrors> {
+    if evals.len() != 1 << point.len() {
+        return Err(SigmaErrors::InvalidParameters(
+            "the evaluations do not match the number of variables".to_string(),
+        ));
+    }
+    let mut evals = evals.to_vec();
+    for r in point {
+        let half = evals.len() / 2;
+        evals = (0..half).map(|i| evals[i] + *r * (evals[half + i] - evals[i])).collect();
+    }
+    Ok(evals[0])
+}
+
+// the number of variables of a multilinear polynomial with len evaluations
+fn num_vars(len: usize) -> Result<usize, SigmaErrors> {
+    if len < 2 || !len.is_power_of_two() {
+        return Err(SigmaErrors::InvalidParameters(
+            "the number of evaluations should be a power of two of at least 2".to_string(),
+        ));
+    }
+    Ok(len.trailing_zeros() as usize)
+}
+
+// both sides absorb the number of variables and the claimed sum before the first round
+fn absorb_statement<F: PrimeField>(
+    transcript: &mut ProofTranscript<F>,
+    num_vars: usize,
+    claimed_sum: &F,
+) -> Result<(), SigmaErrors> {
+    transcript.append_field_element(b"sumcheck variables", &F::from(num_vars as u64))?;
+    transcript.append_field_element(b"sumcheck claim", claimed_sum)?;
+    Ok(())
+}
+
+// the coefficients of the polynomial of degree at most 2 through (0, e0), (1, e1), (2, e2)
+fn interpolate<F: PrimeField>(e0: F, e1: F, e2: F) -> [F; 3] {
+    let c2 = (e2 - e1.double() + e0) * F::from(2u64).inverse().unwrap();
+    let c1 = e1 - e0 - c2;
+    [e0, c1, c2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::Fr;
+    use ark_ff::Field;
+    use ark_std::{UniformRand, Zero};
+
+    // f(x_1, x_2, x_3) = 1 + 2 x_1 + 3 x_2 x_3 + 4 x_1 x_2 x_3, at the bits of 0..8 with x_1 most significant
+    fn evals() -> Vec<Fr> {
+        (0u64..8)
+            .map(|i| {
+                let (x1, x2, x3) = (i >> 2 & 1, i >> 1 & 1, i & 1);
+                Fr::from(1 + 2 * x1 + 3 * x2 * x3 + 4 * x1 * x2 * x3)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sumcheck() {
+        // the sum is 8 + 2*4 + 3*2 + 4 = 26
+        let evals = evals();
+        let sum = Fr::from(26u64);
+        let proof = Sumcheck::prove(&evals, sum, &mut ProofTranscript::new(b"test_sumcheck")).unwrap();
+        assert_eq!(proof.round_polys.len(), 3);
+        // the round polynomials of a multilinear polynomial are linear
+        assert!(proof.round_polys.iter().all(|poly| poly[2].is_zero()));
+
+        let (point, value) = Sumcheck::verify(3, sum, &proof, &mut ProofTranscript::new(b"test_sumcheck")).unwrap();
+        let (x1, x2, x3) = (point[0], point[1], point[2]);
+        let f_at_point = Fr::from(1u64) + Fr::from(2u64) * x1 + Fr::from(3u64) * x2 * x3 + Fr::from(4u64) * x1 * x2 * x3;
+        assert_eq!(value, f_at_point);
+        assert_eq!(evaluate_multilinear(&evals, &point).unwrap(), value);
+
+        // the round polynomials are bound to the challenges of the transcript they were proved on
+        assert!(Sumcheck::verify(3, sum, &proof, &mut ProofTranscript::new(b"another")).is_err());
+    }
+
+    #[test]
+    fn test_sumcheck_false_sum() {
+        let evals = evals();
+        let sum = Fr::from(26u64);
+        // the prover refuses a false claim, and a proof of the true sum does not verify for a false one
+        assert!(Sumcheck::prove(&evals, sum + Fr::from(1u64), &mut ProofTranscript::new(b"test_sumcheck")).is_err());
+        let proof = Sumcheck::prove(&evals, sum, &mut ProofTranscript::new(b"test_sumcheck")).unwrap();
+        let result = Sumcheck::verify(3, sum + Fr::from(1u64), &proof, &mut ProofTranscript::new(b"test_sumcheck"));
+        assert!(matches!(result, Err(SigmaErrors::InvalidProof(msg)) if msg == "round 1: sumcheck fails"));
+
+        // a first round polynomial altered without changing g_1(0) + g_1(1) fails the next round
+        let mut rng = ark_std::test_rng();
+        let mut forged = proof.clone();
+        let delta = Fr::rand(&mut rng);
+        forged.round_polys[0][0] += delta;
+        forged.round_polys[0][1] -= delta.double();
+        let result = Sumcheck::verify(3, sum, &forged, &mut ProofTranscript::new(b"test_sumcheck"));
+        assert!(matches!(result, Err(SigmaErrors::InvalidProof(msg)) if msg == "round 2: sumcheck fails"));
+
+        // evaluations of lengths other than powers of two, and proofs of the wrong length are rejected
+        assert!(Sumcheck::prove(&evals[..6], sum, &mut ProofTranscript::new(b"test_sumcheck")).is_err());
+        assert!(Sumcheck::prove(&evals[..1], evals[0], &mut ProofTranscript::new(b"test_sumcheck")).is_err());
+        assert!(Sumcheck::verify(2, sum, &proof, &mut ProofTranscript::new(b"test_sumcheck")).is_err());
+        assert!(evaluate_multilinear(&evals, &[Fr::zero(); 2]).is_err());
+    }
+}
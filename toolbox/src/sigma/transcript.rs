@@ -1,9 +1,11 @@
 use ark_ff::PrimeField;
 use ark_serialize::CanonicalSerialize;
+use alloc::{string::ToString, vec, vec::Vec};
+use core::marker::PhantomData;
 use merlin::Transcript;
-use std::marker::PhantomData;
 
-use crate::errors::TranscriptError;
+use crate::errors::{SigmaErrors, TranscriptError};
+use crate::hash::Digest;
 
 /// Takes as input a struct, and converts them to a series of bytes. All traits that implement `CanonicalSerialize`
 /// can be automatically converted to bytes in this manner.
@@ -50,6 +52,7 @@ impl<F: PrimeField> ProofTranscript<F> {
     ) -> Result<(), TranscriptError> {
         self.transcript.append_message(label, msg);
         self.is_empty = false;
+        crate::metrics::count_transcript_append();
         Ok(())
     }
 
@@ -73,6 +76,12 @@ impl<F: PrimeField> ProofTranscript<F> {
         self.append_message(label, &to_bytes!(group_elem)?)
     }
 
+    // append the message digest, computed with the hash function D, together with the name of D
+    pub fn append_message_digest<D: Digest>(&mut self, digest: &str) -> Result<(), TranscriptError> {
+        self.append_message(b"digest function", D::NAME)?;
+        self.append_message(b"message digest", &digest.as_bytes()[..32])
+    }
+
     // Generate the challenge from the current transcript and append it to the transcript
     // The output field element is statistical uniform as long as the field has a size less than 2^384.
     pub fn get_and_append_challenge(&mut self, label: &'static [u8]) -> Result<F, TranscriptError> {
@@ -112,4 +121,62 @@ impl<F: PrimeField> ProofTranscript<F> {
         }
         Ok(res)
     }
+
+    /// Generates n challenges from the current transcript, each appended before the next is derived,
+    /// so that the protocols built on SigmaErrors can draw a challenge vector without converting errors
+    pub fn get_and_append_challenge_vector(
+        &mut self,
+        label: &'static [u8],
+        n: usize,
+    ) -> Result<Vec<F>, SigmaErrors> {
+        Ok(self.get_and_append_challenge_vectors(label, n)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_secp256k1::{Affine, Fr};
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_challenge_vector() {
+        let transcript = || {
+            let mut transcript = ProofTranscript::<Fr>::new(b"Test");
+            transcript.append_message(b"message", b"statement").unwrap();
+            transcript
+        };
+        let (mut t1, mut t2) = (transcript(), transcript());
+        let challenges = t1.get_and_append_challenge_vector(b"u", 4).unwrap();
+        assert_eq!(challenges, t2.get_and_append_challenge_vector(b"u", 4).unwrap());
+        for i in 0..4 {
+            for j in 0..i {
+                assert_ne!(challenges[i], challenges[j]);
+            }
+        }
+        // both transcripts end in the same state
+        assert_eq!(
+            t1.get_and_append_challenge(b"next").unwrap(),
+            t2.get_and_append_challenge(b"next").unwrap()
+        );
+        assert!(ProofTranscript::<Fr>::new(b"Test").get_and_append_challenge_vector(b"u", 4).is_err());
+    }
+
+    #[test]
+    fn test_append_group_element() {
+        // the challenge depends on the serialized group element
+        let mut rng = ark_std::test_rng();
+        let (g, h) = (Affine::rand(&mut rng), Affine::rand(&mut rng));
+        let challenge = |point: Option<&Affine>| {
+            let mut transcript = ProofTranscript::<Fr>::new(b"Test");
+            transcript.append_message(b"message", b"statement").unwrap();
+            if let Some(point) = point {
+                transcript.append_serializable_element(b"group element", point).unwrap();
+            }
+            transcript.get_and_append_challenge(b"challenge").unwrap()
+        };
+        assert_eq!(challenge(Some(&g)), challenge(Some(&g)));
+        assert_ne!(challenge(Some(&g)), challenge(None));
+        assert_ne!(challenge(Some(&g)), challenge(Some(&h)));
+    }
 }
@@ -1,7 +1,11 @@
 use ark_ff::PrimeField;
 use ark_ec::CurveGroup;
-use rand::{seq::SliceRandom, thread_rng};
-use std::iter;
+use ark_serialize::CanonicalSerialize;
+use alloc::{string::ToString, vec, vec::Vec};
+use core::iter;
+use rand::{seq::SliceRandom, Rng};
+use subtle::ConstantTimeEq;
+use crate::errors::SigmaErrors;
 
 pub fn convert<F: PrimeField>(m: &[u64]) -> Vec<F> {
     let mut vec_field: Vec<F> = Vec::new();
@@ -11,12 +15,27 @@ pub fn convert<F: PrimeField>(m: &[u64]) -> Vec<F> {
     vec_field
 }
 
-pub fn shuffle<C: CurveGroup>(vec_pk: & mut Vec<C::Affine>, pk: C::Affine) -> Vec<C::ScalarField>{
-    let mut rng = thread_rng();
+// a negative value -x is mapped to the field element p - x
+pub fn field_element<F: PrimeField>(x: i64) -> F {
+    if x < 0 {
+        -F::from(x.unsigned_abs())
+    } else {
+        F::from(x as u64)
+    }
+}
+
+pub fn convert_signed<F: PrimeField>(m: &[i64]) -> Vec<F> {
+    m.iter().map(|&x| field_element(x)).collect()
+}
+
+// shuffles with the thread rng, which is only available with the std feature
+#[cfg(feature = "std")]
+pub fn shuffle<C: CurveGroup>(vec_pk: &mut [C::Affine], pk: C::Affine) -> Vec<C::ScalarField>{
+    let mut rng = rand::thread_rng();
     vec_pk.shuffle(&mut rng);
     let mut vec_b:Vec<C::ScalarField> = Vec::new();
-    for i in 0..vec_pk.len() {
-        if pk == vec_pk[i] {
+    for pk_i in vec_pk.iter() {
+        if pk == *pk_i {
             vec_b.push(C::ScalarField::from(1u64));
         } else {
             vec_b.push(C::ScalarField::from(0u64));
@@ -25,12 +44,68 @@ pub fn shuffle<C: CurveGroup>(vec_pk: & mut Vec<C::Affine>, pk: C::Affine) -> Ve
     vec_b
 }
 
-pub fn scalar_product<F: PrimeField>(vec_a: &Vec<F>, c: &F) -> Vec<F> {
+// shuffles vec_pk with rng and outputs the indicator vector of pk with the index of pk,
+// where pk must be a member of vec_pk (the first occurrence is indicated)
+pub fn shuffle_indexed<C: CurveGroup, R: Rng>(
+    vec_pk: &mut [C::Affine],
+    pk: C::Affine,
+    rng: &mut R,
+) -> (Vec<C::ScalarField>, usize) {
+    vec_pk.shuffle(rng);
+    let index = vec_pk.iter().position(|pk_i| *pk_i == pk)
+        .expect("pk should be a member of vec_pk");
+    let vec_b = (0..vec_pk.len())
+        .map(|i| C::ScalarField::from((i == index) as u64))
+        .collect();
+    (vec_b, index)
+}
+
+// whether every entry of v is zero or one
+pub fn is_binary<F: PrimeField>(v: &[F]) -> bool {
+    v.iter().all(|v_i| v_i.is_zero() || v_i.is_one())
+}
+
+pub fn assert_binary<F: PrimeField>(v: &[F]) {
+    if let Some(i) = v.iter().position(|v_i| !v_i.is_zero() && !v_i.is_one()) {
+        panic!("Vector must be binary, but entry {} of {} is neither 0 nor 1", i, v.len());
+    }
+}
+
+// the number of entries equal to one, e.g., 1 for a one-hot indicator vector
+pub fn count_ones<F: PrimeField>(v: &[F]) -> usize {
+    v.iter().filter(|v_i| v_i.is_one()).count()
+}
+
+// n independently sampled points, unlike vec![C::Affine::rand(rng); n], which clones a single point n times
+pub fn rand_affine_vec<C: CurveGroup, R: Rng>(rng: &mut R, n: usize) -> Vec<C::Affine> {
+    (0..n).map(|_| C::rand(rng).into_affine()).collect()
+}
+
+// `==` on projective points already compares the underlying group elements
+// (coordinates are cross-multiplied by z), but it branches on the coordinates;
+// this normalizes both points to affine and compares the encodings in constant time
+pub fn ct_eq_affine<C: CurveGroup>(a: C, b: C) -> bool {
+    let points = C::normalize_batch(&[a, b]);
+    let mut bytes_a = Vec::new();
+    let mut bytes_b = Vec::new();
+    points[0].serialize_uncompressed(&mut bytes_a).unwrap();
+    points[1].serialize_uncompressed(&mut bytes_b).unwrap();
+    bytes_a.ct_eq(&bytes_b).into()
+}
+
+// scaling by zero or one needs no multiplication
+pub fn scalar_product<F: PrimeField>(vec_a: &[F], c: &F) -> Vec<F> {
+    if c.is_zero() {
+        return vec![F::zero(); vec_a.len()];
+    }
+    if c.is_one() {
+        return vec_a.to_vec();
+    }
     vec_a.iter()
         .map(|&a| a * c).collect()
 }
 
-pub fn inner_product<F: PrimeField>(vec_a: &Vec<F>, vec_b: &Vec<F>) -> F {
+pub fn inner_product<F: PrimeField>(vec_a: &[F], vec_b: &[F]) -> F {
     assert_eq!(vec_a.len(), vec_b.len(), "Vectors must be of the same length");
 
     vec_a.iter()
@@ -39,26 +114,77 @@ pub fn inner_product<F: PrimeField>(vec_a: &Vec<F>, vec_b: &Vec<F>) -> F {
         .fold(F::zero(), |acc, x| acc + x)
 }
 
-pub fn vec_add<F: PrimeField>(vec_a: &Vec<F>, vec_b: &Vec<F>) -> Vec<F> {
+pub fn vec_add<F: PrimeField>(vec_a: &[F], vec_b: &[F]) -> Vec<F> {
     assert_eq!(vec_a.len(), vec_b.len(), "Vectors must be of the same length");
-    let result = vec_a.iter()
+
+    vec_a.iter()
         .zip(vec_b.iter())
-        .map(|(&a, &b)| a + b).collect();
-    result
+        .map(|(&a, &b)| a + b).collect()
+}
+
+// the try_* variants return an error instead of panicking on vectors of different lengths,
+// e.g., when the vectors are parsed from a proof
+pub fn try_inner_product<F: PrimeField>(vec_a: &[F], vec_b: &[F]) -> Result<F, SigmaErrors> {
+    check_same_length(vec_a, vec_b)?;
+    Ok(inner_product(vec_a, vec_b))
 }
 
-pub fn vec_split<T: Clone>(vec: &Vec<T>, n: usize) -> (Vec<T>, Vec<T>) {
+pub fn try_vec_add<F: PrimeField>(vec_a: &[F], vec_b: &[F]) -> Result<Vec<F>, SigmaErrors> {
+    check_same_length(vec_a, vec_b)?;
+    Ok(vec_add(vec_a, vec_b))
+}
+
+pub fn try_hadamard_product<F: PrimeField>(vec_a: &[F], vec_b: &[F]) -> Result<Vec<F>, SigmaErrors> {
+    check_same_length(vec_a, vec_b)?;
+    Ok(hadamard_product(vec_a, vec_b))
+}
+
+fn check_same_length<F: PrimeField>(vec_a: &[F], vec_b: &[F]) -> Result<(), SigmaErrors> {
+    if vec_a.len() != vec_b.len() {
+        return Err(SigmaErrors::InvalidParameters(
+            "vectors must be of the same length".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub fn vec_split<T: Clone>(vec: &[T], n: usize) -> (Vec<T>, Vec<T>) {
     assert!(vec.len() >= n, "Vectors must have length than n");
     let (slice_l, slice_r) = vec.split_at(n);
     (slice_l.to_vec(), slice_r.to_vec())
 }
 
-pub fn hadamard_product<F: PrimeField>(vec_a: &Vec<F>, vec_b: &Vec<F>) -> Vec<F> {
+pub fn hadamard_product<F: PrimeField>(vec_a: &[F], vec_b: &[F]) -> Vec<F> {
     assert_eq!(vec_a.len(), vec_b.len(), "Vectors must be of the same length");
-    let result = vec_a.iter()
+
+    vec_a.iter()
         .zip(vec_b.iter())
-        .map(|(&a, &b)| a * b).collect();
-    result
+        .map(|(&a, &b)| a * b).collect()
+}
+
+// -v, e.g., the exponents of L and R in the final msm of the IPA verifier
+pub fn vec_neg<F: PrimeField>(v: &[F]) -> Vec<F> {
+    v.iter().map(|&v_i| -v_i).collect()
+}
+
+// v in reverse order, e.g., the scalars of vec_H from those of vec_G in the IPA
+pub fn reversed<T: Clone>(v: &[T]) -> Vec<T> {
+    let mut v_rev = v.to_vec();
+    v_rev.reverse();
+    v_rev
+}
+
+pub fn vec_sum<F: PrimeField>(v: &[F]) -> F {
+    v.iter().fold(F::zero(), |acc, &x| acc + x)
+}
+
+pub fn vec_product<F: PrimeField>(v: &[F]) -> F {
+    v.iter().fold(F::one(), |acc, &x| acc * x)
+}
+
+// alias of vec_sum, e.g., <1^n, y^n> is written as scalar_sum(&y^n)
+pub fn scalar_sum<F: PrimeField>(v: &[F]) -> F {
+    vec_sum(v)
 }
 
 pub fn generate_powers<F: PrimeField>(y: F, n: usize) -> Vec<F> {
@@ -67,12 +193,21 @@ pub fn generate_powers<F: PrimeField>(y: F, n: usize) -> Vec<F> {
         .collect()
 }
 
+// (z^0, z^1, ..., z^max_exp) by repeated multiplication, so that the prover and the verifier
+// read the powers of a challenge from one table instead of separate pow calls
+pub fn powers_of<F: PrimeField>(z: F, max_exp: usize) -> Vec<F> {
+    iter::successors(Some(F::one()), |&current_power| Some(current_power * z))
+        .take(max_exp + 1)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use ark_ec::{CurveGroup};
+    use ark_ff::Field;
     use super::*;
     use ark_secp256k1::{Fr, Projective, Affine};
-    use ark_std::{UniformRand};
+    use ark_std::{UniformRand, Zero, One};
+    use proptest::prelude::*;
 
     #[test]
     fn test_convert() {
@@ -82,6 +217,23 @@ mod tests {
     }
 
     #[test]
+    fn test_convert_signed() {
+        let vec_neg: Vec<Fr> = convert_signed(&[-1i64]);
+        assert_eq!(vec_neg, vec![Fr::zero() - Fr::one()]);
+        let vec_m: Vec<Fr> = convert_signed(&[-3i64, 0, 5, i64::MIN, i64::MAX]);
+        assert_eq!(vec_m[0], -Fr::from(3u64));
+        assert_eq!(vec_m[1], Fr::zero());
+        assert_eq!(vec_m[2], Fr::from(5u64));
+        assert_eq!(vec_m[3], -Fr::from(1u64 << 63));
+        assert_eq!(vec_m[4], Fr::from(i64::MAX as u64));
+        // mixed signed/unsigned conversions agree with field arithmetic
+        assert_eq!(field_element::<Fr>(-7) + Fr::from(10u64), field_element::<Fr>(3));
+        assert_eq!(field_element::<Fr>(-4) * field_element::<Fr>(-5), convert::<Fr>(&[20u64])[0]);
+        assert_eq!(field_element::<Fr>(i64::MIN) + field_element::<Fr>(i64::MAX), field_element::<Fr>(-1));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
     fn test_shuffle() {
         let mut rng = ark_std::test_rng();
         let scalar = Fr::from(1u64);
@@ -96,6 +248,82 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vec_neg() {
+        let mut rng = ark_std::test_rng();
+        let v: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let v_neg = vec_neg(&v);
+        assert_eq!(v_neg.len(), v.len());
+        assert_eq!(vec_add(&v, &v_neg), vec![Fr::zero(); 5]);
+        assert_eq!(vec_neg(&v_neg), v);
+        assert_eq!(vec_neg(&convert_signed::<Fr>(&[-3i64, 0, 2])), convert_signed::<Fr>(&[3i64, 0, -2]));
+        assert!(vec_neg::<Fr>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_reversed() {
+        let v: Vec<Fr> = convert(&[1u64, 2u64, 3u64, 4u64]);
+        assert_eq!(reversed(&v), convert::<Fr>(&[4u64, 3u64, 2u64, 1u64]));
+        assert_eq!(reversed(&reversed(&v)), v);
+        assert_eq!(reversed(&[7u8]), vec![7u8]);
+        assert!(reversed::<u8>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_powers_of() {
+        let mut rng = ark_std::test_rng();
+        let z = Fr::rand(&mut rng);
+        let powers_z = powers_of(z, 8);
+        assert_eq!(powers_z.len(), 9);
+        for (k, z_k) in powers_z.iter().enumerate() {
+            assert_eq!(*z_k, z.pow([k as u64]));
+        }
+        assert_eq!(powers_of(z, 0), vec![Fr::one()]);
+        assert_eq!(powers_of(z, 8)[1..], generate_powers(z, 8)[..]);
+    }
+
+    #[test]
+    fn test_binary() {
+        let vec_b: Vec<Fr> = convert(&[0u64, 1u64, 0u64, 0u64]);
+        assert!(is_binary(&vec_b));
+        assert_binary(&vec_b);
+        assert_eq!(count_ones(&vec_b), 1);
+        assert_eq!(count_ones(&convert::<Fr>(&[1u64, 1u64, 2u64])), 2);
+        assert!(is_binary::<Fr>(&[]));
+        assert!(!is_binary(&convert::<Fr>(&[0u64, 2u64])));
+        assert!(!is_binary(&[-Fr::one()]));
+        assert!(std::panic::catch_unwind(|| assert_binary(&convert::<Fr>(&[1u64, 3u64]))).is_err());
+    }
+
+    #[test]
+    fn test_shuffle_indexed() {
+        let mut rng = ark_std::test_rng();
+        let pk = Affine::rand(&mut rng);
+        for n in [1usize, 2, 7, 16] {
+            let mut vec_pk: Vec<Affine> = (0..n-1).map(|_| Affine::rand(&mut rng)).collect();
+            vec_pk.push(pk);
+            let (vec_b, index) = shuffle_indexed::<Projective, _>(&mut vec_pk, pk, &mut rng);
+            assert_eq!(vec_pk[index], pk);
+            assert_eq!(vec_b.len(), n);
+            assert_eq!(count_ones(&vec_b), 1);
+            assert_eq!(vec_b[index], Fr::one());
+        }
+    }
+
+    #[test]
+    fn test_ct_eq_affine() {
+        let mut rng = ark_std::test_rng();
+        let p = Projective::rand(&mut rng);
+        // p and (p + q) - q have different projective coordinates
+        let q = Projective::rand(&mut rng);
+        let p_prime = (p + q) - q;
+        assert_ne!((p.x, p.y, p.z), (p_prime.x, p_prime.y, p_prime.z));
+        assert_eq!(p, p_prime);
+        assert!(ct_eq_affine(p, p_prime));
+        assert!(!ct_eq_affine(p, q));
+        assert!(ct_eq_affine(Projective::zero(), p - p_prime));
+    }
+
     #[test]
     fn test_inner_product() {
         let a: [u64; 4] = [1, 2, 3, 4];
@@ -107,6 +335,31 @@ mod tests {
         assert_eq!(result, Fr::from(20u64));
     }
 
+    #[test]
+    fn test_try_vec_ops() {
+        let vec_a: Vec<Fr> = convert(&[1u64, 2u64, 3u64]);
+        let vec_b: Vec<Fr> = convert(&[4u64, 5u64, 6u64]);
+        assert_eq!(try_inner_product(&vec_a, &vec_b).unwrap(), inner_product(&vec_a, &vec_b));
+        assert_eq!(try_vec_add(&vec_a, &vec_b).unwrap(), vec_add(&vec_a, &vec_b));
+        assert_eq!(try_hadamard_product(&vec_a, &vec_b).unwrap(), hadamard_product(&vec_a, &vec_b));
+        // mismatched lengths are errors rather than panics
+        assert!(try_inner_product(&vec_a, &vec_b[..2]).is_err());
+        assert!(try_vec_add(&vec_a[..1], &vec_b).is_err());
+        assert!(try_hadamard_product(&vec_a, &[]).is_err());
+    }
+
+    #[test]
+    fn test_vec_sum_product() {
+        let vec_a: Vec<Fr> = convert(&[1u64, 2u64, 3u64]);
+        assert_eq!(vec_sum(&vec_a), Fr::from(6u64));
+        assert_eq!(scalar_sum(&vec_a), Fr::from(6u64));
+        let vec_b: Vec<Fr> = convert(&[1u64, 2u64, 3u64, 4u64]);
+        assert_eq!(vec_product(&vec_b), Fr::from(24u64));
+        // empty inputs return the identities
+        assert_eq!(vec_sum::<Fr>(&[]), Fr::zero());
+        assert_eq!(vec_product::<Fr>(&[]), Fr::one());
+    }
+
     #[test]
     fn test_generate_powers() {
         let y = Fr::from(2u64);
@@ -114,4 +367,67 @@ mod tests {
         let result = generate_powers(y, n);
         assert_eq!(result, vec![Fr::from(2u64), Fr::from(4u64), Fr::from(8u64), Fr::from(16u64)]);
     }
+
+    // three vectors of the same length in 1..=16, and a scalar
+    fn same_length_vectors() -> impl Strategy<Value = (Vec<u64>, Vec<u64>, Vec<u64>, u64)> {
+        (1usize..=16).prop_flat_map(|n| (
+            prop::collection::vec(any::<u64>(), n),
+            prop::collection::vec(any::<u64>(), n),
+            prop::collection::vec(any::<u64>(), n),
+            any::<u64>(),
+        ))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(500))]
+
+        #[test]
+        fn prop_inner_product_commutative((a, b, _, _) in same_length_vectors()) {
+            let (vec_a, vec_b): (Vec<Fr>, Vec<Fr>) = (convert(&a), convert(&b));
+            prop_assert_eq!(inner_product(&vec_a, &vec_b), inner_product(&vec_b, &vec_a));
+        }
+
+        #[test]
+        fn prop_inner_product_linear((a, b, _, c) in same_length_vectors()) {
+            let (vec_a, vec_b): (Vec<Fr>, Vec<Fr>) = (convert(&a), convert(&b));
+            let c = Fr::from(c);
+            prop_assert_eq!(
+                inner_product(&scalar_product(&vec_a, &c), &vec_b),
+                c * inner_product(&vec_a, &vec_b)
+            );
+        }
+
+        #[test]
+        fn prop_hadamard_indicator((a, b, _, _) in same_length_vectors()) {
+            // b is reduced to an indicator vector of bits
+            let bits: Vec<u64> = b.iter().map(|b_i| b_i & 1).collect();
+            let (vec_a, vec_b): (Vec<Fr>, Vec<Fr>) = (convert(&a), convert(&bits));
+            let ones = vec![Fr::one(); vec_a.len()];
+            prop_assert_eq!(
+                inner_product(&hadamard_product(&vec_a, &vec_b), &ones),
+                inner_product(&vec_a, &vec_b)
+            );
+        }
+
+        #[test]
+        fn prop_sum_all_ones((a, _, _, c) in same_length_vectors()) {
+            // vec_sum and scalar_product by 0 or 1 agree with the general products
+            let vec_a: Vec<Fr> = convert(&a);
+            let ones = vec![Fr::one(); vec_a.len()];
+            prop_assert_eq!(vec_sum(&vec_a), inner_product(&ones, &vec_a));
+            prop_assert_eq!(scalar_product(&vec_a, &Fr::one()), hadamard_product(&vec_a, &ones));
+            prop_assert_eq!(scalar_product(&vec_a, &Fr::zero()), vec![Fr::zero(); vec_a.len()]);
+            prop_assert_eq!(scalar_product(&vec_a, &Fr::from(c)), hadamard_product(&vec_a, &vec![Fr::from(c); vec_a.len()]));
+        }
+
+        #[test]
+        fn prop_hadamard_commutative_associative((a, b, c, _) in same_length_vectors()) {
+            let (vec_a, vec_b, vec_c): (Vec<Fr>, Vec<Fr>, Vec<Fr>) = (convert(&a), convert(&b), convert(&c));
+            prop_assert_eq!(hadamard_product(&vec_a, &vec_b), hadamard_product(&vec_b, &vec_a));
+            prop_assert_eq!(
+                hadamard_product(&hadamard_product(&vec_a, &vec_b), &vec_c),
+                hadamard_product(&vec_a, &hadamard_product(&vec_b, &vec_c))
+            );
+        }
+    }
 }